@@ -0,0 +1,72 @@
+//! Measures how fast words are written into an sqlite database with different batch sizes,
+//! to choose the default of [`SqliteConfiguration::batch_size`].
+//!
+//! Run with `cargo run --release --example sqlite_batch_size --features sqlite [word count]`.
+
+use std::path::Path;
+use std::time::Instant;
+use wiktionary_dump_parser::error::{Error, Result};
+use wiktionary_dump_parser::parser::words::interner::intern;
+use wiktionary_dump_parser::parser::words::Word;
+use wiktionary_dump_parser::sqlite::{SqliteConfiguration, SqliteWordSink};
+
+const BATCH_SIZES: &[usize] = &[1, 100, 1_000, 10_000, 100_000];
+
+fn main() -> Result<()> {
+    let word_count = if let Some(word_count) = std::env::args().nth(1) {
+        word_count
+            .parse()
+            .map_err(|error| Error::Other(format!("Invalid word count {word_count:?}: {error}")))?
+    } else {
+        100_000
+    };
+    let words: Vec<_> = (0..word_count)
+        .map(|index| Word {
+            word: format!("word{index}"),
+            language_english_name: intern("English"),
+            language_code: intern("en"),
+            word_type: intern(if index % 3 == 0 { "Verb" } else { "Noun" }),
+            features: Default::default(),
+            etymology: None,
+        })
+        .collect();
+
+    for &batch_size in BATCH_SIZES {
+        let path = std::env::temp_dir().join(format!(
+            "wiktionary-dump-parser-sqlite-batch-size-{}-{batch_size}.sqlite",
+            std::process::id()
+        ));
+        let configuration = SqliteConfiguration {
+            batch_size,
+            ..Default::default()
+        };
+
+        let start = Instant::now();
+        let mut sink = SqliteWordSink::open(&path, &configuration)?;
+        sink.insert_batch(words.iter().cloned())?;
+        sink.finish()?;
+        let duration = start.elapsed();
+        remove_database(&path)?;
+
+        println!(
+            "batch size {batch_size:>7}: {word_count} words in {:.2}s ({:.0} words/s)",
+            duration.as_secs_f64(),
+            word_count as f64 / duration.as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
+
+/// Remove the database file together with its write-ahead log, if any.
+fn remove_database(path: &Path) -> Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file_name = path.as_os_str().to_owned();
+        file_name.push(suffix);
+        match std::fs::remove_file(file_name) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wiktionary_dump_parser::parser::parse_dump_stream;
+
+fuzz_target!(|data: &[u8]| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    // Errors are expected for most inputs, we only look for panics.
+    let _ = runtime.block_on(parse_dump_stream(
+        data,
+        None::<tokio::io::Sink>,
+        |_| std::future::ready(Ok(())),
+        std::io::sink(),
+        false,
+    ));
+});
@@ -0,0 +1,21 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wikitext_parser::parse_wikitext;
+use wiktionary_dump_parser::parser::words::wikitext_to_words;
+use wiktionary_dump_parser::wiktionary_edition::WiktionaryEdition;
+
+fuzz_target!(|text: &str| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let wikitext = parse_wikitext(text, "fuzz".to_string(), |_| {});
+    // Errors are expected for most inputs, we only look for panics.
+    let _ = runtime.block_on(wikitext_to_words(
+        &WiktionaryEdition::Unknown,
+        "fuzz",
+        &wikitext,
+        |_| std::future::ready(Ok(())),
+        |_| {},
+    ));
+});
@@ -0,0 +1,118 @@
+use crate::parser::words::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+/// Counts the words per language that each of several editions contains,
+/// to find the best source edition for a language.
+///
+/// Languages are identified by the language names used in the extracted words,
+/// so editions whose language headings are not in English should have their names mapped to English first.
+#[derive(Debug, Clone, Default)]
+pub struct CoverageMatrix {
+    editions: BTreeSet<String>,
+    /// Maps english language names to editions to the number of words.
+    counts: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// The number of words per language and edition.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CoverageReport {
+    /// The editions in the order of the counts of each row.
+    pub editions: Vec<String>,
+    /// Ordered by the total number of words, descending, and secondarily by language name.
+    pub languages: Vec<CoverageRow>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CoverageRow {
+    pub language_english_name: String,
+    /// The number of words in each edition, in the order of [`CoverageReport::editions`].
+    pub counts: Vec<usize>,
+    /// The edition with the most words of this language.
+    pub best_edition: String,
+}
+
+impl CoverageMatrix {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an edition, such that it gets a column even if no words are added for it.
+    pub fn add_edition(&mut self, edition: &str) {
+        self.editions.insert(edition.to_string());
+    }
+
+    pub fn add_word(&mut self, edition: &str, word: &Word) {
+        self.add_edition(edition);
+        *self
+            .counts
+            .entry(word.language_english_name.to_string())
+            .or_default()
+            .entry(edition.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn report(&self) -> CoverageReport {
+        let editions: Vec<_> = self.editions.iter().cloned().collect();
+        let mut languages: Vec<_> = self
+            .counts
+            .iter()
+            .map(|(language_english_name, edition_counts)| {
+                let counts: Vec<_> = editions
+                    .iter()
+                    .map(|edition| edition_counts.get(edition).copied().unwrap_or(0))
+                    .collect();
+                // Ties are broken by the order of the editions.
+                let best_edition = editions
+                    .iter()
+                    .zip(&counts)
+                    .rev()
+                    .max_by_key(|(_, count)| **count)
+                    .map(|(edition, _)| edition.clone())
+                    .unwrap_or_default();
+                CoverageRow {
+                    language_english_name: language_english_name.clone(),
+                    counts,
+                    best_edition,
+                }
+            })
+            .collect();
+        languages.sort_by(|a, b| {
+            b.total()
+                .cmp(&a.total())
+                .then_with(|| a.language_english_name.cmp(&b.language_english_name))
+        });
+
+        CoverageReport {
+            editions,
+            languages,
+        }
+    }
+}
+
+impl CoverageRow {
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+impl CoverageReport {
+    /// Write the matrix as tab-separated values with a header row.
+    pub fn write_tsv(&self, mut output: impl Write) -> std::io::Result<()> {
+        write!(output, "language")?;
+        for edition in &self.editions {
+            write!(output, "\t{edition}")?;
+        }
+        writeln!(output, "\tbest")?;
+
+        for row in &self.languages {
+            write!(output, "{}", row.language_english_name)?;
+            for count in &row.counts {
+                write!(output, "\t{count}")?;
+            }
+            writeln!(output, "\t{}", row.best_edition)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+//! Analyses of the extracted data that help to decide on how to post-process it.
+
+pub mod coverage;
+pub mod near_duplicates;
+pub mod page_history;
+pub mod unknown_headings;
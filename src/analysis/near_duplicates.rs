@@ -0,0 +1,80 @@
+use crate::parser::words::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Collects titles per language and groups those that differ only in case, diacritics or whitespace.
+#[derive(Debug, Clone, Default)]
+pub struct NearDuplicateTitles {
+    /// Maps languages to normalised titles to the original titles.
+    languages: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+/// Clusters of near-duplicate titles per language.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct NearDuplicateReport {
+    /// Maps english language names to the clusters of near-duplicate titles in that language.
+    /// Languages without near-duplicates are omitted.
+    pub languages: BTreeMap<String, Vec<NearDuplicateCluster>>,
+}
+
+/// A set of titles that are equal after normalisation.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct NearDuplicateCluster {
+    /// The normalised title shared by all titles in the cluster.
+    pub key: String,
+    /// The sorted titles in the cluster, of which there are at least two.
+    pub titles: Vec<String>,
+}
+
+impl NearDuplicateTitles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_word(&mut self, word: &Word) {
+        self.add(&word.language_english_name, &word.word);
+    }
+
+    pub fn add(&mut self, language_english_name: &str, title: &str) {
+        self.languages
+            .entry(language_english_name.to_string())
+            .or_default()
+            .entry(near_duplicate_key(title))
+            .or_default()
+            .insert(title.to_string());
+    }
+
+    pub fn report(&self) -> NearDuplicateReport {
+        NearDuplicateReport {
+            languages: self
+                .languages
+                .iter()
+                .map(|(language, titles)| {
+                    (
+                        language.clone(),
+                        titles
+                            .iter()
+                            .filter(|(_, cluster)| cluster.len() > 1)
+                            .map(|(key, cluster)| NearDuplicateCluster {
+                                key: key.clone(),
+                                titles: cluster.iter().cloned().collect(),
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .filter(|(_, clusters)| !clusters.is_empty())
+                .collect(),
+        }
+    }
+}
+
+/// Normalise a title such that titles differing only in case, diacritics or whitespace are equal.
+pub fn near_duplicate_key(title: &str) -> String {
+    title
+        .nfd()
+        .filter(|c| !is_combining_mark(*c) && !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
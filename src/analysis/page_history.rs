@@ -0,0 +1,200 @@
+#[cfg(feature = "parse")]
+use crate::error::Result;
+#[cfg(feature = "parse")]
+use crate::parser::titles::TitleFilter;
+use crate::parser::words::definitions::wikitext_to_definitions;
+use crate::parser::words::inflections::{wikitext_to_inflected_forms, InflectedForm};
+use crate::parser::words::{extract_words, Word};
+#[cfg(feature = "parse")]
+use crate::parser::{parse_dump_file_with_options, DumpParseOptions};
+use crate::wiktionary_edition::WiktionaryEdition;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "parse")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "parse")]
+use std::sync::Arc;
+use wikitext_parser::Wikitext;
+
+/// The data extracted from a single page at one point in time, e.g. from one dump or from the live page.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct PageSnapshot {
+    pub title: String,
+    pub words: BTreeSet<Word>,
+    pub senses: BTreeSet<Sense>,
+    pub inflected_forms: BTreeSet<InflectedForm>,
+}
+
+/// A single definition of a word.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Sense {
+    pub language_english_name: String,
+    pub word_type: String,
+    pub gloss: String,
+    pub labels: Vec<String>,
+}
+
+/// The changes of the extracted data of a page between two snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct PageDiff {
+    pub title: String,
+    pub words_added: Vec<Word>,
+    pub words_removed: Vec<Word>,
+    pub senses_added: Vec<Sense>,
+    pub senses_removed: Vec<Sense>,
+    pub inflected_forms_added: Vec<InflectedForm>,
+    pub inflected_forms_removed: Vec<InflectedForm>,
+}
+
+impl PageSnapshot {
+    /// A snapshot of a page without any extracted data, e.g. because the page did not exist.
+    pub fn empty(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Extract the words, senses and inflected forms of the page.
+    pub fn from_wikitext(edition: &WiktionaryEdition, title: &str, wikitext: &Wikitext) -> Self {
+        let mut snapshot = Self::empty(title);
+
+        extract_words(
+            edition,
+            title,
+            wikitext,
+            |word| {
+                snapshot.words.insert(word);
+            },
+            |_| {},
+        );
+        wikitext_to_definitions(title, wikitext, |entry| {
+            for definition in entry.definitions {
+                snapshot.senses.insert(Sense {
+                    language_english_name: entry.language_english_name.clone(),
+                    word_type: entry.word_type.clone(),
+                    gloss: definition.gloss,
+                    labels: definition.labels,
+                });
+            }
+        });
+        wikitext_to_inflected_forms(title, wikitext, |inflected_form| {
+            snapshot.inflected_forms.insert(inflected_form);
+        });
+
+        snapshot
+    }
+
+    /// The changes from this snapshot to the given newer snapshot.
+    /// A sense whose labels changed is reported as removed and added.
+    pub fn diff(&self, newer: &PageSnapshot) -> PageDiff {
+        PageDiff {
+            title: newer.title.clone(),
+            words_added: newer.words.difference(&self.words).cloned().collect(),
+            words_removed: self.words.difference(&newer.words).cloned().collect(),
+            senses_added: newer.senses.difference(&self.senses).cloned().collect(),
+            senses_removed: self.senses.difference(&newer.senses).cloned().collect(),
+            inflected_forms_added: newer
+                .inflected_forms
+                .difference(&self.inflected_forms)
+                .cloned()
+                .collect(),
+            inflected_forms_removed: self
+                .inflected_forms
+                .difference(&newer.inflected_forms)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl PageDiff {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.words_added.is_empty()
+            && self.words_removed.is_empty()
+            && self.senses_added.is_empty()
+            && self.senses_removed.is_empty()
+            && self.inflected_forms_added.is_empty()
+            && self.inflected_forms_removed.is_empty()
+    }
+}
+
+/// Formats the diff like a unified diff, with one line per change.
+impl Display for PageDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Page {:?}", self.title)?;
+        if self.is_empty() {
+            return writeln!(f, "  no changes");
+        }
+
+        for (sign, word) in changes(&self.words_removed, &self.words_added) {
+            writeln!(
+                f,
+                "{sign} word: {} ({}, {})",
+                word.word, word.language_english_name, word.word_type
+            )?;
+        }
+        for (sign, sense) in changes(&self.senses_removed, &self.senses_added) {
+            write!(
+                f,
+                "{sign} sense ({}, {}): ",
+                sense.language_english_name, sense.word_type
+            )?;
+            if !sense.labels.is_empty() {
+                write!(f, "({}) ", sense.labels.join(", "))?;
+            }
+            writeln!(f, "{}", sense.gloss)?;
+        }
+        for (sign, inflected_form) in
+            changes(&self.inflected_forms_removed, &self.inflected_forms_added)
+        {
+            writeln!(
+                f,
+                "{sign} form ({}): {} of {} [{}]",
+                inflected_form.language_english_name,
+                inflected_form.form,
+                inflected_form.lemma,
+                inflected_form.tags.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn changes<'a, T>(removed: &'a [T], added: &'a [T]) -> impl Iterator<Item = (char, &'a T)> {
+    removed
+        .iter()
+        .map(|item| ('-', item))
+        .chain(added.iter().map(|item| ('+', item)))
+}
+
+/// Parse the dump file and take a snapshot of the page with the given title.
+/// Returns `None` if the dump does not contain the page.
+/// This reads the whole dump, but only parses the wikitext of the page itself.
+#[cfg(feature = "parse")]
+pub async fn dump_page_snapshot(
+    input_file: impl AsRef<Path>,
+    title: &str,
+    error_log: impl AsRef<Path>,
+) -> Result<Option<PageSnapshot>> {
+    let mut snapshot = None;
+    parse_dump_file_with_options(
+        input_file,
+        None::<PathBuf>,
+        |_| std::future::ready(Ok(())),
+        |edition, page_title, wikitext| {
+            if page_title == title {
+                snapshot = Some(PageSnapshot::from_wikitext(edition, page_title, wikitext));
+            }
+        },
+        error_log,
+        &DumpParseOptions {
+            title_filter: Some(Arc::new(TitleFilter::new().with_titles([title]))),
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(snapshot)
+}
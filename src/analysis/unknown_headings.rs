@@ -0,0 +1,131 @@
+use crate::error::Error;
+use crate::parser::words::headings::{heading_key, is_single_edit_apart, FUZZY_MINIMUM_LENGTH};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The amount of example pages kept per cluster.
+const MAXIMUM_EXAMPLE_PAGES: usize = 5;
+
+/// Collects the headings that word extraction does not recognise, i.e. that are reported as
+/// [`Error::UnknownHeading`], and clusters them by their spelling variants.
+#[derive(Debug, Clone, Default)]
+pub struct UnknownHeadings {
+    /// Maps heading keys to the occurrences of headings with that key.
+    clusters: BTreeMap<String, UnknownHeadingCluster>,
+}
+
+/// Unknown headings ranked by how often they occur.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct UnknownHeadingReport {
+    /// The total amount of unknown headings.
+    pub count: u64,
+    /// The clusters, most frequent first.
+    pub clusters: Vec<UnknownHeadingCluster>,
+}
+
+/// Headings that are spelling variants of each other.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct UnknownHeadingCluster {
+    /// The key of the most frequent spelling variant, see [`heading_key`].
+    pub key: String,
+    pub count: u64,
+    /// The counts of the headings as written on the pages.
+    pub spellings: BTreeMap<String, u64>,
+    /// The counts of the english names of the languages whose sections contain the headings.
+    pub languages: BTreeMap<String, u64>,
+    /// The titles of some pages containing the headings.
+    pub example_pages: Vec<String>,
+}
+
+impl UnknownHeadings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the heading of the error if it is an [`Error::UnknownHeading`], and ignore it otherwise.
+    pub fn add_error(&mut self, title: &str, error: &Error) {
+        if let Error::UnknownHeading {
+            language_english_name,
+            label,
+        } = error
+        {
+            self.add(title, language_english_name, label);
+        }
+    }
+
+    pub fn add(&mut self, title: &str, language_english_name: &str, label: &str) {
+        let key = heading_key(label);
+        let cluster = self
+            .clusters
+            .entry(key.clone())
+            .or_insert_with(|| UnknownHeadingCluster {
+                key,
+                ..Default::default()
+            });
+        cluster.count += 1;
+        *cluster
+            .spellings
+            .entry(label.trim().to_string())
+            .or_default() += 1;
+        *cluster
+            .languages
+            .entry(language_english_name.to_string())
+            .or_default() += 1;
+        if cluster.example_pages.len() < MAXIMUM_EXAMPLE_PAGES
+            && !cluster.example_pages.iter().any(|page| page == title)
+        {
+            cluster.example_pages.push(title.to_string());
+        }
+    }
+
+    /// Rank the clusters by their counts, omitting those that occur less than `minimum_count` times.
+    ///
+    /// Clusters whose keys are a single typo apart are merged into the more frequent one,
+    /// like [`classify_heading`](crate::parser::words::headings::classify_heading) matches headings with typos.
+    pub fn report(&self, minimum_count: u64) -> UnknownHeadingReport {
+        let mut clusters: Vec<_> = self.clusters.values().cloned().collect();
+        clusters.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+        let mut merged_clusters: Vec<UnknownHeadingCluster> = Vec::new();
+        for cluster in clusters {
+            let similar_cluster = merged_clusters.iter_mut().find(|merged_cluster| {
+                cluster.key.chars().count() >= FUZZY_MINIMUM_LENGTH
+                    && is_single_edit_apart(&cluster.key, &merged_cluster.key)
+            });
+            if let Some(similar_cluster) = similar_cluster {
+                similar_cluster.merge(cluster);
+            } else {
+                merged_clusters.push(cluster);
+            }
+        }
+        merged_clusters.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+
+        UnknownHeadingReport {
+            count: self.clusters.values().map(|cluster| cluster.count).sum(),
+            clusters: merged_clusters
+                .into_iter()
+                .filter(|cluster| cluster.count >= minimum_count)
+                .collect(),
+        }
+    }
+}
+
+impl UnknownHeadingCluster {
+    fn merge(&mut self, other: UnknownHeadingCluster) {
+        self.count += other.count;
+        for (spelling, count) in other.spellings {
+            *self.spellings.entry(spelling).or_default() += count;
+        }
+        for (language, count) in other.languages {
+            *self.languages.entry(language).or_default() += count;
+        }
+        for page in other.example_pages {
+            if self.example_pages.len() >= MAXIMUM_EXAMPLE_PAGES {
+                break;
+            }
+            if !self.example_pages.contains(&page) {
+                self.example_pages.push(page);
+            }
+        }
+    }
+}
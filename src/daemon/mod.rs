@@ -0,0 +1,374 @@
+use crate::dates::format_dump_date;
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::mirrors::{check_mirrors, MirrorHealth};
+use crate::parser::sink::OutputFormat;
+use crate::parser::{parse_dump_file_with_options, DumpParseOptions};
+use crate::urls::{DumpBaseUrl, DumpIndexUrl};
+use crate::{download_language_at_date, list_available_dates, list_wiktionary_dump_languages};
+use chrono::NaiveDate;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+
+/// How long the list of available dates of a language is reused between jobs.
+const AVAILABLE_DATES_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+/// How long the list of languages with dumps is reused between jobs.
+const DUMP_LANGUAGES_CACHE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long the health of the mirrors is reused between jobs.
+const MIRROR_HEALTH_CACHE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// A job request sent to the daemon.
+/// Requests are sent as one json object per line over the daemon socket.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonJob {
+    /// The wiktionary abbreviation of the language to extract, e.g. `en`.
+    pub wiktionary_abbreviation: String,
+    /// The date of the dump to extract.
+    /// If not given, the second to last available date is used, like in [`download_language`](crate::download_language).
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Output pretty-printed json instead of compact json.
+    #[serde(default)]
+    pub output_pretty: bool,
+    /// How the json objects of the output are separated, `json` or `json-lines`.
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// A status update about a job, sent back to the client as one json object per line.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum DaemonJobStatus {
+    /// The job was accepted, and there are `position` jobs before it in the queue.
+    Queued { position: usize },
+    /// The job was selected to be executed next.
+    Started,
+    /// The dump file was downloaded, or was present already.
+    Downloaded { dump_file: PathBuf },
+    /// The job finished successfully.
+    Finished {
+        output_file: PathBuf,
+        error_log: PathBuf,
+    },
+    /// The job failed.
+    Failed { error: String },
+}
+
+/// The configuration of the daemon.
+#[derive(Debug, Clone)]
+pub struct DaemonConfiguration {
+    /// The unix socket the daemon listens on for job requests.
+    pub socket: PathBuf,
+    /// The directory where dumps and extracted files are stored.
+    pub target_directory: PathBuf,
+    /// Dumps are downloaded from this base url while it is healthy, and otherwise from the best known mirror.
+    pub base_url: DumpBaseUrl,
+    /// The index that lists the languages with dumps, such that jobs for other languages fail early.
+    pub index_url: DumpIndexUrl,
+    pub progress_delay_seconds: u64,
+}
+
+struct QueuedJob {
+    job: DaemonJob,
+    status_sender: mpsc::UnboundedSender<DaemonJobStatus>,
+}
+
+/// Data that is kept between jobs to avoid querying the dump server repeatedly.
+#[derive(Default)]
+struct DaemonCache {
+    available_dates: BTreeMap<LanguageCode, (Instant, Vec<NaiveDate>)>,
+    dump_languages: Option<(Instant, Vec<LanguageCode>)>,
+    /// The health of the base url and its fallback mirrors, ranked by [`check_mirrors`].
+    mirror_health: Option<(Instant, Vec<MirrorHealth>)>,
+}
+
+/// Run the daemon until the socket fails.
+/// Jobs are accepted concurrently from any amount of clients, but executed sequentially in the order they were received.
+pub async fn run_daemon(configuration: DaemonConfiguration) -> Result<()> {
+    if configuration.socket.exists() {
+        debug!("Removing stale socket {:?}", configuration.socket);
+        tokio::fs::remove_file(&configuration.socket).await?;
+    }
+    let listener = UnixListener::bind(&configuration.socket)?;
+    info!("Daemon listening on {:?}", configuration.socket);
+
+    let (job_sender, mut job_receiver) = mpsc::unbounded_channel();
+    let queue_length = Arc::new(AtomicUsize::new(0));
+
+    let accept_queue_length = queue_length.clone();
+    let acceptor = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    debug!("Accepted daemon client");
+                    tokio::spawn(handle_client(
+                        stream,
+                        job_sender.clone(),
+                        accept_queue_length.clone(),
+                    ));
+                }
+                Err(error) => return Error::from(error),
+            }
+        }
+    });
+
+    let mut cache = DaemonCache::default();
+    while let Some(QueuedJob { job, status_sender }) = job_receiver.recv().await {
+        queue_length.fetch_sub(1, Ordering::SeqCst);
+        info!("Starting job {job:?}");
+        // Sending fails only if the client disconnected, in which case we still execute the job.
+        status_sender.send(DaemonJobStatus::Started).ok();
+
+        match execute_job(&configuration, &mut cache, &job, &status_sender).await {
+            Ok((output_file, error_log)) => {
+                info!("Finished job {job:?}");
+                status_sender
+                    .send(DaemonJobStatus::Finished {
+                        output_file,
+                        error_log,
+                    })
+                    .ok();
+            }
+            Err(error) => {
+                warn!("Job {job:?} failed: {error}");
+                status_sender
+                    .send(DaemonJobStatus::Failed {
+                        error: error.to_string(),
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    // The job queue closes only after the acceptor stopped.
+    Err(acceptor
+        .await
+        .map_err(|error| Error::Other(format!("Daemon socket task failed: {error}")))?)
+}
+
+async fn handle_client(
+    stream: UnixStream,
+    job_sender: mpsc::UnboundedSender<QueuedJob>,
+    queue_length: Arc<AtomicUsize>,
+) {
+    if let Err(error) = handle_client_inner(stream, job_sender, queue_length).await {
+        warn!("Daemon client failed: {error}");
+    }
+}
+
+async fn handle_client_inner(
+    stream: UnixStream,
+    job_sender: mpsc::UnboundedSender<QueuedJob>,
+    queue_length: Arc<AtomicUsize>,
+) -> Result<()> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+    let (status_sender, mut status_receiver) = mpsc::unbounded_channel();
+    // Dropped when the client stops sending, such that we stop once all its jobs are done.
+    let mut status_sender = Some(status_sender);
+
+    loop {
+        tokio::select! {
+            line = lines.next_line(), if status_sender.is_some() => {
+                let line = if let Some(line) = line? {
+                    line
+                } else {
+                    status_sender = None;
+                    continue;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let status = match serde_json::from_str::<DaemonJob>(&line) {
+                    Ok(job) => {
+                        let position = queue_length.fetch_add(1, Ordering::SeqCst);
+                        // Unwrap cannot panic because this branch is only enabled while the sender exists.
+                        let status_sender = status_sender.as_ref().unwrap().clone();
+                        job_sender
+                            .send(QueuedJob { job, status_sender })
+                            .map_err(|_| Error::Other(format!("Daemon job queue is closed")))?;
+                        DaemonJobStatus::Queued { position }
+                    }
+                    Err(error) => DaemonJobStatus::Failed { error: format!("Invalid job request: {error}") },
+                };
+                write_status(&mut write_half, &status).await?;
+            }
+            status = status_receiver.recv() => {
+                if let Some(status) = status {
+                    write_status(&mut write_half, &status).await?;
+                } else {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+async fn write_status(
+    write_half: &mut (impl AsyncWrite + Unpin),
+    status: &DaemonJobStatus,
+) -> Result<()> {
+    let mut line = serde_json::to_vec(status)?;
+    line.push(b'\n');
+    write_half.write_all(&line).await?;
+    Ok(())
+}
+
+async fn execute_job(
+    configuration: &DaemonConfiguration,
+    cache: &mut DaemonCache,
+    job: &DaemonJob,
+    status_sender: &mpsc::UnboundedSender<DaemonJobStatus>,
+) -> Result<(PathBuf, PathBuf)> {
+    let language_code = LanguageCode::from_wiktionary_abbreviation(&job.wiktionary_abbreviation)?;
+    match cache_dump_languages(configuration, cache).await {
+        Ok(dump_languages) => {
+            if dump_languages.binary_search(&language_code).is_err() {
+                return Err(Error::Other(format!(
+                    "No wiktionary dumps exist for language {language_code:?}"
+                )));
+            }
+        }
+        Err(error) => warn!("Could not list the languages with dumps, trying anyways: {error}"),
+    }
+    let base_url = cache_healthy_base_url(configuration, cache).await;
+
+    let date = if let Some(date) = &job.date {
+        date.clone()
+    } else {
+        let available_dates = cache_available_dates(&base_url, cache, &language_code).await?;
+        if available_dates.len() < 2 {
+            return Err(Error::Other(format!(
+                "Less than two available dates: {available_dates:?}"
+            )));
+        }
+        format_dump_date(&available_dates[available_dates.len() - 2])
+    };
+
+    let dump_file = download_language_at_date(
+        &base_url,
+        &language_code,
+        &date,
+        &configuration.target_directory,
+        configuration.progress_delay_seconds,
+    )
+    .await?;
+    status_sender
+        .send(DaemonJobStatus::Downloaded {
+            dump_file: dump_file.clone(),
+        })
+        .ok();
+
+    let output_file = dump_file.with_extension(match job.format {
+        OutputFormat::Json => "json",
+        OutputFormat::JsonLines => "jsonl",
+    });
+    let error_log = dump_file.with_extension("errors.txt");
+    parse_dump_file_with_options(
+        &dump_file,
+        Some(&output_file),
+        |_| std::future::ready(Ok(())),
+        |_, _, _| {},
+        &error_log,
+        &DumpParseOptions {
+            output_pretty: job.output_pretty,
+            output_format: job.format,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok((output_file, error_log))
+}
+
+async fn cache_available_dates<'cache>(
+    base_url: &DumpBaseUrl,
+    cache: &'cache mut DaemonCache,
+    language_code: &LanguageCode,
+) -> Result<&'cache Vec<NaiveDate>> {
+    let now = Instant::now();
+    let is_fresh = cache
+        .available_dates
+        .get(language_code)
+        .filter(|(fetched, _)| now - *fetched < AVAILABLE_DATES_CACHE_DURATION)
+        .is_some();
+
+    if !is_fresh {
+        debug!("Refreshing available dates of {language_code:?}");
+        let available_dates = list_available_dates(base_url, language_code).await?;
+        cache
+            .available_dates
+            .insert(language_code.clone(), (now, available_dates));
+    }
+
+    // Unwrap cannot panic because we insert the entry above if it does not exist.
+    Ok(&cache.available_dates.get(language_code).unwrap().1)
+}
+
+async fn cache_dump_languages<'cache>(
+    configuration: &DaemonConfiguration,
+    cache: &'cache mut DaemonCache,
+) -> Result<&'cache Vec<LanguageCode>> {
+    let now = Instant::now();
+    let is_fresh = cache
+        .dump_languages
+        .as_ref()
+        .filter(|(fetched, _)| now - *fetched < DUMP_LANGUAGES_CACHE_DURATION)
+        .is_some();
+
+    if !is_fresh {
+        debug!("Refreshing languages with dumps");
+        let dump_languages = list_wiktionary_dump_languages(&configuration.index_url).await?;
+        cache.dump_languages = Some((now, dump_languages));
+    }
+
+    // Unwrap cannot panic because we set the entry above if it does not exist.
+    Ok(&cache.dump_languages.as_ref().unwrap().1)
+}
+
+/// The configured base url if it answered the last probe, and otherwise the best of the known mirrors.
+async fn cache_healthy_base_url(
+    configuration: &DaemonConfiguration,
+    cache: &mut DaemonCache,
+) -> DumpBaseUrl {
+    let now = Instant::now();
+    let is_fresh = cache
+        .mirror_health
+        .as_ref()
+        .filter(|(checked, _)| now - *checked < MIRROR_HEALTH_CACHE_DURATION)
+        .is_some();
+
+    if !is_fresh {
+        debug!("Refreshing mirror health");
+        let ranking = check_mirrors(&configuration.base_url.with_fallbacks(), None).await;
+        cache.mirror_health = Some((now, ranking));
+    }
+
+    // Unwrap cannot panic because we set the entry above if it does not exist.
+    let ranking = &cache.mirror_health.as_ref().unwrap().1;
+    let is_available = |base_url: &str| {
+        ranking
+            .iter()
+            .any(|health| health.available && health.base_url == base_url)
+    };
+    if is_available(configuration.base_url.as_str()) {
+        configuration.base_url.clone()
+    } else if let Some(health) = ranking.iter().find(|health| health.available) {
+        warn!(
+            "Base url '{}' is unavailable, using mirror '{}'",
+            configuration.base_url.as_str(),
+            health.base_url
+        );
+        health.dump_base_url()
+    } else {
+        configuration.base_url.clone()
+    }
+}
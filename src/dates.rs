@@ -0,0 +1,59 @@
+//! Dates of dumps and timestamps of revisions.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// The format of dump dates in the paths and file names of dumps, e.g. `20230801`.
+pub const DUMP_DATE_FORMAT: &str = "%Y%m%d";
+
+/// The format of revision timestamps in dumps, e.g. `2023-08-01T12:34:56Z`.
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Parse a dump date formatted like `20230801`.
+pub fn parse_dump_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, DUMP_DATE_FORMAT)
+        .map_err(|error| Error::Other(format!("Invalid dump date {date:?}: {error}")))
+}
+
+/// Format a dump date like `20230801`.
+pub fn format_dump_date(date: &NaiveDate) -> String {
+    date.format(DUMP_DATE_FORMAT).to_string()
+}
+
+/// Parse a revision timestamp formatted like `2023-08-01T12:34:56Z`.
+/// Other RFC 3339 timestamps are accepted as well and converted to UTC.
+pub fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|error| Error::Other(format!("Invalid timestamp {timestamp:?}: {error}")))
+}
+
+/// Format a revision timestamp like `2023-08-01T12:34:56Z`, as in dumps.
+pub fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Serialise optional dump dates formatted like `20230801`, as they were before they were typed.
+#[cfg(feature = "words")]
+pub(crate) mod optional_dump_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        date: &Option<NaiveDate>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.serialize_some(&super::format_dump_date(date)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<NaiveDate>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|date| super::parse_dump_date(&date).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
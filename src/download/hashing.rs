@@ -0,0 +1,119 @@
+use crate::error::{Error, Result};
+use digest::Digest;
+use md5::Md5;
+use sha1::Sha1;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// The amount of chunks buffered for each background task before downloading waits for hashing.
+const CHUNK_CHANNEL_CAPACITY: usize = 64;
+
+/// Computes the md5 and sha1 checksums of a download from its chunks, either inline or in background tasks.
+///
+/// With zero tasks, chunks are hashed before the next chunk is received, which can limit the throughput of fast connections.
+/// With one task, all checksums are computed in a single background task fed by a channel.
+/// With more tasks, each algorithm is computed in its own background task.
+pub(crate) struct ChecksumHasher<Chunk> {
+    workers: Vec<DigestWorker<Chunk>>,
+}
+
+enum DigestWorker<Chunk> {
+    Inline(Digests),
+    Background {
+        sender: mpsc::Sender<Chunk>,
+        handle: JoinHandle<Digests>,
+    },
+}
+
+struct Digests {
+    md5: Option<Md5>,
+    sha1: Option<Sha1>,
+}
+
+impl<Chunk: AsRef<[u8]> + Clone + Send + 'static> ChecksumHasher<Chunk> {
+    pub fn new(md5: bool, sha1: bool, tasks: usize) -> Self {
+        if !md5 && !sha1 {
+            return Self {
+                workers: Vec::new(),
+            };
+        }
+        let digests = |md5: bool, sha1: bool| Digests {
+            md5: md5.then(Md5::default),
+            sha1: sha1.then(Sha1::default),
+        };
+        let workers = match tasks {
+            0 => vec![DigestWorker::Inline(digests(md5, sha1))],
+            1 => vec![DigestWorker::spawn(digests(md5, sha1))],
+            _ => [(md5, false), (false, sha1)]
+                .into_iter()
+                .filter(|(md5, sha1)| *md5 || *sha1)
+                .map(|(md5, sha1)| DigestWorker::spawn(digests(md5, sha1)))
+                .collect(),
+        };
+        Self { workers }
+    }
+
+    /// Hash the next chunk, waiting if the background tasks are too far behind.
+    pub async fn update(&mut self, chunk: &Chunk) -> Result<()> {
+        for worker in &mut self.workers {
+            match worker {
+                DigestWorker::Inline(digests) => digests.update(chunk.as_ref()),
+                DigestWorker::Background { sender, .. } => {
+                    sender
+                        .send(chunk.clone())
+                        .await
+                        .map_err(|_| Error::Other(format!("Checksum task stopped unexpectedly")))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait for all chunks to be hashed and return the hexadecimal md5 and sha1 digests that were requested.
+    pub async fn finish(self) -> Result<(Option<String>, Option<String>)> {
+        let mut md5 = None;
+        let mut sha1 = None;
+        for worker in self.workers {
+            let digests = match worker {
+                DigestWorker::Inline(digests) => digests,
+                DigestWorker::Background { sender, handle } => {
+                    drop(sender);
+                    handle
+                        .await
+                        .map_err(|error| Error::Other(format!("Checksum task failed: {error}")))?
+                }
+            };
+            if let Some(digest) = digests.md5 {
+                md5 = Some(format!("{:x}", digest.finalize()));
+            }
+            if let Some(digest) = digests.sha1 {
+                sha1 = Some(format!("{:x}", digest.finalize()));
+            }
+        }
+        Ok((md5, sha1))
+    }
+}
+
+impl<Chunk: AsRef<[u8]> + Send + 'static> DigestWorker<Chunk> {
+    fn spawn(mut digests: Digests) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<Chunk>(CHUNK_CHANNEL_CAPACITY);
+        let handle = tokio::task::spawn_blocking(move || {
+            while let Some(chunk) = receiver.blocking_recv() {
+                digests.update(chunk.as_ref());
+            }
+            digests
+        });
+        Self::Background { sender, handle }
+    }
+}
+
+impl Digests {
+    fn update(&mut self, chunk: &[u8]) {
+        if let Some(md5) = &mut self.md5 {
+            md5.update(chunk);
+        }
+        if let Some(sha1) = &mut self.sha1 {
+            sha1.update(chunk);
+        }
+    }
+}
@@ -1,41 +1,527 @@
 use crate::error::{Error, Result};
-use digest::Digest;
+use crate::progress::{ProgressPhase, ProgressReporter, ProgressUpdate, DEFAULT_PROGRESS_INTERVAL};
+use crate::store::verify_file;
+use futures_util::future::try_join_all;
 use futures_util::stream::StreamExt;
 use lexiclean::Lexiclean;
 use log::{debug, info, warn};
-use md5::Md5;
 use num_integer::Integer;
-use sha1::Sha1;
 use std::collections::VecDeque;
 use std::env;
-use std::path::PathBuf;
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 use tokio::time::Duration;
 use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
-pub async fn download_file_with_progress_log(
+use self::hashing::ChecksumHasher;
+pub use self::path_template::PathTemplate;
+pub use self::retry::RetryPolicy;
+
+pub(crate) mod hashing;
+pub mod path_template;
+pub mod retry;
+
+/// Options for [`download_file`].
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    pub progress_delay_seconds: u64,
+    /// The number of concurrent connections to download a file with.
+    /// If larger than one, the file is split into that many byte ranges, see [`download_file_parallel`].
+    pub connections: usize,
+    /// How failed http requests are retried.
+    pub retry_policy: RetryPolicy,
+    /// Which date is downloaded by [`download_language_with_options`](crate::download_language_with_options).
+    pub date_selection: DumpDateSelection,
+    /// Download dumps again even if they exist already and match their checksums.
+    /// Existing dumps that do not match their checksums are always downloaded again.
+    pub force: bool,
+    /// If a listing or download request fails, retry it on the other known mirrors, see [`DumpBaseUrl::with_fallbacks`](crate::urls::DumpBaseUrl::with_fallbacks).
+    pub mirror_fallback: bool,
+    /// The amount of background tasks that compute the checksums of downloads with a single connection.
+    /// With zero, checksums are computed inline, which can limit the throughput of fast connections.
+    /// With one, all checksums are computed in one task, and with more, each algorithm is computed in its own task.
+    pub checksum_tasks: usize,
+    /// If given, receives the progress of downloads in addition to the progress logged every `progress_delay_seconds`.
+    pub progress: Option<ProgressReporter>,
+    /// If given and cancelled, downloads stop after writing the data received so far, and return [`Error::Cancelled`].
+    /// The partial file is kept, but is downloaded again by the next download, since it does not match its checksums.
+    pub cancellation: Option<CancellationToken>,
+    /// Where dumps are stored within the target directory.
+    pub path_template: PathTemplate,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            progress_delay_seconds: 10,
+            connections: 1,
+            retry_policy: RetryPolicy::default(),
+            date_selection: DumpDateSelection::default(),
+            force: false,
+            mirror_fallback: true,
+            checksum_tasks: 1,
+            progress: None,
+            cancellation: None,
+            path_template: PathTemplate::default(),
+        }
+    }
+}
+
+/// How the date of a dump is selected from the available dates.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DumpDateSelection {
+    /// The latest date, whose dump may still be in progress.
+    Latest,
+    /// The second to last date, whose dump is usually complete.
+    SecondLatest,
+    /// The given date, like `20240101`.
+    Specific(String),
+    /// The latest date whose dump status file reports the articles dump as done.
+    LatestComplete,
+    /// The date the `latest` directory of the mirror stands for, see [`LATEST_DATE`](crate::urls::LATEST_DATE).
+    LatestAlias,
+}
+
+impl Default for DumpDateSelection {
+    fn default() -> Self {
+        Self::SecondLatest
+    }
+}
+
+impl FromStr for DumpDateSelection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "second-latest" => Ok(Self::SecondLatest),
+            "latest-complete" => Ok(Self::LatestComplete),
+            "latest-alias" => Ok(Self::LatestAlias),
+            date if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) => {
+                Ok(Self::Specific(date.to_string()))
+            }
+            other => Err(Error::Other(format!(
+                "Unknown dump date selection {other:?}, expected 'latest', 'second-latest', 'latest-complete', 'latest-alias' or a date like '20240101'"
+            ))),
+        }
+    }
+}
+
+/// Download a file with [`download_file_with_progress_log`] or [`download_file_parallel`], depending on the options.
+/// Downloads with a single connection are restarted when they fail transiently,
+/// while parallel downloads resume the failed byte ranges.
+pub async fn download_file(
+    from_url: &Url,
+    to_path: impl Into<PathBuf>,
+    expected_size: usize,
+    options: &DownloadOptions,
+    md5: Option<&str>,
+    sha1: Option<&str>,
+) -> Result<PathBuf> {
+    let to_path = to_path.into();
+    if options.connections > 1 {
+        download_file_parallel(
+            from_url,
+            to_path,
+            expected_size,
+            options.connections,
+            options.progress_delay_seconds,
+            &options.retry_policy,
+            options.progress.as_ref(),
+            options.cancellation.as_ref(),
+            md5,
+            sha1,
+        )
+        .await
+    } else {
+        options
+            .retry_policy
+            .retry(&format!("download of '{from_url}'"), || {
+                download_file_with_progress_log(
+                    from_url,
+                    to_path.clone(),
+                    expected_size,
+                    options.progress_delay_seconds,
+                    options.checksum_tasks,
+                    options.progress.as_ref(),
+                    options.cancellation.as_ref(),
+                    md5,
+                    sha1,
+                )
+            })
+            .await
+    }
+}
+
+/// Retrieve the body of the given url as text, retrying according to the policy.
+pub async fn get_text(url: &Url, retry_policy: &RetryPolicy) -> Result<String> {
+    retry_policy
+        .retry(&format!("request of '{url}'"), || async move {
+            Ok(reqwest::get(url.clone())
+                .await?
+                .error_for_status()?
+                .text()
+                .await?)
+        })
+        .await
+}
+
+/// Download a file by splitting it into `connections` byte ranges that are downloaded concurrently.
+/// Mirrors often throttle single connections, so this is much faster for large files.
+///
+/// If the server does not support range requests, the file is downloaded with a single connection instead.
+/// Byte ranges that fail transiently are resumed according to the retry policy.
+/// Checksums are verified after all ranges are downloaded.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_file_parallel(
     from_url: &Url,
     to_path: impl Into<PathBuf>,
     expected_size: usize,
+    connections: usize,
     progress_delay_seconds: u64,
+    retry_policy: &RetryPolicy,
+    progress: Option<&ProgressReporter>,
+    cancellation: Option<&CancellationToken>,
     md5: Option<&str>,
     sha1: Option<&str>,
 ) -> Result<PathBuf> {
-    let mut to_path = to_path.into();
-    if to_path.is_relative() {
+    let to_path = absolute_path(to_path.into())?;
+    let to_path_string = to_path.to_string_lossy();
+    let expected_content_length: u64 = expected_size.try_into().map_err(|_| {
+        Error::Other(format!(
+            "File size {} is larger than u64::MAX {}",
+            expected_size,
+            u64::MAX
+        ))
+    })?;
+
+    let client = reqwest::Client::new();
+    let client = &client;
+    let accepts_ranges = retry_policy
+        .retry(&format!("head request of '{from_url}'"), || async move {
+            Ok(client
+                .head(from_url.clone())
+                .send()
+                .await?
+                .error_for_status()?
+                .headers()
+                .get(reqwest::header::ACCEPT_RANGES)
+                .map(|accept_ranges| accept_ranges.as_bytes() == b"bytes")
+                .unwrap_or(false))
+        })
+        .await?;
+    if connections <= 1 || expected_content_length == 0 || !accepts_ranges {
+        if !accepts_ranges {
+            warn!("Server does not accept range requests for '{from_url}', downloading with a single connection");
+        }
+        return retry_policy
+            .retry(&format!("download of '{from_url}'"), || {
+                download_file_with_progress_log(
+                    from_url,
+                    to_path.clone(),
+                    expected_size,
+                    progress_delay_seconds,
+                    1,
+                    progress,
+                    cancellation,
+                    md5,
+                    sha1,
+                )
+            })
+            .await;
+    }
+
+    info!(
+        "Downloading file from '{from_url}' to '{to_path_string}' with {connections} connections"
+    );
+    if let Some(parent_dirs) = to_path.parent() {
+        tokio::fs::create_dir_all(parent_dirs).await?;
+    }
+    File::create(&to_path)
+        .await?
+        .set_len(expected_content_length)
+        .await?;
+
+    // The file is preallocated, so remove it on failure, such that it is not mistaken for a complete download.
+    // Cancelled downloads keep their partial file, which fails the checksums of the next download.
+    let result = async {
+        let start = download_ranges(
+            &to_path,
+            from_url,
+            client,
+            expected_content_length,
+            connections,
+            progress_delay_seconds,
+            progress,
+            cancellation,
+            retry_policy,
+        )
+        .await?;
+        if let Some(reason) = verify_file(
+            &to_path,
+            Some(expected_size),
+            md5.unwrap_or_default(),
+            sha1.unwrap_or_default(),
+            None,
+        )
+        .await?
+        {
+            return Err(Error::Other(reason));
+        }
+        Ok(start)
+    }
+    .await;
+    let start = match result {
+        Ok(start) => start,
+        Err(Error::Cancelled) => return Err(Error::Cancelled),
+        Err(error) => {
+            if let Err(remove_error) = tokio::fs::remove_file(&to_path).await {
+                warn!("Removing incomplete download '{to_path_string}' failed: {remove_error}");
+            }
+            return Err(error);
+        }
+    };
+
+    report_download_progress(
+        progress,
+        expected_content_length,
+        expected_content_length,
+        start,
+        true,
+    );
+    info!("Finished downloading file from '{from_url}' to '{to_path_string}'");
+    Ok(to_path)
+}
+
+/// Download the file in `connections` ranges into the preallocated file, logging and reporting progress.
+/// Returns the time the download started.
+#[allow(clippy::too_many_arguments)]
+async fn download_ranges(
+    to_path: &Path,
+    from_url: &Url,
+    client: &reqwest::Client,
+    expected_content_length: u64,
+    connections: usize,
+    progress_delay_seconds: u64,
+    progress: Option<&ProgressReporter>,
+    cancellation: Option<&CancellationToken>,
+    retry_policy: &RetryPolicy,
+) -> Result<Instant> {
+    let connections = connections as u64;
+    let range_length = (expected_content_length + connections - 1) / connections;
+    let downloaded = AtomicU64::new(0);
+    let downloads = try_join_all(
+        (0..connections)
+            .map(|index| index * range_length)
+            .take_while(|start| *start < expected_content_length)
+            .map(|start| {
+                let end = (start + range_length).min(expected_content_length) - 1;
+                download_range(
+                    client,
+                    from_url,
+                    to_path,
+                    start,
+                    end,
+                    retry_policy,
+                    &downloaded,
+                )
+            }),
+    );
+    tokio::pin!(downloads);
+
+    let expected_content_length_mib = expected_content_length / (1024 * 1024);
+    let start = Instant::now();
+    let mut progress_interval =
+        tokio::time::interval(Duration::from_secs(progress_delay_seconds.max(1)));
+    // Only polled if a reporter is given.
+    let mut report_interval = tokio::time::interval(
+        progress
+            .map(ProgressReporter::interval)
+            .unwrap_or(DEFAULT_PROGRESS_INTERVAL)
+            .max(Duration::from_millis(1)),
+    );
+    // The first ticks complete immediately.
+    progress_interval.tick().await;
+    report_interval.tick().await;
+    loop {
+        tokio::select! {
+            result = &mut downloads => {
+                result?;
+                break;
+            }
+            _ = cancelled(cancellation) => {
+                // Dropping the downloads stops them at their current chunk.
+                warn!("Download of '{from_url}' cancelled");
+                return Err(Error::Cancelled);
+            }
+            _ = progress_interval.tick() => {
+                let current_content_length = downloaded.load(Ordering::Relaxed);
+                let current_content_length_mib = current_content_length / (1024 * 1024);
+                let percent = current_content_length as f64 / expected_content_length as f64 * 100.0;
+                info!("{percent:.1}% {current_content_length_mib}MiB/{expected_content_length_mib}MiB");
+            }
+            _ = report_interval.tick(), if progress.is_some() => {
+                report_download_progress(progress, downloaded.load(Ordering::Relaxed), expected_content_length, start, false);
+            }
+        }
+    }
+    debug!("Download finished");
+    Ok(start)
+}
+
+/// Completes when the token is cancelled, or never if there is no token.
+async fn cancelled(cancellation: Option<&CancellationToken>) {
+    if let Some(cancellation) = cancellation {
+        cancellation.cancelled().await
+    } else {
+        std::future::pending().await
+    }
+}
+
+/// Hand the progress of a download to the reporter, if any.
+pub(crate) fn report_download_progress(
+    progress: Option<&ProgressReporter>,
+    bytes_done: u64,
+    bytes_total: u64,
+    start: Instant,
+    finished: bool,
+) {
+    if let Some(progress) = progress {
+        progress.report(&ProgressUpdate {
+            phase: ProgressPhase::Download,
+            bytes_done,
+            bytes_total: Some(bytes_total),
+            pages: 0,
+            pages_total: None,
+            words: 0,
+            elapsed: start.elapsed(),
+            finished,
+        });
+    }
+}
+
+/// Download the inclusive byte range from `start` to `end` into the same range of the file at `to_path`.
+/// After transient failures, the download resumes after the last byte written.
+async fn download_range(
+    client: &reqwest::Client,
+    from_url: &Url,
+    to_path: &Path,
+    start: u64,
+    end: u64,
+    retry_policy: &RetryPolicy,
+    downloaded: &AtomicU64,
+) -> Result<()> {
+    let mut position = start;
+    let mut attempt = 1;
+    loop {
+        match download_range_attempt(client, from_url, to_path, &mut position, end, downloaded)
+            .await
+        {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt < retry_policy.max_attempts && retry::is_transient(&error) => {
+                let backoff = retry_policy.backoff(attempt);
+                warn!(
+                    "Attempt {attempt}/{} of bytes {start}-{end} of '{from_url}' failed at byte {position}, resuming in {:.1}s: {error}",
+                    retry_policy.max_attempts,
+                    backoff.as_secs_f64()
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Download the inclusive byte range from `position` to `end`, advancing `position` with each byte written.
+async fn download_range_attempt(
+    client: &reqwest::Client,
+    from_url: &Url,
+    to_path: &Path,
+    position: &mut u64,
+    end: u64,
+    downloaded: &AtomicU64,
+) -> Result<()> {
+    let start = *position;
+    debug!("Requesting bytes {start}-{end} of '{from_url}'");
+    let response = client
+        .get(from_url.clone())
+        .header(reqwest::header::RANGE, format!("bytes={start}-{end}"))
+        .send()
+        .await?
+        .error_for_status()?;
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        return Err(Error::Other(format!(
+            "Expected partial content for bytes {start}-{end} of '{from_url}', but got status {}",
+            response.status()
+        )));
+    }
+
+    let mut output_file = OpenOptions::new().write(true).open(to_path).await?;
+    output_file.seek(SeekFrom::Start(start)).await?;
+
+    let mut input_stream = response.bytes_stream();
+    while let Some(chunk) = input_stream.next().await {
+        let chunk = chunk?;
+        let chunk_length = chunk.len() as u64;
+        if chunk_length > end + 1 - *position {
+            return Err(Error::Other(format!(
+                "Received more than the requested bytes {start}-{end} of '{from_url}'"
+            )));
+        }
+        output_file.write_all(&chunk).await?;
+        *position += chunk_length;
+        downloaded.fetch_add(chunk_length, Ordering::Relaxed);
+    }
+    output_file.flush().await?;
+
+    if *position <= end {
+        return Err(Error::Other(format!(
+            "Missing {} bytes of the requested bytes {start}-{end} of '{from_url}'",
+            end + 1 - *position
+        )));
+    }
+    Ok(())
+}
+
+fn absolute_path(mut path: PathBuf) -> Result<PathBuf> {
+    if path.is_relative() {
         let mut current_dir = env::current_dir()?;
-        current_dir.push(to_path);
-        to_path = current_dir;
+        current_dir.push(path);
+        path = current_dir;
     }
+    Ok(path.lexiclean())
+}
 
-    let to_path = to_path.lexiclean();
+/// Download a file with a single connection, logging the progress every `progress_delay_seconds`.
+/// The given checksums are computed by `checksum_tasks` background tasks, see [`DownloadOptions::checksum_tasks`].
+/// If a progress reporter is given, it additionally receives the progress at its own interval.
+/// If the cancellation token is cancelled, the download stops after writing the current chunk.
+/// An `expected_size` of zero means that the size is unknown, and it is not checked.
+#[allow(clippy::too_many_arguments)]
+pub async fn download_file_with_progress_log(
+    from_url: &Url,
+    to_path: impl Into<PathBuf>,
+    expected_size: usize,
+    progress_delay_seconds: u64,
+    checksum_tasks: usize,
+    progress: Option<&ProgressReporter>,
+    cancellation: Option<&CancellationToken>,
+    md5: Option<&str>,
+    sha1: Option<&str>,
+) -> Result<PathBuf> {
+    let to_path = absolute_path(to_path.into())?;
     let to_path_string = to_path.to_string_lossy();
     info!("Downloading file from '{from_url}' to '{to_path_string}'");
 
     debug!("Requesting file from server");
-    let url_connection = reqwest::get(from_url.clone()).await?;
+    let url_connection = reqwest::get(from_url.clone()).await?.error_for_status()?;
     let expected_content_length: u64 = expected_size.try_into().map_err(|_| {
         Error::Other(format!(
             "File size {} is larger than u64::MAX {}",
@@ -43,9 +529,8 @@ pub async fn download_file_with_progress_log(
             u64::MAX
         ))
     })?;
-    let expected_content_length_mib = expected_content_length / (1024 * 1024);
     if let Some(content_length) = url_connection.content_length() {
-        if content_length != expected_content_length {
+        if expected_content_length != 0 && content_length != expected_content_length {
             return Err(Error::Other(format!("Content length mismatch, status file declares {expected_content_length}, but server declares {content_length}")));
         }
     } else {
@@ -62,10 +547,50 @@ pub async fn download_file_with_progress_log(
 
     debug!("Creating local file");
     let mut output_file = File::create(&to_path).await?;
+    download_response(
+        from_url,
+        url_connection,
+        &mut output_file,
+        expected_content_length,
+        progress_delay_seconds,
+        checksum_tasks,
+        progress,
+        cancellation,
+        md5,
+        sha1,
+    )
+    .await?;
+    drop(output_file);
+
+    info!("Finished downloading file from '{from_url}' to '{to_path_string}'");
+    Ok(to_path)
+}
 
+/// Write the body of the response into the output, logging the progress every `progress_delay_seconds`,
+/// and verify the given checksums and the expected size, unless it is zero.
+/// If a progress reporter is given, it additionally receives the progress at its own interval.
+/// If the cancellation token is cancelled, the download stops after writing the current chunk.
+/// Returns the amount of bytes written.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn download_response<Output: AsyncWrite + Unpin + ?Sized>(
+    from_url: &Url,
+    response: reqwest::Response,
+    output: &mut Output,
+    expected_content_length: u64,
+    progress_delay_seconds: u64,
+    checksum_tasks: usize,
+    progress: Option<&ProgressReporter>,
+    cancellation: Option<&CancellationToken>,
+    md5: Option<&str>,
+    sha1: Option<&str>,
+) -> Result<u64> {
     debug!("Starting download");
-    let mut input_stream = url_connection.bytes_stream();
-    let mut last_progress_output = Instant::now();
+    let expected_content_length_mib = expected_content_length / (1024 * 1024);
+    let mut input_stream = response.bytes_stream();
+    let start = Instant::now();
+    let mut last_progress_output = start;
+    let mut last_progress_report = start;
+    let mut downloaded_content_length = 0;
     let mut last_content_lengths: VecDeque<(u64, Instant)> = VecDeque::new();
     let progress_delay = if progress_delay_seconds == 0 {
         warn!("Progress delay was set to zero, but needs to be at least one. Changing to one.");
@@ -77,74 +602,91 @@ pub async fn download_file_with_progress_log(
     let retained_content_length_amount: usize = (60 / progress_delay).max(1).try_into().unwrap();
     last_content_lengths.push_back((0, Instant::now()));
 
-    let mut md5_digest = Md5::default();
-    let mut sha1_digest = Sha1::default();
+    let mut checksum_hasher = ChecksumHasher::new(md5.is_some(), sha1.is_some(), checksum_tasks);
 
     while let Some(chunk) = input_stream.next().await {
         let chunk = chunk?;
+        checksum_hasher.update(&chunk).await?;
+
+        output.write_all(&chunk).await?;
+        downloaded_content_length += chunk.len() as u64;
+        if cancellation.map_or(false, CancellationToken::is_cancelled) {
+            output.flush().await?;
+            warn!("Download of '{from_url}' cancelled after {downloaded_content_length} bytes");
+            return Err(Error::Cancelled);
+        }
 
-        if md5.is_some() {
-            md5_digest.update(&chunk);
+        let now = Instant::now();
+        if let Some(progress) = progress {
+            if now - last_progress_report >= progress.interval() {
+                report_download_progress(
+                    Some(progress),
+                    downloaded_content_length,
+                    expected_content_length,
+                    start,
+                    false,
+                );
+                last_progress_report = now;
+            }
+        }
+        if last_progress_output + Duration::from_secs(progress_delay) >= now {
+            continue;
         }
-        if sha1.is_some() {
-            sha1_digest.update(&chunk);
+        let current_content_length = downloaded_content_length;
+        let current_content_length_mib = current_content_length / (1024 * 1024);
+        if expected_content_length == 0 {
+            info!("{current_content_length_mib}MiB");
+            last_progress_output = now;
+            continue;
         }
 
-        output_file.write_all(&chunk).await?;
+        let fraction = current_content_length as f64 / expected_content_length as f64;
+        let percent = fraction * 100.0;
 
-        let now = Instant::now();
-        if last_progress_output + Duration::from_secs(progress_delay) < now {
-            let current_content_length = output_file.metadata().await?.len();
-            let current_content_length_mib = current_content_length / (1024 * 1024);
-            let fraction = current_content_length as f64 / expected_content_length as f64;
-            let percent = fraction * 100.0;
-
-            let eta = if let Some((eta_content_length, eta_instant)) = last_content_lengths.front()
-            {
-                let eta_content_length_fraction = (current_content_length - eta_content_length)
-                    as f64
-                    / expected_content_length as f64;
-                let eta_multiplier = (1.0 - fraction) / eta_content_length_fraction;
-                let eta_duration_seconds = (now - *eta_instant).as_secs_f64() * eta_multiplier;
-                while last_content_lengths.len() >= retained_content_length_amount {
-                    last_content_lengths.pop_front();
-                }
+        let eta = if let Some((eta_content_length, eta_instant)) = last_content_lengths.front() {
+            let eta_content_length_fraction = (current_content_length - eta_content_length) as f64
+                / expected_content_length as f64;
+            let eta_multiplier = (1.0 - fraction) / eta_content_length_fraction;
+            let eta_duration_seconds = (now - *eta_instant).as_secs_f64() * eta_multiplier;
+            while last_content_lengths.len() >= retained_content_length_amount {
+                last_content_lengths.pop_front();
+            }
+
+            if eta_duration_seconds < 1.0 {
+                "<1s".to_string()
+            } else {
+                let eta_duration_seconds = eta_duration_seconds.round() as u64;
+                let (eta_duration_minutes, seconds) = eta_duration_seconds.div_rem(&60);
+                let (eta_duration_hours, minutes) = eta_duration_minutes.div_rem(&60);
+                let (days, hours) = eta_duration_hours.div_rem(&24);
 
-                if eta_duration_seconds < 1.0 {
-                    "<1s".to_string()
+                if days > 9999 {
+                    ">9999d".to_string()
+                } else if days > 0 {
+                    format!("{days}d {hours}h")
+                } else if hours > 0 {
+                    format!("{hours}h {minutes}m")
+                } else if minutes > 0 {
+                    format!("{minutes}m {seconds}s")
                 } else {
-                    let eta_duration_seconds = eta_duration_seconds.round() as u64;
-                    let (eta_duration_minutes, seconds) = eta_duration_seconds.div_rem(&60);
-                    let (eta_duration_hours, minutes) = eta_duration_minutes.div_rem(&60);
-                    let (days, hours) = eta_duration_hours.div_rem(&24);
-
-                    if days > 9999 {
-                        ">9999d".to_string()
-                    } else if days > 0 {
-                        format!("{days}d {hours}h")
-                    } else if hours > 0 {
-                        format!("{hours}h {minutes}m")
-                    } else if minutes > 0 {
-                        format!("{minutes}m {seconds}s")
-                    } else {
-                        format!("{seconds}s")
-                    }
+                    format!("{seconds}s")
                 }
-            } else {
-                "-".to_string()
-            };
+            }
+        } else {
+            "-".to_string()
+        };
 
-            info!("{percent:.1}% {current_content_length_mib}MiB/{expected_content_length_mib}MiB ETA {eta}");
-            last_progress_output = now;
-            last_content_lengths.push_back((current_content_length, now));
-        }
+        info!("{percent:.1}% {current_content_length_mib}MiB/{expected_content_length_mib}MiB ETA {eta}");
+        last_progress_output = now;
+        last_content_lengths.push_back((current_content_length, now));
     }
     debug!("Download finished");
     drop(input_stream);
+    output.flush().await?;
+    let (md5_digest, sha1_digest) = checksum_hasher.finish().await?;
 
-    if let Some(md5) = md5 {
+    if let (Some(md5), Some(md5_digest)) = (md5, md5_digest) {
         info!("Verifying md5 checksum");
-        let md5_digest = format!("{:x}", md5_digest.finalize());
         if md5_digest == md5 {
             info!("Md5 checksum matches");
         } else {
@@ -156,9 +698,8 @@ pub async fn download_file_with_progress_log(
         debug!("No md5 checksum given, skipping verification");
     }
 
-    if let Some(sha1) = sha1 {
+    if let (Some(sha1), Some(sha1_digest)) = (sha1, sha1_digest) {
         info!("Verifying sha1 checksum");
-        let sha1_digest = format!("{:x}", sha1_digest.finalize());
         if sha1_digest == sha1 {
             info!("Sha1 checksum matches");
         } else {
@@ -170,13 +711,16 @@ pub async fn download_file_with_progress_log(
         debug!("No sha1 checksum given, skipping verification");
     }
 
-    let output_file_length = output_file.metadata().await?.len();
-    if output_file_length != expected_content_length {
-        return Err(Error::Other(format!("Content length mismatch, status file declares {expected_content_length}, but we received {output_file_length}")));
+    if expected_content_length != 0 && downloaded_content_length != expected_content_length {
+        return Err(Error::Other(format!("Content length mismatch, status file declares {expected_content_length}, but we received {downloaded_content_length}")));
     }
 
-    drop(output_file);
-
-    info!("Finished downloading file from '{from_url}' to '{to_path_string}'");
-    Ok(to_path)
+    report_download_progress(
+        progress,
+        downloaded_content_length,
+        downloaded_content_length,
+        start,
+        true,
+    );
+    Ok(downloaded_content_length)
 }
@@ -0,0 +1,108 @@
+//! Templates for the paths of downloaded files, to fit downloads into existing directory layouts.
+
+use crate::error::{Error, Result};
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// The placeholders that can be used in a [`PathTemplate`].
+pub const PLACEHOLDERS: &[&str] = &["lang", "wiki", "date", "job", "file"];
+
+/// The path of a downloaded file relative to the target directory, with placeholders in braces:
+///
+///  * `{lang}`: the wiktionary abbreviation of the language, e.g. `en` or `zh-min-nan`.
+///  * `{wiki}`: the database name of the wiktionary, e.g. `enwiktionary` or `zh_min_nanwiktionary`.
+///  * `{date}`: the date of the dump, e.g. `20240101`.
+///  * `{job}`: the dump job of the file, e.g. `articlesdump`.
+///  * `{file}`: the name of the file, e.g. `enwiktionary-20240101-pages-articles.xml.bz2`.
+///
+/// The default is `{lang}/{date}/{file}`.
+/// The dump status file is stored as the file `dumpstatus.json` of the job.
+/// [`verify_store`](crate::store::verify_store) and [`prune_store`](crate::store::prune_store) expect the default layout.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PathTemplate {
+    template: String,
+}
+
+impl PathTemplate {
+    /// Check that the template has only known placeholders, contains `{file}`,
+    /// and stays within the target directory.
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let mut has_file = false;
+        for part in template.split('{').skip(1) {
+            let placeholder = part.split('}').next().unwrap_or_default();
+            if !part.contains('}') {
+                return Err(Error::Other(format!(
+                    "Unclosed placeholder in path template {template:?}"
+                )));
+            }
+            if !PLACEHOLDERS.contains(&placeholder) {
+                return Err(Error::Other(format!(
+                    "Unknown placeholder {{{placeholder}}} in path template {template:?}, expected one of {PLACEHOLDERS:?}"
+                )));
+            }
+            has_file |= placeholder == "file";
+        }
+        if !has_file {
+            return Err(Error::Other(format!(
+                "Path template {template:?} misses the placeholder {{file}}, such that all files would have the same path"
+            )));
+        }
+        if Path::new(&template)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(Error::Other(format!(
+                "Path template {template:?} must be relative and must not contain '.' or '..'"
+            )));
+        }
+
+        Ok(Self { template })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// The path of the file relative to the target directory.
+    pub fn render(
+        &self,
+        language_abbreviation: &str,
+        date: &str,
+        job: &str,
+        file: &str,
+    ) -> PathBuf {
+        let wiki = format!("{}wiktionary", language_abbreviation.replace('-', "_"));
+        PathBuf::from(
+            self.template
+                .replace("{lang}", language_abbreviation)
+                .replace("{wiki}", &wiki)
+                .replace("{date}", date)
+                .replace("{job}", job)
+                .replace("{file}", file),
+        )
+    }
+}
+
+impl Default for PathTemplate {
+    fn default() -> Self {
+        Self {
+            template: "{lang}/{date}/{file}".to_string(),
+        }
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl Display for PathTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.template)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::{Error, Result};
+use log::warn;
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, Duration};
+
+/// How often and how fast failed http operations are retried.
+/// Only transient errors are retried, i.e. connection errors, timeouts, and server errors.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first. One disables retrying.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The delay is never longer than this, before applying jitter.
+    pub max_backoff: Duration,
+    /// The factor by which the delay grows with each retry.
+    pub multiplier: f64,
+    /// The delay is randomly varied by up to this fraction, such that clients do not retry in lockstep.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// The delay after the given failed attempt, counting from one.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = (self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent))
+            .min(self.max_backoff.as_secs_f64());
+        // Maps the jitter source to [-1, 1).
+        let jitter = (jitter_source() * 2.0 - 1.0) * self.jitter.clamp(0.0, 1.0);
+        Duration::from_secs_f64((backoff * (1.0 + jitter)).max(0.0))
+    }
+
+    /// Run the operation until it succeeds, fails with a permanent error, or the attempts are exhausted.
+    pub async fn retry<T, Operation: Future<Output = Result<T>>>(
+        &self,
+        description: &str,
+        mut operation: impl FnMut() -> Operation,
+    ) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(result) => return Ok(result),
+                Err(error) if attempt < self.max_attempts && is_transient(&error) => {
+                    let backoff = self.backoff(attempt);
+                    warn!(
+                        "Attempt {attempt}/{} of {description} failed, retrying in {:.1}s: {error}",
+                        self.max_attempts,
+                        backoff.as_secs_f64()
+                    );
+                    sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Returns true if the error may disappear when retrying.
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::ReqwestError(error) => {
+            if let Some(status) = error.status() {
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            } else {
+                error.is_timeout() || error.is_connect() || error.is_request() || error.is_body()
+            }
+        }
+        _ => false,
+    }
+}
+
+/// A number in `[0, 1)` that varies between calls.
+/// This does not need to be a good random number, it only has to spread out the retries of different clients.
+fn jitter_source() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1_000_000) / 1_000_000.0
+}
@@ -0,0 +1,49 @@
+//! Edit distances for suggesting and tolerating misspelled names and headings.
+
+/// The levenshtein distance between the strings, counted in chars.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    let mut current_row = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = usize::from(a_char != *b_char);
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+
+    previous_row[b.len()]
+}
+
+/// Returns true if `a` can be transformed into `b` by a single insertion, deletion or substitution.
+#[cfg(feature = "words")]
+pub(crate) fn is_single_edit_apart(a: &str, b: &str) -> bool {
+    // Strings whose lengths differ by more than one are never a single edit apart, so skip computing the distance.
+    let (a_length, b_length) = (a.chars().count(), b.chars().count());
+    if a_length.max(b_length) - a_length.min(b_length) > 1 {
+        return false;
+    }
+    edit_distance(a, b) == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("german", "german"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("germen", "german"), 1);
+        assert_eq!(edit_distance("germn", "german"), 1);
+        assert_eq!(edit_distance("germann", "german"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("français", "francais"), 1);
+    }
+}
@@ -1,9 +1,11 @@
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[cfg(feature = "download")]
     #[error("error sending http request: {0}")]
     ReqwestError(#[from] reqwest::Error),
     #[error("regex error: {0}")]
@@ -16,10 +18,19 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error("error parsing utf-8: {0}")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
+    #[cfg(feature = "parse")]
     #[error("error parsing xml: {0}")]
     QuickXmlError(#[from] quick_xml::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {0}")]
+    SqliteError(#[from] rusqlite::Error),
+    #[cfg(feature = "cloud")]
+    #[error("object store error: {0}")]
+    ObjectStoreError(#[from] object_store::Error),
+    #[cfg(feature = "parse")]
     #[error("error parsing xml attribute: {0}")]
     QuickXmlAttributeError(#[from] quick_xml::events::attributes::AttrError),
+    #[cfg(feature = "words")]
     #[error("error parsing page {page_name:?}: {error}")]
     WikitextParserError {
         /// The error returned by the parser.
@@ -34,6 +45,22 @@ pub enum Error {
     #[error("unknown English language name: {0:?}")]
     UnknownEnglishLanguageName(String),
 
+    /// The given english language name is unknown, but close to a known language name.
+    #[error("unknown English language name: {name:?}, did you mean {suggestion:?}?")]
+    UnknownEnglishLanguageNameWithSuggestion {
+        name: String,
+        /// The closest known language name.
+        suggestion: String,
+    },
+
+    /// A heading in a language section of a page is neither a known word type nor ignored.
+    #[error("Unknown subsection of language {language_english_name}: {label}")]
+    UnknownHeading {
+        language_english_name: String,
+        /// The heading as written on the page.
+        label: String,
+    },
+
     /// The given wiktionary language abbreviation is unknown.
     #[error("unknown wiktionary language abbreviation: {0}")]
     UnknownWiktionaryLanguageAbbreviation(String),
@@ -46,4 +73,110 @@ pub enum Error {
     WordConsumer {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
+
+    /// The operation was cancelled by its cancellation token, after writing the output produced so far.
+    #[error("cancelled")]
+    Cancelled,
+
+    /// An error that occurred at a known location in a dump.
+    #[error("{source} ({context})")]
+    InContext {
+        source: Box<Error>,
+        context: ErrorContext,
+    },
+}
+
+/// Where in a dump an error occurred, to be able to reproduce it against the page causing it.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ErrorContext {
+    /// The title of the page that was parsed, if the error occurred within a page and after its title.
+    pub page_title: Option<String>,
+    /// The id of the page that was parsed, if the error occurred within a page and after its id.
+    pub page_id: Option<i64>,
+    /// The position of the xml reader in the decompressed dump when the error was detected.
+    pub byte_offset: Option<u64>,
+}
+
+impl Error {
+    /// Attach the given context to this error.
+    /// If the error has a context already, its fields are kept and only missing fields are taken from the given context.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Error::InContext {
+                source,
+                context: existing_context,
+            } => Error::InContext {
+                source,
+                context: ErrorContext {
+                    page_title: existing_context.page_title.or(context.page_title),
+                    page_id: existing_context.page_id.or(context.page_id),
+                    byte_offset: existing_context.byte_offset.or(context.byte_offset),
+                },
+            },
+            error => Error::InContext {
+                source: Box::new(error),
+                context,
+            },
+        }
+    }
+
+    /// The location in the dump where this error occurred, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::InContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// This error without its context.
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::InContext { source, .. } => source.without_context(),
+            error => error,
+        }
+    }
+}
+
+impl ErrorContext {
+    /// A context with only the given byte offset.
+    pub fn at_byte_offset(byte_offset: u64) -> Self {
+        Self {
+            byte_offset: Some(byte_offset),
+            ..Default::default()
+        }
+    }
+
+    /// A context with only the given page.
+    pub fn in_page(page_title: &str, page_id: i64) -> Self {
+        Self {
+            page_title: Some(page_title.to_string()),
+            page_id: Some(page_id),
+            ..Default::default()
+        }
+    }
+}
+
+/// Formats the context like `page "word" (id 123) at byte 4567`.
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut separator = "";
+        if let Some(page_title) = &self.page_title {
+            write!(f, "page {page_title:?}")?;
+            separator = " ";
+        }
+        if let Some(page_id) = self.page_id {
+            if self.page_title.is_some() {
+                write!(f, " (id {page_id})")?;
+            } else {
+                write!(f, "page id {page_id}")?;
+            }
+            separator = " ";
+        }
+        if let Some(byte_offset) = self.byte_offset {
+            write!(f, "{separator}at byte {byte_offset}")?;
+        } else if separator.is_empty() {
+            write!(f, "unknown location")?;
+        }
+        Ok(())
+    }
 }
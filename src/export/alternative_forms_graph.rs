@@ -0,0 +1,217 @@
+use crate::error::{Error, Result};
+use crate::parser::words::alternative_forms::AlternativeForm;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+
+/// Identifies the binary format of [`AlternativeFormsGraph`].
+const MAGIC: &[u8; 8] = b"WDPAFG01";
+
+/// An undirected graph of alternative forms per language, for expanding search queries.
+///
+/// The graph is stored in a compact binary format: after a magic number, each language is written as
+/// its name, its sorted terms and its edges as pairs of term indices.
+/// All integers are unsigned LEB128, strings are prefixed by their length in bytes,
+/// and the edges of each term are delta-encoded.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AlternativeFormsGraph {
+    /// The neighbours of each term by language.
+    languages: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl AlternativeFormsGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an undirected edge between the word and its alternative form.
+    pub fn add(&mut self, alternative_form: &AlternativeForm) {
+        self.add_edge(
+            &alternative_form.language_english_name,
+            &alternative_form.word,
+            &alternative_form.alternative,
+        );
+    }
+
+    pub fn add_edge(&mut self, language_english_name: &str, a: &str, b: &str) {
+        if a == b {
+            return;
+        }
+        let terms = self
+            .languages
+            .entry(language_english_name.to_string())
+            .or_default();
+        terms
+            .entry(a.to_string())
+            .or_default()
+            .insert(b.to_string());
+        terms
+            .entry(b.to_string())
+            .or_default()
+            .insert(a.to_string());
+    }
+
+    /// The direct alternative forms of the term.
+    pub fn neighbours<'a>(
+        &'a self,
+        language_english_name: &str,
+        term: &str,
+    ) -> impl Iterator<Item = &'a str> {
+        self.languages
+            .get(language_english_name)
+            .and_then(|terms| terms.get(term))
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// All terms connected to the term, excluding the term itself, sorted.
+    /// This is the set of terms a query for the term should be expanded to.
+    pub fn expansions(&self, language_english_name: &str, term: &str) -> Vec<&str> {
+        let terms = if let Some(terms) = self.languages.get(language_english_name) {
+            terms
+        } else {
+            return Vec::new();
+        };
+
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![term];
+        while let Some(current) = stack.pop() {
+            if let Some(neighbours) = terms.get(current) {
+                for neighbour in neighbours {
+                    if neighbour != term && visited.insert(neighbour.as_str()) {
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+        visited.into_iter().collect()
+    }
+
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.keys().map(String::as_str)
+    }
+
+    /// The number of edges over all languages.
+    pub fn edge_count(&self) -> usize {
+        self.languages
+            .values()
+            .flat_map(|terms| terms.values())
+            .map(BTreeSet::len)
+            .sum::<usize>()
+            / 2
+    }
+
+    /// Write the graph in its binary format.
+    pub fn write(&self, mut output: impl Write) -> Result<()> {
+        output.write_all(MAGIC)?;
+        write_number(&mut output, self.languages.len() as u64)?;
+        for (language_english_name, terms) in &self.languages {
+            write_string(&mut output, language_english_name)?;
+            write_number(&mut output, terms.len() as u64)?;
+            let indices: BTreeMap<_, _> = terms
+                .keys()
+                .enumerate()
+                .map(|(index, term)| (term.as_str(), index as u64))
+                .collect();
+            for term in terms.keys() {
+                write_string(&mut output, term)?;
+            }
+
+            // Only edges to terms with a larger index are written, as the graph is undirected.
+            for (index, neighbours) in terms.values().enumerate() {
+                let index = index as u64;
+                let larger: Vec<_> = neighbours
+                    .iter()
+                    .map(|neighbour| indices[neighbour.as_str()])
+                    .filter(|neighbour| *neighbour > index)
+                    .collect();
+                write_number(&mut output, larger.len() as u64)?;
+                let mut previous = index;
+                for neighbour in larger {
+                    write_number(&mut output, neighbour - previous)?;
+                    previous = neighbour;
+                }
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Read a graph written by [`AlternativeFormsGraph::write`].
+    pub fn read(mut input: impl Read) -> Result<Self> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Error::Other(format!(
+                "Not an alternative forms graph, found magic number {magic:?}"
+            )));
+        }
+
+        let mut graph = Self::new();
+        for _ in 0..read_number(&mut input)? {
+            let language_english_name = read_string(&mut input)?;
+            let term_count = read_number(&mut input)?;
+            let mut terms = Vec::new();
+            for _ in 0..term_count {
+                terms.push(read_string(&mut input)?);
+            }
+
+            for index in 0..terms.len() {
+                let mut neighbour = index as u64;
+                for _ in 0..read_number(&mut input)? {
+                    neighbour += read_number(&mut input)?;
+                    let neighbour = terms.get(neighbour as usize).ok_or_else(|| {
+                        Error::Other(format!("Term index {neighbour} is out of bounds"))
+                    })?;
+                    graph.add_edge(&language_english_name, &terms[index], neighbour);
+                }
+            }
+        }
+        Ok(graph)
+    }
+}
+
+fn write_number(output: &mut impl Write, mut number: u64) -> Result<()> {
+    loop {
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            output.write_all(&[byte])?;
+            return Ok(());
+        }
+        output.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_number(input: &mut impl Read) -> Result<u64> {
+    let mut number = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        if shift >= 64 {
+            return Err(Error::Other(format!("Number is too large")));
+        }
+        number |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(number);
+        }
+        shift += 7;
+    }
+}
+
+fn write_string(output: &mut impl Write, string: &str) -> Result<()> {
+    write_number(output, string.len() as u64)?;
+    output.write_all(string.as_bytes())?;
+    Ok(())
+}
+
+fn read_string(input: &mut impl Read) -> Result<String> {
+    let length = read_number(input)?;
+    let mut bytes = Vec::new();
+    (&mut *input).take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(Error::Other(format!("Unexpected end of string")));
+    }
+    Ok(String::from_utf8(bytes)?)
+}
@@ -0,0 +1,298 @@
+use crate::error::{Error, Result};
+use crate::export::word_tags::{Tags, WordTags};
+use crate::parser::words::clean_text::{clean_text, CleanTextOptions};
+use crate::parser::words::definitions::parse_definition;
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::templates::{section_definitions, text_templates};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+use wikitext_parser::{Section, Wikitext};
+
+/// An entry of a dictionary for e-readers.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DictionaryEntry {
+    pub headword: String,
+    pub word_type: String,
+    /// The definitions as plain text.
+    pub definitions: Vec<String>,
+    /// The inflected forms under which the entry can be looked up, sorted.
+    pub inflections: Vec<String>,
+    /// External tags of the headword like its CEFR level, see [`EreaderDictionaryBuilder::word_tags`].
+    pub tags: Tags,
+}
+
+/// The dictionary formats supported by e-readers.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EreaderFormat {
+    /// Source files for the Kindle dictionary compiler, i.e. an xhtml file with `idx` markup and an opf file.
+    Kindle,
+    /// The `dictfile` format of dictgen, which compiles it into a Kobo `dicthtml` dictionary.
+    Kobo,
+}
+
+impl FromStr for EreaderFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "kindle" => Ok(Self::Kindle),
+            "kobo" => Ok(Self::Kobo),
+            other => Err(Error::Other(format!(
+                "Unknown e-reader format {other:?}, expected 'kindle' or 'kobo'"
+            ))),
+        }
+    }
+}
+
+/// Collects the entries of a single language for an e-reader dictionary.
+///
+/// Definitions that are form-of templates like `{{plural of|en|dog}}` are not added as definitions,
+/// but instead make the page title an inflection of the lemma.
+#[derive(Debug, Clone)]
+pub struct EreaderDictionaryBuilder {
+    language_english_name: String,
+    /// Skip definitions labeled as offensive, see [`Definition::is_offensive`](crate::parser::words::definitions::Definition::is_offensive).
+    exclude_offensive: bool,
+    /// Maps headwords and word types to definitions.
+    definitions: BTreeMap<(String, String), Vec<String>>,
+    /// Maps lemmas to their inflected forms.
+    inflections: BTreeMap<String, BTreeSet<String>>,
+    /// External tags of the headwords and the language code to look them up with.
+    word_tags: Option<(Arc<WordTags>, String)>,
+}
+
+impl EreaderDictionaryBuilder {
+    pub fn new(language_english_name: impl Into<String>) -> Self {
+        Self {
+            language_english_name: language_english_name.into(),
+            exclude_offensive: false,
+            definitions: Default::default(),
+            inflections: Default::default(),
+            word_tags: None,
+        }
+    }
+
+    /// Join the tags of the headwords in the language with the given code onto the entries.
+    pub fn word_tags(mut self, word_tags: Arc<WordTags>, language_code: impl Into<String>) -> Self {
+        self.word_tags = Some((word_tags, language_code.into()));
+        self
+    }
+
+    /// Skip definitions labeled as vulgar, offensive, derogatory and the like,
+    /// e.g. for dictionaries used by children.
+    pub fn exclude_offensive(mut self, exclude_offensive: bool) -> Self {
+        self.exclude_offensive = exclude_offensive;
+        self
+    }
+
+    pub fn add_page(&mut self, title: &str, wikitext: &Wikitext) {
+        if wikitext.root_section.headline.level != 1 {
+            return;
+        }
+
+        for language_subsection in &wikitext.root_section.subsections {
+            if language_subsection.headline.label == self.language_english_name {
+                self.add_section(title, language_subsection);
+            }
+        }
+    }
+
+    fn add_section(&mut self, title: &str, section: &Section) {
+        for subsection in &section.subsections {
+            if let HeadingKind::WordType(word_type) = classify_heading(&subsection.headline.label) {
+                for text in &section_definitions(subsection) {
+                    if let Some(lemma) = text_templates(text)
+                        .iter()
+                        .find(|template| template.name.ends_with(" of"))
+                        .and_then(|template| template.positional(1))
+                        .filter(|lemma| !lemma.is_empty() && *lemma != title)
+                    {
+                        self.inflections
+                            .entry(lemma.to_string())
+                            .or_default()
+                            .insert(title.to_string());
+                        continue;
+                    }
+
+                    if self.exclude_offensive
+                        && parse_definition(text)
+                            .map_or(false, |definition| definition.is_offensive())
+                    {
+                        continue;
+                    }
+
+                    let definition = clean_text(text, &CleanTextOptions::default());
+                    if !definition.is_empty() {
+                        self.definitions
+                            .entry((title.to_string(), word_type.to_string()))
+                            .or_default()
+                            .push(definition);
+                    }
+                }
+            }
+
+            self.add_section(title, subsection);
+        }
+    }
+
+    /// The entries ordered by headword and word type.
+    pub fn finish(self) -> Vec<DictionaryEntry> {
+        let inflections = self.inflections;
+        let word_tags = self.word_tags;
+        self.definitions
+            .into_iter()
+            .map(|((headword, word_type), definitions)| DictionaryEntry {
+                inflections: inflections
+                    .get(&headword)
+                    .map(|forms| forms.iter().cloned().collect())
+                    .unwrap_or_default(),
+                tags: word_tags
+                    .as_ref()
+                    .map(|(word_tags, language_code)| word_tags.get(language_code, &headword))
+                    .unwrap_or_default(),
+                headword,
+                word_type,
+                definitions,
+            })
+            .collect()
+    }
+}
+
+/// Write the entries as xhtml with the `idx` markup of Kindle dictionaries.
+pub fn write_kindle_html(entries: &[DictionaryEntry], mut output: impl Write) -> Result<()> {
+    writeln!(
+        output,
+        r#"<html xmlns:idx="www.mobipocket.com" xmlns:mbp="www.mobipocket.com" xmlns:xlink="http://www.w3.org/1999/xlink">"#
+    )?;
+    writeln!(
+        output,
+        "<head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=utf-8\"></head>"
+    )?;
+    writeln!(output, "<body><mbp:frameset>")?;
+
+    for entry in entries {
+        writeln!(
+            output,
+            "<idx:entry name=\"default\" scriptable=\"yes\" spell=\"yes\">"
+        )?;
+        writeln!(
+            output,
+            "<idx:orth value=\"{}\"><b>{}</b>",
+            escape_html(&entry.headword),
+            escape_html(&entry.headword)
+        )?;
+        if !entry.inflections.is_empty() {
+            write!(output, "<idx:infl>")?;
+            for inflection in &entry.inflections {
+                write!(output, "<idx:iform value=\"{}\"/>", escape_html(inflection))?;
+            }
+            writeln!(output, "</idx:infl>")?;
+        }
+        writeln!(output, "</idx:orth>")?;
+        writeln!(output, "<i>{}</i>", escape_html(&entry.word_type))?;
+        write_tags_html(&entry.tags, &mut output)?;
+        write_definitions_html(&entry.definitions, &mut output)?;
+        writeln!(output, "</idx:entry>")?;
+        writeln!(output, "<hr/>")?;
+    }
+
+    writeln!(output, "</mbp:frameset></body></html>")?;
+    Ok(())
+}
+
+/// Write the package file for the Kindle dictionary compiler, referring to the xhtml file `html_file_name`.
+/// The languages are given as codes like `en`.
+pub fn write_kindle_opf(
+    title: &str,
+    input_language_code: &str,
+    output_language_code: &str,
+    html_file_name: &str,
+    mut output: impl Write,
+) -> Result<()> {
+    let title = escape_html(title);
+    let input_language_code = escape_html(input_language_code);
+    let output_language_code = escape_html(output_language_code);
+    let html_file_name = escape_html(html_file_name);
+    write!(
+        output,
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<package unique-identifier="uid">
+  <metadata>
+    <dc-metadata xmlns:dc="http://purl.org/metadata/dublin_core">
+      <dc:Identifier id="uid">{title}</dc:Identifier>
+      <dc:Title>{title}</dc:Title>
+      <dc:Language>{input_language_code}</dc:Language>
+    </dc-metadata>
+    <x-metadata>
+      <DictionaryInLanguage>{input_language_code}</DictionaryInLanguage>
+      <DictionaryOutLanguage>{output_language_code}</DictionaryOutLanguage>
+    </x-metadata>
+  </metadata>
+  <manifest>
+    <item id="dictionary" href="{html_file_name}" media-type="text/x-oeb1-document"/>
+  </manifest>
+  <spine>
+    <itemref idref="dictionary"/>
+  </spine>
+</package>
+"#
+    )?;
+    Ok(())
+}
+
+/// Write the entries in the `dictfile` format of dictgen.
+pub fn write_kobo_dictfile(entries: &[DictionaryEntry], mut output: impl Write) -> Result<()> {
+    for entry in entries {
+        writeln!(output, "@ {}", sanitise_line(&entry.headword))?;
+        writeln!(output, ": {}", sanitise_line(&entry.word_type))?;
+        for inflection in &entry.inflections {
+            writeln!(output, "& {}", sanitise_line(inflection))?;
+        }
+        write_tags_html(&entry.tags, &mut output)?;
+        write_definitions_html(&entry.definitions, &mut output)?;
+    }
+    Ok(())
+}
+
+fn write_definitions_html(definitions: &[String], output: &mut impl Write) -> Result<()> {
+    write!(output, "<ol>")?;
+    for definition in definitions {
+        write!(
+            output,
+            "<li>{}</li>",
+            escape_html(&sanitise_line(definition))
+        )?;
+    }
+    writeln!(output, "</ol>")?;
+    Ok(())
+}
+
+/// Write the tags like `<p><small>cefr: A1; topic: animals, food</small></p>`, if there are any.
+fn write_tags_html(tags: &Tags, output: &mut impl Write) -> Result<()> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    let tags: Vec<_> = tags
+        .iter()
+        .map(|(category, values)| format!("{category}: {}", values.join(", ")))
+        .collect();
+    writeln!(
+        output,
+        "<p><small>{}</small></p>",
+        escape_html(&sanitise_line(&tags.join("; ")))
+    )?;
+    Ok(())
+}
+
+fn sanitise_line(line: &str) -> String {
+    line.replace(['\n', '\r'], " ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,131 @@
+use crate::error::Result;
+use crate::parser::words::inflections::InflectedForm;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Lookup tables from inflected forms to their lemmas and tags, one per language.
+///
+/// The tables are written as csv files with the columns `form`, `lemma` and `tags`,
+/// where the tags are separated by spaces.
+/// Rows are sorted bytewise by form, such that the files can be turned into finite state transducers
+/// without sorting them again.
+#[derive(Debug, Clone, Default)]
+pub struct InflectionTables {
+    tables: BTreeMap<String, BTreeSet<(String, String, String)>>,
+}
+
+impl InflectionTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the inflected form to the table of its language, unless it is contained already.
+    pub fn add(&mut self, inflected_form: &InflectedForm) {
+        self.tables
+            .entry(inflected_form.language_english_name.clone())
+            .or_default()
+            .insert((
+                inflected_form.form.clone(),
+                inflected_form.lemma.clone(),
+                inflected_form.tags.join(" "),
+            ));
+    }
+
+    /// The english names of the languages that have a table.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// The amount of rows over all tables.
+    pub fn len(&self) -> usize {
+        self.tables.values().map(BTreeSet::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The lemmas and tags of the form in the given language.
+    pub fn lookup<'a>(
+        &'a self,
+        language_english_name: &str,
+        form: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.tables
+            .get(language_english_name)
+            .into_iter()
+            .flat_map(move |table| {
+                table
+                    .range((form.to_string(), String::new(), String::new())..)
+                    .take_while(move |(table_form, _, _)| table_form == form)
+                    .map(|(_, lemma, tags)| (lemma.as_str(), tags.as_str()))
+            })
+    }
+
+    /// Write the table of a single language as csv, including a header row.
+    pub fn write_csv(&self, language_english_name: &str, mut output: impl Write) -> Result<()> {
+        writeln!(output, "form,lemma,tags")?;
+        if let Some(table) = self.tables.get(language_english_name) {
+            for (form, lemma, tags) in table {
+                writeln!(
+                    output,
+                    "{},{},{}",
+                    csv_field(form),
+                    csv_field(lemma),
+                    csv_field(tags)
+                )?;
+            }
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    /// Write the tables of all languages as csv files into the given directory, which is created if necessary.
+    /// The files are named after the languages, like `serbo_croatian.csv`.
+    /// Returns the paths of the written files.
+    pub fn write_csv_directory(&self, directory: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)?;
+
+        let mut paths = Vec::new();
+        for language_english_name in self.tables.keys() {
+            let path = directory.join(format!("{}.csv", table_file_name(language_english_name)));
+            self.write_csv(
+                language_english_name,
+                std::io::BufWriter::new(std::fs::File::create(&path)?),
+            )?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// The file name of the table of a language, e.g. `serbo_croatian` for `Serbo-Croatian`.
+fn table_file_name(language_english_name: &str) -> String {
+    let file_name: String = language_english_name
+        .trim()
+        .chars()
+        .flat_map(|c| {
+            if c.is_alphanumeric() {
+                c.to_lowercase().collect::<Vec<_>>()
+            } else {
+                vec!['_']
+            }
+        })
+        .collect();
+    if file_name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        file_name
+    }
+}
+
+/// Quote the field if it contains characters that are special in csv.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,228 @@
+//! A manifest of produced datasets, such that downstream loaders can find and verify them generically.
+
+use crate::dates::format_timestamp;
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// The default file name of a manifest within the directory of its datasets.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// The version of the format of the manifest itself.
+pub const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+/// The version of the schemas of the datasets written by this crate.
+/// It is incremented whenever fields are removed from a dataset or change their meaning.
+pub const DATASET_SCHEMA_VERSION: u32 = 1;
+
+/// A machine-readable list of produced datasets with their paths, record counts, schema versions and checksums.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DatasetManifest {
+    pub schema_version: u32,
+    /// The name and version of the program that wrote the manifest.
+    pub generator: String,
+    #[serde(with = "timestamp")]
+    pub generated_at: DateTime<Utc>,
+    /// The datasets, ordered by path.
+    pub datasets: Vec<DatasetEntry>,
+}
+
+/// A single produced dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct DatasetEntry {
+    /// The name of the dataset, e.g. `words` or `translations`.
+    pub name: String,
+    /// The path of the dataset, relative to the directory of the manifest if it is within that directory.
+    pub path: PathBuf,
+    /// The format of the dataset, e.g. `json-lines`, `tsv` or `sqlite`.
+    pub format: String,
+    /// The amount of records, if known.
+    /// It is counted for line-based formats, excluding the header row of tabular formats.
+    pub records: Option<u64>,
+    pub schema_version: u32,
+    pub bytes: u64,
+    /// The hexadecimal sha1 digest of the file.
+    pub sha1: String,
+}
+
+impl Default for DatasetManifest {
+    fn default() -> Self {
+        Self {
+            schema_version: MANIFEST_SCHEMA_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            generated_at: Utc::now(),
+            datasets: Vec::new(),
+        }
+    }
+}
+
+impl DatasetManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A manifest of all files in the directory, except for logs and manifests.
+    /// Names and formats are derived from the file names, e.g. `translations.tsv` is the dataset `translations` in the format `tsv`.
+    /// Subdirectories are not included.
+    pub fn from_directory(directory: impl AsRef<Path>) -> Result<Self> {
+        let mut manifest = Self::new();
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let file_name = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .unwrap_or_default();
+            if file_name == MANIFEST_FILE_NAME || file_name.ends_with(".log") {
+                continue;
+            }
+            let name = file_name.split('.').next().unwrap_or(file_name).to_string();
+            let format = format_of_extension(&path);
+            manifest.add_dataset(name, path, format, None)?;
+        }
+        Ok(manifest)
+    }
+
+    /// Add a dataset, computing its size and checksum, and counting its records if `records` is not given.
+    pub fn add_dataset(
+        &mut self,
+        name: impl Into<String>,
+        path: impl Into<PathBuf>,
+        format: impl Into<String>,
+        records: Option<u64>,
+    ) -> Result<()> {
+        let path = path.into();
+        let format = format.into();
+        let (bytes, sha1, lines) = digest_file(&path)?;
+        let records = records.or_else(|| match format.as_str() {
+            "json-lines" => Some(lines),
+            "tsv" | "csv" => Some(lines.saturating_sub(1)),
+            _ => None,
+        });
+
+        self.datasets.push(DatasetEntry {
+            name: name.into(),
+            path,
+            format,
+            records,
+            schema_version: DATASET_SCHEMA_VERSION,
+            bytes,
+            sha1,
+        });
+        self.datasets.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(())
+    }
+
+    /// Write the manifest as pretty json.
+    /// The paths of datasets within the directory of the manifest are written relative to it.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let manifest = Self {
+            datasets: self
+                .datasets
+                .iter()
+                .map(|dataset| DatasetEntry {
+                    path: dataset
+                        .path
+                        .strip_prefix(directory)
+                        .map(Path::to_path_buf)
+                        .unwrap_or_else(|_| dataset.path.clone()),
+                    ..dataset.clone()
+                })
+                .collect(),
+            ..self.clone()
+        };
+        let output = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(output, &manifest)?;
+        Ok(())
+    }
+
+    /// Read a manifest written by [`write_json`](Self::write_json).
+    /// Relative paths of datasets are resolved against the directory of the manifest.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let input = BufReader::new(std::fs::File::open(path)?);
+        let mut manifest: Self = serde_json::from_reader(input)?;
+        if manifest.schema_version > MANIFEST_SCHEMA_VERSION {
+            return Err(Error::Other(format!(
+                "Manifest {path:?} has schema version {}, but only versions up to {MANIFEST_SCHEMA_VERSION} are supported",
+                manifest.schema_version
+            )));
+        }
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        for dataset in &mut manifest.datasets {
+            if dataset.path.is_relative() {
+                dataset.path = directory.join(&dataset.path);
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// The format of a dataset, derived from the extension of its file.
+fn format_of_extension(path: &Path) -> String {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("jsonl" | "ndjson") => "json-lines".to_string(),
+        Some("sqlite" | "db") => "sqlite".to_string(),
+        Some(extension) => extension.to_lowercase(),
+        None => "unknown".to_string(),
+    }
+}
+
+/// The size, hexadecimal sha1 digest and amount of lines of the file.
+fn digest_file(path: &Path) -> Result<(u64, String, u64)> {
+    let mut input = BufReader::new(std::fs::File::open(path)?);
+    let mut hasher = Sha1::new();
+    let mut bytes = 0;
+    let mut lines = 0;
+    let mut ends_with_newline = true;
+    loop {
+        let buffer = input.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        hasher.update(buffer);
+        lines += buffer.iter().filter(|byte| **byte == b'\n').count() as u64;
+        ends_with_newline = buffer.last() == Some(&b'\n');
+        let length = buffer.len();
+        bytes += length as u64;
+        input.consume(length);
+    }
+    if !ends_with_newline {
+        lines += 1;
+    }
+
+    Ok((bytes, format!("{:x}", hasher.finalize()), lines))
+}
+
+/// Serialise timestamps like `2023-08-01T12:34:56Z`.
+mod timestamp {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        timestamp: &DateTime<Utc>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::format_timestamp(timestamp))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<DateTime<Utc>, D::Error> {
+        let timestamp = String::deserialize(deserializer)?;
+        crate::dates::parse_timestamp(&timestamp).map_err(serde::de::Error::custom)
+    }
+}
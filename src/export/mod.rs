@@ -0,0 +1,11 @@
+//! Writers for datasets in formats other than json.
+
+pub mod alternative_forms_graph;
+pub mod ereader;
+pub mod inflection_table;
+#[cfg(feature = "parse")]
+pub mod manifest;
+pub mod text_corpus;
+pub mod translation_tsv;
+pub mod word_tags;
+pub mod words;
@@ -0,0 +1,269 @@
+//! Plain-text corpora of the definitions, examples and usage notes of wiktionary pages,
+//! formatted for the pretraining of language models.
+
+use crate::error::{Error, Result};
+use crate::parser::words::clean_text::{clean_text, CleanTextOptions};
+use crate::parser::words::definitions::parse_definition;
+use crate::parser::words::headings::{classify_heading, heading_matches, HeadingKind};
+use crate::parser::words::language_names::LanguageNames;
+use crate::parser::words::templates::{section_list_items, section_texts, text_templates};
+use crate::parser::words::{TRANSLINGUAL_LANGUAGE_CODE, TRANSLINGUAL_LANGUAGE_ENGLISH_NAME};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+use wikitext_parser::{Section, Text, TextPiece, Wikitext};
+
+/// Templates of usage examples, with the language code as first and the example as second argument.
+static EXAMPLE_TEMPLATES: &[&str] = &["ux", "uxi", "usex", "eg"];
+
+/// The kind of text a line of the corpus is taken from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CorpusTextKind {
+    /// The definitions of word type sections, i.e. list items with prefix `#`.
+    Definition,
+    /// The usage examples below the definitions, i.e. list items with prefix `#:`.
+    Example,
+    /// The text of `Usage notes` sections.
+    UsageNote,
+}
+
+impl CorpusTextKind {
+    pub const ALL: [CorpusTextKind; 3] = [
+        CorpusTextKind::Definition,
+        CorpusTextKind::Example,
+        CorpusTextKind::UsageNote,
+    ];
+}
+
+impl FromStr for CorpusTextKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "definition" | "definitions" => Ok(Self::Definition),
+            "example" | "examples" => Ok(Self::Example),
+            "usage-note" | "usage-notes" => Ok(Self::UsageNote),
+            other => Err(Error::Other(format!(
+                "Unknown corpus text kind {other:?}, expected 'definitions', 'examples' or 'usage-notes'"
+            ))),
+        }
+    }
+}
+
+/// Options for [`TextCorpusWriter`].
+#[derive(Debug, Clone)]
+pub struct TextCorpusOptions {
+    /// The kinds of text to write, all by default.
+    pub kinds: Vec<CorpusTextKind>,
+    /// Resolves the language names of sections to the codes lines are tagged with.
+    /// Sections of unknown languages are skipped.
+    pub language_names: Arc<LanguageNames>,
+    /// Skip sentences with fewer characters, like leftovers of removed markup.
+    pub min_length: usize,
+    /// Skip sentences with more characters, like unsplit paragraphs.
+    pub max_length: usize,
+    /// Skip sentences that were written before in the same language.
+    pub deduplicate: bool,
+    /// Skip definitions labeled as vulgar, offensive, derogatory and the like,
+    /// see [`Definition::is_offensive`](crate::parser::words::definitions::Definition::is_offensive).
+    pub exclude_offensive: bool,
+    /// Also write the text of `Translingual` sections, tagged with [`TRANSLINGUAL_LANGUAGE_CODE`].
+    pub include_translingual: bool,
+}
+
+impl Default for TextCorpusOptions {
+    fn default() -> Self {
+        Self {
+            kinds: CorpusTextKind::ALL.to_vec(),
+            language_names: LanguageNames::shared_builtin(),
+            min_length: 10,
+            max_length: 1000,
+            deduplicate: true,
+            exclude_offensive: false,
+            include_translingual: false,
+        }
+    }
+}
+
+/// Writes the cleaned sentences of wiktionary pages one per line, tagged with the code of their language,
+/// like `en\tA domesticated carnivorous mammal.`.
+///
+/// Sentences are the definitions, examples and usage notes of each language section,
+/// with markup removed by [`clean_text`] and split at sentence ends.
+/// Form-of definitions like `{{plural of|en|dog}}` are skipped, since they are not sentences.
+/// Deduplication keeps only hashes of the written lines, such that it fits into memory for large dumps.
+pub struct TextCorpusWriter<W: Write> {
+    output: W,
+    options: TextCorpusOptions,
+    written_lines: HashSet<u64>,
+    count: usize,
+}
+
+impl<W: Write> TextCorpusWriter<W> {
+    pub fn new(output: W, options: TextCorpusOptions) -> Self {
+        Self {
+            output,
+            options,
+            written_lines: HashSet::new(),
+            count: 0,
+        }
+    }
+
+    /// Write the sentences of the page.
+    /// Returns the amount of written lines.
+    pub fn add_page(&mut self, wikitext: &Wikitext) -> Result<usize> {
+        if wikitext.root_section.headline.level != 1 {
+            return Ok(0);
+        }
+
+        let count = self.count;
+        for language_subsection in &wikitext.root_section.subsections {
+            let language_english_name = language_subsection.headline.label.as_str();
+            if language_english_name == TRANSLINGUAL_LANGUAGE_ENGLISH_NAME {
+                if self.options.include_translingual {
+                    self.add_section(TRANSLINGUAL_LANGUAGE_CODE, language_subsection)?;
+                }
+                continue;
+            }
+            let language_code = if let Some(language_code) =
+                self.options.language_names.code(language_english_name)
+            {
+                language_code.clone()
+            } else {
+                continue;
+            };
+            self.add_section(&language_code, language_subsection)?;
+        }
+        Ok(self.count - count)
+    }
+
+    fn add_section(&mut self, language_code: &str, section: &Section) -> Result<()> {
+        for subsection in &section.subsections {
+            let label = &subsection.headline.label;
+            if let HeadingKind::WordType(_) = classify_heading(label) {
+                for (list_prefix, text) in &section_list_items(subsection) {
+                    match list_prefix.as_str() {
+                        "#" if self.includes(CorpusTextKind::Definition) => {
+                            if text_templates(text)
+                                .iter()
+                                .any(|template| template.name.ends_with(" of"))
+                            {
+                                continue;
+                            }
+                            if self.options.exclude_offensive
+                                && parse_definition(text)
+                                    .map_or(false, |definition| definition.is_offensive())
+                            {
+                                continue;
+                            }
+                            self.write_text(
+                                language_code,
+                                &clean_text(text, &CleanTextOptions::default()),
+                            )?;
+                        }
+                        "#:" if self.includes(CorpusTextKind::Example) => {
+                            self.write_text(language_code, &clean_example(text))?;
+                        }
+                        _ => {}
+                    }
+                }
+            } else if heading_matches(label, "Usage notes")
+                && self.includes(CorpusTextKind::UsageNote)
+            {
+                for text in &section_texts(subsection) {
+                    self.write_text(
+                        language_code,
+                        &clean_text(text, &CleanTextOptions::default()),
+                    )?;
+                }
+            }
+
+            self.add_section(language_code, subsection)?;
+        }
+        Ok(())
+    }
+
+    fn includes(&self, kind: CorpusTextKind) -> bool {
+        self.options.kinds.contains(&kind)
+    }
+
+    /// Split the cleaned text into sentences and write those that pass the filters.
+    fn write_text(&mut self, language_code: &str, text: &str) -> Result<()> {
+        for sentence in split_sentences(text) {
+            let length = sentence.chars().count();
+            if length < self.options.min_length || length > self.options.max_length {
+                continue;
+            }
+
+            let line = format!("{language_code}\t{sentence}");
+            if self.options.deduplicate {
+                let mut hasher = DefaultHasher::new();
+                line.hash(&mut hasher);
+                if !self.written_lines.insert(hasher.finish()) {
+                    continue;
+                }
+            }
+
+            writeln!(self.output, "{line}")?;
+            self.count += 1;
+        }
+        Ok(())
+    }
+
+    /// The amount of lines written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// The text of the usage example template of the line, like the `The dog barked.` of `{{ux|en|The dog barked.}}`,
+/// or the whole line if it has none.
+fn clean_example(text: &Text) -> String {
+    for piece in &text.pieces {
+        if let TextPiece::DoubleBraceExpression { tag, attributes } = piece {
+            let name = clean_text(tag, &CleanTextOptions::default());
+            if EXAMPLE_TEMPLATES.contains(&name.trim()) {
+                return attributes
+                    .iter()
+                    .filter(|attribute| attribute.name.is_none())
+                    .nth(1)
+                    .map(|example| clean_text(&example.value, &CleanTextOptions::default()))
+                    .unwrap_or_default();
+            }
+        }
+    }
+    clean_text(text, &CleanTextOptions::default())
+}
+
+/// Split the text after sentence-ending punctuation that is followed by whitespace and an uppercase letter,
+/// which avoids splitting at most abbreviations like `e.g. a dog`.
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (index, character) in text.char_indices() {
+        if !matches!(character, '.' | '!' | '?') {
+            continue;
+        }
+        let end = index + character.len_utf8();
+        let rest = &text[end..];
+        let next_word = rest.trim_start();
+        if next_word.len() < rest.len()
+            && next_word.chars().next().map_or(false, char::is_uppercase)
+        {
+            sentences.push(text[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
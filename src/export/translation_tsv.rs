@@ -0,0 +1,80 @@
+use crate::error::Result;
+use crate::parser::words::language_names::LanguageNames;
+use crate::parser::words::translations::Translation;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Writes translation pairs as tab-separated values with the columns
+/// `source_lang`, `source`, `target_lang`, `target` and `gloss`.
+///
+/// Both languages are written as codes.
+/// The source language is resolved from the english name of its section by [`LanguageNames`],
+/// and the target language is written as BCP-47 language tag including the script of the translation, if known.
+/// Rows are written as they arrive, skipping rows that were written before.
+/// Tabs and line breaks within fields are replaced by spaces.
+pub struct TranslationTsvWriter<W: Write> {
+    output: W,
+    language_names: Arc<LanguageNames>,
+    written_rows: HashSet<String>,
+}
+
+impl<W: Write> TranslationTsvWriter<W> {
+    /// Create a new writer that resolves source languages by the builtin names, and write the header row.
+    pub fn new(output: W) -> Result<Self> {
+        Self::with_language_names(output, LanguageNames::shared_builtin())
+    }
+
+    /// Create a new writer that resolves source languages by the given names, and write the header row.
+    /// Source languages with unknown names are written as
+    /// [`UNKNOWN_LANGUAGE_CODE`](crate::parser::words::language_names::UNKNOWN_LANGUAGE_CODE).
+    pub fn with_language_names(mut output: W, language_names: Arc<LanguageNames>) -> Result<Self> {
+        writeln!(output, "source_lang\tsource\ttarget_lang\ttarget\tgloss")?;
+        Ok(Self {
+            output,
+            language_names,
+            written_rows: HashSet::new(),
+        })
+    }
+
+    /// Write the translation, unless an equal row was written before.
+    /// Returns true if the row was written.
+    pub fn write(&mut self, translation: &Translation) -> Result<bool> {
+        let source_language = self
+            .language_names
+            .resolve(&translation.source_language_english_name);
+        let target_language = translation
+            .target_language_tag()
+            .map(|tag| tag.to_string())
+            .unwrap_or_else(|| translation.target_language_code.clone());
+        let row = [
+            &*source_language,
+            translation.source.as_str(),
+            target_language.as_str(),
+            translation.target.as_str(),
+            translation.gloss.as_deref().unwrap_or_default(),
+        ]
+        .iter()
+        .map(|field| sanitise_field(field))
+        .collect::<Vec<_>>()
+        .join("\t");
+
+        if self.written_rows.contains(&row) {
+            return Ok(false);
+        }
+
+        writeln!(self.output, "{row}")?;
+        self.written_rows.insert(row);
+        Ok(true)
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+fn sanitise_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
@@ -0,0 +1,134 @@
+use crate::error::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+use std::path::Path;
+
+/// The tags of a word by category, e.g. `cefr` → `[A1]` and `topic` → `[animals, food]`.
+pub type Tags = BTreeMap<String, Vec<String>>;
+
+/// External tags of words, like CEFR levels or topics from vocabulary lists, which exporters join onto their entries.
+///
+/// Tags are given per language code and word.
+/// Tags given without a language code apply to words of all languages.
+#[derive(Debug, Clone, Default)]
+pub struct WordTags {
+    /// Maps language codes and words to tags, where the language code is empty for tags of all languages.
+    tags: HashMap<(String, String), Tags>,
+}
+
+impl WordTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load tags from a tab-separated file with a header row, extending the existing tags.
+    ///
+    /// The column `word` is required, and the column `language_code` is optional.
+    /// All other columns are tag categories named by their header, like `cefr` or `topic`.
+    /// Multiple values of a category are separated by commas, and empty values are ignored.
+    pub fn load_tsv(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut lines = std::io::BufReader::new(std::fs::File::open(path)?).lines();
+        let header = if let Some(header) = lines.next() {
+            header?
+        } else {
+            return Ok(());
+        };
+        let columns: Vec<_> = header.split('\t').map(str::trim).collect();
+        let word_column = columns
+            .iter()
+            .position(|column| *column == "word")
+            .ok_or_else(|| Error::Other(format!("Tag file {path:?} has no column 'word'")))?;
+        let language_code_column = columns.iter().position(|column| *column == "language_code");
+
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<_> = line.split('\t').collect();
+            let word = if let Some(word) = fields.get(word_column) {
+                word.trim()
+            } else {
+                continue;
+            };
+            let language_code = language_code_column
+                .and_then(|column| fields.get(column))
+                .map(|language_code| language_code.trim())
+                .filter(|language_code| !language_code.is_empty());
+
+            for (column, field) in fields.iter().enumerate() {
+                if column == word_column || Some(column) == language_code_column {
+                    continue;
+                }
+                let category = if let Some(category) = columns.get(column) {
+                    category
+                } else {
+                    continue;
+                };
+                for value in field.split(',').map(str::trim) {
+                    if !value.is_empty() {
+                        self.insert(language_code, word, category, value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add a tag to the word of the given language, or of all languages if the language code is `None`.
+    /// Duplicate tags are ignored.
+    pub fn insert(&mut self, language_code: Option<&str>, word: &str, category: &str, value: &str) {
+        let values = self
+            .tags
+            .entry((
+                language_code.unwrap_or_default().to_string(),
+                word.to_string(),
+            ))
+            .or_default()
+            .entry(category.to_string())
+            .or_default();
+        if !values.iter().any(|existing| existing == value) {
+            values.push(value.to_string());
+        }
+    }
+
+    /// The tags of the word of the given language, including the tags given for all languages.
+    /// Words are matched exactly, so lists need to use the spelling of the page titles.
+    pub fn get(&self, language_code: &str, word: &str) -> Tags {
+        let mut tags = Tags::new();
+        for language_code in ["", language_code] {
+            if let Some(language_tags) = self
+                .tags
+                .get(&(language_code.to_string(), word.to_string()))
+            {
+                for (category, values) in language_tags {
+                    let category_values = tags.entry(category.clone()).or_default();
+                    for value in values {
+                        if !category_values.contains(value) {
+                            category_values.push(value.clone());
+                        }
+                    }
+                }
+            }
+        }
+        tags
+    }
+
+    /// The amount of tagged words.
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+}
+
+/// Format tags like `cefr=A1;topic=animals,food`.
+pub fn format_tags(tags: &Tags) -> String {
+    tags.iter()
+        .map(|(category, values)| format!("{category}={}", values.join(",")))
+        .collect::<Vec<_>>()
+        .join(";")
+}
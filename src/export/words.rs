@@ -0,0 +1,161 @@
+use crate::error::{Error, Result};
+use crate::export::word_tags::{format_tags, Tags, WordTags};
+use crate::parser::words::Word;
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The format of a file of words.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WordsFormat {
+    /// One json object per line, containing all fields of the word.
+    JsonLines,
+    /// A json array of all words.
+    Json,
+    /// Tab-separated values with the columns `word`, `language`, `language_code`, `type` and `gender`, and a header row.
+    /// Multiple genders are separated by commas, e.g. `m,f`.
+    /// With [tags](WordsWriter::with_tags), there is an additional column `tags` like `cefr=A1;topic=animals,food`.
+    Tsv,
+}
+
+impl Default for WordsFormat {
+    fn default() -> Self {
+        Self::JsonLines
+    }
+}
+
+impl FromStr for WordsFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json-lines" | "jsonl" | "ndjson" => Ok(Self::JsonLines),
+            "json" => Ok(Self::Json),
+            "tsv" => Ok(Self::Tsv),
+            other => Err(Error::Other(format!(
+                "Unknown words format {other:?}, expected 'json-lines', 'json' or 'tsv'"
+            ))),
+        }
+    }
+}
+
+/// Writes words one by one in the given format.
+/// Call [`finish`](Self::finish) after the last word to complete the output.
+pub struct WordsWriter<W: Write> {
+    output: W,
+    format: WordsFormat,
+    tags: Option<Arc<WordTags>>,
+    count: usize,
+}
+
+/// A word with its external tags, which are serialised as an additional field `tags`.
+#[derive(Serialize)]
+struct TaggedWord<'a> {
+    #[serde(flatten)]
+    word: &'a Word,
+    #[serde(skip_serializing_if = "Tags::is_empty")]
+    tags: Tags,
+}
+
+impl<W: Write> WordsWriter<W> {
+    /// Create a new writer and write the header of the format, if any.
+    pub fn new(output: W, format: WordsFormat) -> Result<Self> {
+        Self::with_tags(output, format, None)
+    }
+
+    /// Like [`new`](Self::new), but joins the given tags onto the words by their language code and word.
+    pub fn with_tags(
+        mut output: W,
+        format: WordsFormat,
+        tags: Option<Arc<WordTags>>,
+    ) -> Result<Self> {
+        match format {
+            WordsFormat::JsonLines => {}
+            WordsFormat::Json => write!(output, "[")?,
+            WordsFormat::Tsv => {
+                write!(output, "word\tlanguage\tlanguage_code\ttype\tgender")?;
+                if tags.is_some() {
+                    write!(output, "\ttags")?;
+                }
+                writeln!(output)?;
+            }
+        }
+        Ok(Self {
+            output,
+            format,
+            tags,
+            count: 0,
+        })
+    }
+
+    pub fn write(&mut self, word: &Word) -> Result<()> {
+        let tags = self
+            .tags
+            .as_ref()
+            .map(|tags| tags.get(&word.language_code, &word.word));
+        match self.format {
+            WordsFormat::JsonLines => {
+                self.write_json(word, tags)?;
+                writeln!(self.output)?;
+            }
+            WordsFormat::Json => {
+                if self.count > 0 {
+                    write!(self.output, ",")?;
+                }
+                writeln!(self.output)?;
+                self.write_json(word, tags)?;
+            }
+            WordsFormat::Tsv => {
+                let genders: Vec<_> = word
+                    .features
+                    .genders
+                    .iter()
+                    .map(|gender| gender.abbreviation())
+                    .collect();
+                write!(
+                    self.output,
+                    "{}\t{}\t{}\t{}\t{}",
+                    sanitise_field(&word.word),
+                    sanitise_field(&word.language_english_name),
+                    sanitise_field(&word.language_code),
+                    sanitise_field(&word.word_type),
+                    genders.join(",")
+                )?;
+                if let Some(tags) = tags {
+                    write!(self.output, "\t{}", sanitise_field(&format_tags(&tags)))?;
+                }
+                writeln!(self.output)?;
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn write_json(&mut self, word: &Word, tags: Option<Tags>) -> Result<()> {
+        if let Some(tags) = tags {
+            serde_json::to_writer(&mut self.output, &TaggedWord { word, tags })?;
+        } else {
+            serde_json::to_writer(&mut self.output, word)?;
+        }
+        Ok(())
+    }
+
+    /// The amount of words written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Complete the output, flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        if self.format == WordsFormat::Json {
+            writeln!(self.output, "\n]")?;
+        }
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+fn sanitise_field(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
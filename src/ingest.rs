@@ -0,0 +1,231 @@
+//! Downloading and parsing the dumps of all wiktionary editions in one run.
+
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::parser::sink::OutputFormat;
+use crate::parser::statistics::ParseStatistics;
+use crate::urls::{DumpBaseUrl, DumpIndexUrl};
+use crate::{
+    download_and_parse, list_wiktionary_dump_languages_with_options, DownloadAndParseOptions,
+};
+use futures_util::stream::{self, StreamExt};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Options for [`ingest_all`].
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    /// The options used for each edition.
+    /// The statistics, siteinfo and word link files of the parse options are ignored,
+    /// since they would be overwritten by each edition.
+    /// The statistics are part of the report instead.
+    pub download_and_parse: DownloadAndParseOptions,
+    /// The editions to ingest.
+    /// If empty, all editions listed in the dump index are ingested.
+    pub languages: Vec<LanguageCode>,
+    /// The amount of editions that are downloaded and parsed at the same time.
+    pub concurrency: usize,
+    /// Abort at the first edition that fails, instead of continuing with the other editions.
+    pub fail_fast: bool,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            download_and_parse: Default::default(),
+            languages: Vec::new(),
+            concurrency: 2,
+            fail_fast: false,
+        }
+    }
+}
+
+/// The outcome of [`ingest_all`] for each edition.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct IngestReport {
+    /// The editions in the order of their wiktionary abbreviations.
+    pub editions: Vec<EditionReport>,
+}
+
+/// The outcome of downloading and parsing a single edition.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EditionReport {
+    /// The wiktionary abbreviation of the edition, e.g. `en`.
+    pub language: String,
+    pub output_file: PathBuf,
+    pub error_log: PathBuf,
+    /// The downloaded dump, which does not exist anymore if it was deleted after parsing.
+    pub dump_file: Option<PathBuf>,
+    /// The statistics of the parsed dump, if parsing succeeded.
+    pub statistics: Option<ParseStatistics>,
+    /// The error that made downloading or parsing fail, if any.
+    pub error: Option<String>,
+    pub duration_seconds: f64,
+}
+
+impl IngestReport {
+    pub fn succeeded(&self) -> impl Iterator<Item = &EditionReport> {
+        self.editions
+            .iter()
+            .filter(|edition| edition.error.is_none())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &EditionReport> {
+        self.editions
+            .iter()
+            .filter(|edition| edition.error.is_some())
+    }
+
+    /// Write the report as pretty json.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let output = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(output, self)?;
+        Ok(())
+    }
+}
+
+/// Formats the report as one line per edition, with a summary line first.
+impl Display for IngestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} editions succeeded, {} failed",
+            self.succeeded().count(),
+            self.failed().count()
+        )?;
+        for edition in &self.editions {
+            write!(
+                f,
+                "\n  {} ({:.0}s): ",
+                edition.language, edition.duration_seconds
+            )?;
+            match (&edition.error, &edition.statistics) {
+                (Some(error), _) => write!(f, "failed: {error}")?,
+                (None, Some(statistics)) => write!(
+                    f,
+                    "{} pages, {} redirects",
+                    statistics.pages, statistics.redirects
+                )?,
+                (None, None) => write!(f, "done")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Download and parse the latest dumps of all wiktionary editions, or of the editions selected in the options.
+///
+/// Each edition is written into `<output_directory>/<abbreviation>.json`, or `.jsonl` for [`OutputFormat::JsonLines`],
+/// with its errors in `<output_directory>/<abbreviation>.errors.log`.
+/// Dumps are downloaded into the target directory like with [`download_language`](crate::download_language).
+///
+/// Editions are processed concurrently within the calling task, such that downloads of some editions overlap with parsing others.
+/// To parse on multiple threads, set the page workers of the parse options.
+/// Unless `fail_fast` is set, an edition that fails is recorded in the report and does not affect the other editions.
+pub async fn ingest_all(
+    base_url: &DumpBaseUrl,
+    index_url: &DumpIndexUrl,
+    target_directory: impl Into<PathBuf>,
+    output_directory: impl Into<PathBuf>,
+    options: &IngestOptions,
+) -> Result<IngestReport> {
+    let target_directory = target_directory.into();
+    let output_directory = output_directory.into();
+    tokio::fs::create_dir_all(&output_directory).await?;
+
+    let languages = if options.languages.is_empty() {
+        list_wiktionary_dump_languages_with_options(index_url, &options.download_and_parse.download)
+            .await?
+    } else {
+        options.languages.clone()
+    };
+    info!("Ingesting {} editions", languages.len());
+
+    let mut download_and_parse_options = options.download_and_parse.clone();
+    download_and_parse_options.parse.statistics_file = None;
+    download_and_parse_options.parse.siteinfo_file = None;
+    download_and_parse_options.parse.word_link_file = None;
+    let download_and_parse_options = &download_and_parse_options;
+    let target_directory = &target_directory;
+    let output_directory = &output_directory;
+
+    let mut results = stream::iter(languages)
+        .map(|language_code| async move {
+            ingest_edition(
+                base_url,
+                &language_code,
+                target_directory,
+                output_directory,
+                download_and_parse_options,
+            )
+            .await
+        })
+        .buffer_unordered(options.concurrency.max(1));
+
+    let mut editions = Vec::new();
+    while let Some(edition) = results.next().await {
+        if let Some(error) = &edition.error {
+            error!("Ingesting edition {} failed: {error}", edition.language);
+            if options.fail_fast {
+                return Err(Error::Other(format!(
+                    "Ingesting edition {} failed: {error}",
+                    edition.language
+                )));
+            }
+        } else {
+            info!("Ingested edition {}", edition.language);
+        }
+        editions.push(edition);
+    }
+    editions.sort_by(|a, b| a.language.cmp(&b.language));
+
+    let report = IngestReport { editions };
+    info!("Finished ingesting: {report}");
+    Ok(report)
+}
+
+async fn ingest_edition(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    target_directory: &Path,
+    output_directory: &Path,
+    options: &DownloadAndParseOptions,
+) -> EditionReport {
+    let language = language_code.to_wiktionary_abbreviation().to_string();
+    let extension = match options.parse.output_format {
+        OutputFormat::Json => "json",
+        OutputFormat::JsonLines => "jsonl",
+    };
+    let output_file = output_directory.join(format!("{language}.{extension}"));
+    let error_log = output_directory.join(format!("{language}.errors.log"));
+    info!("Ingesting edition {language} into {output_file:?}");
+
+    let start_time = Instant::now();
+    let result = download_and_parse(
+        base_url,
+        language_code,
+        target_directory,
+        &output_file,
+        &error_log,
+        options,
+    )
+    .await;
+    let duration_seconds = start_time.elapsed().as_secs_f64();
+
+    let (dump_file, statistics, error) = match result {
+        Ok((dump_file, statistics)) => (Some(dump_file), Some(statistics), None),
+        Err(error) => (None, None, Some(error.to_string())),
+    };
+    EditionReport {
+        language,
+        output_file,
+        error_log,
+        dump_file,
+        statistics,
+        error,
+        duration_seconds,
+    }
+}
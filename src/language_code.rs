@@ -1,22 +1,583 @@
+use crate::edit_distance::edit_distance;
 use crate::error::{Error, Result};
 
+/// A language that a wiktionary edition exists in.
+/// Each variant corresponds to one edition, including closed editions that still have dumps.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum LanguageCode {
+    Afar,
+    Abkhazian,
+    Afrikaans,
+    Akan,
+    Alemannic,
+    Amharic,
+    Aragonese,
+    OldEnglish,
+    Arabic,
+    Assamese,
+    Asturian,
+    Avaric,
+    Aymara,
+    Azerbaijani,
+    Belarusian,
+    Bulgarian,
+    Bhojpuri,
+    Bislama,
+    Bambara,
+    Bengali,
+    Tibetan,
+    Breton,
+    Bosnian,
+    Catalan,
+    Chamorro,
+    Cherokee,
+    Corsican,
+    Cree,
+    Czech,
+    Kashubian,
+    Welsh,
+    Danish,
+    German,
+    Divehi,
+    Dzongkha,
+    Greek,
     English,
+    Esperanto,
+    Spanish,
+    Estonian,
+    Basque,
+    Persian,
+    Finnish,
+    Fijian,
+    Faroese,
     French,
+    WestFrisian,
+    Irish,
+    ScottishGaelic,
+    Galician,
+    Guarani,
+    Gorontalo,
+    Gujarati,
+    Manx,
+    Hausa,
+    Hebrew,
+    Hindi,
+    FijiHindi,
+    Croatian,
+    UpperSorbian,
+    Hungarian,
+    Armenian,
+    Interlingua,
+    Indonesian,
+    Interlingue,
+    Igbo,
+    Inupiaq,
+    Ido,
+    Icelandic,
+    Italian,
+    Inuktitut,
+    Japanese,
+    Lojban,
+    Javanese,
+    Georgian,
+    Karakalpak,
+    Kabardian,
+    Tyap,
+    Kazakh,
+    Greenlandic,
+    Khmer,
+    Kannada,
+    Korean,
+    Kashmiri,
+    Kurdish,
+    Cornish,
+    Kyrgyz,
+    Latin,
+    Luxembourgish,
+    Limburgish,
+    Lombard,
+    Lingala,
+    Lao,
+    Lithuanian,
+    Latvian,
+    Malagasy,
+    Marshallese,
+    Maori,
+    Minangkabau,
+    Macedonian,
+    Malayalam,
+    Mongolian,
+    Manipuri,
+    Mon,
+    Moldovan,
+    Marathi,
+    Malay,
+    Maltese,
+    Burmese,
+    Nauruan,
+    Nahuatl,
+    LowGerman,
+    Nepali,
+    Nias,
+    Dutch,
+    NorwegianNynorsk,
+    Norwegian,
+    Occitan,
+    Oromo,
+    Odia,
+    Punjabi,
+    Pali,
+    Polish,
+    WesternPunjabi,
+    Pashto,
+    Portuguese,
+    Quechua,
+    Romansh,
+    Kirundi,
+    Romanian,
+    Aromanian,
     Russian,
-    German,
-    Finnish,
+    Kinyarwanda,
+    Sanskrit,
+    Sardinian,
+    Sicilian,
+    Sindhi,
+    Sango,
+    SerboCroatian,
+    Shan,
+    Shawiya,
+    Sinhala,
+    SimpleEnglish,
+    Slovak,
+    Saraiki,
+    Slovenian,
+    Samoan,
+    InariSami,
+    Shona,
+    Somali,
+    Albanian,
+    Serbian,
+    Swati,
+    Sotho,
+    Sundanese,
+    Swedish,
+    Swahili,
+    Tamil,
+    Tulu,
+    Telugu,
+    Tajik,
+    Thai,
+    Tigrinya,
+    Turkmen,
+    Tagalog,
+    Tswana,
+    Tongan,
+    TokPisin,
+    Turkish,
+    Tsonga,
+    Tatar,
+    Twi,
+    Uyghur,
+    Ukrainian,
+    Urdu,
+    Uzbek,
+    Venetian,
+    Vietnamese,
+    Volapuk,
+    Walloon,
+    Wolof,
+    Xhosa,
+    Yiddish,
+    Yoruba,
+    Zhuang,
+    Chinese,
+    MinNan,
+    Zulu,
 }
 
 impl LanguageCode {
+    /// All known language codes, ordered by their wiktionary abbreviation.
+    pub const ALL: &'static [LanguageCode] = &[
+        LanguageCode::Afar,
+        LanguageCode::Abkhazian,
+        LanguageCode::Afrikaans,
+        LanguageCode::Akan,
+        LanguageCode::Alemannic,
+        LanguageCode::Amharic,
+        LanguageCode::Aragonese,
+        LanguageCode::OldEnglish,
+        LanguageCode::Arabic,
+        LanguageCode::Assamese,
+        LanguageCode::Asturian,
+        LanguageCode::Avaric,
+        LanguageCode::Aymara,
+        LanguageCode::Azerbaijani,
+        LanguageCode::Belarusian,
+        LanguageCode::Bulgarian,
+        LanguageCode::Bhojpuri,
+        LanguageCode::Bislama,
+        LanguageCode::Bambara,
+        LanguageCode::Bengali,
+        LanguageCode::Tibetan,
+        LanguageCode::Breton,
+        LanguageCode::Bosnian,
+        LanguageCode::Catalan,
+        LanguageCode::Chamorro,
+        LanguageCode::Cherokee,
+        LanguageCode::Corsican,
+        LanguageCode::Cree,
+        LanguageCode::Czech,
+        LanguageCode::Kashubian,
+        LanguageCode::Welsh,
+        LanguageCode::Danish,
+        LanguageCode::German,
+        LanguageCode::Divehi,
+        LanguageCode::Dzongkha,
+        LanguageCode::Greek,
+        LanguageCode::English,
+        LanguageCode::Esperanto,
+        LanguageCode::Spanish,
+        LanguageCode::Estonian,
+        LanguageCode::Basque,
+        LanguageCode::Persian,
+        LanguageCode::Finnish,
+        LanguageCode::Fijian,
+        LanguageCode::Faroese,
+        LanguageCode::French,
+        LanguageCode::WestFrisian,
+        LanguageCode::Irish,
+        LanguageCode::ScottishGaelic,
+        LanguageCode::Galician,
+        LanguageCode::Guarani,
+        LanguageCode::Gorontalo,
+        LanguageCode::Gujarati,
+        LanguageCode::Manx,
+        LanguageCode::Hausa,
+        LanguageCode::Hebrew,
+        LanguageCode::Hindi,
+        LanguageCode::FijiHindi,
+        LanguageCode::Croatian,
+        LanguageCode::UpperSorbian,
+        LanguageCode::Hungarian,
+        LanguageCode::Armenian,
+        LanguageCode::Interlingua,
+        LanguageCode::Indonesian,
+        LanguageCode::Interlingue,
+        LanguageCode::Igbo,
+        LanguageCode::Inupiaq,
+        LanguageCode::Ido,
+        LanguageCode::Icelandic,
+        LanguageCode::Italian,
+        LanguageCode::Inuktitut,
+        LanguageCode::Japanese,
+        LanguageCode::Lojban,
+        LanguageCode::Javanese,
+        LanguageCode::Georgian,
+        LanguageCode::Karakalpak,
+        LanguageCode::Kabardian,
+        LanguageCode::Tyap,
+        LanguageCode::Kazakh,
+        LanguageCode::Greenlandic,
+        LanguageCode::Khmer,
+        LanguageCode::Kannada,
+        LanguageCode::Korean,
+        LanguageCode::Kashmiri,
+        LanguageCode::Kurdish,
+        LanguageCode::Cornish,
+        LanguageCode::Kyrgyz,
+        LanguageCode::Latin,
+        LanguageCode::Luxembourgish,
+        LanguageCode::Limburgish,
+        LanguageCode::Lombard,
+        LanguageCode::Lingala,
+        LanguageCode::Lao,
+        LanguageCode::Lithuanian,
+        LanguageCode::Latvian,
+        LanguageCode::Malagasy,
+        LanguageCode::Marshallese,
+        LanguageCode::Maori,
+        LanguageCode::Minangkabau,
+        LanguageCode::Macedonian,
+        LanguageCode::Malayalam,
+        LanguageCode::Mongolian,
+        LanguageCode::Manipuri,
+        LanguageCode::Mon,
+        LanguageCode::Moldovan,
+        LanguageCode::Marathi,
+        LanguageCode::Malay,
+        LanguageCode::Maltese,
+        LanguageCode::Burmese,
+        LanguageCode::Nauruan,
+        LanguageCode::Nahuatl,
+        LanguageCode::LowGerman,
+        LanguageCode::Nepali,
+        LanguageCode::Nias,
+        LanguageCode::Dutch,
+        LanguageCode::NorwegianNynorsk,
+        LanguageCode::Norwegian,
+        LanguageCode::Occitan,
+        LanguageCode::Oromo,
+        LanguageCode::Odia,
+        LanguageCode::Punjabi,
+        LanguageCode::Pali,
+        LanguageCode::Polish,
+        LanguageCode::WesternPunjabi,
+        LanguageCode::Pashto,
+        LanguageCode::Portuguese,
+        LanguageCode::Quechua,
+        LanguageCode::Romansh,
+        LanguageCode::Kirundi,
+        LanguageCode::Romanian,
+        LanguageCode::Aromanian,
+        LanguageCode::Russian,
+        LanguageCode::Kinyarwanda,
+        LanguageCode::Sanskrit,
+        LanguageCode::Sardinian,
+        LanguageCode::Sicilian,
+        LanguageCode::Sindhi,
+        LanguageCode::Sango,
+        LanguageCode::SerboCroatian,
+        LanguageCode::Shan,
+        LanguageCode::Shawiya,
+        LanguageCode::Sinhala,
+        LanguageCode::SimpleEnglish,
+        LanguageCode::Slovak,
+        LanguageCode::Saraiki,
+        LanguageCode::Slovenian,
+        LanguageCode::Samoan,
+        LanguageCode::InariSami,
+        LanguageCode::Shona,
+        LanguageCode::Somali,
+        LanguageCode::Albanian,
+        LanguageCode::Serbian,
+        LanguageCode::Swati,
+        LanguageCode::Sotho,
+        LanguageCode::Sundanese,
+        LanguageCode::Swedish,
+        LanguageCode::Swahili,
+        LanguageCode::Tamil,
+        LanguageCode::Tulu,
+        LanguageCode::Telugu,
+        LanguageCode::Tajik,
+        LanguageCode::Thai,
+        LanguageCode::Tigrinya,
+        LanguageCode::Turkmen,
+        LanguageCode::Tagalog,
+        LanguageCode::Tswana,
+        LanguageCode::Tongan,
+        LanguageCode::TokPisin,
+        LanguageCode::Turkish,
+        LanguageCode::Tsonga,
+        LanguageCode::Tatar,
+        LanguageCode::Twi,
+        LanguageCode::Uyghur,
+        LanguageCode::Ukrainian,
+        LanguageCode::Urdu,
+        LanguageCode::Uzbek,
+        LanguageCode::Venetian,
+        LanguageCode::Vietnamese,
+        LanguageCode::Volapuk,
+        LanguageCode::Walloon,
+        LanguageCode::Wolof,
+        LanguageCode::Xhosa,
+        LanguageCode::Yiddish,
+        LanguageCode::Yoruba,
+        LanguageCode::Zhuang,
+        LanguageCode::Chinese,
+        LanguageCode::MinNan,
+        LanguageCode::Zulu,
+    ];
+
     pub fn from_wiktionary_abbreviation(string: &str) -> Result<Self> {
         Ok(match string {
+            "aa" => Self::Afar,
+            "ab" => Self::Abkhazian,
+            "af" => Self::Afrikaans,
+            "ak" => Self::Akan,
+            "als" => Self::Alemannic,
+            "am" => Self::Amharic,
+            "an" => Self::Aragonese,
+            "ang" => Self::OldEnglish,
+            "ar" => Self::Arabic,
+            "as" => Self::Assamese,
+            "ast" => Self::Asturian,
+            "av" => Self::Avaric,
+            "ay" => Self::Aymara,
+            "az" => Self::Azerbaijani,
+            "be" => Self::Belarusian,
+            "bg" => Self::Bulgarian,
+            "bh" => Self::Bhojpuri,
+            "bi" => Self::Bislama,
+            "bm" => Self::Bambara,
+            "bn" => Self::Bengali,
+            "bo" => Self::Tibetan,
+            "br" => Self::Breton,
+            "bs" => Self::Bosnian,
+            "ca" => Self::Catalan,
+            "ch" => Self::Chamorro,
+            "chr" => Self::Cherokee,
+            "co" => Self::Corsican,
+            "cr" => Self::Cree,
+            "cs" => Self::Czech,
+            "csb" => Self::Kashubian,
+            "cy" => Self::Welsh,
+            "da" => Self::Danish,
+            "de" => Self::German,
+            "dv" => Self::Divehi,
+            "dz" => Self::Dzongkha,
+            "el" => Self::Greek,
             "en" => Self::English,
+            "eo" => Self::Esperanto,
+            "es" => Self::Spanish,
+            "et" => Self::Estonian,
+            "eu" => Self::Basque,
+            "fa" => Self::Persian,
+            "fi" => Self::Finnish,
+            "fj" => Self::Fijian,
+            "fo" => Self::Faroese,
             "fr" => Self::French,
+            "fy" => Self::WestFrisian,
+            "ga" => Self::Irish,
+            "gd" => Self::ScottishGaelic,
+            "gl" => Self::Galician,
+            "gn" => Self::Guarani,
+            "gor" => Self::Gorontalo,
+            "gu" => Self::Gujarati,
+            "gv" => Self::Manx,
+            "ha" => Self::Hausa,
+            "he" => Self::Hebrew,
+            "hi" => Self::Hindi,
+            "hif" => Self::FijiHindi,
+            "hr" => Self::Croatian,
+            "hsb" => Self::UpperSorbian,
+            "hu" => Self::Hungarian,
+            "hy" => Self::Armenian,
+            "ia" => Self::Interlingua,
+            "id" => Self::Indonesian,
+            "ie" => Self::Interlingue,
+            "ig" => Self::Igbo,
+            "ik" => Self::Inupiaq,
+            "io" => Self::Ido,
+            "is" => Self::Icelandic,
+            "it" => Self::Italian,
+            "iu" => Self::Inuktitut,
+            "ja" => Self::Japanese,
+            "jbo" => Self::Lojban,
+            "jv" => Self::Javanese,
+            "ka" => Self::Georgian,
+            "kaa" => Self::Karakalpak,
+            "kbd" => Self::Kabardian,
+            "kcg" => Self::Tyap,
+            "kk" => Self::Kazakh,
+            "kl" => Self::Greenlandic,
+            "km" => Self::Khmer,
+            "kn" => Self::Kannada,
+            "ko" => Self::Korean,
+            "ks" => Self::Kashmiri,
+            "ku" => Self::Kurdish,
+            "kw" => Self::Cornish,
+            "ky" => Self::Kyrgyz,
+            "la" => Self::Latin,
+            "lb" => Self::Luxembourgish,
+            "li" => Self::Limburgish,
+            "lmo" => Self::Lombard,
+            "ln" => Self::Lingala,
+            "lo" => Self::Lao,
+            "lt" => Self::Lithuanian,
+            "lv" => Self::Latvian,
+            "mg" => Self::Malagasy,
+            "mh" => Self::Marshallese,
+            "mi" => Self::Maori,
+            "min" => Self::Minangkabau,
+            "mk" => Self::Macedonian,
+            "ml" => Self::Malayalam,
+            "mn" => Self::Mongolian,
+            "mni" => Self::Manipuri,
+            "mnw" => Self::Mon,
+            "mo" => Self::Moldovan,
+            "mr" => Self::Marathi,
+            "ms" => Self::Malay,
+            "mt" => Self::Maltese,
+            "my" => Self::Burmese,
+            "na" => Self::Nauruan,
+            "nah" => Self::Nahuatl,
+            "nds" => Self::LowGerman,
+            "ne" => Self::Nepali,
+            "nia" => Self::Nias,
+            "nl" => Self::Dutch,
+            "nn" => Self::NorwegianNynorsk,
+            "no" => Self::Norwegian,
+            "oc" => Self::Occitan,
+            "om" => Self::Oromo,
+            "or" => Self::Odia,
+            "pa" => Self::Punjabi,
+            "pi" => Self::Pali,
+            "pl" => Self::Polish,
+            "pnb" => Self::WesternPunjabi,
+            "ps" => Self::Pashto,
+            "pt" => Self::Portuguese,
+            "qu" => Self::Quechua,
+            "rm" => Self::Romansh,
+            "rn" => Self::Kirundi,
+            "ro" => Self::Romanian,
+            "roa-rup" => Self::Aromanian,
             "ru" => Self::Russian,
-            "de" => Self::German,
-            "fi" => Self::Finnish,
+            "rw" => Self::Kinyarwanda,
+            "sa" => Self::Sanskrit,
+            "sc" => Self::Sardinian,
+            "scn" => Self::Sicilian,
+            "sd" => Self::Sindhi,
+            "sg" => Self::Sango,
+            "sh" => Self::SerboCroatian,
+            "shn" => Self::Shan,
+            "shy" => Self::Shawiya,
+            "si" => Self::Sinhala,
+            "simple" => Self::SimpleEnglish,
+            "sk" => Self::Slovak,
+            "skr" => Self::Saraiki,
+            "sl" => Self::Slovenian,
+            "sm" => Self::Samoan,
+            "smn" => Self::InariSami,
+            "sn" => Self::Shona,
+            "so" => Self::Somali,
+            "sq" => Self::Albanian,
+            "sr" => Self::Serbian,
+            "ss" => Self::Swati,
+            "st" => Self::Sotho,
+            "su" => Self::Sundanese,
+            "sv" => Self::Swedish,
+            "sw" => Self::Swahili,
+            "ta" => Self::Tamil,
+            "tcy" => Self::Tulu,
+            "te" => Self::Telugu,
+            "tg" => Self::Tajik,
+            "th" => Self::Thai,
+            "ti" => Self::Tigrinya,
+            "tk" => Self::Turkmen,
+            "tl" => Self::Tagalog,
+            "tn" => Self::Tswana,
+            "to" => Self::Tongan,
+            "tpi" => Self::TokPisin,
+            "tr" => Self::Turkish,
+            "ts" => Self::Tsonga,
+            "tt" => Self::Tatar,
+            "tw" => Self::Twi,
+            "ug" => Self::Uyghur,
+            "uk" => Self::Ukrainian,
+            "ur" => Self::Urdu,
+            "uz" => Self::Uzbek,
+            "vec" => Self::Venetian,
+            "vi" => Self::Vietnamese,
+            "vo" => Self::Volapuk,
+            "wa" => Self::Walloon,
+            "wo" => Self::Wolof,
+            "xh" => Self::Xhosa,
+            "yi" => Self::Yiddish,
+            "yo" => Self::Yoruba,
+            "za" => Self::Zhuang,
+            "zh" => Self::Chinese,
+            "zh-min-nan" => Self::MinNan,
+            "zu" => Self::Zulu,
             unknown => {
                 return Err(Error::UnknownWiktionaryLanguageAbbreviation(
                     unknown.to_string(),
@@ -25,24 +586,707 @@ impl LanguageCode {
         })
     }
 
+    /// The abbreviation of the wiktionary edition, as used in its domain and dump names.
+    /// Most are ISO 639 codes, but some editions use special codes like `simple`, `zh-min-nan` or `roa-rup`.
     pub fn to_wiktionary_abbreviation(&self) -> &'static str {
         match self {
+            LanguageCode::Afar => "aa",
+            LanguageCode::Abkhazian => "ab",
+            LanguageCode::Afrikaans => "af",
+            LanguageCode::Akan => "ak",
+            LanguageCode::Alemannic => "als",
+            LanguageCode::Amharic => "am",
+            LanguageCode::Aragonese => "an",
+            LanguageCode::OldEnglish => "ang",
+            LanguageCode::Arabic => "ar",
+            LanguageCode::Assamese => "as",
+            LanguageCode::Asturian => "ast",
+            LanguageCode::Avaric => "av",
+            LanguageCode::Aymara => "ay",
+            LanguageCode::Azerbaijani => "az",
+            LanguageCode::Belarusian => "be",
+            LanguageCode::Bulgarian => "bg",
+            LanguageCode::Bhojpuri => "bh",
+            LanguageCode::Bislama => "bi",
+            LanguageCode::Bambara => "bm",
+            LanguageCode::Bengali => "bn",
+            LanguageCode::Tibetan => "bo",
+            LanguageCode::Breton => "br",
+            LanguageCode::Bosnian => "bs",
+            LanguageCode::Catalan => "ca",
+            LanguageCode::Chamorro => "ch",
+            LanguageCode::Cherokee => "chr",
+            LanguageCode::Corsican => "co",
+            LanguageCode::Cree => "cr",
+            LanguageCode::Czech => "cs",
+            LanguageCode::Kashubian => "csb",
+            LanguageCode::Welsh => "cy",
+            LanguageCode::Danish => "da",
+            LanguageCode::German => "de",
+            LanguageCode::Divehi => "dv",
+            LanguageCode::Dzongkha => "dz",
+            LanguageCode::Greek => "el",
             LanguageCode::English => "en",
+            LanguageCode::Esperanto => "eo",
+            LanguageCode::Spanish => "es",
+            LanguageCode::Estonian => "et",
+            LanguageCode::Basque => "eu",
+            LanguageCode::Persian => "fa",
+            LanguageCode::Finnish => "fi",
+            LanguageCode::Fijian => "fj",
+            LanguageCode::Faroese => "fo",
             LanguageCode::French => "fr",
+            LanguageCode::WestFrisian => "fy",
+            LanguageCode::Irish => "ga",
+            LanguageCode::ScottishGaelic => "gd",
+            LanguageCode::Galician => "gl",
+            LanguageCode::Guarani => "gn",
+            LanguageCode::Gorontalo => "gor",
+            LanguageCode::Gujarati => "gu",
+            LanguageCode::Manx => "gv",
+            LanguageCode::Hausa => "ha",
+            LanguageCode::Hebrew => "he",
+            LanguageCode::Hindi => "hi",
+            LanguageCode::FijiHindi => "hif",
+            LanguageCode::Croatian => "hr",
+            LanguageCode::UpperSorbian => "hsb",
+            LanguageCode::Hungarian => "hu",
+            LanguageCode::Armenian => "hy",
+            LanguageCode::Interlingua => "ia",
+            LanguageCode::Indonesian => "id",
+            LanguageCode::Interlingue => "ie",
+            LanguageCode::Igbo => "ig",
+            LanguageCode::Inupiaq => "ik",
+            LanguageCode::Ido => "io",
+            LanguageCode::Icelandic => "is",
+            LanguageCode::Italian => "it",
+            LanguageCode::Inuktitut => "iu",
+            LanguageCode::Japanese => "ja",
+            LanguageCode::Lojban => "jbo",
+            LanguageCode::Javanese => "jv",
+            LanguageCode::Georgian => "ka",
+            LanguageCode::Karakalpak => "kaa",
+            LanguageCode::Kabardian => "kbd",
+            LanguageCode::Tyap => "kcg",
+            LanguageCode::Kazakh => "kk",
+            LanguageCode::Greenlandic => "kl",
+            LanguageCode::Khmer => "km",
+            LanguageCode::Kannada => "kn",
+            LanguageCode::Korean => "ko",
+            LanguageCode::Kashmiri => "ks",
+            LanguageCode::Kurdish => "ku",
+            LanguageCode::Cornish => "kw",
+            LanguageCode::Kyrgyz => "ky",
+            LanguageCode::Latin => "la",
+            LanguageCode::Luxembourgish => "lb",
+            LanguageCode::Limburgish => "li",
+            LanguageCode::Lombard => "lmo",
+            LanguageCode::Lingala => "ln",
+            LanguageCode::Lao => "lo",
+            LanguageCode::Lithuanian => "lt",
+            LanguageCode::Latvian => "lv",
+            LanguageCode::Malagasy => "mg",
+            LanguageCode::Marshallese => "mh",
+            LanguageCode::Maori => "mi",
+            LanguageCode::Minangkabau => "min",
+            LanguageCode::Macedonian => "mk",
+            LanguageCode::Malayalam => "ml",
+            LanguageCode::Mongolian => "mn",
+            LanguageCode::Manipuri => "mni",
+            LanguageCode::Mon => "mnw",
+            LanguageCode::Moldovan => "mo",
+            LanguageCode::Marathi => "mr",
+            LanguageCode::Malay => "ms",
+            LanguageCode::Maltese => "mt",
+            LanguageCode::Burmese => "my",
+            LanguageCode::Nauruan => "na",
+            LanguageCode::Nahuatl => "nah",
+            LanguageCode::LowGerman => "nds",
+            LanguageCode::Nepali => "ne",
+            LanguageCode::Nias => "nia",
+            LanguageCode::Dutch => "nl",
+            LanguageCode::NorwegianNynorsk => "nn",
+            LanguageCode::Norwegian => "no",
+            LanguageCode::Occitan => "oc",
+            LanguageCode::Oromo => "om",
+            LanguageCode::Odia => "or",
+            LanguageCode::Punjabi => "pa",
+            LanguageCode::Pali => "pi",
+            LanguageCode::Polish => "pl",
+            LanguageCode::WesternPunjabi => "pnb",
+            LanguageCode::Pashto => "ps",
+            LanguageCode::Portuguese => "pt",
+            LanguageCode::Quechua => "qu",
+            LanguageCode::Romansh => "rm",
+            LanguageCode::Kirundi => "rn",
+            LanguageCode::Romanian => "ro",
+            LanguageCode::Aromanian => "roa-rup",
             LanguageCode::Russian => "ru",
-            LanguageCode::German => "de",
-            LanguageCode::Finnish => "fi",
+            LanguageCode::Kinyarwanda => "rw",
+            LanguageCode::Sanskrit => "sa",
+            LanguageCode::Sardinian => "sc",
+            LanguageCode::Sicilian => "scn",
+            LanguageCode::Sindhi => "sd",
+            LanguageCode::Sango => "sg",
+            LanguageCode::SerboCroatian => "sh",
+            LanguageCode::Shan => "shn",
+            LanguageCode::Shawiya => "shy",
+            LanguageCode::Sinhala => "si",
+            LanguageCode::SimpleEnglish => "simple",
+            LanguageCode::Slovak => "sk",
+            LanguageCode::Saraiki => "skr",
+            LanguageCode::Slovenian => "sl",
+            LanguageCode::Samoan => "sm",
+            LanguageCode::InariSami => "smn",
+            LanguageCode::Shona => "sn",
+            LanguageCode::Somali => "so",
+            LanguageCode::Albanian => "sq",
+            LanguageCode::Serbian => "sr",
+            LanguageCode::Swati => "ss",
+            LanguageCode::Sotho => "st",
+            LanguageCode::Sundanese => "su",
+            LanguageCode::Swedish => "sv",
+            LanguageCode::Swahili => "sw",
+            LanguageCode::Tamil => "ta",
+            LanguageCode::Tulu => "tcy",
+            LanguageCode::Telugu => "te",
+            LanguageCode::Tajik => "tg",
+            LanguageCode::Thai => "th",
+            LanguageCode::Tigrinya => "ti",
+            LanguageCode::Turkmen => "tk",
+            LanguageCode::Tagalog => "tl",
+            LanguageCode::Tswana => "tn",
+            LanguageCode::Tongan => "to",
+            LanguageCode::TokPisin => "tpi",
+            LanguageCode::Turkish => "tr",
+            LanguageCode::Tsonga => "ts",
+            LanguageCode::Tatar => "tt",
+            LanguageCode::Twi => "tw",
+            LanguageCode::Uyghur => "ug",
+            LanguageCode::Ukrainian => "uk",
+            LanguageCode::Urdu => "ur",
+            LanguageCode::Uzbek => "uz",
+            LanguageCode::Venetian => "vec",
+            LanguageCode::Vietnamese => "vi",
+            LanguageCode::Volapuk => "vo",
+            LanguageCode::Walloon => "wa",
+            LanguageCode::Wolof => "wo",
+            LanguageCode::Xhosa => "xh",
+            LanguageCode::Yiddish => "yi",
+            LanguageCode::Yoruba => "yo",
+            LanguageCode::Zhuang => "za",
+            LanguageCode::Chinese => "zh",
+            LanguageCode::MinNan => "zh-min-nan",
+            LanguageCode::Zulu => "zu",
         }
     }
 
+    /// Parse the prefix of a database name like `zh_min_nan` in `zh_min_nanwiktionary`,
+    /// which is the wiktionary abbreviation with underscores instead of hyphens.
+    pub fn from_database_prefix(string: &str) -> Result<Self> {
+        Self::from_wiktionary_abbreviation(&string.replace('_', "-"))
+    }
+
+    /// The prefix of the database name of the wiktionary edition, as used in the paths of dumps,
+    /// e.g. `zh_min_nan` in `zh_min_nanwiktionary`.
+    pub fn to_database_prefix(&self) -> String {
+        self.to_wiktionary_abbreviation().replace('-', "_")
+    }
+
+    /// Parse a language name, ignoring case.
+    /// Besides the english name, this accepts the native name, the wiktionary abbreviation and some ISO 639 codes,
+    /// e.g. `Deutsch`, `de` or `deu`.
+    /// On failure, the error suggests the closest known name, if any is close.
     pub fn from_english_name(string: &str) -> Result<Self> {
-        Ok(match string {
-            "English" => Self::English,
-            "French" => Self::French,
-            "Russian" => Self::Russian,
-            "German" => Self::German,
-            "Finnish" => Self::Finnish,
-            unknown => return Err(Error::UnknownEnglishLanguageName(unknown.to_string())),
+        let normalised = string.trim().to_lowercase();
+        if let Some((_, language_code)) =
+            language_name_aliases().find(|(alias, _)| *alias == normalised)
+        {
+            return Ok(language_code.clone());
+        }
+
+        let suggestion = language_name_aliases()
+            .map(|(alias, language_code)| {
+                let distance = edit_distance(&alias, &normalised);
+                (alias, distance, language_code)
+            })
+            // Short aliases like codes are close to nearly everything, so the tolerance grows with the length.
+            .filter(|(alias, distance, _)| *distance <= (alias.chars().count() / 3).max(1))
+            .min_by_key(|(_, distance, _)| *distance)
+            .map(|(_, _, language_code)| language_code.to_english_name().to_string());
+        Err(if let Some(suggestion) = suggestion {
+            Error::UnknownEnglishLanguageNameWithSuggestion {
+                name: string.to_string(),
+                suggestion,
+            }
+        } else {
+            Error::UnknownEnglishLanguageName(string.to_string())
         })
     }
+
+    pub fn to_english_name(&self) -> &'static str {
+        match self {
+            LanguageCode::Afar => "Afar",
+            LanguageCode::Abkhazian => "Abkhazian",
+            LanguageCode::Afrikaans => "Afrikaans",
+            LanguageCode::Akan => "Akan",
+            LanguageCode::Alemannic => "Alemannic",
+            LanguageCode::Amharic => "Amharic",
+            LanguageCode::Aragonese => "Aragonese",
+            LanguageCode::OldEnglish => "Old English",
+            LanguageCode::Arabic => "Arabic",
+            LanguageCode::Assamese => "Assamese",
+            LanguageCode::Asturian => "Asturian",
+            LanguageCode::Avaric => "Avaric",
+            LanguageCode::Aymara => "Aymara",
+            LanguageCode::Azerbaijani => "Azerbaijani",
+            LanguageCode::Belarusian => "Belarusian",
+            LanguageCode::Bulgarian => "Bulgarian",
+            LanguageCode::Bhojpuri => "Bhojpuri",
+            LanguageCode::Bislama => "Bislama",
+            LanguageCode::Bambara => "Bambara",
+            LanguageCode::Bengali => "Bengali",
+            LanguageCode::Tibetan => "Tibetan",
+            LanguageCode::Breton => "Breton",
+            LanguageCode::Bosnian => "Bosnian",
+            LanguageCode::Catalan => "Catalan",
+            LanguageCode::Chamorro => "Chamorro",
+            LanguageCode::Cherokee => "Cherokee",
+            LanguageCode::Corsican => "Corsican",
+            LanguageCode::Cree => "Cree",
+            LanguageCode::Czech => "Czech",
+            LanguageCode::Kashubian => "Kashubian",
+            LanguageCode::Welsh => "Welsh",
+            LanguageCode::Danish => "Danish",
+            LanguageCode::German => "German",
+            LanguageCode::Divehi => "Divehi",
+            LanguageCode::Dzongkha => "Dzongkha",
+            LanguageCode::Greek => "Greek",
+            LanguageCode::English => "English",
+            LanguageCode::Esperanto => "Esperanto",
+            LanguageCode::Spanish => "Spanish",
+            LanguageCode::Estonian => "Estonian",
+            LanguageCode::Basque => "Basque",
+            LanguageCode::Persian => "Persian",
+            LanguageCode::Finnish => "Finnish",
+            LanguageCode::Fijian => "Fijian",
+            LanguageCode::Faroese => "Faroese",
+            LanguageCode::French => "French",
+            LanguageCode::WestFrisian => "West Frisian",
+            LanguageCode::Irish => "Irish",
+            LanguageCode::ScottishGaelic => "Scottish Gaelic",
+            LanguageCode::Galician => "Galician",
+            LanguageCode::Guarani => "Guarani",
+            LanguageCode::Gorontalo => "Gorontalo",
+            LanguageCode::Gujarati => "Gujarati",
+            LanguageCode::Manx => "Manx",
+            LanguageCode::Hausa => "Hausa",
+            LanguageCode::Hebrew => "Hebrew",
+            LanguageCode::Hindi => "Hindi",
+            LanguageCode::FijiHindi => "Fiji Hindi",
+            LanguageCode::Croatian => "Croatian",
+            LanguageCode::UpperSorbian => "Upper Sorbian",
+            LanguageCode::Hungarian => "Hungarian",
+            LanguageCode::Armenian => "Armenian",
+            LanguageCode::Interlingua => "Interlingua",
+            LanguageCode::Indonesian => "Indonesian",
+            LanguageCode::Interlingue => "Interlingue",
+            LanguageCode::Igbo => "Igbo",
+            LanguageCode::Inupiaq => "Inupiaq",
+            LanguageCode::Ido => "Ido",
+            LanguageCode::Icelandic => "Icelandic",
+            LanguageCode::Italian => "Italian",
+            LanguageCode::Inuktitut => "Inuktitut",
+            LanguageCode::Japanese => "Japanese",
+            LanguageCode::Lojban => "Lojban",
+            LanguageCode::Javanese => "Javanese",
+            LanguageCode::Georgian => "Georgian",
+            LanguageCode::Karakalpak => "Karakalpak",
+            LanguageCode::Kabardian => "Kabardian",
+            LanguageCode::Tyap => "Tyap",
+            LanguageCode::Kazakh => "Kazakh",
+            LanguageCode::Greenlandic => "Greenlandic",
+            LanguageCode::Khmer => "Khmer",
+            LanguageCode::Kannada => "Kannada",
+            LanguageCode::Korean => "Korean",
+            LanguageCode::Kashmiri => "Kashmiri",
+            LanguageCode::Kurdish => "Kurdish",
+            LanguageCode::Cornish => "Cornish",
+            LanguageCode::Kyrgyz => "Kyrgyz",
+            LanguageCode::Latin => "Latin",
+            LanguageCode::Luxembourgish => "Luxembourgish",
+            LanguageCode::Limburgish => "Limburgish",
+            LanguageCode::Lombard => "Lombard",
+            LanguageCode::Lingala => "Lingala",
+            LanguageCode::Lao => "Lao",
+            LanguageCode::Lithuanian => "Lithuanian",
+            LanguageCode::Latvian => "Latvian",
+            LanguageCode::Malagasy => "Malagasy",
+            LanguageCode::Marshallese => "Marshallese",
+            LanguageCode::Maori => "Maori",
+            LanguageCode::Minangkabau => "Minangkabau",
+            LanguageCode::Macedonian => "Macedonian",
+            LanguageCode::Malayalam => "Malayalam",
+            LanguageCode::Mongolian => "Mongolian",
+            LanguageCode::Manipuri => "Manipuri",
+            LanguageCode::Mon => "Mon",
+            LanguageCode::Moldovan => "Moldovan",
+            LanguageCode::Marathi => "Marathi",
+            LanguageCode::Malay => "Malay",
+            LanguageCode::Maltese => "Maltese",
+            LanguageCode::Burmese => "Burmese",
+            LanguageCode::Nauruan => "Nauruan",
+            LanguageCode::Nahuatl => "Nahuatl",
+            LanguageCode::LowGerman => "Low German",
+            LanguageCode::Nepali => "Nepali",
+            LanguageCode::Nias => "Nias",
+            LanguageCode::Dutch => "Dutch",
+            LanguageCode::NorwegianNynorsk => "Norwegian Nynorsk",
+            LanguageCode::Norwegian => "Norwegian",
+            LanguageCode::Occitan => "Occitan",
+            LanguageCode::Oromo => "Oromo",
+            LanguageCode::Odia => "Odia",
+            LanguageCode::Punjabi => "Punjabi",
+            LanguageCode::Pali => "Pali",
+            LanguageCode::Polish => "Polish",
+            LanguageCode::WesternPunjabi => "Western Punjabi",
+            LanguageCode::Pashto => "Pashto",
+            LanguageCode::Portuguese => "Portuguese",
+            LanguageCode::Quechua => "Quechua",
+            LanguageCode::Romansh => "Romansh",
+            LanguageCode::Kirundi => "Kirundi",
+            LanguageCode::Romanian => "Romanian",
+            LanguageCode::Aromanian => "Aromanian",
+            LanguageCode::Russian => "Russian",
+            LanguageCode::Kinyarwanda => "Kinyarwanda",
+            LanguageCode::Sanskrit => "Sanskrit",
+            LanguageCode::Sardinian => "Sardinian",
+            LanguageCode::Sicilian => "Sicilian",
+            LanguageCode::Sindhi => "Sindhi",
+            LanguageCode::Sango => "Sango",
+            LanguageCode::SerboCroatian => "Serbo-Croatian",
+            LanguageCode::Shan => "Shan",
+            LanguageCode::Shawiya => "Shawiya",
+            LanguageCode::Sinhala => "Sinhala",
+            LanguageCode::SimpleEnglish => "Simple English",
+            LanguageCode::Slovak => "Slovak",
+            LanguageCode::Saraiki => "Saraiki",
+            LanguageCode::Slovenian => "Slovenian",
+            LanguageCode::Samoan => "Samoan",
+            LanguageCode::InariSami => "Inari Sami",
+            LanguageCode::Shona => "Shona",
+            LanguageCode::Somali => "Somali",
+            LanguageCode::Albanian => "Albanian",
+            LanguageCode::Serbian => "Serbian",
+            LanguageCode::Swati => "Swati",
+            LanguageCode::Sotho => "Sotho",
+            LanguageCode::Sundanese => "Sundanese",
+            LanguageCode::Swedish => "Swedish",
+            LanguageCode::Swahili => "Swahili",
+            LanguageCode::Tamil => "Tamil",
+            LanguageCode::Tulu => "Tulu",
+            LanguageCode::Telugu => "Telugu",
+            LanguageCode::Tajik => "Tajik",
+            LanguageCode::Thai => "Thai",
+            LanguageCode::Tigrinya => "Tigrinya",
+            LanguageCode::Turkmen => "Turkmen",
+            LanguageCode::Tagalog => "Tagalog",
+            LanguageCode::Tswana => "Tswana",
+            LanguageCode::Tongan => "Tongan",
+            LanguageCode::TokPisin => "Tok Pisin",
+            LanguageCode::Turkish => "Turkish",
+            LanguageCode::Tsonga => "Tsonga",
+            LanguageCode::Tatar => "Tatar",
+            LanguageCode::Twi => "Twi",
+            LanguageCode::Uyghur => "Uyghur",
+            LanguageCode::Ukrainian => "Ukrainian",
+            LanguageCode::Urdu => "Urdu",
+            LanguageCode::Uzbek => "Uzbek",
+            LanguageCode::Venetian => "Venetian",
+            LanguageCode::Vietnamese => "Vietnamese",
+            LanguageCode::Volapuk => "Volapük",
+            LanguageCode::Walloon => "Walloon",
+            LanguageCode::Wolof => "Wolof",
+            LanguageCode::Xhosa => "Xhosa",
+            LanguageCode::Yiddish => "Yiddish",
+            LanguageCode::Yoruba => "Yoruba",
+            LanguageCode::Zhuang => "Zhuang",
+            LanguageCode::Chinese => "Chinese",
+            LanguageCode::MinNan => "Min Nan",
+            LanguageCode::Zulu => "Zulu",
+        }
+    }
+
+    /// The name of the language in the language itself, e.g. `Deutsch` for German.
+    pub fn to_native_name(&self) -> &'static str {
+        match self {
+            LanguageCode::Afar => "Qafár af",
+            LanguageCode::Abkhazian => "Аԥсшәа",
+            LanguageCode::Afrikaans => "Afrikaans",
+            LanguageCode::Akan => "Akan",
+            LanguageCode::Alemannic => "Alemannisch",
+            LanguageCode::Amharic => "አማርኛ",
+            LanguageCode::Aragonese => "aragonés",
+            LanguageCode::OldEnglish => "Ænglisc",
+            LanguageCode::Arabic => "العربية",
+            LanguageCode::Assamese => "অসমীয়া",
+            LanguageCode::Asturian => "asturianu",
+            LanguageCode::Avaric => "авар",
+            LanguageCode::Aymara => "Aymar aru",
+            LanguageCode::Azerbaijani => "azərbaycanca",
+            LanguageCode::Belarusian => "беларуская",
+            LanguageCode::Bulgarian => "български",
+            LanguageCode::Bhojpuri => "भोजपुरी",
+            LanguageCode::Bislama => "Bislama",
+            LanguageCode::Bambara => "bamanankan",
+            LanguageCode::Bengali => "বাংলা",
+            LanguageCode::Tibetan => "བོད་ཡིག",
+            LanguageCode::Breton => "brezhoneg",
+            LanguageCode::Bosnian => "bosanski",
+            LanguageCode::Catalan => "català",
+            LanguageCode::Chamorro => "Chamoru",
+            LanguageCode::Cherokee => "ᏣᎳᎩ",
+            LanguageCode::Corsican => "corsu",
+            LanguageCode::Cree => "Nēhiyawēwin",
+            LanguageCode::Czech => "čeština",
+            LanguageCode::Kashubian => "kaszëbsczi",
+            LanguageCode::Welsh => "Cymraeg",
+            LanguageCode::Danish => "dansk",
+            LanguageCode::German => "Deutsch",
+            LanguageCode::Divehi => "ދިވެހިބަސް",
+            LanguageCode::Dzongkha => "རྫོང་ཁ",
+            LanguageCode::Greek => "Ελληνικά",
+            LanguageCode::English => "English",
+            LanguageCode::Esperanto => "Esperanto",
+            LanguageCode::Spanish => "español",
+            LanguageCode::Estonian => "eesti",
+            LanguageCode::Basque => "euskara",
+            LanguageCode::Persian => "فارسی",
+            LanguageCode::Finnish => "suomi",
+            LanguageCode::Fijian => "Na Vosa Vakaviti",
+            LanguageCode::Faroese => "føroyskt",
+            LanguageCode::French => "français",
+            LanguageCode::WestFrisian => "Frysk",
+            LanguageCode::Irish => "Gaeilge",
+            LanguageCode::ScottishGaelic => "Gàidhlig",
+            LanguageCode::Galician => "galego",
+            LanguageCode::Guarani => "Avañe'ẽ",
+            LanguageCode::Gorontalo => "Bahasa Hulontalo",
+            LanguageCode::Gujarati => "ગુજરાતી",
+            LanguageCode::Manx => "Gaelg",
+            LanguageCode::Hausa => "Hausa",
+            LanguageCode::Hebrew => "עברית",
+            LanguageCode::Hindi => "हिन्दी",
+            LanguageCode::FijiHindi => "Fiji Baat",
+            LanguageCode::Croatian => "hrvatski",
+            LanguageCode::UpperSorbian => "hornjoserbsce",
+            LanguageCode::Hungarian => "magyar",
+            LanguageCode::Armenian => "հայերեն",
+            LanguageCode::Interlingua => "interlingua",
+            LanguageCode::Indonesian => "Bahasa Indonesia",
+            LanguageCode::Interlingue => "Interlingue",
+            LanguageCode::Igbo => "Igbo",
+            LanguageCode::Inupiaq => "Iñupiatun",
+            LanguageCode::Ido => "Ido",
+            LanguageCode::Icelandic => "íslenska",
+            LanguageCode::Italian => "italiano",
+            LanguageCode::Inuktitut => "ᐃᓄᒃᑎᑐᑦ",
+            LanguageCode::Japanese => "日本語",
+            LanguageCode::Lojban => "la .lojban.",
+            LanguageCode::Javanese => "Jawa",
+            LanguageCode::Georgian => "ქართული",
+            LanguageCode::Karakalpak => "Qaraqalpaqsha",
+            LanguageCode::Kabardian => "адыгэбзэ",
+            LanguageCode::Tyap => "Tyap",
+            LanguageCode::Kazakh => "қазақша",
+            LanguageCode::Greenlandic => "kalaallisut",
+            LanguageCode::Khmer => "ភាសាខ្មែរ",
+            LanguageCode::Kannada => "ಕನ್ನಡ",
+            LanguageCode::Korean => "한국어",
+            LanguageCode::Kashmiri => "कॉशुर",
+            LanguageCode::Kurdish => "kurdî",
+            LanguageCode::Cornish => "kernowek",
+            LanguageCode::Kyrgyz => "кыргызча",
+            LanguageCode::Latin => "Latina",
+            LanguageCode::Luxembourgish => "Lëtzebuergesch",
+            LanguageCode::Limburgish => "Limburgs",
+            LanguageCode::Lombard => "lombard",
+            LanguageCode::Lingala => "lingála",
+            LanguageCode::Lao => "ລາວ",
+            LanguageCode::Lithuanian => "lietuvių",
+            LanguageCode::Latvian => "latviešu",
+            LanguageCode::Malagasy => "Malagasy",
+            LanguageCode::Marshallese => "Kajin M̧ajeļ",
+            LanguageCode::Maori => "Māori",
+            LanguageCode::Minangkabau => "Minangkabau",
+            LanguageCode::Macedonian => "македонски",
+            LanguageCode::Malayalam => "മലയാളം",
+            LanguageCode::Mongolian => "монгол",
+            LanguageCode::Manipuri => "ꯃꯤꯇꯩ ꯂꯣꯟ",
+            LanguageCode::Mon => "ဘာသာ မန်",
+            LanguageCode::Moldovan => "молдовеняскэ",
+            LanguageCode::Marathi => "मराठी",
+            LanguageCode::Malay => "Bahasa Melayu",
+            LanguageCode::Maltese => "Malti",
+            LanguageCode::Burmese => "မြန်မာဘာသာ",
+            LanguageCode::Nauruan => "Dorerin Naoero",
+            LanguageCode::Nahuatl => "Nāhuatl",
+            LanguageCode::LowGerman => "Plattdüütsch",
+            LanguageCode::Nepali => "नेपाली",
+            LanguageCode::Nias => "Li Niha",
+            LanguageCode::Dutch => "Nederlands",
+            LanguageCode::NorwegianNynorsk => "norsk nynorsk",
+            LanguageCode::Norwegian => "norsk",
+            LanguageCode::Occitan => "occitan",
+            LanguageCode::Oromo => "Oromoo",
+            LanguageCode::Odia => "ଓଡ଼ିଆ",
+            LanguageCode::Punjabi => "ਪੰਜਾਬੀ",
+            LanguageCode::Pali => "पालि",
+            LanguageCode::Polish => "polski",
+            LanguageCode::WesternPunjabi => "پنجابی",
+            LanguageCode::Pashto => "پښتو",
+            LanguageCode::Portuguese => "português",
+            LanguageCode::Quechua => "Runa Simi",
+            LanguageCode::Romansh => "rumantsch",
+            LanguageCode::Kirundi => "ikirundi",
+            LanguageCode::Romanian => "română",
+            LanguageCode::Aromanian => "armãneashti",
+            LanguageCode::Russian => "русский",
+            LanguageCode::Kinyarwanda => "Ikinyarwanda",
+            LanguageCode::Sanskrit => "संस्कृतम्",
+            LanguageCode::Sardinian => "sardu",
+            LanguageCode::Sicilian => "sicilianu",
+            LanguageCode::Sindhi => "سنڌي",
+            LanguageCode::Sango => "Sängö",
+            LanguageCode::SerboCroatian => "srpskohrvatski",
+            LanguageCode::Shan => "ၽႃႇသႃႇတႆး",
+            LanguageCode::Shawiya => "tacawit",
+            LanguageCode::Sinhala => "සිංහල",
+            LanguageCode::SimpleEnglish => "Simple English",
+            LanguageCode::Slovak => "slovenčina",
+            LanguageCode::Saraiki => "سرائیکی",
+            LanguageCode::Slovenian => "slovenščina",
+            LanguageCode::Samoan => "Gagana Samoa",
+            LanguageCode::InariSami => "anarâškielâ",
+            LanguageCode::Shona => "chiShona",
+            LanguageCode::Somali => "Soomaaliga",
+            LanguageCode::Albanian => "shqip",
+            LanguageCode::Serbian => "српски",
+            LanguageCode::Swati => "SiSwati",
+            LanguageCode::Sotho => "Sesotho",
+            LanguageCode::Sundanese => "Sunda",
+            LanguageCode::Swedish => "svenska",
+            LanguageCode::Swahili => "Kiswahili",
+            LanguageCode::Tamil => "தமிழ்",
+            LanguageCode::Tulu => "ತುಳು",
+            LanguageCode::Telugu => "తెలుగు",
+            LanguageCode::Tajik => "тоҷикӣ",
+            LanguageCode::Thai => "ไทย",
+            LanguageCode::Tigrinya => "ትግርኛ",
+            LanguageCode::Turkmen => "Türkmençe",
+            LanguageCode::Tagalog => "Tagalog",
+            LanguageCode::Tswana => "Setswana",
+            LanguageCode::Tongan => "lea faka-Tonga",
+            LanguageCode::TokPisin => "Tok Pisin",
+            LanguageCode::Turkish => "Türkçe",
+            LanguageCode::Tsonga => "Xitsonga",
+            LanguageCode::Tatar => "татарча",
+            LanguageCode::Twi => "Twi",
+            LanguageCode::Uyghur => "ئۇيغۇرچە",
+            LanguageCode::Ukrainian => "українська",
+            LanguageCode::Urdu => "اردو",
+            LanguageCode::Uzbek => "oʻzbekcha",
+            LanguageCode::Venetian => "vèneto",
+            LanguageCode::Vietnamese => "Tiếng Việt",
+            LanguageCode::Volapuk => "Volapük",
+            LanguageCode::Walloon => "walon",
+            LanguageCode::Wolof => "Wolof",
+            LanguageCode::Xhosa => "isiXhosa",
+            LanguageCode::Yiddish => "ייִדיש",
+            LanguageCode::Yoruba => "Yorùbá",
+            LanguageCode::Zhuang => "Vahcuengh",
+            LanguageCode::Chinese => "中文",
+            LanguageCode::MinNan => "Bân-lâm-gú",
+            LanguageCode::Zulu => "isiZulu",
+        }
+    }
+}
+
+/// Lowercase names and codes of languages, besides their english names, native names and wiktionary abbreviations.
+static LANGUAGE_NAME_ALIASES: &[(&str, LanguageCode)] = &[
+    ("eng", LanguageCode::English),
+    ("francais", LanguageCode::French),
+    ("fra", LanguageCode::French),
+    ("fre", LanguageCode::French),
+    ("rus", LanguageCode::Russian),
+    ("deu", LanguageCode::German),
+    ("ger", LanguageCode::German),
+    ("fin", LanguageCode::Finnish),
+];
+
+/// All lowercase names and codes that identify a language.
+fn language_name_aliases() -> impl Iterator<Item = (String, &'static LanguageCode)> {
+    LanguageCode::ALL
+        .iter()
+        .flat_map(|language_code| {
+            [
+                language_code.to_english_name().to_lowercase(),
+                language_code.to_native_name().to_lowercase(),
+                language_code.to_wiktionary_abbreviation().to_string(),
+            ]
+            .into_iter()
+            .map(move |alias| (alias, language_code))
+        })
+        .chain(
+            LANGUAGE_NAME_ALIASES
+                .iter()
+                .map(|(alias, language_code)| (alias.to_string(), language_code)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_aliases() {
+        for name in [
+            "German", "german", " GERMAN ", "Deutsch", "de", "deu", "ger",
+        ] {
+            assert_eq!(
+                LanguageCode::from_english_name(name).unwrap(),
+                LanguageCode::German
+            );
+        }
+    }
+
+    #[test]
+    fn suggests_close_names() {
+        match LanguageCode::from_english_name("Germen") {
+            Err(Error::UnknownEnglishLanguageNameWithSuggestion { name, suggestion }) => {
+                assert_eq!(name, "Germen");
+                assert_eq!(suggestion, "German");
+            }
+            other => panic!("Expected a suggestion, but got {other:?}"),
+        }
+        match LanguageCode::from_english_name("Finish") {
+            Err(Error::UnknownEnglishLanguageNameWithSuggestion { suggestion, .. }) => {
+                assert_eq!(suggestion, "Finnish");
+            }
+            other => panic!("Expected a suggestion, but got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn does_not_suggest_distant_names() {
+        match LanguageCode::from_english_name("Qwertzuiop") {
+            Err(Error::UnknownEnglishLanguageName(name)) => assert_eq!(name, "Qwertzuiop"),
+            other => panic!("Expected no suggestion, but got {other:?}"),
+        }
+        // Short inputs only tolerate a single edit.
+        assert!(matches!(
+            LanguageCode::from_english_name("xqz"),
+            Err(Error::UnknownEnglishLanguageName(_))
+        ));
+    }
 }
@@ -0,0 +1,233 @@
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A BCP-47 language tag, like `sr-Latn`, `zh-Hant` or `pt-BR`.
+///
+/// Tags are parsed case-insensitively and formatted in their canonical case,
+/// i.e. the language in lowercase, the script in titlecase and the region in uppercase.
+/// Extensions and private use subtags are not supported.
+/// Tags are serialised as strings.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct LanguageTag {
+    /// The primary language subtag, e.g. `sr` or `nds`.
+    pub language: String,
+    /// Extended language subtags, e.g. `min` and `nan` in `zh-min-nan`.
+    pub extended_languages: Vec<String>,
+    /// The script subtag, e.g. `Latn`.
+    pub script: Option<String>,
+    /// The region subtag, e.g. `BR` or `419`.
+    pub region: Option<String>,
+    /// Variant subtags, e.g. `1996` in `de-1996`.
+    pub variants: Vec<String>,
+}
+
+impl LanguageTag {
+    /// A tag consisting only of the given primary language subtag.
+    pub fn new(language: &str) -> Self {
+        Self {
+            language: language.to_lowercase(),
+            extended_languages: Vec::new(),
+            script: None,
+            region: None,
+            variants: Vec::new(),
+        }
+    }
+
+    pub fn with_script(mut self, script: &str) -> Self {
+        self.script = Some(titlecase(script));
+        self
+    }
+
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_uppercase());
+        self
+    }
+
+    /// The tag without script, region and variant subtags, e.g. `sr` for `sr-Latn-RS`.
+    pub fn base_language(&self) -> Self {
+        Self {
+            extended_languages: self.extended_languages.clone(),
+            ..Self::new(&self.language)
+        }
+    }
+
+    /// The wiktionary edition of the language of this tag, ignoring script, region and variants.
+    pub fn language_code(&self) -> Result<LanguageCode> {
+        LanguageCode::from_wiktionary_abbreviation(&self.base_language().to_string())
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::Other(format!("Invalid language tag {s:?}"));
+        let mut subtags = s.split(['-', '_']).peekable();
+
+        let language = subtags.next().ok_or_else(invalid)?;
+        if !(2..=8).contains(&language.len()) || language.len() == 4 || !is_alphabetic(language) {
+            return Err(invalid());
+        }
+        let mut tag = Self::new(language);
+
+        if language.len() <= 3 {
+            while let Some(subtag) = subtags.peek() {
+                if tag.extended_languages.len() < 3 && subtag.len() == 3 && is_alphabetic(subtag) {
+                    tag.extended_languages.push(subtag.to_lowercase());
+                    subtags.next();
+                } else {
+                    break;
+                }
+            }
+        }
+        if let Some(subtag) = subtags.peek() {
+            if subtag.len() == 4 && is_alphabetic(subtag) {
+                tag = tag.with_script(subtag);
+                subtags.next();
+            }
+        }
+        if let Some(subtag) = subtags.peek() {
+            if (subtag.len() == 2 && is_alphabetic(subtag))
+                || (subtag.len() == 3 && subtag.bytes().all(|byte| byte.is_ascii_digit()))
+            {
+                tag = tag.with_region(subtag);
+                subtags.next();
+            }
+        }
+        for subtag in subtags {
+            let is_variant = subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+                && ((5..=8).contains(&subtag.len())
+                    || (subtag.len() == 4 && subtag.as_bytes()[0].is_ascii_digit()));
+            if !is_variant {
+                return Err(invalid());
+            }
+            tag.variants.push(subtag.to_lowercase());
+        }
+
+        Ok(tag)
+    }
+}
+
+impl Display for LanguageTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.language)?;
+        for subtag in self
+            .extended_languages
+            .iter()
+            .chain(&self.script)
+            .chain(&self.region)
+            .chain(&self.variants)
+        {
+            write!(f, "-{subtag}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for LanguageTag {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<LanguageTag> for String {
+    fn from(tag: LanguageTag) -> Self {
+        tag.to_string()
+    }
+}
+
+impl From<&LanguageCode> for LanguageTag {
+    /// The tag of the wiktionary abbreviation of the language, e.g. `zh-min-nan`.
+    fn from(language_code: &LanguageCode) -> Self {
+        language_code
+            .to_wiktionary_abbreviation()
+            .parse()
+            .unwrap_or_else(|_| Self::new(language_code.to_wiktionary_abbreviation()))
+    }
+}
+
+fn is_alphabetic(subtag: &str) -> bool {
+    subtag.bytes().all(|byte| byte.is_ascii_alphabetic())
+}
+
+fn titlecase(subtag: &str) -> String {
+    let subtag = subtag.to_lowercase();
+    let mut chars = subtag.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_script() {
+        let tag: LanguageTag = "sr-Latn".parse().unwrap();
+        assert_eq!(tag.language, "sr");
+        assert_eq!(tag.script.as_deref(), Some("Latn"));
+        assert_eq!(tag.region, None);
+        assert_eq!(tag.to_string(), "sr-Latn");
+
+        let tag: LanguageTag = "zh-Hant".parse().unwrap();
+        assert_eq!(tag.language, "zh");
+        assert_eq!(tag.script.as_deref(), Some("Hant"));
+        assert_eq!(tag.base_language().to_string(), "zh");
+    }
+
+    #[test]
+    fn parses_region() {
+        let tag: LanguageTag = "pt-BR".parse().unwrap();
+        assert_eq!(tag.language, "pt");
+        assert_eq!(tag.script, None);
+        assert_eq!(tag.region.as_deref(), Some("BR"));
+        assert_eq!(tag.to_string(), "pt-BR");
+        assert_eq!(
+            "es-419".parse::<LanguageTag>().unwrap().region.as_deref(),
+            Some("419")
+        );
+    }
+
+    #[test]
+    fn canonicalises_case_and_separators() {
+        let tag: LanguageTag = "SR_latn_rs".parse().unwrap();
+        assert_eq!(tag.to_string(), "sr-Latn-RS");
+        assert_eq!(
+            tag,
+            LanguageTag::new("sr").with_script("Latn").with_region("rs")
+        );
+    }
+
+    #[test]
+    fn parses_extended_languages_and_variants() {
+        let tag: LanguageTag = "zh-min-nan".parse().unwrap();
+        assert_eq!(tag.extended_languages, vec!["min", "nan"]);
+        assert_eq!(tag.to_string(), "zh-min-nan");
+
+        let tag: LanguageTag = "de-1996".parse().unwrap();
+        assert_eq!(tag.variants, vec!["1996"]);
+    }
+
+    #[test]
+    fn rejects_invalid_tags() {
+        for invalid in [
+            "",
+            "e",
+            "engl",
+            "en-",
+            "en-x",
+            "123",
+            "sr-Latn-toolongvariant",
+        ] {
+            assert!(invalid.parse::<LanguageTag>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -1,35 +1,108 @@
 #![allow(clippy::useless_format)]
 
-use crate::download::download_file_with_progress_log;
+#[cfg(feature = "download")]
+use crate::dates::{format_dump_date, parse_dump_date};
+#[cfg(feature = "download")]
+use crate::download::{download_file, get_text, DownloadOptions, DumpDateSelection};
+#[cfg(feature = "download")]
 use crate::error::Error;
+#[cfg(feature = "download")]
 use crate::language_code::LanguageCode;
-use crate::urls::{available_dates, dump_status_file, dump_url, DumpBaseUrl, DumpIndexUrl};
+#[cfg(feature = "download")]
+use crate::urls::{
+    available_dates, dump_status_file, dump_url, latest_articles_dump_feed, DumpBaseUrl,
+    DumpIndexUrl, LATEST_DATE,
+};
+#[cfg(feature = "download")]
+use chrono::NaiveDate;
+#[cfg(feature = "download")]
 use error::Result;
+#[cfg(feature = "download")]
 use itertools::Itertools;
+#[cfg(feature = "download")]
 use lazy_static::lazy_static;
+#[cfg(feature = "download")]
 use log::{debug, info, trace, warn};
+#[cfg(all(feature = "download", feature = "parse"))]
+use parser::DumpParseOptions;
+#[cfg(feature = "download")]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "download")]
+use std::future::Future;
+#[cfg(all(feature = "download", feature = "parse"))]
+use std::path::Path;
+#[cfg(feature = "download")]
 use std::path::PathBuf;
+#[cfg(feature = "download")]
+use url::Url;
 
+#[cfg(feature = "words")]
+pub mod analysis;
+#[cfg(all(unix, feature = "download", feature = "parse"))]
+pub mod daemon;
+pub mod dates;
+#[cfg(feature = "download")]
 pub mod download;
+mod edit_distance;
 pub mod error;
+#[cfg(feature = "words")]
+pub mod export;
+#[cfg(all(feature = "download", feature = "parse"))]
+pub mod ingest;
 pub mod language_code;
+pub mod language_tag;
+pub mod log_excerpt;
+#[cfg(feature = "words")]
+pub mod lookup;
+#[cfg(feature = "words")]
+pub mod merge;
+#[cfg(feature = "download")]
+pub mod mirrors;
+#[cfg(feature = "cloud")]
+pub mod object_storage;
+#[cfg(all(feature = "rayon", feature = "words"))]
+pub mod parallel;
+#[cfg(feature = "words")]
 pub mod parser;
+pub mod progress;
+#[cfg(all(feature = "download", feature = "words"))]
+pub mod qa;
+pub mod resource_usage;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "download")]
+pub mod store;
 pub mod urls;
+pub mod wiktionary_edition;
 
+#[cfg(feature = "download")]
 lazy_static! {
     static ref LIST_WIKTIONARY_DUMP_LANGUAGES_REGEX: Regex =
-        Regex::new(r#"<a href="([a-z\-]{2,20})wiktionary/[0-9]{8}">"#).unwrap();
+        Regex::new(r#"<a href="([a-z_]{2,20})wiktionary/[0-9]{8}">"#).unwrap();
     static ref LIST_AVAILABLE_DATES_REGEX: Regex =
         Regex::new(r#"<a href=".*([0-9]{8})/?">"#).unwrap();
+    static ref LATEST_ARTICLES_DUMP_FEED_LINK_REGEX: Regex =
+        Regex::new(r#"<link>[^<]*/([0-9]{8})/?\s*</link>"#).unwrap();
 }
 
+#[cfg(feature = "download")]
 /// Query wiktionary to get a list of languages that wiktionary dumps are available in.
 /// These are the languages wiktionary itself exists in, not the languages it has data about.
+/// The languages are sorted and contain no duplicates.
 pub async fn list_wiktionary_dump_languages(url: &DumpIndexUrl) -> Result<Vec<LanguageCode>> {
-    let body = reqwest::get(url.as_str()).await?.text().await?;
+    list_wiktionary_dump_languages_with_options(url, &DownloadOptions::default()).await
+}
+
+#[cfg(feature = "download")]
+/// Like [`list_wiktionary_dump_languages`], but retries failed requests according to the given options.
+pub async fn list_wiktionary_dump_languages_with_options(
+    url: &DumpIndexUrl,
+    options: &DownloadOptions,
+) -> Result<Vec<LanguageCode>> {
+    let body = get_text(&Url::parse(url.as_str())?, &options.retry_policy).await?;
     trace!("{body}");
     debug!(
         "language_regex: {:?}",
@@ -38,48 +111,282 @@ pub async fn list_wiktionary_dump_languages(url: &DumpIndexUrl) -> Result<Vec<La
     Ok(LIST_WIKTIONARY_DUMP_LANGUAGES_REGEX
         .captures_iter(&body)
         .filter_map(|captures| {
-            let abbreviation = &captures[1];
-            if let Ok(language_code) = LanguageCode::from_wiktionary_abbreviation(abbreviation) {
+            let database_prefix = &captures[1];
+            if let Ok(language_code) = LanguageCode::from_database_prefix(database_prefix) {
                 Some(language_code)
             } else {
-                warn!("Unknown language abbreviation '{abbreviation}'");
+                warn!("Unknown language abbreviation '{database_prefix}'");
                 None
             }
         })
+        .sorted()
+        .dedup()
         .collect())
 }
 
-/// Given a language code, list the available dates for which dumps exist.
+#[cfg(feature = "download")]
+/// Given a language code, list the available dates for which dumps exist, oldest first.
+/// Entries of the listing that are not dates, like `latest`, are skipped.
 pub async fn list_available_dates(
     base_url: &DumpBaseUrl,
     language_code: &LanguageCode,
-) -> Result<Vec<String>> {
+) -> Result<Vec<NaiveDate>> {
+    list_available_dates_with_options(base_url, language_code, &DownloadOptions::default()).await
+}
+
+#[cfg(feature = "download")]
+/// Like [`list_available_dates`], but retries failed requests according to the given options.
+pub async fn list_available_dates_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    options: &DownloadOptions,
+) -> Result<Vec<NaiveDate>> {
     let url = available_dates(base_url, language_code)?;
-    let body = reqwest::get(url).await?.text().await?;
+    let body = get_text(&url, &options.retry_policy).await?;
     trace!("{body}");
     debug!("available_dates_regex: {:?}", *LIST_AVAILABLE_DATES_REGEX);
     Ok(LIST_AVAILABLE_DATES_REGEX
         .captures_iter(&body)
-        .map(|captures| captures[1].to_string())
+        .filter_map(|captures| match parse_dump_date(&captures[1]) {
+            Ok(date) => Some(date),
+            Err(error) => {
+                debug!("Skipping listing entry: {error}");
+                None
+            }
+        })
         .sorted()
-        .unique()
+        .dedup()
         .collect())
 }
 
+#[cfg(feature = "download")]
+/// Resolve the [`LATEST_DATE`] to the date of the dump the `latest` directory holds,
+/// by following the link of the feed announcing its articles dump.
+/// Other dates are returned unchanged.
+pub async fn resolve_dump_date(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    options: &DownloadOptions,
+) -> Result<String> {
+    if date != LATEST_DATE {
+        return Ok(date.to_string());
+    }
+
+    let url = latest_articles_dump_feed(base_url, language_code)?;
+    let body = get_text(&url, &options.retry_policy).await?;
+    trace!("{body}");
+    let captures = LATEST_ARTICLES_DUMP_FEED_LINK_REGEX
+        .captures(&body)
+        .ok_or_else(|| Error::Other(format!("Feed '{url}' does not link to a dated dump")))?;
+    let date = format_dump_date(&parse_dump_date(&captures[1])?);
+    debug!("Resolved '{LATEST_DATE}' to date '{date}'");
+    Ok(date)
+}
+
+/// A date for which a dump exists, together with whether the dump is complete.
+#[cfg(feature = "download")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AvailableDate {
+    pub date: NaiveDate,
+    /// Whether the dump status file reports the articles dump as done,
+    /// or `None` if the dump status file could not be fetched or read.
+    pub articles_dump_complete: Option<bool>,
+}
+
+#[cfg(feature = "download")]
+/// Like [`list_available_dates_with_options`], but additionally fetches the dump status file of each date
+/// to check if its dump is complete.
+/// This makes one request per date.
+pub async fn list_available_dates_with_status(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    options: &DownloadOptions,
+) -> Result<Vec<AvailableDate>> {
+    let mut available_dates = Vec::new();
+    for date in list_available_dates_with_options(base_url, language_code, options).await? {
+        let articles_dump_complete = match fetch_dump_status_with_options(
+            base_url,
+            language_code,
+            &format_dump_date(&date),
+            options,
+        )
+        .await
+        {
+            Ok(dump_status_file) => Some(dump_status_file.articles_dump().is_ok()),
+            Err(error) => {
+                debug!("No readable dump status file for date {date}: {error}");
+                None
+            }
+        };
+        available_dates.push(AvailableDate {
+            date,
+            articles_dump_complete,
+        });
+    }
+    Ok(available_dates)
+}
+
+/// The `dumpstatus.json` of a dump, which lists the jobs of the dump with their status and files.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DumpStatusFile {
     version: String,
     jobs: BTreeMap<String, DumpStatusFileEntry>,
 }
 
+impl DumpStatusFile {
+    /// The version of the format of the status file, e.g. `0.8`.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// All jobs of the dump by name, regardless of their status.
+    pub fn jobs(&self) -> &BTreeMap<String, DumpStatusFileEntry> {
+        &self.jobs
+    }
+
+    /// The jobs of the dump that are not done, by name.
+    pub fn unfinished_jobs(&self) -> impl Iterator<Item = (&str, &DumpStatusFileEntry)> {
+        self.jobs
+            .iter()
+            .filter(|(_, entry)| !entry.status.is_done())
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+}
+
+/// The name of the dump job that creates the dump of the current revisions of all pages.
+pub const ARTICLES_DUMP_JOB: &str = "articlesdump";
+
+#[cfg(feature = "download")]
+impl DumpStatusFile {
+    /// The entry of the given job, like `metahistorybz2dump` or `pagetitlesdump`,
+    /// if the status file has a supported version, and the job is done and has at least one file.
+    pub fn job(&self, job: &str) -> Result<&DumpStatusFileEntry> {
+        let dump_status_file_version = &self.version;
+        if dump_status_file_version != "0.8" {
+            return Err(Error::Other(format!("Wrong dump status file version '{dump_status_file_version}', currently only 0.8 is supported.")));
+        }
+
+        let entry = self.jobs.get(job).ok_or_else(|| {
+            Error::Other(format!(
+                "Dump status file misses job entry for '{job}', available jobs are {:?}",
+                self.jobs.keys().collect::<Vec<_>>()
+            ))
+        })?;
+        trace!("{entry:#?}");
+
+        let status = &entry.status;
+        if !status.is_done() {
+            return Err(Error::Other(format!(
+                "Wrong {job} status '{status}', expected 'done'."
+            )));
+        }
+        if entry.files.is_empty() {
+            return Err(Error::Other(format!("Job {job} has no files.")));
+        }
+
+        Ok(entry)
+    }
+
+    /// The entry of the articles dump job, like [`job`](Self::job), but additionally requires it to consist of a single file.
+    pub fn articles_dump(&self) -> Result<&DumpStatusFileEntry> {
+        let articles_dump = self.job(ARTICLES_DUMP_JOB)?;
+        let articles_dump_file_amount = articles_dump.files.len();
+        if articles_dump_file_amount != 1 {
+            return Err(Error::Other(format!(
+                "Wrong articlesdump file amount {articles_dump_file_amount}, expected 1."
+            )));
+        }
+
+        Ok(articles_dump)
+    }
+}
+
+/// A job of a dump in the [`DumpStatusFile`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DumpStatusFileEntry {
-    status: String,
+    status: DumpJobStatus,
     updated: String,
     #[serde(default)]
     files: BTreeMap<String, DumpStatusFileEntryFile>,
 }
 
+impl DumpStatusFileEntry {
+    pub fn status(&self) -> &DumpJobStatus {
+        &self.status
+    }
+
+    /// The time the status of the job was last updated, like `2024-01-02 03:04:05`.
+    pub fn updated(&self) -> &str {
+        &self.updated
+    }
+
+    /// The files of the job by file name, which are only listed once the job is done.
+    pub fn files(&self) -> &BTreeMap<String, DumpStatusFileEntryFile> {
+        &self.files
+    }
+}
+
+/// The status of a job in the [`DumpStatusFile`].
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(from = "String", into = "String")]
+pub enum DumpJobStatus {
+    Done,
+    InProgress,
+    Failed,
+    Waiting,
+    /// The job is not run for this wiki.
+    Skipped,
+    /// A status not known to this crate, as written in the status file.
+    Unknown(String),
+}
+
+impl DumpJobStatus {
+    pub fn is_done(&self) -> bool {
+        *self == Self::Done
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Done => "done",
+            Self::InProgress => "in-progress",
+            Self::Failed => "failed",
+            Self::Waiting => "waiting",
+            Self::Skipped => "skipped",
+            Self::Unknown(status) => status,
+        }
+    }
+}
+
+impl From<String> for DumpJobStatus {
+    fn from(status: String) -> Self {
+        match status.as_str() {
+            "done" => Self::Done,
+            "in-progress" => Self::InProgress,
+            "failed" => Self::Failed,
+            "waiting" => Self::Waiting,
+            "skipped" => Self::Skipped,
+            _ => Self::Unknown(status),
+        }
+    }
+}
+
+impl From<DumpJobStatus> for String {
+    fn from(status: DumpJobStatus) -> Self {
+        match status {
+            DumpJobStatus::Unknown(status) => status,
+            status => status.as_str().to_string(),
+        }
+    }
+}
+
+impl Display for DumpJobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A file of a job in the [`DumpStatusFile`].
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DumpStatusFileEntryFile {
     #[serde(default)]
@@ -92,6 +399,47 @@ pub struct DumpStatusFileEntryFile {
     sha1: String,
 }
 
+impl DumpStatusFileEntryFile {
+    /// The size of the file in bytes, or zero if unknown.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The path of the file relative to the dump base url, like `/enwiktionary/20240101/enwiktionary-20240101-pages-articles.xml.bz2`.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The md5 checksum of the file in hex, or empty if unknown.
+    pub fn md5(&self) -> &str {
+        &self.md5
+    }
+
+    /// The sha1 checksum of the file in hex, or empty if unknown.
+    pub fn sha1(&self) -> &str {
+        &self.sha1
+    }
+
+    /// The size of the file in bytes, or `None` if the status file does not declare it.
+    #[cfg(feature = "download")]
+    pub(crate) fn known_size(&self) -> Option<usize> {
+        Some(self.size).filter(|size| *size != 0)
+    }
+
+    /// The md5 checksum of the file in hex, or `None` if the status file does not declare it.
+    #[cfg(feature = "download")]
+    pub(crate) fn known_md5(&self) -> Option<&str> {
+        Some(self.md5.as_str()).filter(|md5| !md5.is_empty())
+    }
+
+    /// The sha1 checksum of the file in hex, or `None` if the status file does not declare it.
+    #[cfg(feature = "download")]
+    pub(crate) fn known_sha1(&self) -> Option<&str> {
+        Some(self.sha1.as_str()).filter(|sha1| !sha1.is_empty())
+    }
+}
+
+#[cfg(feature = "download")]
 /// Download the latest dump of wiktionary in the given language.
 pub async fn download_language(
     base_url: &DumpBaseUrl,
@@ -99,70 +447,496 @@ pub async fn download_language(
     target_directory: impl Into<PathBuf>,
     progress_delay_seconds: u64,
 ) -> Result<PathBuf> {
-    let available_dates = list_available_dates(base_url, language_code).await?;
-    debug!("Available dates: {available_dates:?}");
+    download_language_with_options(
+        base_url,
+        language_code,
+        target_directory,
+        &DownloadOptions {
+            progress_delay_seconds,
+            ..Default::default()
+        },
+    )
+    .await
+}
 
-    if available_dates.len() < 2 {
-        return Err(Error::Other(format!(
-            "Less than two available dates: {available_dates:?}"
-        )));
+#[cfg(feature = "download")]
+/// Like [`download_language`], but configured by the given options.
+pub async fn download_language_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    target_directory: impl Into<PathBuf>,
+    options: &DownloadOptions,
+) -> Result<PathBuf> {
+    let target_directory = target_directory.into();
+    with_mirror_fallback(base_url, options, |mirror| {
+        let target_directory = target_directory.clone();
+        async move {
+            let date = select_dump_date(&mirror, language_code, options).await?;
+            download_language_at_date_from_mirror(
+                &mirror,
+                language_code,
+                &date,
+                target_directory,
+                options,
+            )
+            .await
+        }
+    })
+    .await
+}
+
+#[cfg(feature = "download")]
+/// Run the attempt with the given base url, and if it fails, with the other known mirrors if falling back is enabled.
+/// Returns the result of the first successful attempt, or the error of the last attempt.
+pub(crate) async fn with_mirror_fallback<T, Attempt: Future<Output = Result<T>>>(
+    base_url: &DumpBaseUrl,
+    options: &DownloadOptions,
+    mut attempt: impl FnMut(DumpBaseUrl) -> Attempt,
+) -> Result<T> {
+    let mirrors = if options.mirror_fallback {
+        base_url.with_fallbacks()
+    } else {
+        vec![base_url.clone()]
+    };
+
+    let mut last_error = None;
+    for mirror in mirrors {
+        let mirror_name = mirror.as_str().to_string();
+        match attempt(mirror).await {
+            Ok(result) => return Ok(result),
+            Err(Error::Cancelled) => return Err(Error::Cancelled),
+            Err(error) => {
+                warn!("Downloading from mirror {mirror_name} failed: {error}");
+                last_error = Some(error);
+            }
+        }
+    }
+
+    // Unwrap cannot panic because there is always at least one mirror to try.
+    Err(last_error.unwrap())
+}
+
+#[cfg(feature = "download")]
+/// Select the date of the dump to download by the date selection of the options.
+pub async fn select_dump_date(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    options: &DownloadOptions,
+) -> Result<String> {
+    // The available dates are only listed for the selections that need them.
+    let list_available_dates = || async {
+        let available_dates =
+            list_available_dates_with_options(base_url, language_code, options).await?;
+        debug!("Available dates: {available_dates:?}");
+        Result::<_>::Ok(available_dates)
+    };
+
+    match &options.date_selection {
+        DumpDateSelection::LatestAlias => {
+            resolve_dump_date(base_url, language_code, LATEST_DATE, options).await
+        }
+        DumpDateSelection::Latest => {
+            let available_dates = list_available_dates().await?;
+            let date = available_dates
+                .last()
+                .ok_or_else(|| Error::Other(format!("No available dates")))?;
+            debug!("Selected latest date '{date}'");
+            Ok(format_dump_date(date))
+        }
+        DumpDateSelection::SecondLatest => {
+            let available_dates = list_available_dates().await?;
+            if available_dates.len() < 2 {
+                return Err(Error::Other(format!(
+                    "Less than two available dates: {available_dates:?}"
+                )));
+            }
+            let date = &available_dates[available_dates.len() - 2];
+            debug!("Selected second to last date '{date}'");
+            Ok(format_dump_date(date))
+        }
+        DumpDateSelection::Specific(date) => {
+            let available_dates = list_available_dates().await?;
+            if !available_dates.contains(&parse_dump_date(date)?) {
+                return Err(Error::Other(format!(
+                    "Date '{date}' is not available, available dates are {available_dates:?}"
+                )));
+            }
+            debug!("Selected given date '{date}'");
+            Ok(date.clone())
+        }
+        DumpDateSelection::LatestComplete => {
+            let available_dates = list_available_dates().await?;
+            for date in available_dates.iter().rev() {
+                let date = format_dump_date(date);
+                let dump_status_file =
+                    match fetch_dump_status_with_options(base_url, language_code, &date, options)
+                        .await
+                    {
+                        Ok(dump_status_file) => dump_status_file,
+                        Err(error) => {
+                            debug!(
+                                "Skipping date '{date}' without readable dump status file: {error}"
+                            );
+                            continue;
+                        }
+                    };
+
+                match dump_status_file.articles_dump() {
+                    Ok(_) => {
+                        debug!("Selected latest complete date '{date}'");
+                        return Ok(date);
+                    }
+                    Err(error) => debug!("Skipping incomplete date '{date}': {error}"),
+                }
+            }
+
+            Err(Error::Other(format!(
+                "No complete dump among the available dates {available_dates:?}"
+            )))
+        }
     }
-    let date = &available_dates[available_dates.len() - 2];
-    debug!("Selected second to last date '{date}'");
+}
+
+#[cfg(feature = "download")]
+/// Download the dump of wiktionary in the given language from the given date.
+pub async fn download_language_at_date(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    target_directory: impl Into<PathBuf>,
+    progress_delay_seconds: u64,
+) -> Result<PathBuf> {
+    download_language_at_date_with_options(
+        base_url,
+        language_code,
+        date,
+        target_directory,
+        &DownloadOptions {
+            progress_delay_seconds,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+#[cfg(feature = "download")]
+/// Like [`download_language_at_date`], but configured by the given options.
+pub async fn download_language_at_date_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    target_directory: impl Into<PathBuf>,
+    options: &DownloadOptions,
+) -> Result<PathBuf> {
+    let target_directory = target_directory.into();
+    with_mirror_fallback(base_url, options, |mirror| {
+        let target_directory = target_directory.clone();
+        async move {
+            download_language_at_date_from_mirror(
+                &mirror,
+                language_code,
+                date,
+                target_directory,
+                options,
+            )
+            .await
+        }
+    })
+    .await
+}
+
+#[cfg(feature = "download")]
+/// Download all files of the given dump job of wiktionary in the given language from the given date,
+/// like `metahistorybz2dump`, `pagetitlesdump`, `categorylinkstable` or `langlinkstable`.
+/// Jobs split into multiple parts have one file per part.
+/// Returns the paths of the files, ordered by file name.
+pub async fn download_job_at_date_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    job: &str,
+    target_directory: impl Into<PathBuf>,
+    options: &DownloadOptions,
+) -> Result<Vec<PathBuf>> {
+    let target_directory = target_directory.into();
+    with_mirror_fallback(base_url, options, |mirror| {
+        let target_directory = target_directory.clone();
+        async move {
+            let date = &resolve_dump_date(&mirror, language_code, date, options).await?;
+            let (dump_status_file, body) =
+                fetch_dump_status_file(&mirror, language_code, date, options).await?;
+            let entry = dump_status_file.job(job)?;
+            download_job_files(
+                &mirror,
+                language_code,
+                date,
+                &body,
+                job,
+                entry,
+                target_directory,
+                options,
+            )
+            .await
+        }
+    })
+    .await
+}
+
+#[cfg(feature = "download")]
+/// Like [`download_job_at_date_with_options`], but selects the date like [`download_language_with_options`].
+/// Note that [`DumpDateSelection::LatestComplete`] selects the latest date whose articles dump is done,
+/// regardless of the given job.
+pub async fn download_job_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    job: &str,
+    target_directory: impl Into<PathBuf>,
+    options: &DownloadOptions,
+) -> Result<Vec<PathBuf>> {
+    let target_directory = target_directory.into();
+    with_mirror_fallback(base_url, options, |mirror| {
+        let target_directory = target_directory.clone();
+        async move {
+            let date = select_dump_date(&mirror, language_code, options).await?;
+            download_job_at_date_with_options(
+                &mirror,
+                language_code,
+                &date,
+                job,
+                target_directory,
+                &DownloadOptions {
+                    mirror_fallback: false,
+                    ..options.clone()
+                },
+            )
+            .await
+        }
+    })
+    .await
+}
+
+#[cfg(feature = "download")]
+async fn download_language_at_date_from_mirror(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    target_directory: PathBuf,
+    options: &DownloadOptions,
+) -> Result<PathBuf> {
+    let date = &resolve_dump_date(base_url, language_code, date, options).await?;
+    let (dump_status_file, body) =
+        fetch_dump_status_file(base_url, language_code, date, options).await?;
+    let articles_dump = dump_status_file.articles_dump()?;
+    let mut target_files = download_job_files(
+        base_url,
+        language_code,
+        date,
+        &body,
+        ARTICLES_DUMP_JOB,
+        articles_dump,
+        target_directory,
+        options,
+    )
+    .await?;
+
+    // Unwrap cannot panic because the articles dump consists of exactly one file.
+    Ok(target_files.pop().unwrap())
+}
+
+#[cfg(feature = "download")]
+/// Fetch the status file of the dump of wiktionary in the given language from the given date,
+/// to inspect the status of its jobs.
+pub async fn fetch_dump_status(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+) -> Result<DumpStatusFile> {
+    fetch_dump_status_with_options(base_url, language_code, date, &DownloadOptions::default()).await
+}
+
+#[cfg(feature = "download")]
+/// Like [`fetch_dump_status`], but retries failed requests according to the given options.
+pub async fn fetch_dump_status_with_options(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    options: &DownloadOptions,
+) -> Result<DumpStatusFile> {
+    let date = &resolve_dump_date(base_url, language_code, date, options).await?;
+    Ok(
+        fetch_dump_status_file(base_url, language_code, date, options)
+            .await?
+            .0,
+    )
+}
 
+#[cfg(feature = "download")]
+/// Fetch and parse the dump status file, returning it together with its raw content.
+pub(crate) async fn fetch_dump_status_file(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    options: &DownloadOptions,
+) -> Result<(DumpStatusFile, String)> {
     let url = dump_status_file(base_url, language_code, date)?;
-    let body = reqwest::get(url).await?.text().await?;
+    let body = get_text(&url, &options.retry_policy).await?;
     trace!("{body}");
     let dump_status_file: DumpStatusFile = serde_json::from_str(&body)?;
     trace!("{dump_status_file:#?}");
+    Ok((dump_status_file, body))
+}
 
-    let dump_status_file_version = &dump_status_file.version;
-    if dump_status_file_version != "0.8" {
-        return Err(Error::Other(format!("Wrong dump status file version '{dump_status_file_version}', currently only 0.8 is supported.")));
-    }
-
-    let articles_dump = dump_status_file.jobs.get("articlesdump").ok_or_else(|| {
-        Error::Other(format!(
-            "Dump status file misses job entry for 'articlesdump'"
+#[cfg(feature = "download")]
+/// Download the files of the job entry of the dump status file with the given raw content
+/// to the paths given by the path template of the options.
+/// Existing files are kept if they match their checksums.
+#[allow(clippy::too_many_arguments)]
+async fn download_job_files(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    dump_status_file_body: &str,
+    job: &str,
+    entry: &DumpStatusFileEntry,
+    target_directory: PathBuf,
+    options: &DownloadOptions,
+) -> Result<Vec<PathBuf>> {
+    let language_abbreviation = language_code.to_wiktionary_abbreviation();
+    let target_path = |file_name: &str| {
+        target_directory.join(options.path_template.render(
+            language_abbreviation,
+            date,
+            job,
+            file_name,
         ))
-    })?;
-    trace!("{articles_dump:#?}");
+    };
 
-    let articles_dump_status = &articles_dump.status;
-    if articles_dump_status != "done" {
-        return Err(Error::Other(format!(
-            "Wrong articlesdump status '{articles_dump_status}', expected 'done'."
-        )));
-    }
-    let articles_dump_file_amount = articles_dump.files.len();
-    if articles_dump_file_amount != 1 {
-        return Err(Error::Other(format!(
-            "Wrong articlesdump file amount {articles_dump_file_amount}, expected 1."
-        )));
+    // Keep the status file, such that the checksums can be verified again later.
+    let status_file = target_path(store::DUMP_STATUS_FILE_NAME);
+    if let Some(parent) = status_file.parent() {
+        tokio::fs::create_dir_all(parent).await?;
     }
+    tokio::fs::write(&status_file, dump_status_file_body).await?;
 
-    // Unwrap cannot panic because we abort if there is not exactly one entry.
-    let (file_name, properties) = articles_dump.files.iter().next().unwrap();
-    let url = dump_url(base_url, &properties.url)?;
-    let language_abbreviation = language_code.to_wiktionary_abbreviation();
-    let mut target_file = target_directory.into();
-    target_file.push(language_abbreviation);
-    target_file.push(date);
-    target_file.push(file_name);
+    let mut target_files = Vec::new();
+    for (file_name, properties) in &entry.files {
+        let url = dump_url(base_url, &properties.url)?;
+        let target_file = target_path(file_name);
+        if let Some(parent) = target_file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
 
-    if target_file.exists() {
-        info!("Skipping download, because file exists already.");
-    } else {
-        download_file_with_progress_log(
-            &url,
+        let download_required = if !target_file.exists() {
+            true
+        } else if options.force {
+            info!("Downloading {file_name} again, because the download is forced.");
+            true
+        } else if let Some(reason) = store::verify_file(
             &target_file,
-            properties.size,
-            progress_delay_seconds,
-            Some(&properties.md5),
-            Some(&properties.sha1),
+            properties.known_size(),
+            &properties.md5,
+            &properties.sha1,
+            None,
         )
-        .await?;
+        .await?
+        {
+            warn!(
+                "Downloading {file_name} again, because the existing file does not match: {reason}"
+            );
+            true
+        } else {
+            info!("Skipping download of {file_name}, because the file exists already and matches its checksums.");
+            false
+        };
+
+        if download_required {
+            if target_file.exists() {
+                tokio::fs::remove_file(&target_file).await?;
+            }
+            download_file(
+                &url,
+                &target_file,
+                properties.size,
+                options,
+                properties.known_md5(),
+                properties.known_sha1(),
+            )
+            .await?;
+        }
+
+        target_files.push(target_file);
+    }
+
+    Ok(target_files)
+}
+
+/// Options for [`download_and_parse_language`].
+#[cfg(all(feature = "download", feature = "parse"))]
+#[derive(Debug, Clone, Default)]
+pub struct DownloadAndParseOptions {
+    pub download: DownloadOptions,
+    pub parse: DumpParseOptions,
+    /// Delete the compressed dump after it was parsed successfully.
+    pub delete_dump: bool,
+}
+
+#[cfg(all(feature = "download", feature = "parse"))]
+/// Download the latest dump of wiktionary in the given language like [`download_language`],
+/// and parse it into the output file like [`parse_dump_file_with_options`](parser::parse_dump_file_with_options).
+/// Returns the path of the dump file, which does not exist anymore if it was deleted.
+pub async fn download_and_parse_language(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    target_directory: impl Into<PathBuf>,
+    output_file: impl AsRef<Path>,
+    error_log: impl AsRef<Path>,
+    options: &DownloadAndParseOptions,
+) -> Result<PathBuf> {
+    let (dump_file, _) = download_and_parse(
+        base_url,
+        language_code,
+        target_directory,
+        output_file,
+        error_log,
+        options,
+    )
+    .await?;
+    Ok(dump_file)
+}
+
+#[cfg(all(feature = "download", feature = "parse"))]
+/// Like [`download_and_parse_language`], but also returns the statistics of the parsed dump.
+pub(crate) async fn download_and_parse(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    target_directory: impl Into<PathBuf>,
+    output_file: impl AsRef<Path>,
+    error_log: impl AsRef<Path>,
+    options: &DownloadAndParseOptions,
+) -> Result<(PathBuf, parser::statistics::ParseStatistics)> {
+    let dump_file = download_language_with_options(
+        base_url,
+        language_code,
+        target_directory,
+        &options.download,
+    )
+    .await?;
+
+    info!("Parsing dump file {dump_file:?}");
+    let statistics = parser::parse_dump_file_with_options(
+        &dump_file,
+        Some(output_file),
+        |_| std::future::ready(Ok(())),
+        |_, _, _| {},
+        error_log,
+        &options.parse,
+    )
+    .await?;
+
+    if options.delete_dump {
+        info!("Deleting dump file {dump_file:?}");
+        tokio::fs::remove_file(&dump_file).await?;
     }
 
-    Ok(target_file)
+    Ok((dump_file, statistics))
 }
@@ -0,0 +1,70 @@
+//! Truncation of content in log messages, such that unexpected tags or texts of megabytes do not flood the terminal.
+//!
+//! Excerpts keep the start and the end of the content, cut at character boundaries,
+//! and state how many bytes were omitted in between.
+//! Where the parser has an error log or a quarantine file, the full content is written there instead.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The default maximum length of an excerpt in bytes.
+pub const DEFAULT_EXCERPT_LIMIT: usize = 256;
+
+static EXCERPT_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_EXCERPT_LIMIT);
+
+/// Set the maximum length of all excerpts in bytes, or zero to never truncate.
+pub fn set_excerpt_limit(limit: usize) {
+    EXCERPT_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// The maximum length of all excerpts in bytes, where zero means no limit.
+pub fn excerpt_limit() -> usize {
+    EXCERPT_LIMIT.load(Ordering::Relaxed)
+}
+
+/// The longest prefix of the text that has at most `max_bytes` bytes and ends at a character boundary.
+pub fn truncate_utf8(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// The longest suffix of the text that has at most `max_bytes` bytes and starts at a character boundary.
+pub fn truncate_utf8_start(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// The text shortened to the [excerpt limit](excerpt_limit), like `<page><title>a … [1234 bytes omitted] … </page>`.
+/// Two thirds of the limit are taken from the start of the text, and one third from its end.
+pub fn excerpt(text: &str) -> Cow<'_, str> {
+    excerpt_with_limit(text, excerpt_limit())
+}
+
+/// Like [`excerpt`], but with the given limit instead of the global one.
+pub fn excerpt_with_limit(text: &str, limit: usize) -> Cow<'_, str> {
+    if limit == 0 || text.len() <= limit {
+        return Cow::Borrowed(text);
+    }
+    let head = truncate_utf8(text, limit - limit / 3);
+    let tail = truncate_utf8_start(text, limit / 3);
+    let omitted = text.len() - head.len() - tail.len();
+    Cow::Owned(format!("{head} … [{omitted} bytes omitted] … {tail}"))
+}
+
+/// The debug representation of the value, shortened like [`excerpt`].
+pub fn debug_excerpt(value: &impl Debug) -> String {
+    excerpt(&format!("{value:?}")).into_owned()
+}
@@ -0,0 +1,121 @@
+//! Looking up the extracted words of a title, e.g. to serve them.
+
+use crate::error::Result;
+use crate::parser::words::Word;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// A read-only store of extracted words that can be queried by title.
+pub trait WordLookup {
+    /// All words with the given title, in all languages.
+    fn lookup(&mut self, word: &str) -> Result<Vec<Word>>;
+}
+
+/// Caches the results of the most recently requested titles of a [`WordLookup`],
+/// such that frequently requested titles do not hit the store on each request.
+///
+/// Since the store is read-only, cached results never become stale.
+/// Titles without words are cached as well.
+pub struct CachedWordLookup<Lookup> {
+    lookup: Lookup,
+    cache: LruCache<Arc<Vec<Word>>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<Lookup: WordLookup> CachedWordLookup<Lookup> {
+    /// Cache the results of up to `capacity` titles.
+    /// If the capacity is zero, nothing is cached.
+    pub fn new(lookup: Lookup, capacity: usize) -> Self {
+        Self {
+            lookup,
+            cache: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// All words with the given title, from the cache if possible.
+    pub fn lookup(&mut self, word: &str) -> Result<Arc<Vec<Word>>> {
+        if let Some(words) = self.cache.get(word) {
+            self.hits += 1;
+            return Ok(words);
+        }
+
+        self.misses += 1;
+        let words = Arc::new(self.lookup.lookup(word)?);
+        self.cache.insert(word.to_string(), words.clone());
+        Ok(words)
+    }
+
+    /// The amount of lookups that were answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The amount of lookups that were passed to the store.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Remove all cached results, e.g. after the store was replaced.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn into_inner(self) -> Lookup {
+        self.lookup
+    }
+}
+
+/// A map of limited capacity that evicts the least recently used entry when full.
+#[derive(Debug, Clone)]
+struct LruCache<Value> {
+    capacity: usize,
+    /// Maps keys to their values and the time they were last used.
+    entries: HashMap<String, (Value, u64)>,
+    /// Maps the times of last use to their keys, oldest first.
+    usage: BTreeMap<u64, String>,
+    time: u64,
+}
+
+impl<Value: Clone> LruCache<Value> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            usage: BTreeMap::new(),
+            time: 0,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Value> {
+        self.time += 1;
+        let (value, last_use) = self.entries.get_mut(key)?;
+        let key = self.usage.remove(last_use)?;
+        *last_use = self.time;
+        self.usage.insert(self.time, key);
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: String, value: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.time += 1;
+        if let Some((_, last_use)) = self.entries.insert(key.clone(), (value, self.time)) {
+            self.usage.remove(&last_use);
+        } else if self.entries.len() > self.capacity {
+            let oldest_use = *self.usage.keys().next().unwrap();
+            let oldest_key = self.usage.remove(&oldest_use).unwrap();
+            self.entries.remove(&oldest_key);
+        }
+        self.usage.insert(self.time, key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.usage.clear();
+    }
+}
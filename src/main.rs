@@ -1,21 +1,134 @@
 #![allow(clippy::useless_format)]
 
+use chrono::NaiveDate;
 use clap::Parser;
-use log::{info, LevelFilter};
+use log::{info, warn, LevelFilter};
 use simplelog::{ColorChoice, CombinedLogger, Config, TermLogger, TerminalMode};
+use std::io::{BufRead, Write};
 use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+use wiktionary_dump_parser::analysis::coverage::CoverageMatrix;
+use wiktionary_dump_parser::analysis::near_duplicates::NearDuplicateTitles;
+use wiktionary_dump_parser::analysis::page_history::{dump_page_snapshot, PageSnapshot};
+use wiktionary_dump_parser::analysis::unknown_headings::UnknownHeadings;
+#[cfg(unix)]
+use wiktionary_dump_parser::daemon::{run_daemon, DaemonConfiguration};
+use wiktionary_dump_parser::dates::parse_dump_date;
+use wiktionary_dump_parser::download::{
+    DownloadOptions, DumpDateSelection, PathTemplate, RetryPolicy,
+};
 use wiktionary_dump_parser::error::{Error, Result};
+use wiktionary_dump_parser::export::alternative_forms_graph::AlternativeFormsGraph;
+use wiktionary_dump_parser::export::ereader::{
+    write_kindle_html, write_kindle_opf, write_kobo_dictfile, EreaderDictionaryBuilder,
+    EreaderFormat,
+};
+use wiktionary_dump_parser::export::inflection_table::InflectionTables;
+use wiktionary_dump_parser::export::manifest::{DatasetManifest, MANIFEST_FILE_NAME};
+use wiktionary_dump_parser::export::text_corpus::{
+    CorpusTextKind, TextCorpusOptions, TextCorpusWriter,
+};
+use wiktionary_dump_parser::export::translation_tsv::TranslationTsvWriter;
+use wiktionary_dump_parser::export::word_tags::WordTags;
+use wiktionary_dump_parser::export::words::{WordsFormat, WordsWriter};
+use wiktionary_dump_parser::ingest::{ingest_all, IngestOptions};
 use wiktionary_dump_parser::language_code::LanguageCode;
-use wiktionary_dump_parser::urls::{DumpBaseUrl, DumpIndexUrl};
+use wiktionary_dump_parser::log_excerpt::set_excerpt_limit;
+use wiktionary_dump_parser::merge::{CaseMergeConfiguration, CaseMergePolicy, WordMerger};
+use wiktionary_dump_parser::mirrors::{
+    best_mirror, check_mirrors, load_mirror_ranking, save_mirror_ranking, MirrorProbeTarget,
+};
+use wiktionary_dump_parser::parser::diff::{diff_dumps, DumpDiffOptions};
+use wiktionary_dump_parser::parser::partition::PartitionBy;
+use wiktionary_dump_parser::parser::parts::{expand_input_files, parse_dump_files_with_options};
+use wiktionary_dump_parser::parser::sink::{
+    read_json_pages, read_json_siteinfo, OutputFormat, SiteinfoSink,
+};
+use wiktionary_dump_parser::parser::statistics::collect_dump_statistics;
+use wiktionary_dump_parser::parser::template_store::{TemplateExpander, TemplateStore};
+use wiktionary_dump_parser::parser::titles::{write_titles, TitleFilter};
+use wiktionary_dump_parser::parser::warnings::{WarningKind, WarningReporter};
+use wiktionary_dump_parser::parser::words::alternative_forms::wikitext_to_alternative_forms;
+use wiktionary_dump_parser::parser::words::citations::wikitext_to_citations;
+use wiktionary_dump_parser::parser::words::classification::PageClassifier;
+use wiktionary_dump_parser::parser::words::definitions::wikitext_to_definitions;
+use wiktionary_dump_parser::parser::words::etymology::wikitext_to_etymology_origins;
+use wiktionary_dump_parser::parser::words::function_words::{
+    FunctionWordLists, FunctionWordProvenance,
+};
+use wiktionary_dump_parser::parser::words::inflections::wikitext_to_inflected_forms;
+use wiktionary_dump_parser::parser::words::language_names::LanguageNames;
+use wiktionary_dump_parser::parser::words::language_pack::LanguagePacks;
+use wiktionary_dump_parser::parser::words::morphology::wikitext_to_morphology;
+use wiktionary_dump_parser::parser::words::phrases::wikitext_to_phrases;
+use wiktionary_dump_parser::parser::words::symbols::wikitext_to_symbols;
+use wiktionary_dump_parser::parser::words::translations::wikitext_to_translations;
+use wiktionary_dump_parser::parser::words::word_types::WordTypeNaming;
+use wiktionary_dump_parser::parser::words::{
+    extract_words, extract_words_with_namespace, Word, WordExtractionOptions,
+};
+use wiktionary_dump_parser::progress::{
+    CountingReader, ProgressPhase, ProgressReporter, ProgressTracker,
+};
+use wiktionary_dump_parser::qa::html_verification::verify_words_against_html;
+use wiktionary_dump_parser::qa::{live_page_snapshot, sample_category};
+use wiktionary_dump_parser::resource_usage::ResourceUsageRecorder;
+use wiktionary_dump_parser::store::checksums::{
+    fetch_checksum_file, find_checksum_files, verify_directory, ChecksumAlgorithm, ChecksumFile,
+};
+use wiktionary_dump_parser::store::{prune_store, verify_store, PruneOptions, VerifyStoreOptions};
+use wiktionary_dump_parser::urls::{DumpBaseUrl, DumpIndexUrl, Mirror, KNOWN_MIRRORS};
+use wiktionary_dump_parser::wiktionary_edition::WiktionaryEdition;
 use wiktionary_dump_parser::{
-    download_language, list_wiktionary_dump_languages, parser::parse_dump_file,
+    download_and_parse_language, download_job_with_options, download_language_with_options,
+    fetch_dump_status, list_wiktionary_dump_languages,
+    parser::{
+        parse_dump_file_with_options, parse_dump_file_with_page_consumer,
+        parse_dump_file_with_sink, parse_dump_url, DumpParseOptions, RevisionHistory,
+        WikitextErrorPolicy,
+    },
+    DownloadAndParseOptions,
 };
 
+/// Options can also be given as environment variables, which are overridden by flags.
 #[derive(Parser)]
 struct Configuration {
-    #[clap(long, default_value = "Info")]
+    #[clap(long, default_value = "Info", env = "WDP_LOG_LEVEL")]
     log_level: LevelFilter,
 
+    /// The maximum length in bytes of tags, texts and errors quoted in log messages, or zero for no limit.
+    /// The full content is written to the error log or the quarantine file instead.
+    #[clap(
+        long,
+        global = true,
+        default_value = "256",
+        env = "WDP_LOG_EXCERPT_LIMIT"
+    )]
+    log_excerpt_limit: usize,
+
+    /// The base url of the mirror to download dumps from.
+    #[clap(long, global = true, env = "WDP_BASE_URL")]
+    base_url: Option<String>,
+
+    /// The name of a known mirror to download dumps from, one of `umu`, `wikimedia`, `accum` or `us-dev`.
+    /// `wikimedia` is the official host `dumps.wikimedia.org`.
+    #[clap(long, global = true, env = "WDP_MIRROR", conflicts_with = "base_url")]
+    mirror: Option<String>,
+
+    /// The number of worker threads, defaults to the number of cores.
+    /// If given, it is also the default of the page workers and decompression threads of parsing commands.
+    /// Options are taken from the command line first, and then from the `WDP_*` environment variables.
+    #[clap(long, global = true, env = "WDP_JOBS")]
+    jobs: Option<usize>,
+
+    /// If given, the resource usage summary of the run is additionally written to this file as json.
+    #[clap(long, global = true)]
+    resource_usage_file: Option<PathBuf>,
+
     #[clap(subcommand)]
     command: CliCommand,
 }
@@ -31,89 +144,2342 @@ enum CliCommand {
         english_name: Option<String>,
         #[clap(long)]
         wiktionary_abbreviation: Option<String>,
-        #[clap(long, default_value = ".")]
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
+        target_directory: PathBuf,
+        #[clap(long, default_value = "10")]
+        progress_delay: u64,
+        /// Download from the best mirror of a ranking saved by `check-mirrors`.
+        #[clap(long)]
+        mirror_ranking: Option<PathBuf>,
+        /// Download the dump with this many concurrent connections.
+        #[clap(long, default_value = "1")]
+        connections: usize,
+        /// The amount of background tasks that compute checksums while downloading with a single connection.
+        /// Zero computes them inline, more than one computes each algorithm in its own task.
+        #[clap(long, default_value = "1")]
+        checksum_tasks: usize,
+        /// The maximum number of attempts of each http request, retrying with exponential backoff.
+        #[clap(long, default_value = "5")]
+        max_attempts: u32,
+        /// One of `latest`, `second-latest`, `latest-complete`, `latest-alias` or a date like `20240101`.
+        /// `latest-complete` selects the latest date whose articles dump is done,
+        /// `latest-alias` the date the `latest` directory of the mirror stands for.
+        #[clap(long, default_value = "second-latest")]
+        date: DumpDateSelection,
+        /// Download the dump again even if it exists already and matches its checksums.
+        #[clap(long)]
+        force: bool,
+        /// Do not fall back to other known mirrors if requests to the mirror fail.
+        #[clap(long)]
+        no_mirror_fallback: bool,
+        /// Download all files of this dump job instead of the articles dump,
+        /// e.g. `metahistorybz2dump`, `pagetitlesdump`, `categorylinkstable` or `langlinkstable`.
+        #[clap(long)]
+        job: Option<String>,
+        /// Where dumps are stored in the target directory, with the placeholders
+        /// `{lang}`, `{wiki}`, `{date}`, `{job}` and `{file}`, e.g. `s3-sync/{date}/{lang}/{file}`.
+        #[clap(
+            long,
+            default_value = "{lang}/{date}/{file}",
+            env = "WDP_PATH_TEMPLATE"
+        )]
+        path_template: PathTemplate,
+    },
+
+    /// Downloads the latest dump of a single language and parses it.
+    DownloadAndParse {
+        #[clap(long)]
+        english_name: Option<String>,
+        #[clap(long)]
+        wiktionary_abbreviation: Option<String>,
+        /// The directory the dump is downloaded into, like with `download-language`.
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
+        target_directory: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        #[clap(long)]
+        output_pretty: bool,
+        /// One of `json` for concatenated json objects, or `json-lines` for one object per line.
+        #[clap(long, default_value = "json", conflicts_with = "output_pretty")]
+        format: OutputFormat,
+        /// Delete the compressed dump after parsing it.
+        #[clap(long)]
+        delete_dump: bool,
+        /// Write the counts of pages, redirects and text bytes per namespace to this file as json.
+        #[clap(long)]
+        stats_file: Option<PathBuf>,
+        #[clap(long, default_value = "10")]
+        progress_delay: u64,
+        /// Download the dump with this many concurrent connections.
+        #[clap(long, default_value = "1")]
+        connections: usize,
+        /// The amount of background tasks that compute checksums while downloading with a single connection.
+        /// Zero computes them inline, more than one computes each algorithm in its own task.
+        #[clap(long, default_value = "1")]
+        checksum_tasks: usize,
+        /// The maximum number of attempts of each http request, retrying with exponential backoff.
+        #[clap(long, default_value = "5")]
+        max_attempts: u32,
+        /// One of `latest`, `second-latest`, `latest-complete`, `latest-alias` or a date like `20240101`.
+        /// `latest-complete` selects the latest date whose articles dump is done,
+        /// `latest-alias` the date the `latest` directory of the mirror stands for.
+        #[clap(long, default_value = "second-latest")]
+        date: DumpDateSelection,
+        /// Download the dump again even if it exists already and matches its checksums.
+        #[clap(long)]
+        force: bool,
+        /// Do not fall back to other known mirrors if requests to the mirror fail.
+        #[clap(long)]
+        no_mirror_fallback: bool,
+        /// Where dumps are stored in the target directory, with the placeholders
+        /// `{lang}`, `{wiki}`, `{date}`, `{job}` and `{file}`, e.g. `s3-sync/{date}/{lang}/{file}`.
+        #[clap(
+            long,
+            default_value = "{lang}/{date}/{file}",
+            env = "WDP_PATH_TEMPLATE"
+        )]
+        path_template: PathTemplate,
+    },
+
+    /// Downloads and parses the dumps of all editions, or of the given editions, into one file per edition,
+    /// and writes a report of the editions that succeeded and failed.
+    IngestAll {
+        /// The directory the dumps are downloaded into, like with `download-language`.
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
+        target_directory: PathBuf,
+        /// The directory the parsed editions and their error logs are written into.
+        #[clap(long)]
+        output_directory: PathBuf,
+        /// Only ingest these editions. Defaults to all editions that have dumps.
+        #[clap(long)]
+        wiktionary_abbreviation: Vec<String>,
+        /// Write the report as json to this file.
+        #[clap(long)]
+        report_file: Option<PathBuf>,
+        /// The amount of editions that are downloaded and parsed at the same time.
+        #[clap(long, default_value = "2")]
+        concurrency: usize,
+        /// Stop at the first edition that fails.
+        #[clap(long)]
+        fail_fast: bool,
+        /// One of `json` for concatenated json objects, or `json-lines` for one object per line.
+        #[clap(long, default_value = "json-lines")]
+        format: OutputFormat,
+        /// The amount of threads that parse the wikitext of pages of each edition while its dump is read.
+        /// Defaults to `--jobs` if given, and to zero otherwise.
+        #[clap(long)]
+        page_workers: Option<usize>,
+        /// Delete the compressed dumps after parsing them.
+        #[clap(long)]
+        delete_dump: bool,
+        /// One of `latest`, `second-latest`, `latest-complete`, `latest-alias` or a date like `20240101`.
+        #[clap(long, default_value = "second-latest")]
+        date: DumpDateSelection,
+        /// The maximum number of attempts of each http request, retrying with exponential backoff.
+        #[clap(long, default_value = "5")]
+        max_attempts: u32,
+        /// Where dumps are stored in the target directory, with the placeholders
+        /// `{lang}`, `{wiki}`, `{date}`, `{job}` and `{file}`, e.g. `s3-sync/{date}/{lang}/{file}`.
+        #[clap(
+            long,
+            default_value = "{lang}/{date}/{file}",
+            env = "WDP_PATH_TEMPLATE"
+        )]
+        path_template: PathTemplate,
+    },
+
+    /// Prints the status, last update and file count of each job of a dump.
+    DumpStatus {
+        #[clap(long)]
+        wiktionary_abbreviation: String,
+        /// The date of the dump, like `20240101`, or `latest` for the dump of the `latest` directory.
+        #[clap(long)]
+        date: String,
+    },
+
+    /// Probes mirrors for availability, latency and the presence of a dump, and prints them ranked.
+    CheckMirrors {
+        /// The mirrors to check, defaults to all known mirrors.
+        #[clap(long)]
+        mirror: Vec<String>,
+        #[clap(long)]
+        wiktionary_abbreviation: Option<String>,
+        /// Requires `--wiktionary-abbreviation`.
+        #[clap(long)]
+        date: Option<String>,
+        /// Save the ranking to be used by subsequent downloads.
+        #[clap(long)]
+        save_ranking: Option<PathBuf>,
+    },
+
+    /// Verifies all stored dumps against the checksums recorded when downloading them.
+    VerifyStore {
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
         target_directory: PathBuf,
+        /// Limits the rate at which files are read.
+        #[clap(long)]
+        max_mib_per_second: Option<u64>,
+        /// Download corrupted files again.
+        #[clap(long)]
+        repair: bool,
         #[clap(long, default_value = "10")]
         progress_delay: u64,
     },
 
+    /// Verifies the files of a directory against published `md5sums.txt` and `sha1sums.txt` checksum files.
+    VerifyChecksums {
+        #[clap(long)]
+        directory: PathBuf,
+        /// The checksum files to verify against. By default, the checksum files in the directory are used.
+        #[clap(long)]
+        checksum_file: Vec<PathBuf>,
+        /// Fetch the checksum files of this wiktionary from the mirror instead.
+        #[clap(long, requires = "date")]
+        wiktionary_abbreviation: Option<String>,
+        /// The date of the dump whose checksum files are fetched.
+        #[clap(long, requires = "wiktionary_abbreviation")]
+        date: Option<String>,
+        /// Limits the rate at which files are read.
+        #[clap(long)]
+        max_mib_per_second: Option<u64>,
+    },
+
+    /// Removes superseded dumps from the target directory.
+    Prune {
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
+        target_directory: PathBuf,
+        /// The number of dates to keep per language.
+        #[clap(long, default_value = "1")]
+        keep_last: usize,
+        /// Also remove dates whose files do not match their checksums.
+        #[clap(long)]
+        keep_verified_only: bool,
+        /// Only print what would be removed.
+        #[clap(long)]
+        dry_run: bool,
+        /// Limits the rate at which files are read while verifying.
+        #[clap(long)]
+        max_mib_per_second: Option<u64>,
+    },
+
+    /// Extracts words from random live pages of a category, e.g. `English lemmas`,
+    /// and reports the pages that fail, to evaluate the coverage of the extraction.
+    SampleCategory {
+        #[clap(long)]
+        category: String,
+        #[clap(long, default_value = "en")]
+        wiktionary_abbreviation: String,
+        #[clap(long, default_value = "50")]
+        count: usize,
+        /// Write the full report as json to this file.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Checks a random sample of extracted words against the html of their pages rendered by the Wikimedia REST api,
+    /// and reports the words whose headword or word type is not found, to estimate the precision of the extraction.
+    VerifyWords {
+        /// Words written by `extract-words` with `--format json-lines`.
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long, default_value = "en")]
+        wiktionary_abbreviation: String,
+        #[clap(long, default_value = "100")]
+        count: usize,
+        /// Write the full report as json to this file.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Compares the words, senses and inflected forms extracted from a single page in two dumps,
+    /// or in a dump and the live page, e.g. to review recent edits.
+    ComparePage {
+        #[clap(long)]
+        title: String,
+        /// The older dump.
+        #[clap(long)]
+        old_input_file: PathBuf,
+        /// The newer dump.
+        #[clap(long, required_unless_present = "live")]
+        new_input_file: Option<PathBuf>,
+        /// Compare against the live page of this wiktionary instead of a newer dump.
+        #[clap(long, conflicts_with = "new_input_file")]
+        live: Option<String>,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Write the diff as json to this file.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+
     ParseDumpFile {
+        /// The dump file, or the parts of a dump split into multiple files, parsed into a single output
+        /// in the order given.
+        /// File names may contain the wildcards `*` and `?`, like `pages-articles*.xml-p*.bz2`,
+        /// whose matches are sorted by name with numbers compared by value.
+        #[clap(long, required = true)]
+        input_file: Vec<PathBuf>,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        #[clap(long)]
+        output_pretty: bool,
+        /// One of `json` for concatenated json objects, or `json-lines` for one object per line.
+        #[clap(long, default_value = "json", conflicts_with = "output_pretty")]
+        format: OutputFormat,
+        /// Continue at the next page after malformed pages, appending them to this file.
+        #[clap(long)]
+        quarantine_file: Option<PathBuf>,
+        /// Write the siteinfo to this file instead of the output file.
+        #[clap(long)]
+        siteinfo_file: Option<PathBuf>,
+        /// A template store created with `collect-templates`, required by `--expand-template`.
+        #[clap(long)]
+        template_store: Option<PathBuf>,
+        /// Expand invocations of this template before parsing the pages, e.g. `given name`.
+        #[clap(long, requires = "template_store")]
+        expand_template: Vec<String>,
+        /// Write links from stable word identifiers to page and revision ids to this file.
+        #[clap(long)]
+        word_link_file: Option<PathBuf>,
+        /// Also extract words from `Translingual` sections, e.g. for the word link file.
+        #[clap(long)]
+        include_translingual: bool,
+        /// The date of the dump recorded in the word links, like `20230801`.
+        /// Inferred from the input file name by default.
+        #[clap(long, value_parser = parse_dump_date)]
+        dump_date: Option<NaiveDate>,
+        /// Check the sha1 of each revision against its text, writing mismatches to the error log.
+        #[clap(long)]
+        verify_sha1: bool,
+        /// One of `language` or `namespace`.
+        /// Write one file per partition into the output file, which is a directory then.
+        #[clap(long)]
+        partition_by: Option<PartitionBy>,
+        /// Only parse pages whose titles match this regex.
+        #[clap(long)]
+        title_pattern: Option<String>,
+        /// Only parse pages whose titles are listed in this file, one per line.
+        /// Combined with `--title-pattern`, pages matching either are parsed.
+        #[clap(long)]
+        title_list: Option<PathBuf>,
+        /// The amount of threads that parse the wikitext of pages while the dump is read.
+        /// If zero, pages are parsed one after the other by the reading thread.
+        /// Defaults to `--jobs` if given, and to zero otherwise.
+        #[clap(long)]
+        page_workers: Option<usize>,
+        /// The amount of threads that decompress `.xml.bz2` dumps.
+        /// Multistream dumps are decompressed in parallel if this is greater than one.
+        /// Defaults to `--jobs` if given, and to one otherwise.
+        #[clap(long)]
+        threads: Option<usize>,
+        /// The amount of input files parsed at the same time.
+        /// The output is in the order of the input files either way.
+        #[clap(long, default_value = "1")]
+        parallel_parts: usize,
+        /// Write pages in the order they finish parsing instead of in the order of the dump.
+        #[clap(long)]
+        relaxed_page_order: bool,
+        /// A json file with rules that decide which pages words are extracted from,
+        /// instead of the rules for the English wiktionary.
+        #[clap(long)]
+        classification_rules: Option<PathBuf>,
+        /// A json file of wiktionary's language data, mapping language codes to names,
+        /// to resolve the language names of words not covered by the builtin names.
+        #[clap(long)]
+        language_data: Option<PathBuf>,
+        /// A directory of json language packs, which replace the builtin extraction rules of their editions.
+        #[clap(long, env = "WDP_LANGUAGE_PACK_DIR")]
+        language_pack_dir: Option<PathBuf>,
+        /// Write the counts of pages, redirects and text bytes per namespace to this file as json.
+        #[clap(long)]
+        stats_file: Option<PathBuf>,
+        /// One of `latest`, `collect` or `stream`.
+        /// For full-history dumps, `collect` writes the earlier revisions of each page into its `history`,
+        /// and `stream` writes each earlier revision as a separate object before its page.
+        #[clap(long, default_value = "latest")]
+        revision_history: RevisionHistory,
+        /// One of `keep`, `fail`, `skip` or `quarantine`, deciding what is done with pages
+        /// whose wikitext has parse errors.
+        /// `quarantine` appends these pages with their errors to the `--quarantine-file`.
+        #[clap(long, default_value = "keep")]
+        wikitext_errors: WikitextErrorPolicy,
+        /// Abort parsing on warnings of this kind, e.g. `length_mismatch` or `sha1_mismatch`.
+        /// Can be given multiple times. Other warnings are logged.
+        #[clap(long)]
+        fail_on_warning: Vec<WarningKind>,
+    },
+
+    /// Extracts the words of a dump, or of the json output of `parse-dump-file`.
+    ExtractWords {
+        /// A `.xml.bz2`, `.xml.gz`, `.xml.zst` or `.xml` dump, or a `.json` or `.jsonl` file written by `parse-dump-file`.
         #[clap(long)]
         input_file: PathBuf,
         #[clap(long)]
         output_file: PathBuf,
         #[clap(long)]
         error_log: PathBuf,
+        /// One of `json-lines`, `json` or `tsv`.
+        #[clap(long, default_value = "json-lines")]
+        format: WordsFormat,
+        /// Also extract words from `Translingual` sections.
+        #[clap(long)]
+        include_translingual: bool,
+        /// One of `canonical` for the English names of word types, like `Noun`,
+        /// or `localised` for the names used by the edition of the dump, like `Substantiv`.
+        #[clap(long, default_value = "canonical")]
+        word_type_naming: WordTypeNaming,
+        /// A json file with rules that decide which pages words are extracted from.
+        #[clap(long)]
+        classification_rules: Option<PathBuf>,
+        /// A json file of wiktionary's language data, mapping language codes to names,
+        /// to resolve the language names of words not covered by the builtin names.
+        #[clap(long)]
+        language_data: Option<PathBuf>,
+        /// A directory of json language packs, which replace the builtin extraction rules of their editions.
+        #[clap(long, env = "WDP_LANGUAGE_PACK_DIR")]
+        language_pack_dir: Option<PathBuf>,
+        /// The amount of threads that decompress `.xml.bz2` dumps.
+        /// Multistream dumps are decompressed in parallel if this is greater than one.
+        /// Defaults to `--jobs` if given, and to one otherwise.
+        #[clap(long)]
+        threads: Option<usize>,
+        /// Tab-separated files of external tags like CEFR levels or topics, joined onto the words.
+        /// Each file has a header row with the columns `word`, optionally `language_code`, and one column per tag category.
+        #[clap(long)]
+        word_tags: Vec<PathBuf>,
+    },
+
+    /// Parses a dump while streaming it from the given url, without storing the dump on disk.
+    ParseDumpUrl {
+        /// The url of a `.xml.bz2`, `.xml.gz`, `.xml.zst` or `.xml` dump.
+        #[clap(long)]
+        url: Url,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
         #[clap(long)]
         output_pretty: bool,
+        /// Write the counts of pages, redirects and text bytes per namespace to this file as json.
+        #[clap(long)]
+        stats_file: Option<PathBuf>,
     },
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let configuration = Configuration::parse();
-    initialise_logging(configuration.log_level);
+    /// Writes the titles of all pages as `namespace \t title \t page id` lines, skipping their revisions.
+    ExtractTitles {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+    },
 
-    match configuration.command {
-        CliCommand::ListAvailableLanguages => {
-            for language_code in list_wiktionary_dump_languages(&DumpIndexUrl::Default).await? {
-                println!("{language_code:?}")
-            }
-        }
+    /// Collects the templates and modules of a dump into a template store, for expanding templates while parsing.
+    CollectTemplates {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+    },
 
-        CliCommand::DownloadLanguage {
-            english_name,
-            wiktionary_abbreviation,
-            target_directory,
-            progress_delay,
-        } => {
-            let language_code = match (english_name, wiktionary_abbreviation) {
-                (Some(english_name), None) => LanguageCode::from_english_name(&english_name)?,
-                (None, Some(wiktionary_abbreviation)) => LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?,
-                (None, None) => return Err(Error::Other(format!("No language to download specified."))),
-                (Some(english_name), Some(wiktionary_abbreviation)) => return Err(Error::Other(format!("Specified both the english name '{english_name}' and the wiktionary abbreviation '{wiktionary_abbreviation}' of the language to download."))),
-            };
+    /// Reports clusters of titles that differ only in case, diacritics or whitespace, per language.
+    NearDuplicateTitles {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Also extract taxonomic names, symbols and other entries of `Translingual` sections.
+        #[clap(long)]
+        include_translingual: bool,
+    },
 
-            info!("Downloading language {language_code:?}");
-            download_language(
-                &DumpBaseUrl::Default,
-                &language_code,
-                &target_directory,
-                progress_delay,
-            )
-            .await?;
-        }
+    /// Compares two dumps of the same edition, e.g. of consecutive dates, and writes the added, removed and changed
+    /// pages as one json object per line, to update downstream databases incrementally.
+    DiffDumps {
+        #[clap(long)]
+        old_input_file: PathBuf,
+        #[clap(long)]
+        new_input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Compare the extracted words of the pages instead of their texts,
+        /// and include the added and removed words of each page.
+        #[clap(long)]
+        words: bool,
+        /// Also extract taxonomic names, symbols and other entries of `Translingual` sections.
+        #[clap(long)]
+        include_translingual: bool,
+    },
 
-        CliCommand::ParseDumpFile {
-            input_file,
-            output_file,
-            error_log,
-            output_pretty,
-        } => {
-            info!("Parsing dump file {input_file:?} into {output_file:?}");
-            parse_dump_file(
-                &input_file,
-                Some(&output_file),
-                |_| std::future::ready(Ok(())),
-                &error_log,
-                output_pretty,
-            )
-            .await?;
-        }
-    }
+    /// Reports the page counts per namespace, redirects, average text size, words per language and word type,
+    /// and the sections not recognised by word extraction, of a dump or of the output of `parse-dump-file`.
+    Stats {
+        /// A dump, or a `.json` or `.jsonl` file written by `parse-dump-file`.
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Write the statistics as json to this file.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+        /// Also extract taxonomic names, symbols and other entries of `Translingual` sections.
+        #[clap(long)]
+        include_translingual: bool,
+        /// A directory of json language packs, which replace the builtin extraction rules of their editions.
+        #[clap(long, env = "WDP_LANGUAGE_PACK_DIR")]
+        language_pack_dir: Option<PathBuf>,
+    },
 
-    info!("Terminating");
-    Ok(())
-}
+    /// Reports the headings that are not recognised by word extraction, clustered by spelling variants
+    /// and ranked by frequency, to find word types and headings that should be ignored.
+    UnknownHeadings {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Omit clusters of headings that occur less often than this.
+        #[clap(long, default_value = "1")]
+        min_count: u64,
+    },
 
-fn initialise_logging(log_level: LevelFilter) {
-    CombinedLogger::init(vec![TermLogger::new(
-        log_level,
-        Config::default(),
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )])
-    .unwrap();
+    /// Counts the words per language in the dumps of several editions, written as tab-separated matrix.
+    CoverageMatrix {
+        /// The dumps of the editions to compare, whose editions are inferred from their file names.
+        #[clap(long, required = true)]
+        input_file: Vec<PathBuf>,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
 
-    info!("Logging initialised successfully");
+    /// Writes the function words, like articles, pronouns and conjunctions, of each language to a file per language,
+    /// e.g. to be used as stopword lists.
+    ExtractFunctionWords {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_directory: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Only write the lists of these languages, given by english name. Defaults to all languages.
+        #[clap(long)]
+        language: Vec<String>,
+    },
+
+    /// Merges words whose titles differ only in case, written as one json object per line.
+    MergeCaseVariants {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// One of `keep`, `first-letter` or `all`.
+        /// Defaults to the case-sensitivity given by the siteinfo of the dump, or to `all` if it has none.
+        #[clap(long)]
+        default_policy: Option<CaseMergePolicy>,
+        /// The policy of a single language, given as `<english name>=<policy>`, e.g. `German=keep`.
+        #[clap(long)]
+        language_policy: Vec<String>,
+        /// Also extract taxonomic names, symbols and other entries of `Translingual` sections.
+        #[clap(long)]
+        include_translingual: bool,
+    },
+
+    /// Extracts a derivational morphology dataset, written as one json object per line.
+    ExtractMorphology {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Extracts the definitions of each word, with their labels, written as one json object per line.
+    ExtractDefinitions {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// Drop definitions labeled as vulgar, offensive, derogatory and the like.
+        #[clap(long)]
+        exclude_offensive: bool,
+    },
+
+    /// Extracts structured etymological origins, written as one json object per line.
+    ExtractEtymology {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Extracts the quotations of the pages of the `Citations:` namespace with their dates and sources,
+    /// written as one json object per line.
+    ExtractCitations {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Extracts phrases, proverbs and idioms with their definitions and literal translations,
+    /// written as one json object per line.
+    ExtractPhrases {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Extracts the entries of punctuation marks, symbols and emoji, including translingual ones,
+    /// with their unicode codepoints as json lines.
+    ExtractSymbols {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Writes a manifest of the datasets in a directory, with their paths, record counts, schema versions and checksums.
+    WriteManifest {
+        /// The directory containing the datasets. Logs and subdirectories are not included.
+        #[clap(long)]
+        directory: PathBuf,
+        /// Defaults to `manifest.json` in the directory.
+        #[clap(long)]
+        output_file: Option<PathBuf>,
+    },
+
+    /// Extracts translation pairs into a tab-separated file without duplicate rows.
+    ExtractTranslationPairs {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// A json file of wiktionary's language data, mapping language codes to names,
+        /// to resolve the source languages not covered by the builtin names.
+        #[clap(long)]
+        language_data: Option<PathBuf>,
+    },
+
+    /// Exports the alternative forms, variant spellings and romanizations of all words
+    /// as a compact binary graph for search query expansion.
+    ExportAlternativeForms {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Exports lookup tables from inflected forms to their lemmas and tags as one csv file per language.
+    ExportInflections {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_directory: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+    },
+
+    /// Exports the definitions of a language as a dictionary for e-readers.
+    /// Inflected forms found via form-of definitions are added as lookup forms.
+    ExportEreaderDictionary {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_directory: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// One of `kindle` or `kobo`.
+        #[clap(long)]
+        format: EreaderFormat,
+        /// The english name of the language of the words.
+        #[clap(long, default_value = "English")]
+        language: String,
+        /// The code of the language of the words, used in the Kindle package file.
+        #[clap(long, default_value = "en")]
+        language_code: String,
+        /// The code of the language of the definitions, i.e. of the wiktionary edition.
+        #[clap(long, default_value = "en")]
+        definition_language_code: String,
+        /// Drop definitions labeled as vulgar, offensive, derogatory and the like.
+        #[clap(long)]
+        exclude_offensive: bool,
+        /// Tab-separated files of external tags like CEFR levels or topics, shown with the entries.
+        #[clap(long)]
+        word_tags: Vec<PathBuf>,
+    },
+
+    /// Exports the definitions, examples and usage notes as cleaned plain-text sentences,
+    /// one per line and prefixed with the language code and a tab, e.g. for language-model pretraining.
+    ExportTextCorpus {
+        #[clap(long)]
+        input_file: PathBuf,
+        #[clap(long)]
+        output_file: PathBuf,
+        #[clap(long)]
+        error_log: PathBuf,
+        /// The kinds of text to export, any of `definitions`, `examples` and `usage-notes`, defaults to all.
+        #[clap(long)]
+        kind: Vec<CorpusTextKind>,
+        /// Skip sentences with fewer characters.
+        #[clap(long, default_value = "10")]
+        min_length: usize,
+        /// Skip sentences with more characters.
+        #[clap(long, default_value = "1000")]
+        max_length: usize,
+        /// Keep sentences that were written before in the same language.
+        #[clap(long)]
+        keep_duplicates: bool,
+        /// Drop definitions labeled as vulgar, offensive, derogatory and the like.
+        #[clap(long)]
+        exclude_offensive: bool,
+        /// Also export the text of `Translingual` sections, tagged with the code `mul`.
+        #[clap(long)]
+        include_translingual: bool,
+    },
+
+    /// Runs as a daemon that accepts extraction jobs over a unix socket.
+    /// Jobs are json objects sent one per line, and are executed sequentially.
+    /// Only available on unix.
+    #[cfg(unix)]
+    Daemon {
+        #[clap(long)]
+        socket: PathBuf,
+        #[clap(long, default_value = ".", env = "WDP_TARGET_DIR")]
+        target_directory: PathBuf,
+        #[clap(long, default_value = "10")]
+        progress_delay: u64,
+    },
+}
+
+impl CliCommand {
+    /// Whether the command stops gracefully on ctrl-c, because it passes the cancellation token on.
+    fn is_cancellable(&self) -> bool {
+        matches!(
+            self,
+            Self::DownloadLanguage { .. }
+                | Self::DownloadAndParse { .. }
+                | Self::ParseDumpFile { .. }
+                | Self::ExtractWords { .. }
+                | Self::DiffDumps { .. }
+                | Self::Stats { .. }
+        )
+    }
+}
+
+fn main() -> Result<()> {
+    let configuration = Configuration::parse();
+    initialise_logging(configuration.log_level);
+    set_excerpt_limit(configuration.log_excerpt_limit);
+
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    runtime.enable_all();
+    if let Some(jobs) = configuration.jobs {
+        if jobs == 0 {
+            return Err(Error::Other(format!(
+                "The number of jobs must be at least one."
+            )));
+        }
+        runtime.worker_threads(jobs);
+    }
+    let resource_usage_file = configuration.resource_usage_file.clone();
+    let mut resource_usage = ResourceUsageRecorder::start();
+    runtime
+        .build()?
+        .block_on(run(configuration, &mut resource_usage))?;
+
+    let resource_usage = resource_usage.finish();
+    info!("Resource usage:\n{resource_usage}");
+    if let Some(resource_usage_file) = resource_usage_file {
+        let output = std::io::BufWriter::new(std::fs::File::create(resource_usage_file)?);
+        serde_json::to_writer_pretty(output, &resource_usage)?;
+    }
+    Ok(())
+}
+
+/// Runs the command, recording its phases in `resource_usage`.
+async fn run(
+    configuration: Configuration,
+    resource_usage: &mut ResourceUsageRecorder,
+) -> Result<()> {
+    let jobs = configuration.jobs;
+    let base_url = if let Some(mirror) = &configuration.mirror {
+        Mirror::by_name(mirror)
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "Unknown mirror {mirror:?}, expected one of {:?}",
+                    KNOWN_MIRRORS
+                        .iter()
+                        .map(|mirror| mirror.name)
+                        .collect::<Vec<_>>()
+                ))
+            })?
+            .dump_base_url()
+    } else {
+        configuration
+            .base_url
+            .map(DumpBaseUrl::Custom)
+            .unwrap_or(DumpBaseUrl::Default)
+    };
+
+    // Long downloads and parses stop gracefully on ctrl-c, completing the output written so far.
+    // Other commands keep the default handling of ctrl-c, which exits immediately.
+    let cancellation = CancellationToken::new();
+    if configuration.command.is_cancellable() {
+        tokio::spawn({
+            let cancellation = cancellation.clone();
+            async move {
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    warn!("Received ctrl-c, stopping at the next safe point, press ctrl-c again to exit immediately");
+                    cancellation.cancel();
+                }
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    std::process::exit(130);
+                }
+            }
+        });
+    }
+
+    match configuration.command {
+        CliCommand::ListAvailableLanguages => {
+            for language_code in list_wiktionary_dump_languages(&DumpIndexUrl::Default).await? {
+                println!("{language_code:?}")
+            }
+        }
+
+        CliCommand::DownloadLanguage {
+            english_name,
+            wiktionary_abbreviation,
+            target_directory,
+            progress_delay,
+            mirror_ranking,
+            connections,
+            checksum_tasks,
+            max_attempts,
+            date,
+            force,
+            no_mirror_fallback,
+            job,
+            path_template,
+        } => {
+            let language_code = language_code_to_download(english_name, wiktionary_abbreviation)?;
+
+            let base_url = if let Some(mirror_ranking) = mirror_ranking {
+                best_mirror(&load_mirror_ranking(&mirror_ranking)?).ok_or_else(|| {
+                    Error::Other(format!("No available mirror in ranking {mirror_ranking:?}"))
+                })?
+            } else {
+                base_url
+            };
+
+            let options = DownloadOptions {
+                progress_delay_seconds: progress_delay,
+                connections,
+                checksum_tasks,
+                retry_policy: RetryPolicy {
+                    max_attempts: max_attempts.max(1),
+                    ..Default::default()
+                },
+                date_selection: date,
+                force,
+                mirror_fallback: !no_mirror_fallback,
+                progress: None,
+                cancellation: Some(cancellation),
+                path_template,
+            };
+            if let Some(job) = job {
+                info!(
+                    "Downloading job {job} of language {language_code:?} from {}",
+                    base_url.as_str()
+                );
+                let target_files = download_job_with_options(
+                    &base_url,
+                    &language_code,
+                    &job,
+                    &target_directory,
+                    &options,
+                )
+                .await?;
+                for target_file in target_files {
+                    info!("Downloaded {target_file:?}");
+                }
+            } else {
+                info!(
+                    "Downloading language {language_code:?} from {}",
+                    base_url.as_str()
+                );
+                download_language_with_options(
+                    &base_url,
+                    &language_code,
+                    &target_directory,
+                    &options,
+                )
+                .await?;
+            }
+        }
+
+        CliCommand::DownloadAndParse {
+            english_name,
+            wiktionary_abbreviation,
+            target_directory,
+            output_file,
+            error_log,
+            output_pretty,
+            format,
+            delete_dump,
+            stats_file,
+            progress_delay,
+            connections,
+            checksum_tasks,
+            max_attempts,
+            date,
+            force,
+            no_mirror_fallback,
+            path_template,
+        } => {
+            let language_code = language_code_to_download(english_name, wiktionary_abbreviation)?;
+            info!(
+                "Downloading and parsing language {language_code:?} from {}",
+                base_url.as_str()
+            );
+            resource_usage.phase("download and parse");
+            let dump_file = download_and_parse_language(
+                &base_url,
+                &language_code,
+                &target_directory,
+                &output_file,
+                &error_log,
+                &DownloadAndParseOptions {
+                    download: DownloadOptions {
+                        progress_delay_seconds: progress_delay,
+                        connections,
+                        checksum_tasks,
+                        retry_policy: RetryPolicy {
+                            max_attempts: max_attempts.max(1),
+                            ..Default::default()
+                        },
+                        date_selection: date,
+                        force,
+                        mirror_fallback: !no_mirror_fallback,
+                        progress: None,
+                        cancellation: Some(cancellation.clone()),
+                        path_template,
+                    },
+                    parse: DumpParseOptions {
+                        output_pretty,
+                        output_format: format,
+                        statistics_file: stats_file,
+                        cancellation: Some(cancellation),
+                        ..Default::default()
+                    },
+                    delete_dump,
+                },
+            )
+            .await?;
+            info!("Parsed dump file {dump_file:?} into {output_file:?}");
+        }
+
+        CliCommand::IngestAll {
+            target_directory,
+            output_directory,
+            wiktionary_abbreviation,
+            report_file,
+            concurrency,
+            fail_fast,
+            format,
+            page_workers,
+            delete_dump,
+            date,
+            max_attempts,
+            path_template,
+        } => {
+            let languages = wiktionary_abbreviation
+                .iter()
+                .map(|abbreviation| LanguageCode::from_wiktionary_abbreviation(abbreviation))
+                .collect::<Result<Vec<_>>>()?;
+            resource_usage.phase("ingest");
+            let report = ingest_all(
+                &base_url,
+                &DumpIndexUrl::Default,
+                &target_directory,
+                &output_directory,
+                &IngestOptions {
+                    download_and_parse: DownloadAndParseOptions {
+                        download: DownloadOptions {
+                            retry_policy: RetryPolicy {
+                                max_attempts: max_attempts.max(1),
+                                ..Default::default()
+                            },
+                            date_selection: date,
+                            path_template,
+                            ..Default::default()
+                        },
+                        parse: DumpParseOptions {
+                            output_format: format,
+                            page_workers: page_workers.or(jobs).unwrap_or(0),
+                            ..Default::default()
+                        },
+                        delete_dump,
+                    },
+                    languages,
+                    concurrency,
+                    fail_fast,
+                },
+            )
+            .await?;
+
+            if let Some(report_file) = report_file {
+                report.write_json(report_file)?;
+            }
+            DatasetManifest::from_directory(&output_directory)?
+                .write_json(output_directory.join(MANIFEST_FILE_NAME))?;
+            println!("{report}");
+            if report.failed().next().is_some() {
+                return Err(Error::Other(format!(
+                    "{} editions failed",
+                    report.failed().count()
+                )));
+            }
+        }
+
+        CliCommand::DumpStatus {
+            wiktionary_abbreviation,
+            date,
+        } => {
+            let language_code =
+                LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?;
+            let dump_status = fetch_dump_status(&base_url, &language_code, &date).await?;
+            println!("job\tstatus\tupdated\tfiles");
+            for (job, entry) in dump_status.jobs() {
+                println!(
+                    "{job}\t{}\t{}\t{}",
+                    entry.status(),
+                    entry.updated(),
+                    entry.files().len()
+                );
+            }
+        }
+
+        CliCommand::CheckMirrors {
+            mirror,
+            wiktionary_abbreviation,
+            date,
+            save_ranking,
+        } => {
+            let mirrors = if mirror.is_empty() {
+                DumpBaseUrl::known_mirrors()
+            } else {
+                mirror.into_iter().map(DumpBaseUrl::Custom).collect()
+            };
+            let target = match (wiktionary_abbreviation, date) {
+                (Some(wiktionary_abbreviation), date) => Some(MirrorProbeTarget {
+                    language_code: LanguageCode::from_wiktionary_abbreviation(
+                        &wiktionary_abbreviation,
+                    )?,
+                    date,
+                }),
+                (None, None) => None,
+                (None, Some(_)) => {
+                    return Err(Error::Other(format!(
+                        "A date was given without a wiktionary abbreviation."
+                    )))
+                }
+            };
+
+            let ranking = check_mirrors(&mirrors, target.as_ref()).await;
+            println!("rank\tavailable\tlatency\thas dump\tmirror");
+            for (rank, health) in ranking.iter().enumerate() {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}{}",
+                    rank + 1,
+                    health.available,
+                    health
+                        .latency_millis
+                        .map(|latency| format!("{latency}ms"))
+                        .unwrap_or_else(|| "-".to_string()),
+                    health
+                        .has_dump
+                        .map(|has_dump| has_dump.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    health.base_url,
+                    health
+                        .error
+                        .as_ref()
+                        .map(|error| format!(" ({error})"))
+                        .unwrap_or_default(),
+                );
+            }
+
+            if let Some(save_ranking) = save_ranking {
+                save_mirror_ranking(&save_ranking, &ranking)?;
+                info!("Saved mirror ranking to {save_ranking:?}");
+            }
+        }
+
+        CliCommand::VerifyStore {
+            target_directory,
+            max_mib_per_second,
+            repair,
+            progress_delay,
+        } => {
+            let verifications = verify_store(
+                &target_directory,
+                &VerifyStoreOptions {
+                    max_bytes_per_second: max_mib_per_second
+                        .map(|max_mib_per_second| max_mib_per_second * 1024 * 1024),
+                    repair,
+                    base_url,
+                    progress_delay_seconds: progress_delay,
+                },
+            )
+            .await?;
+
+            for verification in &verifications {
+                println!("{:?}\t{:?}", verification.path, verification.status);
+            }
+            let invalid = verifications
+                .iter()
+                .filter(|verification| !verification.status.is_valid())
+                .count();
+            if invalid > 0 {
+                return Err(Error::Other(format!(
+                    "{invalid} of {} stored files are corrupted",
+                    verifications.len()
+                )));
+            }
+            info!("All {} stored files are valid", verifications.len());
+        }
+
+        CliCommand::VerifyChecksums {
+            directory,
+            checksum_file,
+            wiktionary_abbreviation,
+            date,
+            max_mib_per_second,
+        } => {
+            let checksum_files = if let (Some(wiktionary_abbreviation), Some(date)) =
+                (wiktionary_abbreviation, date)
+            {
+                let language_code =
+                    LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?;
+                let mut checksum_files = Vec::new();
+                for algorithm in ChecksumAlgorithm::ALL {
+                    checksum_files.push(
+                        fetch_checksum_file(
+                            &base_url,
+                            &language_code,
+                            &date,
+                            algorithm,
+                            &RetryPolicy::default(),
+                        )
+                        .await?,
+                    );
+                }
+                checksum_files
+            } else if checksum_file.is_empty() {
+                find_checksum_files(&directory)?
+            } else {
+                checksum_file
+                    .iter()
+                    .map(ChecksumFile::load)
+                    .collect::<Result<_>>()?
+            };
+            if checksum_files.is_empty() {
+                return Err(Error::Other(format!(
+                    "No checksum files found in {directory:?}"
+                )));
+            }
+
+            let verifications = verify_directory(
+                &directory,
+                &checksum_files,
+                max_mib_per_second.map(|max_mib_per_second| max_mib_per_second * 1024 * 1024),
+            )
+            .await?;
+
+            for verification in &verifications {
+                println!("{:?}\t{:?}", verification.path, verification.status);
+            }
+            let invalid = verifications
+                .iter()
+                .filter(|verification| !verification.status.is_valid())
+                .count();
+            if invalid > 0 {
+                return Err(Error::Other(format!(
+                    "{invalid} of {} files are corrupted",
+                    verifications.len()
+                )));
+            }
+            info!("All {} files are valid", verifications.len());
+        }
+
+        CliCommand::Prune {
+            target_directory,
+            keep_last,
+            keep_verified_only,
+            dry_run,
+            max_mib_per_second,
+        } => {
+            let pruned_dates = prune_store(
+                &target_directory,
+                &PruneOptions {
+                    keep_last,
+                    keep_verified_only,
+                    dry_run,
+                    max_bytes_per_second: max_mib_per_second
+                        .map(|max_mib_per_second| max_mib_per_second * 1024 * 1024),
+                },
+            )
+            .await?;
+
+            for pruned_date in &pruned_dates {
+                println!(
+                    "{:?}\t{} bytes\t{:?}",
+                    pruned_date.directory, pruned_date.freed_bytes, pruned_date.reason
+                );
+            }
+            let freed_bytes: u64 = pruned_dates
+                .iter()
+                .map(|pruned_date| pruned_date.freed_bytes)
+                .sum();
+            if dry_run {
+                info!(
+                    "Would prune {} dates, freeing {freed_bytes} bytes",
+                    pruned_dates.len()
+                );
+            } else {
+                info!(
+                    "Pruned {} dates, freeing {freed_bytes} bytes",
+                    pruned_dates.len()
+                );
+            }
+        }
+
+        CliCommand::SampleCategory {
+            category,
+            wiktionary_abbreviation,
+            count,
+            output_file,
+        } => {
+            let language_code =
+                LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?;
+            let report = sample_category(&language_code, &category, count).await?;
+
+            for failure in report.failures() {
+                println!(
+                    "{}\t{} words\t{} parser errors\t{} extraction errors",
+                    failure.title,
+                    failure.words.len(),
+                    failure.parser_errors.len(),
+                    failure.extraction_errors.len()
+                );
+            }
+            info!(
+                "{} of {} sampled pages failed",
+                report.failures().count(),
+                report.pages.len()
+            );
+
+            if let Some(output_file) = output_file {
+                let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+                serde_json::to_writer_pretty(output, &report)?;
+            }
+        }
+
+        CliCommand::VerifyWords {
+            input_file,
+            wiktionary_abbreviation,
+            count,
+            output_file,
+        } => {
+            let language_code =
+                LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?;
+            let words = std::io::BufReader::new(std::fs::File::open(&input_file)?)
+                .lines()
+                .map(|line| Ok(serde_json::from_str(&line?)?))
+                .collect::<Result<Vec<Word>>>()?;
+            let report = verify_words_against_html(&language_code, words, count).await?;
+
+            for unconfirmed in report.unconfirmed() {
+                println!(
+                    "{}\t{}\t{}\tpage found: {}\theadword found: {}\tword type found: {}",
+                    unconfirmed.word.word,
+                    unconfirmed.word.language_english_name,
+                    unconfirmed.word.word_type,
+                    unconfirmed.page_found,
+                    unconfirmed.headword_found,
+                    unconfirmed.word_type_found
+                );
+            }
+            if let (Some(precision), Some((low, high))) =
+                (report.precision(), report.precision_interval())
+            {
+                info!(
+                    "{} of {} sampled words confirmed, precision {:.1}% (95% interval {:.1}% to {:.1}%)",
+                    report.confirmed().count(),
+                    report.words.len(),
+                    precision * 100.0,
+                    low * 100.0,
+                    high * 100.0
+                );
+            }
+
+            if let Some(output_file) = output_file {
+                let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+                serde_json::to_writer_pretty(output, &report)?;
+            }
+        }
+
+        CliCommand::ComparePage {
+            title,
+            old_input_file,
+            new_input_file,
+            live,
+            error_log,
+            output_file,
+        } => {
+            resource_usage.phase("old snapshot");
+            info!("Extracting page {title:?} from {old_input_file:?}");
+            let old_snapshot = dump_page_snapshot(&old_input_file, &title, &error_log)
+                .await?
+                .unwrap_or_else(|| PageSnapshot::empty(&title));
+
+            resource_usage.phase("new snapshot");
+            let new_snapshot = if let Some(wiktionary_abbreviation) = live {
+                info!("Fetching live page {title:?} from {wiktionary_abbreviation} wiktionary");
+                let language_code =
+                    LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation)?;
+                live_page_snapshot(&language_code, &title).await?
+            } else {
+                // Clap ensures that the new input file is given if the page is not compared against the live page.
+                let new_input_file = new_input_file.unwrap();
+                info!("Extracting page {title:?} from {new_input_file:?}");
+                dump_page_snapshot(&new_input_file, &title, &error_log).await?
+            }
+            .unwrap_or_else(|| PageSnapshot::empty(&title));
+
+            let diff = old_snapshot.diff(&new_snapshot);
+            print!("{diff}");
+            if let Some(output_file) = output_file {
+                let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+                serde_json::to_writer_pretty(output, &diff)?;
+            }
+        }
+
+        CliCommand::ParseDumpFile {
+            input_file,
+            output_file,
+            error_log,
+            output_pretty,
+            format,
+            quarantine_file,
+            siteinfo_file,
+            template_store,
+            expand_template,
+            word_link_file,
+            include_translingual,
+            dump_date,
+            verify_sha1,
+            partition_by,
+            title_pattern,
+            title_list,
+            page_workers,
+            threads,
+            parallel_parts,
+            relaxed_page_order,
+            classification_rules,
+            language_data,
+            language_pack_dir,
+            stats_file,
+            revision_history,
+            wikitext_errors,
+            fail_on_warning,
+        } => {
+            let input_files = expand_input_files(&input_file)?;
+            resource_usage.phase("prepare");
+            let title_filter = if title_pattern.is_some() || title_list.is_some() {
+                let mut title_filter = TitleFilter::new();
+                if let Some(title_pattern) = title_pattern {
+                    title_filter = title_filter.with_pattern(&title_pattern)?;
+                }
+                if let Some(title_list) = title_list {
+                    title_filter = title_filter.with_title_list_file(title_list)?;
+                }
+                Some(Arc::new(title_filter))
+            } else {
+                None
+            };
+
+            let mut word_extraction = WordExtractionOptions {
+                include_translingual,
+                ..Default::default()
+            };
+            if let Some(classification_rules) = classification_rules {
+                word_extraction.page_classifier = PageClassifier::load(classification_rules)?;
+            }
+            if let Some(language_data) = language_data {
+                word_extraction.language_names = Arc::new(LanguageNames::load(language_data)?);
+            }
+            if let Some(language_pack_dir) = language_pack_dir {
+                word_extraction.language_packs =
+                    Arc::new(LanguagePacks::load_directory(language_pack_dir)?);
+            }
+
+            let template_expander = if let Some(template_store) = template_store {
+                let template_store = TemplateStore::load(template_store)?;
+                Some(Arc::new(TemplateExpander::new(
+                    template_store,
+                    &expand_template,
+                )?))
+            } else {
+                None
+            };
+
+            let options = DumpParseOptions {
+                output_pretty,
+                output_format: format,
+                word_extraction,
+                quarantine_file,
+                siteinfo_file,
+                template_expander,
+                word_link_file,
+                dump_date,
+                verify_sha1,
+                partition_by,
+                title_filter,
+                page_workers: page_workers.or(jobs).unwrap_or(0),
+                relaxed_page_order,
+                statistics_file: stats_file,
+                decompression_threads: threads.or(jobs).unwrap_or(1),
+                parallel_parts,
+                revision_history,
+                wikitext_errors,
+                warnings: WarningReporter::failing_on(fail_on_warning),
+                cancellation: Some(cancellation),
+                ..Default::default()
+            };
+
+            resource_usage.phase("parse");
+            if let [input_file] = input_files.as_slice() {
+                info!("Parsing dump file {input_file:?} into {output_file:?}");
+                parse_dump_file_with_options(
+                    input_file,
+                    Some(&output_file),
+                    |_| std::future::ready(Ok(())),
+                    |_, _, _| {},
+                    &error_log,
+                    &options,
+                )
+                .await?;
+            } else {
+                info!(
+                    "Parsing {} dump files {input_files:?} into {output_file:?}",
+                    input_files.len()
+                );
+                parse_dump_files_with_options(
+                    &input_files,
+                    Some(&output_file),
+                    |_| std::future::ready(Ok(())),
+                    |_, _, _| {},
+                    &error_log,
+                    &options,
+                )
+                .await?;
+            }
+        }
+
+        CliCommand::ExtractWords {
+            input_file,
+            output_file,
+            error_log,
+            format,
+            include_translingual,
+            word_type_naming,
+            classification_rules,
+            language_data,
+            language_pack_dir,
+            threads,
+            word_tags,
+        } => {
+            let mut parse_options = translingual_parse_options(include_translingual);
+            parse_options.word_extraction.word_type_naming = word_type_naming;
+            parse_options.cancellation = Some(cancellation);
+            parse_options.decompression_threads = threads.or(jobs).unwrap_or(1);
+            if let Some(classification_rules) = classification_rules {
+                parse_options.word_extraction.page_classifier =
+                    PageClassifier::load(classification_rules)?;
+            }
+            if let Some(language_data) = language_data {
+                parse_options.word_extraction.language_names =
+                    Arc::new(LanguageNames::load(language_data)?);
+            }
+            if let Some(language_pack_dir) = language_pack_dir {
+                parse_options.word_extraction.language_packs =
+                    Arc::new(LanguagePacks::load_directory(language_pack_dir)?);
+            }
+
+            info!("Extracting words of {input_file:?} into {output_file:?}");
+            let mut writer = WordsWriter::with_tags(
+                std::io::BufWriter::new(std::fs::File::create(&output_file)?),
+                format,
+                load_word_tags(&word_tags)?,
+            )?;
+            let is_json = input_file
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| matches!(extension, "json" | "jsonl" | "ndjson"))
+                .unwrap_or(false);
+
+            if is_json {
+                // Like when parsing a dump, the siteinfo decides the edition, and otherwise the file name.
+                let siteinfo =
+                    read_json_siteinfo(std::io::BufReader::new(std::fs::File::open(&input_file)?))?;
+                let edition = &mut parse_options.word_extraction.edition;
+                *edition = siteinfo
+                    .map(|siteinfo| WiktionaryEdition::from_dbname(siteinfo.dbname()))
+                    .filter(WiktionaryEdition::is_known)
+                    .unwrap_or_else(|| WiktionaryEdition::from_file_name(&input_file));
+                info!("Extracting words of edition {edition:?}");
+
+                let mut error_log = std::io::BufWriter::new(std::fs::File::create(&error_log)?);
+                let input = std::fs::File::open(&input_file)?;
+                let input_size = input.metadata()?.len();
+                let input = CountingReader::new(input);
+                let bytes_read = input.bytes_read();
+                let mut progress = ProgressTracker::new(
+                    ProgressReporter::log().with_interval(Duration::from_secs(10)),
+                    ProgressPhase::Extract,
+                );
+                for page in read_json_pages(std::io::BufReader::new(input)) {
+                    let page = page?;
+                    progress.set_bytes(bytes_read.load(Ordering::Relaxed), Some(input_size));
+                    if let Some(text) = page.revision().text() {
+                        let mut words = Vec::new();
+                        extract_words_with_namespace(
+                            &parse_options.word_extraction,
+                            Some(page.namespace()),
+                            page.title(),
+                            text.wikitext(),
+                            |word| words.push(word),
+                            |error| {
+                                writeln!(error_log, "Page: {}\n{error:#?}", page.title())
+                                    .unwrap_or_else(|error| {
+                                        panic!("Writing to error log failed: {error}")
+                                    })
+                            },
+                        );
+                        for word in &words {
+                            writer.write(word)?;
+                        }
+                        progress.add_page(words.len() as u64);
+                    }
+                }
+                progress.finish();
+                error_log.flush()?;
+            } else {
+                let mut write_result = Ok(());
+                parse_dump_file_with_options(
+                    &input_file,
+                    None::<&PathBuf>,
+                    |word| {
+                        if write_result.is_ok() {
+                            write_result = writer.write(&word);
+                        }
+                        std::future::ready(Ok(()))
+                    },
+                    |_, _, _| {},
+                    &error_log,
+                    &parse_options,
+                )
+                .await?;
+                write_result?;
+            }
+
+            info!("Wrote {} words", writer.count());
+            writer.finish()?;
+        }
+
+        CliCommand::ParseDumpUrl {
+            url,
+            output_file,
+            error_log,
+            output_pretty,
+            stats_file,
+        } => {
+            parse_dump_url(
+                &url,
+                Some(&output_file),
+                |_| std::future::ready(Ok(())),
+                |_, _, _| {},
+                &error_log,
+                &DumpParseOptions {
+                    output_pretty,
+                    statistics_file: stats_file,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        }
+
+        CliCommand::ExtractTitles {
+            input_file,
+            output_file,
+        } => {
+            let count = write_titles(&input_file, std::fs::File::create(&output_file)?).await?;
+            info!("Wrote {count} titles to {output_file:?}");
+        }
+
+        CliCommand::CollectTemplates {
+            input_file,
+            output_file,
+        } => {
+            let template_store = TemplateStore::from_dump_file(&input_file).await?;
+            template_store.save(&output_file)?;
+        }
+
+        CliCommand::NearDuplicateTitles {
+            input_file,
+            output_file,
+            error_log,
+            include_translingual,
+        } => {
+            info!("Collecting near-duplicate titles of {input_file:?} into {output_file:?}");
+            let mut near_duplicate_titles = NearDuplicateTitles::new();
+            parse_dump_file_with_options(
+                &input_file,
+                None::<&PathBuf>,
+                |word| {
+                    near_duplicate_titles.add_word(&word);
+                    std::future::ready(Ok(()))
+                },
+                |_, _, _| {},
+                &error_log,
+                &translingual_parse_options(include_translingual),
+            )
+            .await?;
+
+            let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            serde_json::to_writer_pretty(output, &near_duplicate_titles.report())?;
+        }
+
+        CliCommand::DiffDumps {
+            old_input_file,
+            new_input_file,
+            output_file,
+            error_log,
+            words,
+            include_translingual,
+        } => {
+            let mut parse_options = translingual_parse_options(include_translingual);
+            parse_options.cancellation = Some(cancellation);
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let statistics = diff_dumps(
+                &old_input_file,
+                &new_input_file,
+                &error_log,
+                &DumpDiffOptions {
+                    parse_options,
+                    compare_words: words,
+                },
+                |change| write_json_line(&mut output, &change),
+            )
+            .await?;
+            output.flush()?;
+            println!("{statistics}");
+        }
+
+        CliCommand::Stats {
+            input_file,
+            error_log,
+            output_file,
+            include_translingual,
+            language_pack_dir,
+        } => {
+            let mut parse_options = translingual_parse_options(include_translingual);
+            parse_options.cancellation = Some(cancellation);
+            if let Some(language_pack_dir) = language_pack_dir {
+                parse_options.word_extraction.language_packs =
+                    Arc::new(LanguagePacks::load_directory(language_pack_dir)?);
+            }
+
+            info!("Collecting statistics of {input_file:?}");
+            let statistics =
+                collect_dump_statistics(&input_file, &error_log, &parse_options).await?;
+            println!("{statistics}");
+            if let Some(output_file) = output_file {
+                statistics.write_json(output_file)?;
+            }
+        }
+
+        CliCommand::UnknownHeadings {
+            input_file,
+            output_file,
+            error_log,
+            min_count,
+        } => {
+            info!("Collecting unknown headings of {input_file:?} into {output_file:?}");
+            let mut unknown_headings = UnknownHeadings::new();
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |edition, title, wikitext| {
+                    extract_words(
+                        edition,
+                        title,
+                        wikitext,
+                        |_| {},
+                        |error| unknown_headings.add_error(title, &error),
+                    );
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+
+            let report = unknown_headings.report(min_count);
+            info!(
+                "Found {} unknown headings in {} clusters",
+                report.count,
+                report.clusters.len()
+            );
+            let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            serde_json::to_writer_pretty(output, &report)?;
+        }
+
+        CliCommand::CoverageMatrix {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            let mut coverage_matrix = CoverageMatrix::new();
+            for input_file in &input_file {
+                let edition = match WiktionaryEdition::from_file_name(input_file) {
+                    WiktionaryEdition::Known(language_code) => {
+                        language_code.to_wiktionary_abbreviation().to_string()
+                    }
+                    WiktionaryEdition::Unknown => {
+                        return Err(Error::Other(format!(
+                            "Cannot infer the edition of {input_file:?} from its file name"
+                        )))
+                    }
+                };
+                coverage_matrix.add_edition(&edition);
+
+                info!("Counting words of edition {edition} in {input_file:?}");
+                parse_dump_file_with_options(
+                    input_file,
+                    None::<&PathBuf>,
+                    |word| {
+                        coverage_matrix.add_word(&edition, &word);
+                        std::future::ready(Ok(()))
+                    },
+                    |_, _, _| {},
+                    &error_log,
+                    &DumpParseOptions::default(),
+                )
+                .await?;
+            }
+
+            let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            coverage_matrix.report().write_tsv(output)?;
+        }
+
+        CliCommand::ExtractFunctionWords {
+            input_file,
+            output_directory,
+            error_log,
+            language,
+        } => {
+            info!("Extracting function words of {input_file:?} into {output_directory:?}");
+            let mut function_word_lists = FunctionWordLists::new();
+            parse_dump_file_with_options(
+                &input_file,
+                None::<&PathBuf>,
+                |word| {
+                    function_word_lists.add_word(&word);
+                    std::future::ready(Ok(()))
+                },
+                |_, _, _| {},
+                &error_log,
+                &DumpParseOptions::default(),
+            )
+            .await?;
+
+            let provenance = FunctionWordProvenance {
+                source: input_file
+                    .file_name()
+                    .map(|file_name| file_name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                edition: WiktionaryEdition::from_file_name(&input_file)
+                    .language_code()
+                    .map(|language_code| language_code.to_wiktionary_abbreviation().to_string()),
+            };
+            std::fs::create_dir_all(&output_directory)?;
+            for language_english_name in function_word_lists.languages() {
+                if !language.is_empty()
+                    && !language
+                        .iter()
+                        .any(|language| language.eq_ignore_ascii_case(language_english_name))
+                {
+                    continue;
+                }
+
+                let output_file = output_directory.join(format!(
+                    "{}.tsv",
+                    language_english_name.replace(['/', '\\'], "_")
+                ));
+                let output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+                function_word_lists.write_tsv(language_english_name, &provenance, output)?;
+                info!(
+                    "Wrote {} function words of {language_english_name} to {output_file:?}",
+                    function_word_lists.len(language_english_name)
+                );
+            }
+        }
+
+        CliCommand::MergeCaseVariants {
+            input_file,
+            output_file,
+            error_log,
+            default_policy,
+            language_policy,
+            include_translingual,
+        } => {
+            let mut configuration = CaseMergeConfiguration {
+                default_policy: default_policy.unwrap_or(CaseMergePolicy::FoldAll),
+                language_policies: Default::default(),
+            };
+            for language_policy in language_policy {
+                let (language, policy) = language_policy.split_once('=').ok_or_else(|| {
+                    Error::Other(format!(
+                        "Language policy {language_policy:?} is not of the form <english name>=<policy>"
+                    ))
+                })?;
+                configuration
+                    .language_policies
+                    .insert(language.to_string(), policy.parse()?);
+            }
+
+            info!("Merging case variants of {input_file:?} into {output_file:?}");
+            let mut word_merger = WordMerger::new(configuration);
+            let mut siteinfo_sink = SiteinfoSink::default();
+            parse_dump_file_with_sink(
+                &input_file,
+                &mut siteinfo_sink,
+                |word| {
+                    word_merger.add(&word);
+                    std::future::ready(Ok(()))
+                },
+                |_, _, _| {},
+                &error_log,
+                &translingual_parse_options(include_translingual),
+            )
+            .await?;
+            if let (None, Some(siteinfo)) = (default_policy, &siteinfo_sink.siteinfo) {
+                let policy = CaseMergePolicy::from_siteinfo_case(siteinfo.case())?;
+                info!(
+                    "Merging with policy {policy:?} by default, since the siteinfo declares case {:?}",
+                    siteinfo.case()
+                );
+                word_merger.configuration_mut().default_policy = policy;
+            }
+
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            for merged_word in word_merger.finish() {
+                write_json_line(&mut output, &merged_word)?;
+            }
+            output.flush()?;
+        }
+
+        CliCommand::ExtractMorphology {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Extracting morphology of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_morphology(title, wikitext, |entry| {
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &entry);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::ExtractEtymology {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Extracting etymology of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_etymology_origins(title, wikitext, |origin| {
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &origin);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::ExtractCitations {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Extracting citations of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_citations(title, wikitext, |citation| {
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &citation);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::ExtractDefinitions {
+            input_file,
+            output_file,
+            error_log,
+            exclude_offensive,
+        } => {
+            info!("Extracting definitions of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_definitions(title, wikitext, |mut entry| {
+                        if exclude_offensive {
+                            entry.remove_offensive_definitions();
+                            if entry.definitions.is_empty() {
+                                return;
+                            }
+                        }
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &entry);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::ExportAlternativeForms {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Exporting alternative forms of {input_file:?} into {output_file:?}");
+            resource_usage.phase("parse");
+            let mut graph = AlternativeFormsGraph::new();
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_alternative_forms(title, wikitext, |alternative_form| {
+                        graph.add(&alternative_form)
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            resource_usage.phase("write");
+            graph.write(std::io::BufWriter::new(std::fs::File::create(
+                &output_file,
+            )?))?;
+            info!(
+                "Wrote {} alternative forms in {} languages",
+                graph.edge_count(),
+                graph.languages().count()
+            );
+        }
+
+        CliCommand::ExportInflections {
+            input_file,
+            output_directory,
+            error_log,
+        } => {
+            info!("Exporting inflections of {input_file:?} into {output_directory:?}");
+            resource_usage.phase("parse");
+            let mut tables = InflectionTables::new();
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_inflected_forms(title, wikitext, |inflected_form| {
+                        tables.add(&inflected_form)
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            resource_usage.phase("write");
+            let paths = tables.write_csv_directory(&output_directory)?;
+            info!(
+                "Wrote {} inflected forms into {} files",
+                tables.len(),
+                paths.len()
+            );
+        }
+
+        CliCommand::ExtractPhrases {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Extracting phrases of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_phrases(title, wikitext, |entry| {
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &entry);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::ExtractSymbols {
+            input_file,
+            output_file,
+            error_log,
+        } => {
+            info!("Extracting symbols of {input_file:?} into {output_file:?}");
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&output_file)?);
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_symbols(title, wikitext, |entry| {
+                        if write_result.is_ok() {
+                            write_result = write_json_line(&mut output, &entry);
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.flush()?;
+        }
+
+        CliCommand::WriteManifest {
+            directory,
+            output_file,
+        } => {
+            let output_file = output_file.unwrap_or_else(|| directory.join(MANIFEST_FILE_NAME));
+            info!("Writing manifest of {directory:?} into {output_file:?}");
+            let manifest = DatasetManifest::from_directory(&directory)?;
+            manifest.write_json(&output_file)?;
+            info!("Listed {} datasets", manifest.datasets.len());
+        }
+
+        CliCommand::ExtractTranslationPairs {
+            input_file,
+            output_file,
+            error_log,
+            language_data,
+        } => {
+            info!("Extracting translation pairs of {input_file:?} into {output_file:?}");
+            let language_names = if let Some(language_data) = language_data {
+                Arc::new(LanguageNames::load(language_data)?)
+            } else {
+                LanguageNames::shared_builtin()
+            };
+            let mut output = TranslationTsvWriter::with_language_names(
+                std::io::BufWriter::new(std::fs::File::create(&output_file)?),
+                language_names,
+            )?;
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| {
+                    wikitext_to_translations(title, wikitext, |translation| {
+                        if write_result.is_ok() {
+                            write_result = output.write(&translation).map(|_| ());
+                        }
+                    })
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            output.finish()?;
+        }
+
+        CliCommand::ExportEreaderDictionary {
+            input_file,
+            output_directory,
+            error_log,
+            format,
+            language,
+            language_code,
+            definition_language_code,
+            exclude_offensive,
+            word_tags,
+        } => {
+            info!("Exporting {language} dictionary of {input_file:?} into {output_directory:?}");
+            resource_usage.phase("parse");
+            let mut builder =
+                EreaderDictionaryBuilder::new(&language).exclude_offensive(exclude_offensive);
+            if let Some(word_tags) = load_word_tags(&word_tags)? {
+                builder = builder.word_tags(word_tags, &language_code);
+            }
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, title, wikitext| builder.add_page(title, wikitext),
+                &error_log,
+                false,
+            )
+            .await?;
+            resource_usage.phase("collect");
+            let entries = builder.finish();
+            info!("Collected {} entries", entries.len());
+
+            resource_usage.phase("write");
+            std::fs::create_dir_all(&output_directory)?;
+            match format {
+                EreaderFormat::Kindle => {
+                    let html_file_name = "dictionary.html";
+                    let mut output = std::io::BufWriter::new(std::fs::File::create(
+                        output_directory.join(html_file_name),
+                    )?);
+                    write_kindle_html(&entries, &mut output)?;
+                    output.flush()?;
+
+                    let mut output = std::io::BufWriter::new(std::fs::File::create(
+                        output_directory.join("dictionary.opf"),
+                    )?);
+                    write_kindle_opf(
+                        &format!("{language} Wiktionary"),
+                        &language_code,
+                        &definition_language_code,
+                        html_file_name,
+                        &mut output,
+                    )?;
+                    output.flush()?;
+                }
+                EreaderFormat::Kobo => {
+                    let mut output = std::io::BufWriter::new(std::fs::File::create(
+                        output_directory.join("dictionary.df"),
+                    )?);
+                    write_kobo_dictfile(&entries, &mut output)?;
+                    output.flush()?;
+                }
+            }
+        }
+
+        CliCommand::ExportTextCorpus {
+            input_file,
+            output_file,
+            error_log,
+            kind,
+            min_length,
+            max_length,
+            keep_duplicates,
+            exclude_offensive,
+            include_translingual,
+        } => {
+            info!("Exporting text corpus of {input_file:?} into {output_file:?}");
+            let mut output = TextCorpusWriter::new(
+                std::io::BufWriter::new(std::fs::File::create(&output_file)?),
+                TextCorpusOptions {
+                    kinds: if kind.is_empty() {
+                        CorpusTextKind::ALL.to_vec()
+                    } else {
+                        kind
+                    },
+                    min_length,
+                    max_length,
+                    deduplicate: !keep_duplicates,
+                    exclude_offensive,
+                    include_translingual,
+                    ..Default::default()
+                },
+            );
+            let mut write_result = Ok(());
+            parse_dump_file_with_page_consumer(
+                &input_file,
+                None::<&PathBuf>,
+                |_| std::future::ready(Ok(())),
+                |_, _, wikitext| {
+                    if write_result.is_ok() {
+                        write_result = output.add_page(wikitext).map(|_| ());
+                    }
+                },
+                &error_log,
+                false,
+            )
+            .await?;
+            write_result?;
+            info!("Wrote {} sentences", output.count());
+            output.finish()?;
+        }
+
+        #[cfg(unix)]
+        CliCommand::Daemon {
+            socket,
+            target_directory,
+            progress_delay,
+        } => {
+            run_daemon(DaemonConfiguration {
+                socket,
+                target_directory,
+                base_url,
+                index_url: DumpIndexUrl::Default,
+                progress_delay_seconds: progress_delay,
+            })
+            .await?;
+        }
+    }
+
+    info!("Terminating");
+    Ok(())
+}
+
+fn initialise_logging(log_level: LevelFilter) {
+    CombinedLogger::init(vec![TermLogger::new(
+        log_level,
+        Config::default(),
+        TerminalMode::Mixed,
+        ColorChoice::Auto,
+    )])
+    .unwrap();
+
+    info!("Logging initialised successfully");
+}
+
+/// The language given by either its english name or its wiktionary abbreviation.
+fn language_code_to_download(
+    english_name: Option<String>,
+    wiktionary_abbreviation: Option<String>,
+) -> Result<LanguageCode> {
+    match (english_name, wiktionary_abbreviation) {
+        (Some(english_name), None) => LanguageCode::from_english_name(&english_name),
+        (None, Some(wiktionary_abbreviation)) => LanguageCode::from_wiktionary_abbreviation(&wiktionary_abbreviation),
+        (None, None) => Err(Error::Other(format!("No language to download specified."))),
+        (Some(english_name), Some(wiktionary_abbreviation)) => Err(Error::Other(format!("Specified both the english name '{english_name}' and the wiktionary abbreviation '{wiktionary_abbreviation}' of the language to download."))),
+    }
+}
+
+/// Load the tags of all given files, or `None` if no files are given.
+fn load_word_tags(paths: &[PathBuf]) -> Result<Option<Arc<WordTags>>> {
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    let mut word_tags = WordTags::new();
+    for path in paths {
+        word_tags.load_tsv(path)?;
+    }
+    info!("Loaded tags of {} words", word_tags.len());
+    Ok(Some(Arc::new(word_tags)))
+}
+
+fn translingual_parse_options(include_translingual: bool) -> DumpParseOptions {
+    DumpParseOptions {
+        word_extraction: WordExtractionOptions {
+            include_translingual,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Write the value as json, followed by a newline.
+fn write_json_line(output: &mut impl Write, value: &impl serde::Serialize) -> Result<()> {
+    serde_json::to_writer(&mut *output, value)?;
+    output.write_all(b"\n")?;
+    Ok(())
 }
@@ -0,0 +1,186 @@
+//! Merging of entries that are split across pages whose titles differ only in case,
+//! like `internet` and `Internet`.
+
+use crate::error::{Error, Result};
+use crate::parser::words::Word;
+use crate::parser::Siteinfo;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// How titles of a language are folded before merging.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum CaseMergePolicy {
+    /// Titles are never merged.
+    Keep,
+    /// Titles differing only in the case of their first letter are merged.
+    FoldFirstLetter,
+    /// Titles differing only in case are merged.
+    FoldAll,
+}
+
+impl Default for CaseMergePolicy {
+    fn default() -> Self {
+        Self::Keep
+    }
+}
+
+impl CaseMergePolicy {
+    /// The policy matching the case-sensitivity of a wiki, as given by the `case` field of its siteinfo.
+    /// Wikis with `first-letter` already treat titles differing in their first letter as the same page,
+    /// while `case-sensitive` wikis like wiktionary keep all titles apart.
+    pub fn from_siteinfo_case(case: &str) -> Result<Self> {
+        match case {
+            "first-letter" => Ok(Self::FoldFirstLetter),
+            "case-sensitive" => Ok(Self::Keep),
+            other => Err(Error::Other(format!("Unknown siteinfo case: {other:?}"))),
+        }
+    }
+
+    /// The key of the title under this policy. Titles with the same key are merged.
+    pub fn key(&self, title: &str) -> String {
+        match self {
+            Self::Keep => title.to_string(),
+            Self::FoldFirstLetter => {
+                let mut chars = title.chars();
+                chars
+                    .next()
+                    .into_iter()
+                    .flat_map(char::to_lowercase)
+                    .chain(chars)
+                    .collect()
+            }
+            Self::FoldAll => title.chars().flat_map(char::to_lowercase).collect(),
+        }
+    }
+}
+
+impl FromStr for CaseMergePolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "first-letter" => Ok(Self::FoldFirstLetter),
+            "all" => Ok(Self::FoldAll),
+            other => Err(Error::Other(format!(
+                "Unknown case merge policy {other:?}, expected one of 'keep', 'first-letter' or 'all'"
+            ))),
+        }
+    }
+}
+
+/// The merge policy per language.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct CaseMergeConfiguration {
+    /// The policy of languages that have no policy of their own.
+    pub default_policy: CaseMergePolicy,
+    /// Maps english language names to their policy.
+    /// This allows e.g. to keep German nouns apart from other words while merging English entries.
+    pub language_policies: BTreeMap<String, CaseMergePolicy>,
+}
+
+impl CaseMergeConfiguration {
+    /// A configuration that applies the case-sensitivity of the wiki to all languages.
+    pub fn from_siteinfo(siteinfo: &Siteinfo) -> Result<Self> {
+        Ok(Self {
+            default_policy: CaseMergePolicy::from_siteinfo_case(siteinfo.case())?,
+            language_policies: Default::default(),
+        })
+    }
+
+    pub fn policy(&self, language_english_name: &str) -> CaseMergePolicy {
+        self.language_policies
+            .get(language_english_name)
+            .copied()
+            .unwrap_or(self.default_policy)
+    }
+}
+
+/// A word whose entries may come from multiple pages.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MergedWord {
+    /// The title with the fewest uppercase letters among `sources`, and the smallest of those.
+    pub word: String,
+    pub language_english_name: String,
+    pub word_type: String,
+    /// The sorted titles of the pages this word was found on.
+    pub sources: Vec<String>,
+}
+
+/// Collects words and merges those whose titles are equal under the policy of their language.
+/// The policies are only applied in [`finish`](Self::finish), such that the configuration
+/// can still be changed after adding words, e.g. once the siteinfo of the dump is known.
+#[derive(Debug, Clone, Default)]
+pub struct WordMerger {
+    configuration: CaseMergeConfiguration,
+    /// Maps language and word type to the original titles.
+    entries: BTreeMap<(String, String), BTreeSet<String>>,
+}
+
+impl WordMerger {
+    pub fn new(configuration: CaseMergeConfiguration) -> Self {
+        Self {
+            configuration,
+            entries: Default::default(),
+        }
+    }
+
+    /// The configuration the merged words are computed with.
+    pub fn configuration_mut(&mut self) -> &mut CaseMergeConfiguration {
+        &mut self.configuration
+    }
+
+    pub fn add(&mut self, word: &Word) {
+        self.entries
+            .entry((
+                word.language_english_name.to_string(),
+                word.word_type.to_string(),
+            ))
+            .or_default()
+            .insert(word.word.clone());
+    }
+
+    /// The merged words, ordered by language, word type and key.
+    pub fn finish(self) -> Vec<MergedWord> {
+        let configuration = self.configuration;
+        let mut merged_entries: BTreeMap<(String, String, String), BTreeSet<String>> =
+            BTreeMap::new();
+        for ((language_english_name, word_type), titles) in self.entries {
+            let policy = configuration.policy(&language_english_name);
+            for title in titles {
+                merged_entries
+                    .entry((
+                        language_english_name.clone(),
+                        word_type.clone(),
+                        policy.key(&title),
+                    ))
+                    .or_default()
+                    .insert(title);
+            }
+        }
+
+        merged_entries
+            .into_iter()
+            .map(|((language_english_name, word_type, _), sources)| {
+                // Sources are non-empty, since they are only created when inserting.
+                let word = sources
+                    .iter()
+                    .min_by_key(|source| {
+                        (
+                            source.chars().filter(|c| c.is_uppercase()).count(),
+                            source.as_str(),
+                        )
+                    })
+                    .unwrap()
+                    .clone();
+                MergedWord {
+                    word,
+                    language_english_name,
+                    word_type,
+                    sources: sources.into_iter().collect(),
+                }
+            })
+            .collect()
+    }
+}
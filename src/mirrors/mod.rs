@@ -0,0 +1,156 @@
+use crate::download::retry::{is_transient, RetryPolicy};
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::urls::{
+    available_dates, dump_status_file, latest_articles_dump_feed, DumpBaseUrl, LATEST_DATE,
+};
+use futures_util::future::join_all;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::time::Instant;
+use url::Url;
+
+/// The result of probing a mirror.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct MirrorHealth {
+    pub base_url: String,
+    /// True if the mirror answered the probe successfully.
+    pub available: bool,
+    /// The time until the mirror answered the probe, if it answered.
+    pub latency_millis: Option<u64>,
+    /// Whether the mirror has the dump of the requested language and date, if any was requested.
+    pub has_dump: Option<bool>,
+    /// The reason the mirror is unavailable, if it is.
+    pub error: Option<String>,
+}
+
+impl MirrorHealth {
+    pub fn dump_base_url(&self) -> DumpBaseUrl {
+        DumpBaseUrl::Custom(self.base_url.clone())
+    }
+}
+
+/// The language and date of a dump to look for when probing mirrors.
+#[derive(Debug, Clone)]
+pub struct MirrorProbeTarget {
+    pub language_code: LanguageCode,
+    /// If not given, only the directory of the language is probed.
+    pub date: Option<String>,
+}
+
+/// Probe a single mirror.
+/// The mirror is probed by requesting the directory of the target language, or of English if there is no target.
+pub async fn check_mirror(
+    base_url: &DumpBaseUrl,
+    target: Option<&MirrorProbeTarget>,
+) -> MirrorHealth {
+    let language_code = target
+        .map(|target| target.language_code.clone())
+        .unwrap_or(LanguageCode::English);
+    let mut health = MirrorHealth {
+        base_url: base_url.as_str().to_string(),
+        available: false,
+        latency_millis: None,
+        has_dump: None,
+        error: None,
+    };
+
+    let start = Instant::now();
+    match probe(available_dates(base_url, &language_code)).await {
+        Ok(true) => {
+            health.available = true;
+            health.latency_millis =
+                Some(start.elapsed().as_millis().try_into().unwrap_or(u64::MAX));
+        }
+        Ok(false) => health.error = Some(format!("Language directory not found")),
+        Err(error) => health.error = Some(error.to_string()),
+    }
+
+    if let Some(MirrorProbeTarget {
+        language_code,
+        date: Some(date),
+    }) = target
+    {
+        if health.available {
+            let url = if date == LATEST_DATE {
+                latest_articles_dump_feed(base_url, language_code)
+            } else {
+                dump_status_file(base_url, language_code, date)
+            };
+            match probe(url).await {
+                Ok(has_dump) => health.has_dump = Some(has_dump),
+                Err(error) => health.error = Some(error.to_string()),
+            }
+        }
+    } else if target.is_some() {
+        health.has_dump = Some(health.available);
+    }
+
+    debug!("Mirror health {health:?}");
+    health
+}
+
+/// Returns true if the url can be retrieved successfully.
+/// Transient failures like server errors are retried with the default [`RetryPolicy`].
+async fn probe(url: Result<Url>) -> Result<bool> {
+    let url = &url?;
+    RetryPolicy::default()
+        .retry(&format!("probe of '{url}'"), || async move {
+            let response = reqwest::get(url.clone()).await?;
+            if let Err(error) = response.error_for_status_ref() {
+                let error = Error::from(error);
+                if is_transient(&error) {
+                    return Err(error);
+                }
+            }
+            Ok(response.status().is_success())
+        })
+        .await
+}
+
+/// Probe all given mirrors concurrently and rank them.
+/// Mirrors that have the requested dump come first, then available mirrors, each ordered by latency.
+pub async fn check_mirrors(
+    base_urls: &[DumpBaseUrl],
+    target: Option<&MirrorProbeTarget>,
+) -> Vec<MirrorHealth> {
+    info!("Checking {} mirrors", base_urls.len());
+    let mut healths = join_all(
+        base_urls
+            .iter()
+            .map(|base_url| check_mirror(base_url, target)),
+    )
+    .await;
+
+    healths.sort_by_key(|health| {
+        (
+            health.has_dump != Some(true),
+            !health.available,
+            health.latency_millis.unwrap_or(u64::MAX),
+            health.base_url.clone(),
+        )
+    });
+    healths
+}
+
+/// Persist a mirror ranking as json, such that subsequent downloads can use the best mirror.
+pub fn save_mirror_ranking(path: impl AsRef<Path>, ranking: &[MirrorHealth]) -> Result<()> {
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(file, ranking)?;
+    Ok(())
+}
+
+/// Load a mirror ranking saved with [`save_mirror_ranking`].
+pub fn load_mirror_ranking(path: impl AsRef<Path>) -> Result<Vec<MirrorHealth>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// The best available mirror from a saved ranking, if any is available.
+pub fn best_mirror(ranking: &[MirrorHealth]) -> Option<DumpBaseUrl> {
+    ranking
+        .iter()
+        .find(|health| health.available)
+        .map(MirrorHealth::dump_base_url)
+}
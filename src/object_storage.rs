@@ -0,0 +1,292 @@
+//! Downloading dumps into object storage like S3, Google Cloud Storage or Azure Blob Storage,
+//! such that pipelines can run without local disks.
+//! The dumps can then be parsed from there with [`parse_dump_object`](crate::parser::parse_dump_object).
+
+use crate::download::{download_response, DownloadOptions};
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::urls::{dump_url, DumpBaseUrl};
+use crate::{
+    fetch_dump_status_file, resolve_dump_date, select_dump_date, store, with_mirror_fallback,
+    DumpStatusFileEntry, ARTICLES_DUMP_JOB,
+};
+use log::{debug, info, warn};
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::memory::InMemory;
+use object_store::MultipartId;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use url::Url;
+
+pub use object_store::path::Path as ObjectPath;
+pub use object_store::ObjectStore;
+
+/// Open the object store given by a url like `s3://bucket/prefix`, `gs://bucket/prefix`, `az://container/prefix`,
+/// `file:///directory` or `memory:///`, and return it together with the path of the url within the store.
+/// Credentials and regions are read from the environment variables of each provider, e.g. `AWS_ACCESS_KEY_ID`.
+pub fn object_store_from_url(url: &Url) -> Result<(Arc<dyn ObjectStore>, ObjectPath)> {
+    let store: Arc<dyn ObjectStore> = match url.scheme() {
+        "s3" | "s3a" => Arc::new(AmazonS3Builder::from_env().with_url(url.as_str()).build()?),
+        "gs" => Arc::new(
+            GoogleCloudStorageBuilder::from_env()
+                .with_url(url.as_str())
+                .build()?,
+        ),
+        "az" | "adl" | "azure" | "abfs" | "abfss" => Arc::new(
+            MicrosoftAzureBuilder::from_env()
+                .with_url(url.as_str())
+                .build()?,
+        ),
+        "file" => Arc::new(LocalFileSystem::new()),
+        "memory" => Arc::new(InMemory::new()),
+        other => {
+            return Err(Error::Other(format!(
+                "Unsupported object store scheme {other:?} in '{url}', expected 's3', 'gs', 'az', 'file' or 'memory'"
+            )))
+        }
+    };
+    let path = ObjectPath::from_url_path(url.path()).map_err(object_store::Error::from)?;
+    Ok((store, path))
+}
+
+/// Like [`download_language_with_options`](crate::download_language_with_options),
+/// but streams the dump into the object store instead of the local file system.
+/// The paths of the objects are given by the path template of the options, below `prefix`.
+/// Returns the path of the object of the dump.
+///
+/// The checksums of each file are verified while it is uploaded, and the upload is aborted if they do not match.
+/// Existing objects are kept if they have the expected size, since object stores do not provide md5 or sha1 checksums of arbitrary objects.
+pub async fn download_language_to_object_store(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    store: &dyn ObjectStore,
+    prefix: &ObjectPath,
+    options: &DownloadOptions,
+) -> Result<ObjectPath> {
+    with_mirror_fallback(base_url, options, |mirror| async move {
+        let date = select_dump_date(&mirror, language_code, options).await?;
+        let (dump_status_file, body) =
+            fetch_dump_status_file(&mirror, language_code, &date, options).await?;
+        let articles_dump = dump_status_file.articles_dump()?;
+        let mut paths = upload_job_files(
+            &mirror,
+            language_code,
+            &date,
+            &body,
+            ARTICLES_DUMP_JOB,
+            articles_dump,
+            store,
+            prefix,
+            options,
+        )
+        .await?;
+
+        // Unwrap cannot panic because the articles dump consists of exactly one file.
+        Ok(paths.pop().unwrap())
+    })
+    .await
+}
+
+/// Like [`download_job_at_date_with_options`](crate::download_job_at_date_with_options),
+/// but streams the files of the job into the object store like [`download_language_to_object_store`].
+/// Returns the paths of the objects, ordered by file name.
+pub async fn download_job_to_object_store(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    job: &str,
+    store: &dyn ObjectStore,
+    prefix: &ObjectPath,
+    options: &DownloadOptions,
+) -> Result<Vec<ObjectPath>> {
+    with_mirror_fallback(base_url, options, |mirror| async move {
+        let date = &resolve_dump_date(&mirror, language_code, date, options).await?;
+        let (dump_status_file, body) =
+            fetch_dump_status_file(&mirror, language_code, date, options).await?;
+        let entry = dump_status_file.job(job)?;
+        upload_job_files(
+            &mirror,
+            language_code,
+            date,
+            &body,
+            job,
+            entry,
+            store,
+            prefix,
+            options,
+        )
+        .await
+    })
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn upload_job_files(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    dump_status_file_body: &str,
+    job: &str,
+    entry: &DumpStatusFileEntry,
+    store: &dyn ObjectStore,
+    prefix: &ObjectPath,
+    options: &DownloadOptions,
+) -> Result<Vec<ObjectPath>> {
+    let language_abbreviation = language_code.to_wiktionary_abbreviation();
+    let object_path = |file_name: &str| {
+        let relative_path =
+            options
+                .path_template
+                .render(language_abbreviation, date, job, file_name);
+        ObjectPath::from(format!("{prefix}/{}", relative_path.to_string_lossy()))
+    };
+
+    // Keep the status file, such that the checksums can be looked up again later.
+    store
+        .put(
+            &object_path(store::DUMP_STATUS_FILE_NAME),
+            dump_status_file_body.to_string().into(),
+        )
+        .await?;
+
+    let mut paths = Vec::new();
+    for (file_name, properties) in entry.files() {
+        let url = dump_url(base_url, properties.url())?;
+        let path = object_path(file_name);
+
+        let upload_required = if options.force {
+            info!("Downloading {file_name} again, because the download is forced.");
+            true
+        } else {
+            match store.head(&path).await {
+                Ok(_) if properties.known_size().is_none() => {
+                    info!("Skipping download of {file_name}, because the object exists already and the status file does not declare its size.");
+                    false
+                }
+                Ok(meta) if meta.size == properties.size() => {
+                    info!("Skipping download of {file_name}, because the object exists already and has the expected size.");
+                    false
+                }
+                Ok(meta) => {
+                    warn!(
+                        "Downloading {file_name} again, because the existing object has size {}, but should have size {}",
+                        meta.size,
+                        properties.size()
+                    );
+                    true
+                }
+                Err(object_store::Error::NotFound { .. }) => true,
+                Err(error) => return Err(error.into()),
+            }
+        };
+
+        if upload_required {
+            options
+                .retry_policy
+                .retry(&format!("download of '{url}'"), || {
+                    upload_file(
+                        &url,
+                        store,
+                        &path,
+                        properties.size(),
+                        properties.known_md5(),
+                        properties.known_sha1(),
+                        options,
+                    )
+                })
+                .await?;
+        }
+
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// Stream the file at the url into the object, verifying its size, unless it is zero, and the given checksums.
+async fn upload_file(
+    from_url: &Url,
+    store: &dyn ObjectStore,
+    to_path: &ObjectPath,
+    expected_size: usize,
+    md5: Option<&str>,
+    sha1: Option<&str>,
+    options: &DownloadOptions,
+) -> Result<()> {
+    info!("Downloading file from '{from_url}' to object '{to_path}'");
+    let response = reqwest::get(from_url.clone()).await?.error_for_status()?;
+    let mut upload = ObjectUpload::start(store, to_path).await?;
+
+    let result = download_response(
+        from_url,
+        response,
+        upload.writer(),
+        expected_size as u64,
+        options.progress_delay_seconds,
+        options.checksum_tasks,
+        options.progress.as_ref(),
+        options.cancellation.as_ref(),
+        md5,
+        sha1,
+    )
+    .await;
+
+    match result {
+        Ok(_) => {
+            upload.finish().await?;
+            info!("Finished downloading file from '{from_url}' to object '{to_path}'");
+            Ok(())
+        }
+        Err(error) => {
+            upload.abort(store).await;
+            Err(error)
+        }
+    }
+}
+
+/// A multipart upload of an object, which becomes visible in the store only once it is finished.
+pub(crate) struct ObjectUpload {
+    path: ObjectPath,
+    multipart_id: MultipartId,
+    writer: Box<dyn AsyncWrite + Unpin + Send>,
+}
+
+impl ObjectUpload {
+    pub(crate) async fn start(store: &dyn ObjectStore, path: &ObjectPath) -> Result<Self> {
+        debug!("Starting upload of object '{path}'");
+        let (multipart_id, writer) = store.put_multipart(path).await?;
+        Ok(Self {
+            path: path.clone(),
+            multipart_id,
+            writer,
+        })
+    }
+
+    /// The writer of the uploaded data.
+    pub(crate) fn writer(&mut self) -> &mut (dyn AsyncWrite + Unpin + Send) {
+        &mut *self.writer
+    }
+
+    pub(crate) async fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes).await?;
+        Ok(())
+    }
+
+    /// Upload the remaining data and complete the object.
+    pub(crate) async fn finish(mut self) -> Result<()> {
+        self.writer.shutdown().await?;
+        debug!("Finished upload of object '{}'", self.path);
+        Ok(())
+    }
+
+    /// Discard the uploaded parts, such that the object is not created.
+    /// Failures are only logged, since the upload failed already.
+    pub(crate) async fn abort(self, store: &dyn ObjectStore) {
+        if let Err(error) = store.abort_multipart(&self.path, &self.multipart_id).await {
+            warn!("Aborting upload of object '{}' failed: {error}", self.path);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::error::Error;
+use crate::parser::words::{extract_words, Word};
+use crate::wiktionary_edition::WiktionaryEdition;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use wikitext_parser::parse_wikitext;
+
+/// The title and raw wikitext of a page.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RawPage {
+    pub title: String,
+    pub text: String,
+}
+
+/// The combined result of extracting words from many pages in parallel.
+#[derive(Debug, Default)]
+pub struct ParallelExtraction {
+    /// The extracted words, in no particular order.
+    pub words: Vec<Word>,
+    /// The errors that occurred, each with the title of the page it occurred in.
+    pub errors: Vec<(String, Error)>,
+}
+
+impl ParallelExtraction {
+    fn merge(mut self, mut other: Self) -> Self {
+        self.words.append(&mut other.words);
+        self.errors.append(&mut other.errors);
+        self
+    }
+}
+
+/// Parse pages and extract their words on the rayon thread pool.
+///
+/// Each thread accumulates words and errors separately, and the results are merged at the end,
+/// so threads do not contend on shared state.
+/// Plain iterators of pages can be used via [`par_bridge`](rayon::iter::ParallelBridge::par_bridge).
+pub fn extract_words_parallel(
+    pages: impl IntoParallelIterator<Item = RawPage>,
+    edition: &WiktionaryEdition,
+) -> ParallelExtraction {
+    pages
+        .into_par_iter()
+        .fold(ParallelExtraction::default, |mut extraction, page| {
+            let mut parser_errors = Vec::new();
+            let wikitext = parse_wikitext(&page.text, page.title.clone(), |error| {
+                parser_errors.push(error)
+            });
+            for error in parser_errors {
+                extraction.errors.push((
+                    page.title.clone(),
+                    Error::WikitextParserError {
+                        error: Box::new(error),
+                        page_name: page.title.clone(),
+                        page_content: page.text.clone(),
+                    },
+                ));
+            }
+
+            let ParallelExtraction { words, errors } = &mut extraction;
+            extract_words(
+                edition,
+                &page.title,
+                &wikitext,
+                |word| words.push(word),
+                |error| errors.push((page.title.clone(), error)),
+            );
+            extraction
+        })
+        .reduce(ParallelExtraction::default, ParallelExtraction::merge)
+}
@@ -0,0 +1,100 @@
+//! Blocking variants of the dump parser and the words extractor, for applications without an async runtime,
+//! like command line tools and rayon-based pipelines.
+//!
+//! The consumers are plain closures instead of closures returning futures.
+//! Internally, dumps are parsed on a private single-threaded runtime, so the functions must not be called
+//! from within an async runtime.
+//! Words can be extracted from parsed wikitext without any runtime with [`extract_words`](crate::parser::words::extract_words).
+
+use std::path::Path;
+use wikitext_parser::Wikitext;
+
+use crate::error::{Error, Result};
+use crate::parser::statistics::ParseStatistics;
+use crate::parser::words::{Word, WordExtractionOptions};
+use crate::parser::DumpParseOptions;
+use crate::wiktionary_edition::WiktionaryEdition;
+
+/// The result of a blocking word consumer.
+pub type WordConsumerResult = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Like [`parse_dump_file`](crate::parser::parse_dump_file), but blocks until the dump is parsed.
+pub fn parse_dump_file(
+    input_file: impl AsRef<Path>,
+    output_file: Option<impl AsRef<Path>>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_log: impl AsRef<Path>,
+    output_pretty: bool,
+) -> Result<()> {
+    parse_dump_file_with_options(
+        input_file,
+        output_file,
+        word_consumer,
+        |_, _, _| {},
+        error_log,
+        &DumpParseOptions {
+            output_pretty,
+            ..Default::default()
+        },
+    )?;
+    Ok(())
+}
+
+/// Like [`parse_dump_file_with_options`](crate::parser::parse_dump_file_with_options), but blocks until the dump is parsed.
+pub fn parse_dump_file_with_options(
+    input_file: impl AsRef<Path>,
+    output_file: Option<impl AsRef<Path>>,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    block_on(crate::parser::parse_dump_file_with_options(
+        input_file,
+        output_file,
+        |word| std::future::ready(word_consumer(word)),
+        page_consumer,
+        error_log,
+        options,
+    ))
+}
+
+/// Like [`wikitext_to_words_with_options`](crate::parser::words::wikitext_to_words_with_options),
+/// but with a blocking result consumer.
+/// Errors while extracting are handed to `error_consumer`,
+/// while errors while consuming results are returned.
+pub fn wikitext_to_words(
+    options: &WordExtractionOptions,
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_consumer: impl FnMut(Error),
+) -> Result<()> {
+    let mut words = Vec::new();
+    crate::parser::words::extract_words_with_options(
+        options,
+        title,
+        wikitext,
+        |word| words.push(word),
+        error_consumer,
+    );
+
+    for word in words {
+        result_consumer(word).map_err(|error| Error::WordConsumer { source: error })?;
+    }
+
+    Ok(())
+}
+
+/// Run the future to completion on a new single-threaded runtime.
+fn block_on<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Err(Error::Other(format!(
+            "Blocking parser functions cannot be called from within an async runtime, use the async functions instead"
+        )));
+    }
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(future)
+}
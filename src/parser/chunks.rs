@@ -0,0 +1,151 @@
+//! Pull-based parsing of dumps in batches of pages, for embedding into other execution models.
+
+use crate::error::{Error, Result};
+use crate::parser::sink::PageSink;
+use crate::parser::{parse_dump_file_with_sink, DumpParseOptions, Page, Siteinfo};
+use log::debug;
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+
+/// The number of chunks that are parsed ahead of the consumer.
+const CHUNKS_AHEAD: usize = 2;
+
+/// Yields the pages of a dump file in chunks of owned `Vec`s.
+///
+/// The dump is parsed on a separate thread with its own async runtime, at most a few chunks ahead of the consumer.
+/// Retrieving a chunk blocks until it is parsed, so async consumers should call [`DumpChunker::next_chunk`]
+/// from a blocking task, while Rayon can consume the chunks directly with `par_bridge`.
+/// Dropping the chunker stops the parsing after the current chunk.
+pub struct DumpChunker {
+    receiver: Receiver<Result<Vec<Page>>>,
+    siteinfo: Arc<Mutex<Option<Siteinfo>>>,
+}
+
+impl DumpChunker {
+    /// Parse the given dump file in chunks of `chunk_size` pages, writing errors in pages to `error_log`.
+    pub fn new(
+        input_file: impl Into<PathBuf>,
+        error_log: impl Into<PathBuf>,
+        chunk_size: usize,
+    ) -> Result<Self> {
+        Self::with_options(
+            input_file,
+            error_log,
+            chunk_size,
+            DumpParseOptions::default(),
+        )
+    }
+
+    /// Like [`DumpChunker::new`], but configured by the given options.
+    /// The options of output files are ignored.
+    pub fn with_options(
+        input_file: impl Into<PathBuf>,
+        error_log: impl Into<PathBuf>,
+        chunk_size: usize,
+        options: DumpParseOptions,
+    ) -> Result<Self> {
+        if chunk_size == 0 {
+            return Err(Error::Other(format!("The chunk size must be at least one")));
+        }
+
+        let input_file = input_file.into();
+        let error_log = error_log.into();
+        let (sender, receiver) = sync_channel(CHUNKS_AHEAD);
+        let siteinfo = Arc::new(Mutex::new(None));
+        let mut sink = ChunkSink {
+            chunk: Vec::with_capacity(chunk_size),
+            chunk_size,
+            sender: sender.clone(),
+            siteinfo: siteinfo.clone(),
+        };
+
+        std::thread::Builder::new()
+            .name("dump-chunker".to_string())
+            .spawn(move || {
+                let result = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(Error::from)
+                    .and_then(|runtime| {
+                        runtime.block_on(parse_dump_file_with_sink(
+                            &input_file,
+                            &mut sink,
+                            |_| std::future::ready(Ok(())),
+                            |_, _, _| {},
+                            &error_log,
+                            &options,
+                        ))
+                    });
+                if let Err(error) = result {
+                    // If the receiver was dropped, nobody is interested in the error anymore.
+                    sender.send(Err(error)).ok();
+                }
+                debug!("Finished parsing {input_file:?} in chunks");
+            })?;
+
+        Ok(Self { receiver, siteinfo })
+    }
+
+    /// The next chunk of pages, or `None` if all pages were retrieved.
+    /// Only the last chunk may have less than `chunk_size` pages.
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<Page>>> {
+        match self.receiver.recv() {
+            Ok(chunk) => chunk.map(Some),
+            // The parser finished and dropped its sender.
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// The siteinfo of the dump, once it was parsed.
+    /// It is always parsed before the first chunk is complete.
+    pub fn siteinfo(&self) -> Option<Siteinfo> {
+        self.siteinfo.lock().unwrap().clone()
+    }
+}
+
+impl Iterator for DumpChunker {
+    type Item = Result<Vec<Page>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+struct ChunkSink {
+    chunk: Vec<Page>,
+    chunk_size: usize,
+    sender: SyncSender<Result<Vec<Page>>>,
+    siteinfo: Arc<Mutex<Option<Siteinfo>>>,
+}
+
+impl ChunkSink {
+    fn send_chunk(&mut self) -> Result<()> {
+        let chunk = std::mem::replace(&mut self.chunk, Vec::with_capacity(self.chunk_size));
+        self.sender
+            .send(Ok(chunk))
+            .map_err(|_| Error::Other(format!("The chunker was dropped")))
+    }
+}
+
+impl PageSink for ChunkSink {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        *self.siteinfo.lock().unwrap() = Some(siteinfo);
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        self.chunk.push(page);
+        if self.chunk.len() >= self.chunk_size {
+            self.send_chunk()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.chunk.is_empty() {
+            self.send_chunk()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// The version of the format of a dump, as declared by the dump itself.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct DumpVersion {
+    /// The `version` attribute of the `mediawiki` element, e.g. `0.10`.
+    pub schema_version: Option<String>,
+    /// The MediaWiki version from the generator in the siteinfo, e.g. `1.41.0-wmf.18`.
+    pub generator_version: Option<String>,
+}
+
+type PageTextShim = Arc<dyn Fn(&str, String) -> String + Send + Sync>;
+
+#[derive(Clone)]
+struct CompatibilityShim {
+    schema_version_prefix: Option<String>,
+    generator_version_prefix: Option<String>,
+    shim: PageTextShim,
+}
+
+impl CompatibilityShim {
+    fn applies_to(&self, version: &DumpVersion) -> bool {
+        fn matches(prefix: &Option<String>, version: &Option<String>) -> bool {
+            match (prefix, version) {
+                (None, _) => true,
+                (Some(prefix), Some(version)) => version.starts_with(prefix.as_str()),
+                (Some(_), None) => false,
+            }
+        }
+
+        matches(&self.schema_version_prefix, &version.schema_version)
+            && matches(&self.generator_version_prefix, &version.generator_version)
+    }
+}
+
+/// Adaptations of the raw wikitext of pages, applied only to dumps of matching versions.
+/// This allows to adapt to changes of the dump format while still supporting the old format.
+#[derive(Clone, Default)]
+pub struct CompatibilityShims {
+    shims: Vec<CompatibilityShim>,
+}
+
+impl CompatibilityShims {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a shim that receives the title and raw wikitext of each page and returns the adapted wikitext.
+    /// It is applied to dumps whose schema version and generator version start with the given prefixes.
+    /// A prefix of `None` matches any version, including a missing one.
+    /// Shims are applied in the order of registration.
+    pub fn register(
+        &mut self,
+        schema_version_prefix: Option<&str>,
+        generator_version_prefix: Option<&str>,
+        shim: impl Fn(&str, String) -> String + Send + Sync + 'static,
+    ) {
+        self.shims.push(CompatibilityShim {
+            schema_version_prefix: schema_version_prefix.map(ToString::to_string),
+            generator_version_prefix: generator_version_prefix.map(ToString::to_string),
+            shim: Arc::new(shim),
+        });
+    }
+
+    /// Apply all shims matching the version to the raw wikitext of a page.
+    pub fn apply(&self, version: &DumpVersion, title: &str, mut text: String) -> String {
+        for shim in &self.shims {
+            if shim.applies_to(version) {
+                text = (shim.shim)(title, text);
+            }
+        }
+        text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shims.is_empty()
+    }
+}
+
+impl Debug for CompatibilityShims {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(
+                self.shims
+                    .iter()
+                    .map(|shim| (&shim.schema_version_prefix, &shim.generator_version_prefix)),
+            )
+            .finish()
+    }
+}
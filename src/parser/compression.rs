@@ -0,0 +1,129 @@
+//! The compressions of dump files, like the `.xml.bz2` dumps of wiktionary,
+//! or dumps re-compressed with gzip or zstd by users.
+
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, ZstdDecoder};
+use log::debug;
+use std::ffi::OsStr;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncReadExt, BufReader};
+
+use crate::error::{Error, Result};
+
+/// The compression of a dump file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum DumpCompression {
+    /// An uncompressed `.xml` file.
+    None,
+    /// A `.xml.bz2` file, as published by wiktionary.
+    Bzip2,
+    /// A `.xml.gz` file.
+    Gzip,
+    /// A `.xml.zst` file.
+    Zstd,
+}
+
+impl DumpCompression {
+    /// The compression given by the extension of the file name, like `.xml.zst`,
+    /// or `None` if the extension is not known.
+    ///
+    /// Compressed files must have an `.xml` extension before the extension of the compression.
+    pub fn from_file_name(file: impl AsRef<Path>) -> Result<Option<Self>> {
+        let file = file.as_ref();
+        let compression = match file.extension().and_then(OsStr::to_str) {
+            Some("xml") => return Ok(Some(Self::None)),
+            Some("bz2") => Self::Bzip2,
+            Some("gz") => Self::Gzip,
+            Some("zst") | Some("zstd") => Self::Zstd,
+            _ => return Ok(None),
+        };
+
+        if file
+            .file_stem()
+            .and_then(OsStr::to_str)
+            // Multi-part dumps are named like `...-pages-articles1.xml-p1p41242.bz2`.
+            .filter(|stem| stem.ends_with("xml") || stem.contains(".xml-p"))
+            .is_none()
+        {
+            return Err(Error::Other(format!(
+                "Found a '.{}' file extension that is not preceded by a '.xml' file extension in file {file:?}",
+                compression.extension()
+            )));
+        }
+        Ok(Some(compression))
+    }
+
+    /// The compression given by the first bytes of a file, or `None` if they are neither
+    /// the magic bytes of a known compression nor the start of an xml document.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"BZh") {
+            Some(Self::Bzip2)
+        } else if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else if bytes
+            .strip_prefix(&[0xef, 0xbb, 0xbf])
+            .unwrap_or(bytes)
+            .iter()
+            .find(|byte| !byte.is_ascii_whitespace())
+            == Some(&b'<')
+        {
+            Some(Self::None)
+        } else {
+            None
+        }
+    }
+
+    /// The compression of a file, by the extension of its name, or by its first bytes if the extension is not known.
+    pub async fn detect(file: impl AsRef<Path>) -> Result<Self> {
+        let file = file.as_ref();
+        if let Some(compression) = Self::from_file_name(file)? {
+            debug!("Found compression {compression:?} by the file extension of {file:?}");
+            return Ok(compression);
+        }
+
+        let mut bytes = Vec::new();
+        File::open(file)
+            .await?
+            .take(64)
+            .read_to_end(&mut bytes)
+            .await?;
+        let compression = Self::from_magic_bytes(&bytes).ok_or_else(|| {
+            Error::Other(format!(
+                "Unknown file extension in file {file:?}, and its content is neither compressed nor xml"
+            ))
+        })?;
+        debug!("Found compression {compression:?} by the first bytes of {file:?}");
+        Ok(compression)
+    }
+
+    /// The file extension of the compression, without the `.xml` before it.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::None => "xml",
+            Self::Bzip2 => "bz2",
+            Self::Gzip => "gz",
+            Self::Zstd => "zst",
+        }
+    }
+
+    /// Decompress the given stream.
+    pub fn decoder(
+        &self,
+        input: impl AsyncBufRead + Unpin + Send + 'static,
+    ) -> Box<dyn AsyncBufRead + Unpin + Send> {
+        match self {
+            Self::None => Box::new(input),
+            Self::Bzip2 => Box::new(BufReader::with_capacity(1024 * 1024, BzDecoder::new(input))),
+            Self::Gzip => Box::new(BufReader::with_capacity(
+                1024 * 1024,
+                GzipDecoder::new(input),
+            )),
+            Self::Zstd => Box::new(BufReader::with_capacity(
+                1024 * 1024,
+                ZstdDecoder::new(input),
+            )),
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Comparison of two dumps of the same wiki, e.g. of consecutive dates,
+//! to update downstream databases incrementally instead of importing each dump in full.
+
+use crate::error::Result;
+use crate::parser::sink::PageSink;
+use crate::parser::words::classification::{PageClassifier, PagePipeline};
+use crate::parser::words::{extract_words_with_namespace, Word, WordExtractionOptions};
+use crate::parser::{parse_dump_file_with_sink, DumpParseOptions, Page, Siteinfo};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Options for [`diff_dumps`].
+#[derive(Debug, Clone, Default)]
+pub struct DumpDiffOptions {
+    /// Options for parsing both dumps. The options of output files are ignored.
+    pub parse_options: DumpParseOptions,
+    /// Compare the words extracted from the pages instead of their texts.
+    /// Pages whose texts changed but whose words did not are not reported, and pages without words are ignored.
+    /// This holds the words of all pages of the old dump in memory.
+    pub compare_words: bool,
+}
+
+/// Identifies the latest revision of a page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PageFingerprint {
+    pub page_id: i64,
+    pub revision_id: i64,
+    /// The SHA-1 of the text of the revision as given in the dump, encoded in base 36.
+    pub sha1: String,
+}
+
+impl PageFingerprint {
+    fn new(page: &Page) -> Self {
+        Self {
+            page_id: page.id(),
+            revision_id: page.revision().id(),
+            sha1: page.revision().sha1().to_string(),
+        }
+    }
+
+    /// Compare by SHA-1, or by revision id if the dumps have no SHA-1s,
+    /// such that reverts to an earlier text are not reported as changes.
+    fn has_same_text(&self, other: &Self) -> bool {
+        if self.sha1.is_empty() || other.sha1.is_empty() {
+            self.revision_id == other.revision_id
+        } else {
+            self.sha1 == other.sha1
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PageChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A page that differs between two dumps.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PageChange {
+    pub title: String,
+    pub kind: PageChangeKind,
+    /// The page in the old dump, unless it was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<PageFingerprint>,
+    /// The page in the new dump, unless it was removed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<PageFingerprint>,
+    /// The words only extracted from the new page, if words are compared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_words: Vec<Word>,
+    /// The words only extracted from the old page, if words are compared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_words: Vec<Word>,
+}
+
+/// The amount of pages of each kind of change found by [`diff_dumps`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct DumpDiffStatistics {
+    pub added: u64,
+    pub removed: u64,
+    pub changed: u64,
+    pub unchanged: u64,
+}
+
+impl Display for DumpDiffStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pages added, {} removed, {} changed and {} unchanged",
+            self.added, self.removed, self.changed, self.unchanged
+        )
+    }
+}
+
+/// Compare two dumps of the same wiki page by page, matching pages by title,
+/// and hand each added, removed or changed page to the consumer.
+///
+/// Added and changed pages are handed over in the order of the new dump, followed by the removed pages ordered by title.
+/// Parse errors of both dumps are written to `error_log`.
+pub async fn diff_dumps(
+    old_input_file: impl AsRef<Path>,
+    new_input_file: impl AsRef<Path>,
+    error_log: impl AsRef<Path>,
+    options: &DumpDiffOptions,
+    consumer: impl FnMut(PageChange) -> Result<()>,
+) -> Result<DumpDiffStatistics> {
+    let error_log = error_log.as_ref();
+    // The words are extracted by the sinks, so the parser does not need to extract them as well.
+    let mut parse_options = options.parse_options.clone();
+    parse_options.word_extraction.page_classifier = PageClassifier {
+        rules: Vec::new(),
+        default_pipeline: PagePipeline::Ignore,
+    };
+    let word_extraction = if options.compare_words {
+        Some(&options.parse_options.word_extraction)
+    } else {
+        None
+    };
+
+    let old_input_file = old_input_file.as_ref();
+    info!("Collecting the pages of the old dump {old_input_file:?}");
+    let mut old_sink = OldPagesSink {
+        pages: HashMap::new(),
+        word_extraction,
+    };
+    parse_dump_file_with_sink(
+        old_input_file,
+        &mut old_sink,
+        |_| std::future::ready(Ok(())),
+        |_, _, _| {},
+        error_log,
+        &parse_options,
+    )
+    .await?;
+
+    let new_input_file = new_input_file.as_ref();
+    info!("Comparing the pages of the new dump {new_input_file:?}");
+    let mut new_sink = NewPagesSink {
+        old_pages: old_sink.pages,
+        word_extraction,
+        consumer,
+        statistics: DumpDiffStatistics::default(),
+    };
+    // The log of the old dump would be truncated by parsing the new one.
+    let mut new_error_log = error_log.as_os_str().to_owned();
+    new_error_log.push(".new");
+    parse_dump_file_with_sink(
+        new_input_file,
+        &mut new_sink,
+        |_| std::future::ready(Ok(())),
+        |_, _, _| {},
+        &new_error_log,
+        &parse_options,
+    )
+    .await?;
+    append_and_remove(Path::new(&new_error_log), error_log)?;
+
+    let NewPagesSink {
+        old_pages,
+        mut consumer,
+        mut statistics,
+        ..
+    } = new_sink;
+    let mut removed_pages: Vec<_> = old_pages.into_iter().collect();
+    removed_pages.sort_by(|a, b| a.0.cmp(&b.0));
+    for (title, old_page) in removed_pages {
+        if word_extraction.is_some() && old_page.words.is_empty() {
+            continue;
+        }
+        statistics.removed += 1;
+        consumer(PageChange {
+            title,
+            kind: PageChangeKind::Removed,
+            old: Some(old_page.fingerprint),
+            new: None,
+            added_words: Vec::new(),
+            removed_words: old_page.words,
+        })?;
+    }
+
+    info!("Compared dumps: {statistics}");
+    Ok(statistics)
+}
+
+fn append_and_remove(source: &Path, target: &Path) -> Result<()> {
+    let mut source_file = std::fs::File::open(source)?;
+    let mut target_file = std::fs::OpenOptions::new().append(true).open(target)?;
+    std::io::copy(&mut source_file, &mut target_file)?;
+    std::fs::remove_file(source)?;
+    Ok(())
+}
+
+/// The words of the page, sorted and without duplicates.
+fn page_words(word_extraction: &WordExtractionOptions, page: &Page) -> Vec<Word> {
+    let mut words = BTreeSet::new();
+    if let Some(text) = page.revision().text() {
+        extract_words_with_namespace(
+            word_extraction,
+            Some(page.namespace()),
+            page.title(),
+            text.wikitext(),
+            |word| {
+                words.insert(word);
+            },
+            |_| {},
+        );
+    }
+    words.into_iter().collect()
+}
+
+struct OldPage {
+    fingerprint: PageFingerprint,
+    /// Empty if words are not compared.
+    words: Vec<Word>,
+}
+
+struct OldPagesSink<'a> {
+    pages: HashMap<String, OldPage>,
+    word_extraction: Option<&'a WordExtractionOptions>,
+}
+
+impl PageSink for OldPagesSink<'_> {
+    fn site_info(&mut self, _siteinfo: Siteinfo) -> Result<()> {
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        let words = self
+            .word_extraction
+            .map(|word_extraction| page_words(word_extraction, &page))
+            .unwrap_or_default();
+        self.pages.insert(
+            page.title().to_string(),
+            OldPage {
+                fingerprint: PageFingerprint::new(&page),
+                words,
+            },
+        );
+        Ok(())
+    }
+}
+
+struct NewPagesSink<'a, Consumer> {
+    old_pages: HashMap<String, OldPage>,
+    word_extraction: Option<&'a WordExtractionOptions>,
+    consumer: Consumer,
+    statistics: DumpDiffStatistics,
+}
+
+impl<Consumer: FnMut(PageChange) -> Result<()>> PageSink for NewPagesSink<'_, Consumer> {
+    fn site_info(&mut self, _siteinfo: Siteinfo) -> Result<()> {
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        let fingerprint = PageFingerprint::new(&page);
+        let old_page = self.old_pages.remove(page.title());
+        if let Some(old_page) = &old_page {
+            if old_page.fingerprint.has_same_text(&fingerprint) {
+                self.statistics.unchanged += 1;
+                return Ok(());
+            }
+        }
+
+        let words = self
+            .word_extraction
+            .map(|word_extraction| page_words(word_extraction, &page))
+            .unwrap_or_default();
+        let (added_words, removed_words) = if let Some(old_page) = &old_page {
+            (
+                difference(&words, &old_page.words),
+                difference(&old_page.words, &words),
+            )
+        } else {
+            (words, Vec::new())
+        };
+        if self.word_extraction.is_some() && added_words.is_empty() && removed_words.is_empty() {
+            if old_page.is_some() {
+                self.statistics.unchanged += 1;
+            }
+            return Ok(());
+        }
+
+        let kind = if old_page.is_some() {
+            self.statistics.changed += 1;
+            PageChangeKind::Changed
+        } else {
+            self.statistics.added += 1;
+            PageChangeKind::Added
+        };
+        (self.consumer)(PageChange {
+            title: page.title().to_string(),
+            kind,
+            old: old_page.map(|old_page| old_page.fingerprint),
+            new: Some(fingerprint),
+            added_words,
+            removed_words,
+        })
+    }
+}
+
+/// The words of the sorted `a` that are not in the sorted `b`.
+fn difference(a: &[Word], b: &[Word]) -> Vec<Word> {
+    a.iter()
+        .filter(|word| b.binary_search(word).is_err())
+        .cloned()
+        .collect()
+}
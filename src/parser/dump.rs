@@ -0,0 +1,2862 @@
+use crate::dates::{parse_dump_date, parse_timestamp};
+use crate::error::{Error, ErrorContext, Result};
+use crate::log_excerpt::excerpt;
+#[cfg(feature = "cloud")]
+use crate::object_storage::{ObjectPath, ObjectStore, ObjectUpload};
+use crate::parser::compatibility::{CompatibilityShims, DumpVersion};
+use crate::parser::compression::DumpCompression;
+use crate::parser::parallel_bzip2::ParallelBzDecoder;
+use crate::parser::partition::{PartitionBy, PartitionedOutput};
+use crate::parser::sink::{JsonPageSink, OutputFormat, PageSink};
+use crate::parser::statistics::ParseStatistics;
+use crate::parser::template_store::TemplateExpander;
+use crate::parser::titles::TitleFilter;
+use crate::parser::warnings::{Warning, WarningReporter};
+use crate::parser::words::ids::WordLink;
+use crate::parser::words::{extract_words_with_namespace, Word, WordExtractionOptions};
+use crate::parser::xml::{dump_reader, read_relevant_event, RelevantEvent};
+use crate::parser::{
+    sha1_base36, Contributor, Namespace, Page, PageRevision, Revision, Siteinfo, Text, XmlSpace,
+};
+use crate::progress::{ProgressPhase, ProgressReporter, ProgressUpdate};
+use crate::wiktionary_edition::WiktionaryEdition;
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, ZstdDecoder};
+use chrono::NaiveDate;
+use log::{debug, info, trace, warn};
+use quick_xml::escape::escape;
+use quick_xml::events::attributes::Attributes;
+use quick_xml::events::BytesStart;
+use quick_xml::name::QName;
+use quick_xml::Reader;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use tokio::fs::File;
+use tokio::io::{
+    AsyncBufRead, AsyncRead, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf,
+};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::{CancellationToken, PollSender};
+use wikitext_parser::{parse_wikitext, Wikitext};
+
+/// Parse a dump file, either compressed as `.xml.bz2`, `.xml.gz` or `.xml.zst`, or uncompressed as `.xml`.
+/// Files with other extensions are recognised by their first bytes.
+/// Uncompressed files may also be exports created with `Special:Export`,
+/// including fragments that consist only of `<page>` elements without the `<mediawiki>` wrapper and siteinfo.
+pub async fn parse_dump_file<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_file: impl AsRef<Path>,
+    output_file: Option<impl AsRef<Path>>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_log: impl AsRef<Path>,
+    output_pretty: bool,
+) -> Result<()> {
+    parse_dump_file_with_page_consumer(
+        input_file,
+        output_file,
+        word_consumer,
+        |_, _, _| {},
+        error_log,
+        output_pretty,
+    )
+    .await
+}
+
+/// Like [`parse_dump_file`], but additionally hands the title and parsed wikitext of each page to `page_consumer`.
+/// This allows to extract datasets that need more than the words.
+pub async fn parse_dump_file_with_page_consumer<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_file: impl AsRef<Path>,
+    output_file: Option<impl AsRef<Path>>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    output_pretty: bool,
+) -> Result<()> {
+    parse_dump_file_with_options(
+        input_file,
+        output_file,
+        word_consumer,
+        page_consumer,
+        error_log,
+        &DumpParseOptions {
+            output_pretty,
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Options for [`parse_dump_file_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct DumpParseOptions {
+    pub output_pretty: bool,
+    /// How the objects in the output file and the siteinfo file are separated.
+    pub output_format: OutputFormat,
+    /// Options for extracting words from the pages.
+    /// If the edition is unknown, it is inferred from the file name.
+    /// Either way, it is replaced by the edition given in the siteinfo of the dump, if any.
+    pub word_extraction: WordExtractionOptions,
+    /// If given, malformed pages do not abort parsing.
+    /// Instead, parsing continues at the next page, and the error and the skipped xml are appended to this file.
+    /// Pages with wikitext errors are appended to it as well with [`WikitextErrorPolicy::Quarantine`].
+    pub quarantine_file: Option<PathBuf>,
+    /// If given, the siteinfo is written to this file instead of to the output file.
+    pub siteinfo_file: Option<PathBuf>,
+    /// Adaptations of the page texts for specific versions of the dump format.
+    pub compatibility_shims: CompatibilityShims,
+    /// If given, invocations of the selected templates are expanded before the page texts are parsed.
+    /// The templates are obtained by a first pass with [`TemplateStore::from_dump_file`](crate::parser::template_store::TemplateStore::from_dump_file).
+    pub template_expander: Option<Arc<TemplateExpander>>,
+    /// If given, a [`WordLink`] is written for each extracted word to this file, as one json object per line.
+    pub word_link_file: Option<PathBuf>,
+    /// The date of the dump, recorded in the word links.
+    /// If not given, it is inferred from the file name.
+    pub dump_date: Option<NaiveDate>,
+    /// Compute the SHA-1 of each revision text and compare it to the one given in the dump.
+    /// Mismatches are logged and written to the error log, and the computed SHA-1 is kept for [`Revision::verify_sha1`].
+    pub verify_sha1: bool,
+    /// If given, the output file is a directory, and the pages are written into one file per partition,
+    /// as one json object per line.
+    pub partition_by: Option<PartitionBy>,
+    /// If given, only pages whose titles match the filter are parsed.
+    /// Other pages are skipped without parsing their revisions.
+    pub title_filter: Option<Arc<TitleFilter>>,
+    /// The amount of worker threads that parse the wikitext of pages and extract their words,
+    /// while the xml is read and decompressed by the calling task.
+    /// If zero, pages are parsed by the calling task one after the other.
+    pub page_workers: usize,
+    /// Hand pages to the sink and consumers in the order their workers finish, instead of in the order of the dump.
+    /// This only has an effect if `page_workers` is not zero.
+    pub relaxed_page_order: bool,
+    /// If given, the [`ParseStatistics`] are written to this file as json after parsing.
+    pub statistics_file: Option<PathBuf>,
+    /// What is done with the earlier revisions of pages in full-history dumps.
+    pub revision_history: RevisionHistory,
+    /// If given, receives the bytes of the dump file read and the pages parsed,
+    /// in addition to the progress logged every ten seconds.
+    pub progress: Option<ProgressReporter>,
+    /// If given and cancelled, parsing stops before the next page.
+    /// The pages parsed so far are written and the output is completed, and then [`Error::Cancelled`] is returned.
+    pub cancellation: Option<CancellationToken>,
+    /// The amount of threads that decompress `.xml.bz2` dumps.
+    /// If greater than one, the independent streams of multistream dumps are decompressed in parallel
+    /// by a [`ParallelBzDecoder`], which is usually faster than parsing with a single decompressing task.
+    /// Dumps that consist of a single stream are decompressed by a single thread either way.
+    pub decompression_threads: usize,
+    /// The amount of input files that are parsed at the same time by [`parse_dump_files_with_options`].
+    /// If greater than one, each part is parsed on its own thread into temporary files next to the error log,
+    /// which are then handed to the sink and consumers in the order of the input files.
+    /// If zero or one, the input files are parsed one after the other.
+    ///
+    /// [`parse_dump_files_with_options`]: crate::parser::parts::parse_dump_files_with_options
+    pub parallel_parts: usize,
+    /// What is done with pages whose wikitext has errors according to the wikitext parser.
+    pub wikitext_errors: WikitextErrorPolicy,
+    /// Receives the [`Warning`]s about unexpected content that parsing continues after.
+    /// By default, they are logged.
+    pub warnings: WarningReporter,
+}
+
+/// What is done with a page whose wikitext the wikitext parser reports errors for.
+/// The errors are always written to the error log.
+/// Only the latest revision of a page is checked, earlier revisions are kept either way.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WikitextErrorPolicy {
+    /// Keep the page with the wikitext as far as it could be parsed, and extract its words.
+    Keep,
+    /// Abort parsing with an error.
+    Fail,
+    /// Drop the page without extracting its words.
+    Skip,
+    /// Drop the page without extracting its words, and append it to the
+    /// [`quarantine_file`](DumpParseOptions::quarantine_file) together with the errors,
+    /// with its text as it is in the dump.
+    /// The quarantine file is required by this policy.
+    Quarantine,
+}
+
+impl Default for WikitextErrorPolicy {
+    fn default() -> Self {
+        Self::Keep
+    }
+}
+
+impl FromStr for WikitextErrorPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "fail" => Ok(Self::Fail),
+            "skip" => Ok(Self::Skip),
+            "quarantine" => Ok(Self::Quarantine),
+            other => Err(Error::Other(format!(
+                "Unknown wikitext error policy {other:?}, expected 'keep', 'fail', 'skip' or 'quarantine'"
+            ))),
+        }
+    }
+}
+
+/// What is done with the earlier revisions of a page, if the dump contains more than one revision per page,
+/// like the `pages-meta-history` dumps.
+/// The latest revision is always part of the page, and only its words are extracted.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RevisionHistory {
+    /// Skip the earlier revisions without parsing their texts.
+    Latest,
+    /// Parse the texts of the earlier revisions and collect them into the [`Page::history`] of their page.
+    /// This holds the whole history of a page in memory.
+    Collect,
+    /// Parse the texts of the earlier revisions and hand each to [`PageSink::revision`] as soon as it is parsed,
+    /// such that only one revision of a page is held in memory at a time.
+    Stream,
+}
+
+impl Default for RevisionHistory {
+    fn default() -> Self {
+        Self::Latest
+    }
+}
+
+impl FromStr for RevisionHistory {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "collect" => Ok(Self::Collect),
+            "stream" => Ok(Self::Stream),
+            other => Err(Error::Other(format!(
+                "Unknown revision history {other:?}, expected 'latest', 'collect' or 'stream'"
+            ))),
+        }
+    }
+}
+
+/// The state shared by all pages of a dump.
+struct PageParseContext<'a> {
+    word_extraction_options: WordExtractionOptions,
+    dump_version: DumpVersion,
+    compatibility_shims: &'a CompatibilityShims,
+    template_expander: Option<Arc<TemplateExpander>>,
+    dump_date: Option<NaiveDate>,
+    verify_sha1: bool,
+    title_filter: Option<&'a TitleFilter>,
+    page_workers: usize,
+    relaxed_page_order: bool,
+    revision_history: RevisionHistory,
+    wikitext_errors: WikitextErrorPolicy,
+    warnings: &'a WarningReporter,
+    progress: Option<&'a ProgressReporter>,
+    cancellation: Option<&'a CancellationToken>,
+    /// The position in the input file, for inputs that are not read from a [`File`] by the parsing task.
+    input_position: Option<Arc<AtomicU64>>,
+}
+
+impl<'a> PageParseContext<'a> {
+    /// The context for parsing a dump with the given options.
+    /// The word extraction options and the dump date are given separately,
+    /// since the entry points infer them from the name of their input if the options do not give them.
+    fn new(
+        options: &'a DumpParseOptions,
+        word_extraction_options: WordExtractionOptions,
+        dump_date: Option<NaiveDate>,
+    ) -> Self {
+        Self {
+            word_extraction_options,
+            dump_version: Default::default(),
+            compatibility_shims: &options.compatibility_shims,
+            template_expander: options.template_expander.clone(),
+            dump_date,
+            verify_sha1: options.verify_sha1,
+            title_filter: options.title_filter.as_deref(),
+            page_workers: options.page_workers,
+            relaxed_page_order: options.relaxed_page_order,
+            revision_history: options.revision_history,
+            wikitext_errors: options.wikitext_errors,
+            warnings: &options.warnings,
+            progress: options.progress.as_ref(),
+            cancellation: options.cancellation.as_ref(),
+            input_position: None,
+        }
+    }
+}
+
+/// Like [`parse_dump_file_with_page_consumer`], but configured by the given options.
+/// Returns statistics about the pages of the dump.
+pub async fn parse_dump_file_with_options<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_file: impl AsRef<Path>,
+    output_file: Option<impl AsRef<Path>>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let mut sink = open_output_sink(output_file, options)?;
+    parse_dump_file_with_sink(
+        input_file,
+        &mut sink,
+        word_consumer,
+        page_consumer,
+        error_log,
+        options,
+    )
+    .await
+}
+
+/// Like [`parse_dump_file_with_options`], but hands the siteinfo and the parsed pages to the given sink
+/// instead of writing them into an output file.
+/// The options `output_pretty`, `siteinfo_file` and `partition_by` only apply to output files, and are ignored.
+pub async fn parse_dump_file_with_sink<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_file: impl AsRef<Path>,
+    sink: &mut impl PageSink,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    mut page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let input_file = input_file.as_ref();
+    let mut error_log = std::io::BufWriter::new(std::fs::File::create(error_log)?);
+    let mut quarantine = open_quarantine(options)?;
+    let mut word_links = open_word_links(options)?;
+    let statistics = parse_dump_file_part(
+        input_file,
+        sink,
+        &mut word_consumer,
+        &mut page_consumer,
+        &mut error_log,
+        quarantine.as_mut(),
+        word_links.as_mut(),
+        options,
+    )
+    .await?;
+    write_statistics(&statistics, options)?;
+    Ok(statistics)
+}
+
+/// Parse a single dump file into the sink, writing into the given logs, without writing the statistics.
+/// This is also used for each part of a dump that consists of multiple files.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn parse_dump_file_part<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_file: &Path,
+    sink: &mut impl PageSink,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: &mut impl Write,
+    quarantine: Option<&mut impl Write>,
+    word_links: Option<&mut impl Write>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let mut word_extraction_options = options.word_extraction.clone();
+    if !word_extraction_options.edition.is_known() {
+        word_extraction_options.edition = WiktionaryEdition::from_file_name(input_file);
+        debug!(
+            "Inferred edition {:?} from input file name",
+            word_extraction_options.edition
+        );
+    }
+
+    let dump_date = options
+        .dump_date
+        .or_else(|| dump_date_from_file_name(input_file));
+
+    let compression = DumpCompression::detect(input_file).await?;
+    let input_file = File::open(input_file).await?;
+    let input_size = input_file.metadata().await?.len();
+    let input_stream = BufReader::with_capacity(1024 * 1024, input_file);
+    let context = PageParseContext::new(options, word_extraction_options, dump_date);
+
+    // For compressed files, progress is measured in the compressed file, since the decompressed size is unknown.
+    let statistics = match compression {
+        DumpCompression::None => {
+            parse_dump_file_with_streams(
+                input_stream,
+                |input_stream| Some(input_stream.get_mut()),
+                input_size,
+                context,
+                sink,
+                word_consumer,
+                page_consumer,
+                error_log,
+                quarantine,
+                word_links,
+            )
+            .await?
+        }
+        DumpCompression::Bzip2 if options.decompression_threads > 1 => {
+            let decoder = ParallelBzDecoder::new(
+                input_stream.into_inner().into_std().await,
+                options.decompression_threads,
+            );
+            let mut context = context;
+            context.input_position = Some(decoder.bytes_read());
+            parse_dump_file_with_streams(
+                BufReader::with_capacity(1024 * 1024, decoder),
+                |_| None,
+                input_size,
+                context,
+                sink,
+                word_consumer,
+                page_consumer,
+                error_log,
+                quarantine,
+                word_links,
+            )
+            .await?
+        }
+        DumpCompression::Bzip2 => {
+            parse_dump_file_with_streams(
+                BufReader::with_capacity(1024 * 1024, BzDecoder::new(input_stream)),
+                |input_stream| Some(input_stream.get_mut().get_mut().get_mut()),
+                input_size,
+                context,
+                sink,
+                word_consumer,
+                page_consumer,
+                error_log,
+                quarantine,
+                word_links,
+            )
+            .await?
+        }
+        DumpCompression::Gzip => {
+            parse_dump_file_with_streams(
+                BufReader::with_capacity(1024 * 1024, GzipDecoder::new(input_stream)),
+                |input_stream| Some(input_stream.get_mut().get_mut().get_mut()),
+                input_size,
+                context,
+                sink,
+                word_consumer,
+                page_consumer,
+                error_log,
+                quarantine,
+                word_links,
+            )
+            .await?
+        }
+        DumpCompression::Zstd => {
+            parse_dump_file_with_streams(
+                BufReader::with_capacity(1024 * 1024, ZstdDecoder::new(input_stream)),
+                |input_stream| Some(input_stream.get_mut().get_mut().get_mut()),
+                input_size,
+                context,
+                sink,
+                word_consumer,
+                page_consumer,
+                error_log,
+                quarantine,
+                word_links,
+            )
+            .await?
+        }
+    };
+    Ok(statistics)
+}
+
+pub(crate) fn write_statistics(
+    statistics: &ParseStatistics,
+    options: &DumpParseOptions,
+) -> Result<()> {
+    if let Some(statistics_file) = &options.statistics_file {
+        debug!("Writing statistics to {statistics_file:?}");
+        statistics.write_json(statistics_file)?;
+    }
+    Ok(())
+}
+
+/// Like [`parse_dump_file_with_options`], but streams the dump directly from the given url.
+/// The dump is decompressed and parsed while it is downloaded, so it is never written to disk.
+/// Dumps are decompressed by the extension of the path of the url, like `.xml.bz2`, and the edition is inferred from it if unknown.
+/// The request is retried with the default [`RetryPolicy`](crate::download::RetryPolicy) if it fails transiently,
+/// but a stream that breaks off after the dump started to be parsed is not resumed.
+#[cfg(feature = "download")]
+pub async fn parse_dump_url<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    url: &url::Url,
+    output_file: Option<impl AsRef<Path>>,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    mut page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    use crate::download::RetryPolicy;
+    use futures_util::stream::TryStreamExt;
+
+    let mut sink = open_output_sink(output_file, options)?;
+    let mut word_extraction_options = options.word_extraction.clone();
+    if !word_extraction_options.edition.is_known() {
+        word_extraction_options.edition = WiktionaryEdition::from_file_name(url.path());
+        debug!(
+            "Inferred edition {:?} from url",
+            word_extraction_options.edition
+        );
+    }
+
+    info!("Streaming dump from '{url}'");
+    let response = RetryPolicy::default()
+        .retry(&format!("request of '{url}'"), || async move {
+            Ok(reqwest::get(url.clone()).await?.error_for_status()?)
+        })
+        .await?;
+    let input_size = response.content_length().unwrap_or(0);
+    // The response is not a file, so its position is counted from the received bytes.
+    let input_position = Arc::new(AtomicU64::new(0));
+    let body = tokio_util::io::StreamReader::new(
+        response
+            .bytes_stream()
+            .inspect_ok({
+                let input_position = Arc::clone(&input_position);
+                move |bytes| {
+                    input_position.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                }
+            })
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error)),
+    );
+    let compression = if let Some(compression) = DumpCompression::from_file_name(url.path())? {
+        compression
+    } else {
+        return Err(Error::Other(format!(
+            "Unknown file extension in url '{url}'"
+        )));
+    };
+    let input_stream = compression.decoder(BufReader::with_capacity(1024 * 1024, body));
+
+    let error_log = std::io::BufWriter::new(std::fs::File::create(error_log)?);
+    let quarantine = open_quarantine(options)?;
+
+    let statistics = parse_dump_file_with_streams(
+        input_stream,
+        |_| None,
+        input_size,
+        PageParseContext {
+            input_position: Some(input_position),
+            ..PageParseContext::new(
+                options,
+                word_extraction_options,
+                options
+                    .dump_date
+                    .or_else(|| dump_date_from_file_name(url.path())),
+            )
+        },
+        &mut sink,
+        &mut word_consumer,
+        &mut page_consumer,
+        error_log,
+        quarantine,
+        open_word_links(options)?,
+    )
+    .await?;
+    write_statistics(&statistics, options)?;
+    Ok(statistics)
+}
+
+/// Like [`parse_dump_url`], but streams the dump from an object store, and writes the output and the error log into it.
+/// The output is written with the format of the options, and its object is only created if parsing succeeds.
+/// The error log is written either way.
+/// The siteinfo file and the partitioning of the options are ignored, while the other files of the options are written locally.
+#[cfg(feature = "cloud")]
+pub async fn parse_dump_object<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    store: &dyn ObjectStore,
+    input: &ObjectPath,
+    output: Option<&ObjectPath>,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    mut page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: &ObjectPath,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    use futures_util::stream::TryStreamExt;
+
+    let mut word_extraction_options = options.word_extraction.clone();
+    if !word_extraction_options.edition.is_known() {
+        word_extraction_options.edition = WiktionaryEdition::from_file_name(input.as_ref());
+        debug!(
+            "Inferred edition {:?} from object path",
+            word_extraction_options.edition
+        );
+    }
+
+    info!("Streaming dump from object '{input}'");
+    let object = store.get(input).await?;
+    let input_size = object.meta.size as u64;
+    // The object is not a file, so its position is counted from the received bytes.
+    let input_position = Arc::new(AtomicU64::new(0));
+    let body = tokio_util::io::StreamReader::new(
+        object
+            .into_stream()
+            .inspect_ok({
+                let input_position = Arc::clone(&input_position);
+                move |bytes| {
+                    input_position.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                }
+            })
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error)),
+    );
+    let compression = if let Some(compression) = DumpCompression::from_file_name(input.as_ref())? {
+        compression
+    } else {
+        return Err(Error::Other(format!(
+            "Unknown file extension in object path '{input}'"
+        )));
+    };
+    let input_stream = compression.decoder(BufReader::with_capacity(1024 * 1024, body));
+
+    let mut output_upload = if let Some(output) = output {
+        Some(ObjectUpload::start(store, output).await?)
+    } else {
+        None
+    };
+    let mut error_log_upload = ObjectUpload::start(store, error_log).await?;
+
+    // The sink and the error log are synchronous, so their contents are handed to the uploads through channels.
+    let (output_writer, mut output_receiver) = ChannelWriter::channel();
+    let (error_log_writer, mut error_log_receiver) = ChannelWriter::channel();
+    let input_stream = Backpressured::new(input_stream, &[&output_writer, &error_log_writer]);
+    let mut sink = output.map(|_| {
+        JsonPageSink::with_format(
+            std::io::BufWriter::with_capacity(1024 * 1024, output_writer.clone()),
+            options.output_format,
+            options.output_pretty,
+        )
+    });
+
+    let parse = async {
+        let quarantine = open_quarantine(options)?;
+        let word_links = open_word_links(options)?;
+        let result = parse_dump_file_with_streams(
+            input_stream,
+            |_| None,
+            input_size,
+            PageParseContext {
+                input_position: Some(input_position),
+                ..PageParseContext::new(
+                    options,
+                    word_extraction_options,
+                    options
+                        .dump_date
+                        .or_else(|| dump_date_from_file_name(input.as_ref())),
+                )
+            },
+            &mut sink,
+            &mut word_consumer,
+            &mut page_consumer,
+            std::io::BufWriter::with_capacity(1024 * 1024, error_log_writer.clone()),
+            quarantine,
+            word_links,
+        )
+        .await;
+        // Close the channels, such that the uploads end.
+        drop(sink);
+        let output_sent = output_writer.send_queued().await;
+        let error_log_sent = error_log_writer.send_queued().await;
+        drop((output_writer, error_log_writer));
+        let statistics = result?;
+        output_sent?;
+        error_log_sent?;
+        Ok(statistics)
+    };
+    let write_output = async {
+        while let Some(bytes) = output_receiver.recv().await {
+            if let Some(output_upload) = output_upload.as_mut() {
+                output_upload.write_all(&bytes).await?;
+            }
+        }
+        Ok::<_, Error>(())
+    };
+    let write_error_log = async {
+        while let Some(bytes) = error_log_receiver.recv().await {
+            error_log_upload.write_all(&bytes).await?;
+        }
+        Ok::<_, Error>(())
+    };
+    let (parse_result, output_result, error_log_result) =
+        tokio::join!(parse, write_output, write_error_log);
+
+    if error_log_result.is_ok() {
+        error_log_upload.finish().await?;
+    } else {
+        error_log_upload.abort(store).await;
+    }
+    let result = parse_result.and_then(|statistics| {
+        output_result?;
+        error_log_result?;
+        Ok(statistics)
+    });
+    match result {
+        Ok(statistics) => {
+            if let Some(output_upload) = output_upload {
+                output_upload.finish().await?;
+            }
+            write_statistics(&statistics, options)?;
+            Ok(statistics)
+        }
+        Err(error) => {
+            if let Some(output_upload) = output_upload {
+                output_upload.abort(store).await;
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Open a dump file for reading, decompressing it according to its [detected](DumpCompression::detect) compression.
+pub(crate) async fn open_dump_file(
+    input_file: &Path,
+) -> Result<Box<dyn AsyncBufRead + Unpin + Send>> {
+    let compression = DumpCompression::detect(input_file).await?;
+    let file = File::open(input_file).await?;
+    Ok(compression.decoder(BufReader::with_capacity(1024 * 1024, file)))
+}
+
+type FileJsonSink = JsonPageSink<std::io::BufWriter<std::fs::File>>;
+
+/// The output files selected by the options.
+/// Pages are written either into the output file or into the partitions, so they never need to be cloned.
+pub(crate) struct OutputFiles {
+    siteinfo: Option<FileJsonSink>,
+    output: Option<FileJsonSink>,
+    partitioned: Option<PartitionedOutput>,
+}
+
+impl PageSink for OutputFiles {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        if let Some(partitioned) = self.partitioned.as_mut() {
+            partitioned.site_info(siteinfo.clone())?;
+        }
+        if let Some(siteinfo_sink) = self.siteinfo.as_mut() {
+            siteinfo_sink.site_info(siteinfo)
+        } else {
+            self.output.site_info(siteinfo)
+        }
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        if let Some(partitioned) = self.partitioned.as_mut() {
+            partitioned.page(page)
+        } else {
+            self.output.page(page)
+        }
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        if let Some(partitioned) = self.partitioned.as_mut() {
+            partitioned.revision(revision)
+        } else {
+            self.output.revision(revision)
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.siteinfo.finish()?;
+        self.output.finish()?;
+        self.partitioned.finish()
+    }
+}
+
+pub(crate) fn open_output_sink(
+    output_file: Option<impl AsRef<Path>>,
+    options: &DumpParseOptions,
+) -> Result<OutputFiles> {
+    let siteinfo = if let Some(siteinfo_file) = &options.siteinfo_file {
+        Some(
+            JsonPageSink::with_format(
+                std::io::BufWriter::new(std::fs::File::create(siteinfo_file)?),
+                options.output_format,
+                options.output_pretty,
+            )
+            .siteinfo_only(),
+        )
+    } else {
+        None
+    };
+
+    let (output, partitioned) = match (output_file, options.partition_by) {
+        (Some(output_directory), Some(partition_by)) => (
+            None,
+            Some(PartitionedOutput::new(output_directory, partition_by)?),
+        ),
+        (Some(output_file), None) => (
+            Some(JsonPageSink::with_format(
+                std::io::BufWriter::with_capacity(1024 * 1024, std::fs::File::create(output_file)?),
+                options.output_format,
+                options.output_pretty,
+            )),
+            None,
+        ),
+        (None, _) => (None, None),
+    };
+
+    Ok(OutputFiles {
+        siteinfo,
+        output,
+        partitioned,
+    })
+}
+
+pub(crate) fn open_word_links(
+    options: &DumpParseOptions,
+) -> Result<Option<std::io::BufWriter<std::fs::File>>> {
+    Ok(if let Some(word_link_file) = &options.word_link_file {
+        Some(std::io::BufWriter::new(std::fs::File::create(
+            word_link_file,
+        )?))
+    } else {
+        None
+    })
+}
+
+/// Infer the date of a dump from a file name like `enwiktionary-20230801-pages-articles.xml.bz2`.
+fn dump_date_from_file_name(file: impl AsRef<Path>) -> Option<NaiveDate> {
+    file.as_ref()
+        .file_name()?
+        .to_str()?
+        .split('-')
+        .nth(1)
+        .filter(|date| date.len() == 8 && date.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|date| parse_dump_date(date).ok())
+}
+
+pub(crate) fn open_quarantine(
+    options: &DumpParseOptions,
+) -> Result<Option<std::io::BufWriter<std::fs::File>>> {
+    Ok(if let Some(quarantine_file) = &options.quarantine_file {
+        Some(std::io::BufWriter::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(quarantine_file)?,
+        ))
+    } else if options.wikitext_errors == WikitextErrorPolicy::Quarantine {
+        return Err(Error::Other(format!(
+            "The wikitext error policy 'quarantine' requires a quarantine file"
+        )));
+    } else {
+        None
+    })
+}
+
+/// Parse an uncompressed dump from a stream, e.g. from memory or from the network.
+/// Words are handed to `word_consumer` like in [`parse_dump_file`], and errors in pages are written to `error_log`.
+/// Since the stream has no known size, no progress is logged.
+pub async fn parse_dump_stream<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_stream: impl AsyncBufRead + Unpin,
+    output_stream: Option<impl AsyncWrite + Unpin>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_log: impl Write,
+    output_pretty: bool,
+) -> Result<()> {
+    // The sink is synchronous, so the json is handed to the asynchronous output stream through a channel.
+    let (output, mut receiver) = ChannelWriter::channel();
+    let input_stream = Backpressured::new(input_stream, &[&output]);
+    let mut sink = output_stream.as_ref().map(|_| {
+        JsonPageSink::new(
+            std::io::BufWriter::with_capacity(1024 * 1024, output.clone()),
+            output_pretty,
+        )
+    });
+
+    let parse = async move {
+        parse_dump_stream_with_sink(input_stream, &mut sink, word_consumer, error_log).await?;
+        // Close the channel, such that the output stream ends.
+        drop(sink);
+        output.send_queued().await?;
+        drop(output);
+        Ok::<_, Error>(())
+    };
+    let write = async move {
+        if let Some(mut output_stream) = output_stream {
+            while let Some(bytes) = receiver.recv().await {
+                output_stream.write_all(&bytes).await?;
+            }
+            output_stream.flush().await?;
+        }
+        Ok::<_, Error>(())
+    };
+    tokio::try_join!(parse, write)?;
+    Ok(())
+}
+
+/// Like [`parse_dump_stream`], but hands the siteinfo and the parsed pages to the given sink.
+pub async fn parse_dump_stream_with_sink<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_stream: impl AsyncBufRead + Unpin,
+    sink: &mut impl PageSink,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_log: impl Write,
+) -> Result<()> {
+    parse_dump_file_with_streams(
+        input_stream,
+        |_| None,
+        0,
+        PageParseContext::new(&DumpParseOptions::default(), Default::default(), None),
+        sink,
+        &mut word_consumer,
+        &mut |_, _, _| {},
+        error_log,
+        None::<std::io::Sink>,
+        None::<std::io::Sink>,
+    )
+    .await?;
+    Ok(())
+}
+
+/// The number of written chunks that may wait in the channel of a [`ChannelWriter`].
+const OUTPUT_CHANNEL_CAPACITY: usize = 16;
+
+/// Sends everything written to it through a bounded channel.
+///
+/// The parser writes synchronously on the same task that receives from the channel, so writes cannot wait for space in it.
+/// Chunks that do not fit are queued instead, and the input of the parser is wrapped into [`Backpressured`],
+/// which stops reading until the queued chunks are sent.
+#[derive(Clone)]
+struct ChannelWriter {
+    sender: tokio::sync::mpsc::Sender<Vec<u8>>,
+    queued: Arc<Mutex<VecDeque<Vec<u8>>>>,
+}
+
+impl ChannelWriter {
+    fn channel() -> (Self, tokio::sync::mpsc::Receiver<Vec<u8>>) {
+        let (sender, receiver) = tokio::sync::mpsc::channel(OUTPUT_CHANNEL_CAPACITY);
+        (
+            Self {
+                sender,
+                queued: Default::default(),
+            },
+            receiver,
+        )
+    }
+
+    fn queued(&self) -> MutexGuard<'_, VecDeque<Vec<u8>>> {
+        // Unwrap cannot panic because the lock is never held across code that panics.
+        self.queued.lock().unwrap()
+    }
+
+    /// Send the queued chunks, waiting for space in the channel.
+    /// This must be called after the last write, since there is no more input to wait for the queued chunks.
+    async fn send_queued(&self) -> std::io::Result<()> {
+        loop {
+            let chunk = self.queued().pop_front();
+            if let Some(chunk) = chunk {
+                self.sender
+                    .send(chunk)
+                    .await
+                    .map_err(|_| output_stream_closed())?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut queued = self.queued();
+        if self.sender.is_closed() {
+            return Err(output_stream_closed());
+        }
+        if queued.is_empty() {
+            match self.sender.try_send(buf.to_vec()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(chunk)) => queued.push_back(chunk),
+                Err(TrySendError::Closed(_)) => return Err(output_stream_closed()),
+            }
+        } else {
+            queued.push_back(buf.to_vec());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn output_stream_closed() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Output stream was closed")
+}
+
+/// An input stream that is only read while no chunks of the given [`ChannelWriter`]s are queued.
+struct Backpressured<Input> {
+    input: Input,
+    outputs: Vec<(PollSender<Vec<u8>>, ChannelWriter)>,
+}
+
+impl<Input> Backpressured<Input> {
+    fn new(input: Input, outputs: &[&ChannelWriter]) -> Self {
+        Self {
+            input,
+            outputs: outputs
+                .iter()
+                .map(|output| (PollSender::new(output.sender.clone()), (*output).clone()))
+                .collect(),
+        }
+    }
+
+    fn poll_send_queued(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        for (sender, output) in &mut self.outputs {
+            while !output.queued().is_empty() {
+                match sender.poll_reserve(cx) {
+                    Poll::Ready(Ok(())) => {}
+                    Poll::Ready(Err(_)) => return Poll::Ready(Err(output_stream_closed())),
+                    Poll::Pending => return Poll::Pending,
+                }
+                let chunk = output.queued().pop_front();
+                if let Some(chunk) = chunk {
+                    sender
+                        .send_item(chunk)
+                        .map_err(|_| output_stream_closed())?;
+                } else {
+                    sender.abort_send();
+                }
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<Input: AsyncRead + Unpin> AsyncRead for Backpressured<Input> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_send_queued(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.input).poll_read(cx, buf),
+            other => other,
+        }
+    }
+}
+
+impl<Input: AsyncBufRead + Unpin> AsyncBufRead for Backpressured<Input> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+        let this = self.get_mut();
+        match this.poll_send_queued(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.input).poll_fill_buf(cx),
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn consume(self: Pin<&mut Self>, amount: usize) {
+        Pin::new(&mut self.get_mut().input).consume(amount)
+    }
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+async fn parse_dump_file_with_streams<
+    InputStream: AsyncBufRead + Unpin,
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_stream: InputStream,
+    input_stream_to_file: impl Fn(&mut InputStream) -> Option<&mut File>,
+    input_size: u64,
+    mut context: PageParseContext<'_>,
+    sink: &mut impl PageSink,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    mut error_log: impl Write,
+    mut quarantine: Option<impl Write>,
+    mut word_links: Option<impl Write>,
+) -> Result<ParseStatistics> {
+    let mut reader = dump_reader(input_stream).await?;
+    let mut statistics = ParseStatistics::default();
+    let mut buffer = Vec::new();
+    let start = Instant::now();
+    let mut last_progress_log = start;
+    let mut last_progress_report = start;
+    let mut tag_stack = Vec::new();
+    // Created at the first page, such that it includes the edition and version from the siteinfo.
+    let mut text_processor: Option<Arc<TextProcessor>> = None;
+    let mut pending_pages = VecDeque::new();
+    let mut cancelled = false;
+
+    // Errors are annotated with the position of the reader, and within pages with the page.
+    let result: Result<()> = async {
+        loop {
+            if context
+                .cancellation
+                .map_or(false, CancellationToken::is_cancelled)
+            {
+                cancelled = true;
+                break;
+            }
+
+            let current_time = Instant::now();
+            if current_time - last_progress_log >= Duration::from_secs(10) {
+                last_progress_log = current_time;
+
+                let current = if let Some(input_file) = input_stream_to_file(reader.get_mut()) {
+                    Some(input_file.stream_position().await?)
+                } else {
+                    context
+                        .input_position
+                        .as_ref()
+                        .map(|input_position| input_position.load(Ordering::Relaxed))
+                };
+                if let Some(current) = current {
+                    let current_mib = current / (1024 * 1024);
+                    let input_size_mib = input_size / (1024 * 1024);
+
+                    info!("Parsing input file at {current_mib}/{input_size_mib}MiB");
+                }
+            }
+            if let Some(progress) = context.progress {
+                if current_time - last_progress_report >= progress.interval() {
+                    last_progress_report = current_time;
+                    let bytes_done =
+                        if let Some(input_file) = input_stream_to_file(reader.get_mut()) {
+                            Some(input_file.stream_position().await?)
+                        } else {
+                            context
+                                .input_position
+                                .as_ref()
+                                .map(|input_position| input_position.load(Ordering::Relaxed))
+                        };
+                    report_parse_progress(
+                        progress,
+                        bytes_done,
+                        input_size,
+                        &statistics,
+                        start,
+                        false,
+                    );
+                }
+            }
+
+            let level = tag_stack.len();
+            match read_relevant_event(&mut reader, &mut buffer).await {
+                Ok(event) => match event {
+                    RelevantEvent::Start(tag) => {
+                        let tag_name = String::from_utf8(tag.name().into_inner().to_vec())?;
+                        if level == 0 && tag_name == "mediawiki" {
+                            for attribute in tag.attributes() {
+                                let attribute = attribute?;
+                                if attribute.key.into_inner() == b"version" {
+                                    context.dump_version.schema_version =
+                                        Some(String::from_utf8(attribute.value.to_vec())?);
+                                }
+                            }
+                            debug!("Found dump version {:?}", context.dump_version);
+                            tag_stack.push(tag_name);
+                        } else if level <= 1 {
+                            // Fragments of exports may contain pages without the `mediawiki` wrapper.
+                            match tag_name.as_str() {
+                                "siteinfo" => {
+                                    let siteinfo = parse_siteinfo(
+                                        tag.attributes(),
+                                        &mut reader,
+                                        &mut buffer,
+                                        context.warnings,
+                                    )
+                                    .await?;
+                                    info!(
+                                        "{} ({} {})",
+                                        siteinfo.sitename, siteinfo.dbname, siteinfo.generator
+                                    );
+                                    let siteinfo_edition =
+                                        WiktionaryEdition::from_dbname(&siteinfo.dbname);
+                                    context.dump_version.generator_version =
+                                        siteinfo.generator_version().map(ToString::to_string);
+                                    let edition = &mut context.word_extraction_options.edition;
+                                    if siteinfo_edition.is_known() && siteinfo_edition != *edition {
+                                        if edition.is_known() {
+                                            context.warnings.report(Warning::EditionMismatch {
+                                                given: format!("{edition:?}"),
+                                                siteinfo: format!("{siteinfo_edition:?}"),
+                                            })?;
+                                        }
+                                        *edition = siteinfo_edition;
+                                    }
+                                    text_processor = None;
+                                    statistics.set_namespace_names(&siteinfo.namespaces);
+                                    sink.site_info(siteinfo)?;
+                                }
+                                "page" => {
+                                    let text_processor =
+                                        Arc::clone(text_processor.get_or_insert_with(|| {
+                                            Arc::new(TextProcessor::new(&context))
+                                        }));
+                                    let mut page_context = ErrorContext::default();
+                                    let result = parse_page(
+                                        tag.attributes(),
+                                        &context,
+                                        &text_processor,
+                                        &mut reader,
+                                        &mut buffer,
+                                        &mut error_log,
+                                        &mut |revision| sink.revision(revision),
+                                        &mut page_context,
+                                    )
+                                    .await
+                                    .map_err(|error| {
+                                        page_context.byte_offset =
+                                            Some(reader.buffer_position() as u64);
+                                        error.with_context(page_context)
+                                    });
+                                    let (page, raw_text) = match result {
+                                        Ok(Some(page)) => page,
+                                        Ok(None) => continue,
+                                        Err(error) => match quarantine.as_mut() {
+                                            Some(quarantine) if is_recoverable(&error) => {
+                                                skip_to_next_page(
+                                                    error,
+                                                    &mut reader,
+                                                    &mut buffer,
+                                                    quarantine,
+                                                    &mut error_log,
+                                                    context.warnings,
+                                                )
+                                                .await?;
+                                                continue;
+                                            }
+                                            _ => return Err(error),
+                                        },
+                                    };
+                                    statistics.add_page(
+                                        &page,
+                                        raw_text.as_ref().map_or(0, |raw_text| raw_text.text.len()),
+                                    );
+
+                                    if context.page_workers == 0 {
+                                        let processed_text = raw_text.map(|raw_text| {
+                                            text_processor.process(
+                                                page.namespace,
+                                                &page.title,
+                                                raw_text,
+                                            )
+                                        });
+                                        consume_page(
+                                            page,
+                                            processed_text,
+                                            &context,
+                                            sink,
+                                            &mut statistics,
+                                            word_consumer,
+                                            page_consumer,
+                                            &mut error_log,
+                                            quarantine.as_mut(),
+                                            word_links.as_mut(),
+                                        )
+                                        .await?;
+                                    } else {
+                                        while pending_pages.len() >= context.page_workers {
+                                            let (page, processed_text) = next_processed_page(
+                                                &mut pending_pages,
+                                                context.relaxed_page_order,
+                                            )
+                                            .await?;
+                                            consume_page(
+                                                page,
+                                                processed_text,
+                                                &context,
+                                                sink,
+                                                &mut statistics,
+                                                word_consumer,
+                                                page_consumer,
+                                                &mut error_log,
+                                                quarantine.as_mut(),
+                                                word_links.as_mut(),
+                                            )
+                                            .await?;
+                                        }
+                                        pending_pages.push_back(tokio::task::spawn_blocking(
+                                            move || {
+                                                let processed_text = raw_text.map(|raw_text| {
+                                                    text_processor.process(
+                                                        page.namespace,
+                                                        &page.title,
+                                                        raw_text,
+                                                    )
+                                                });
+                                                (page, processed_text)
+                                            },
+                                        ));
+                                    }
+                                }
+                                _ if level == 0 => {
+                                    return Err(Error::Other(format!(
+                                        "Found unexpected toplevel tag {tag:?}"
+                                    )))
+                                }
+                                _ => {
+                                    return Err(Error::Other(format!(
+                                        "Found unexpected level 1 tag {tag:?}"
+                                    )))
+                                }
+                            }
+                        }
+                    }
+                    RelevantEvent::End(tag) => {
+                        let tag_name = String::from_utf8(tag.name().into_inner().to_vec())?;
+                        let stacked_tag = tag_stack.pop().ok_or_else(|| {
+                            Error::Other(format!("Unexpected closing tag {tag:?}"))
+                        })?;
+                        if tag_name != stacked_tag {
+                            return Err(Error::Other(format!("Unexpected closing tag {tag:?}")));
+                        }
+                    }
+                    RelevantEvent::Empty(tag) => {
+                        return Err(Error::Other(format!("Unexpected empty tag {tag:?}")));
+                    }
+                    RelevantEvent::Text(text) => {
+                        return Err(Error::Other(format!("Unexpected text {text:?}")));
+                    }
+                    RelevantEvent::Eof => {
+                        if level > 0 {
+                            return Err(Error::Other(format!("Unexpected eof")));
+                        } else {
+                            break;
+                        }
+                    }
+                },
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+    .await;
+    result.map_err(|error| {
+        error.with_context(ErrorContext::at_byte_offset(reader.buffer_position() as u64))
+    })?;
+
+    while !pending_pages.is_empty() {
+        let (page, processed_text) =
+            next_processed_page(&mut pending_pages, context.relaxed_page_order).await?;
+        consume_page(
+            page,
+            processed_text,
+            &context,
+            sink,
+            &mut statistics,
+            word_consumer,
+            page_consumer,
+            &mut error_log,
+            quarantine.as_mut(),
+            word_links.as_mut(),
+        )
+        .await?;
+    }
+
+    if let Some(quarantine) = quarantine.as_mut() {
+        quarantine.flush()?;
+    }
+    sink.finish()?;
+    if cancelled {
+        warn!("Parsing cancelled after {} pages", statistics.pages);
+        return Err(Error::Cancelled);
+    }
+    if let Some(progress) = context.progress {
+        report_parse_progress(
+            progress,
+            Some(input_size),
+            input_size,
+            &statistics,
+            start,
+            true,
+        );
+    }
+    info!("Successfully parsed dump file: {statistics}");
+    Ok(statistics)
+}
+
+/// Hand the progress of parsing to the reporter.
+/// The bytes done are only known if the input is a file, and the total only if the input size is not zero.
+fn report_parse_progress(
+    progress: &ProgressReporter,
+    bytes_done: Option<u64>,
+    input_size: u64,
+    statistics: &ParseStatistics,
+    start: Instant,
+    finished: bool,
+) {
+    let bytes_total = Some(input_size).filter(|input_size| *input_size > 0);
+    progress.report(&ProgressUpdate {
+        phase: ProgressPhase::Parse,
+        bytes_done: bytes_done.unwrap_or(0),
+        bytes_total: bytes_done.and(bytes_total),
+        pages: statistics.pages,
+        pages_total: None,
+        words: statistics.words,
+        elapsed: start.elapsed(),
+        finished,
+    });
+}
+
+/// A page whose text is processed by a page worker.
+type PendingPage = tokio::task::JoinHandle<(Page, Option<ProcessedText>)>;
+
+/// Wait for the next pending page.
+/// This is the oldest page, or if the order is relaxed, the oldest page that is finished already.
+async fn next_processed_page(
+    pending_pages: &mut VecDeque<PendingPage>,
+    relaxed_page_order: bool,
+) -> Result<(Page, Option<ProcessedText>)> {
+    let index = if relaxed_page_order {
+        pending_pages
+            .iter()
+            .position(|pending_page| pending_page.is_finished())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let pending_page = pending_pages
+        .remove(index)
+        .ok_or_else(|| Error::Other(format!("No pending page")))?;
+    pending_page
+        .await
+        .map_err(|error| Error::Other(format!("Page worker failed: {error}")))
+}
+
+/// Hand a page whose text was processed to the consumers and the sink, and count its words.
+#[allow(clippy::too_many_arguments)]
+async fn consume_page<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    page: Page,
+    processed_text: Option<ProcessedText>,
+    context: &PageParseContext<'_>,
+    sink: &mut impl PageSink,
+    statistics: &mut ParseStatistics,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: &mut impl Write,
+    quarantine: Option<&mut impl Write>,
+    word_links: Option<&mut impl Write>,
+) -> Result<()> {
+    let page_context = ErrorContext::in_page(&page.title, page.id);
+    consume_page_without_context(
+        page,
+        processed_text,
+        context,
+        sink,
+        statistics,
+        word_consumer,
+        page_consumer,
+        error_log,
+        quarantine,
+        word_links,
+    )
+    .await
+    .map_err(|error| error.with_context(page_context))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn consume_page_without_context<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    mut page: Page,
+    processed_text: Option<ProcessedText>,
+    context: &PageParseContext<'_>,
+    sink: &mut impl PageSink,
+    statistics: &mut ParseStatistics,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: &mut impl Write,
+    quarantine: Option<&mut impl Write>,
+    mut word_links: Option<&mut impl Write>,
+) -> Result<()> {
+    if let Some(ProcessedText {
+        text,
+        words,
+        error_log_entry,
+        rejection,
+        warnings,
+    }) = processed_text
+    {
+        if let Some(error_log_entry) = error_log_entry {
+            error_log.write_all(error_log_entry.as_bytes())?;
+        }
+        for warning in warnings {
+            context.warnings.report(warning)?;
+        }
+        if let Some(rejection) = rejection {
+            return reject_page(&page, rejection, context, statistics, quarantine);
+        }
+
+        for word in words {
+            statistics.add_word(&word);
+            if let Some(word_links) = word_links.as_mut() {
+                let word_link = WordLink::new(&word, page.id, page.revision.id, context.dump_date);
+                serde_json::to_writer(&mut **word_links, &word_link)?;
+                writeln!(word_links)?;
+            }
+            word_consumer(word)
+                .await
+                .map_err(|error| Error::WordConsumer { source: error })?;
+        }
+
+        page_consumer(
+            &context.word_extraction_options.edition,
+            &page.title,
+            &text.text,
+        );
+        page.revision.text = Some(text);
+    }
+
+    trace!("{page:?}");
+    sink.page(page)
+}
+
+/// Drop a page whose wikitext has errors, or fail, according to the [`WikitextErrorPolicy`].
+/// The errors were written to the error log already.
+fn reject_page(
+    page: &Page,
+    rejection: WikitextRejection,
+    context: &PageParseContext<'_>,
+    statistics: &mut ParseStatistics,
+    quarantine: Option<&mut impl Write>,
+) -> Result<()> {
+    let WikitextRejection {
+        errors,
+        original_text,
+    } = rejection;
+    match context.wikitext_errors {
+        WikitextErrorPolicy::Keep => unreachable!("pages are not rejected with policy keep"),
+        WikitextErrorPolicy::Fail => Err(Error::Other(format!(
+            "Found {} wikitext errors, the first being {}",
+            errors.len(),
+            excerpt(&errors[0])
+        ))),
+        WikitextErrorPolicy::Skip => {
+            debug!("Skipping page {:?} with wikitext errors", page.title);
+            statistics.rejected_pages += 1;
+            Ok(())
+        }
+        WikitextErrorPolicy::Quarantine => {
+            debug!("Quarantining page {:?} with wikitext errors", page.title);
+            statistics.rejected_pages += 1;
+            let quarantine = quarantine.ok_or_else(|| {
+                Error::Other(format!("No quarantine file for page with wikitext errors"))
+            })?;
+            for error in &errors {
+                writeln!(quarantine, "<!-- {} -->", error.replace("--", "- -"))?;
+            }
+            writeln!(
+                quarantine,
+                "<page><title>{}</title><ns>{}</ns><id>{}</id><revision><id>{}</id><text xml:space=\"preserve\">{}</text></revision></page>",
+                escape(&page.title),
+                page.namespace,
+                page.id,
+                page.revision.id,
+                escape(original_text.as_deref().unwrap_or_default()),
+            )?;
+            Ok(())
+        }
+    }
+}
+
+/// Errors of consumers and of writing cannot be recovered from by skipping the page.
+fn is_recoverable(error: &Error) -> bool {
+    !matches!(
+        error.without_context(),
+        Error::WordConsumer { .. } | Error::IoError(_)
+    )
+}
+
+/// Skip the rest of the current page after an error.
+/// The error and the skipped xml are written to the quarantine.
+async fn skip_to_next_page(
+    error: Error,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    quarantine: &mut impl Write,
+    error_log: &mut impl Write,
+    warnings: &WarningReporter,
+) -> Result<()> {
+    warnings.report(Warning::SkippedPage {
+        error: error.to_string(),
+    })?;
+    writeln!(error_log, "Skipped rest of page after error: {error}")?;
+    writeln!(
+        quarantine,
+        "<!-- {} -->",
+        error.to_string().replace("--", "- -")
+    )?;
+
+    let mut consecutive_errors = 0;
+    loop {
+        match read_relevant_event(reader, buffer).await {
+            Ok(RelevantEvent::Start(tag)) => {
+                write!(quarantine, "<{}>", String::from_utf8_lossy(&tag))?;
+            }
+            Ok(RelevantEvent::Empty(tag)) => {
+                write!(quarantine, "<{}/>", String::from_utf8_lossy(&tag))?;
+            }
+            Ok(RelevantEvent::End(tag)) => {
+                let name = tag.name().into_inner();
+                write!(quarantine, "</{}>", String::from_utf8_lossy(name))?;
+                if name == b"page" {
+                    break;
+                }
+            }
+            Ok(RelevantEvent::Text(text)) => write!(quarantine, "{}", escape(&text))?,
+            Ok(RelevantEvent::Eof) => return Err(error),
+            Err(read_error) => {
+                // The reader advances past errors, but give up if the input seems to be garbage.
+                consecutive_errors += 1;
+                if consecutive_errors > 100 {
+                    return Err(read_error);
+                }
+                writeln!(
+                    quarantine,
+                    "<!-- {} -->",
+                    read_error.to_string().replace("--", "- -")
+                )?;
+                continue;
+            }
+        }
+        consecutive_errors = 0;
+    }
+
+    writeln!(quarantine)?;
+    Ok(())
+}
+
+async fn parse_siteinfo(
+    attributes: Attributes<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    warnings: &WarningReporter,
+) -> Result<Siteinfo> {
+    let extra = collect_attributes(attributes)?;
+
+    let mut sitename = None;
+    let mut dbname = None;
+    let mut base = None;
+    let mut generator = None;
+    let mut case = None;
+    let mut namespaces = None;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => match tag.name().into_inner() {
+                b"sitename" => {
+                    sitename = Some(
+                        parse_string("sitename", tag.attributes(), reader, buffer, warnings)
+                            .await?,
+                    );
+                }
+                b"dbname" => {
+                    dbname = Some(
+                        parse_string("dbname", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"base" => {
+                    base = Some(
+                        parse_string("base", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"generator" => {
+                    generator = Some(
+                        parse_string("generator", tag.attributes(), reader, buffer, warnings)
+                            .await?,
+                    );
+                }
+                b"case" => {
+                    case = Some(
+                        parse_string("case", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"namespaces" => {
+                    namespaces =
+                        Some(parse_namespaces(tag.attributes(), reader, buffer, warnings).await?);
+                }
+                _ => return Err(Error::Other(format!("Found unexpected tag {tag:?}"))),
+            },
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(b"siteinfo") {
+                    Ok(Siteinfo {
+                        sitename: if let Some(sitename) = sitename {
+                            sitename
+                        } else {
+                            return Err(Error::Other(format!("Missing sitename in siteinfo")));
+                        },
+                        dbname: if let Some(dbname) = dbname {
+                            dbname
+                        } else {
+                            return Err(Error::Other(format!("Missing dbname in siteinfo")));
+                        },
+                        base: if let Some(base) = base {
+                            base
+                        } else {
+                            return Err(Error::Other(format!("Missing base in siteinfo")));
+                        },
+                        generator: if let Some(generator) = generator {
+                            generator
+                        } else {
+                            return Err(Error::Other(format!("Missing generator in siteinfo")));
+                        },
+                        case: if let Some(case) = case {
+                            case
+                        } else {
+                            return Err(Error::Other(format!("Missing case in siteinfo")));
+                        },
+                        namespaces: if let Some(namespaces) = namespaces {
+                            namespaces
+                        } else {
+                            return Err(Error::Other(format!("Missing namespaces in siteinfo")));
+                        },
+                        extra,
+                    })
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => warnings.report(unknown_tag(&tag))?,
+            RelevantEvent::Text(text) => warnings.report(Warning::UnexpectedText { text })?,
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+async fn parse_namespaces(
+    attributes: Attributes<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    warnings: &WarningReporter,
+) -> Result<Vec<Namespace>> {
+    warn_about_attributes(b"namespaces", attributes, warnings)?;
+
+    struct NamespaceTag {
+        key: i64,
+        case: String,
+        extra: BTreeMap<String, String>,
+    }
+    let mut current_namespace_tag = None;
+    let mut namespaces = Vec::new();
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => {
+                if tag.name() == QName(b"namespace") {
+                    if current_namespace_tag.is_some() {
+                        return Err(Error::Other(format!("Found nested namespace tag {tag:?}")));
+                    }
+
+                    current_namespace_tag = Some(NamespaceTag {
+                        key: String::from_utf8_lossy(
+                            &tag.try_get_attribute(b"key")?
+                                .ok_or_else(|| {
+                                    Error::Other(format!("Missing attribute key in {tag:?}"))
+                                })?
+                                .value,
+                        )
+                        .parse()
+                        .map_err(|_| Error::Other(format!("Key is not an integer in {tag:?}")))?,
+                        case: String::from_utf8_lossy(
+                            &tag.try_get_attribute(b"case")?
+                                .ok_or_else(|| {
+                                    Error::Other(format!("Missing attribute case in {tag:?}"))
+                                })?
+                                .value,
+                        )
+                        .into_owned(),
+                        extra: collect_attributes(tag.attributes())?
+                            .into_iter()
+                            .filter(|(name, _)| name != "key" && name != "case")
+                            .collect(),
+                    });
+                } else {
+                    return Err(Error::Other(format!("Found unexpected tag {tag:?}")));
+                }
+            }
+            RelevantEvent::End(tag) => {
+                if tag.name() == QName(b"namespaces") {
+                    // Sort to make the output independent of the order in the dump file.
+                    namespaces.sort_by(|a: &Namespace, b: &Namespace| {
+                        a.key.cmp(&b.key).then_with(|| a.name.cmp(&b.name))
+                    });
+                    return Ok(namespaces);
+                } else if tag.name() == QName(b"namespace") {
+                    if current_namespace_tag.is_some() {
+                        return Err(Error::Other(format!(
+                            "Found namespace tag without text {tag:?}"
+                        )));
+                    }
+                } else {
+                    return Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )));
+                };
+            }
+            RelevantEvent::Empty(tag) => {
+                match tag.name().into_inner() {
+                    b"namespace" => { /* ignore nameless namespace */ }
+                    _ => warnings.report(unknown_tag(&tag))?,
+                }
+            }
+            RelevantEvent::Text(text) => {
+                if let Some(current_namespace_tag) = current_namespace_tag {
+                    namespaces.push(Namespace {
+                        key: current_namespace_tag.key,
+                        case: current_namespace_tag.case,
+                        name: text,
+                        extra: current_namespace_tag.extra,
+                    });
+                } else {
+                    return Err(Error::Other(format!(
+                        "Found text outside of namespace tag: {text:?}"
+                    )));
+                }
+
+                current_namespace_tag = None;
+            }
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+/// Parse the xml of a page, without parsing the wikitext of its latest revision.
+/// Earlier revisions are skipped, collected or handed to `stream_revision` as selected by the context.
+/// Returns `None` if the page does not match the title filter.
+/// The title and id of the page are stored into `error_context` as soon as they are parsed.
+#[allow(clippy::too_many_arguments)]
+async fn parse_page(
+    attributes: Attributes<'_>,
+    context: &PageParseContext<'_>,
+    text_processor: &TextProcessor,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    error_log: &mut impl Write,
+    stream_revision: &mut impl FnMut(PageRevision) -> Result<()>,
+    error_context: &mut ErrorContext,
+) -> Result<Option<(Page, Option<RawText>)>> {
+    let warnings = context.warnings;
+    let extra = collect_attributes(attributes)?;
+
+    let mut title = None;
+    let mut namespace = None;
+    let mut id = None;
+    let mut revision = None;
+    let mut raw_text = None;
+    let mut history = Vec::new();
+    let mut redirect = None;
+    let mut restrictions = None;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => match tag.name().into_inner() {
+                b"title" => {
+                    let parsed_title =
+                        parse_string("title", tag.attributes(), reader, buffer, warnings).await?;
+                    if let Some(title_filter) = context.title_filter {
+                        if !title_filter.matches(&parsed_title) {
+                            trace!("Skipping page {parsed_title:?}");
+                            let mut skip_buffer = Vec::new();
+                            reader
+                                .read_to_end_into_async(QName(b"page"), &mut skip_buffer)
+                                .await?;
+                            return Ok(None);
+                        }
+                    }
+                    error_context.page_title = Some(parsed_title.clone());
+                    title = Some(parsed_title);
+                }
+                b"ns" => {
+                    namespace = Some(
+                        parse_string("ns", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("ns is not an integer in {tag:?}"))
+                            })?,
+                    );
+                }
+                b"id" => {
+                    id = Some(
+                        parse_string("id", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("id is not an integer in {tag:?}"))
+                            })?,
+                    );
+                    error_context.page_id = id;
+                }
+                b"revision" => {
+                    let (parsed_revision, parsed_raw_text) = parse_revision(
+                        tag.attributes(),
+                        title.clone(),
+                        context,
+                        reader,
+                        buffer,
+                        error_log,
+                    )
+                    .await?;
+                    let earlier_raw_text = std::mem::replace(&mut raw_text, parsed_raw_text);
+                    let earlier_revision = revision.replace(parsed_revision);
+                    match (earlier_revision, context.revision_history) {
+                        (None, _) | (Some(_), RevisionHistory::Latest) => {}
+                        (Some(earlier_revision), RevisionHistory::Collect) => {
+                            history.push(parse_earlier_revision(
+                                earlier_revision,
+                                earlier_raw_text,
+                                title.as_deref(),
+                                text_processor,
+                                error_log,
+                            )?);
+                        }
+                        (Some(earlier_revision), RevisionHistory::Stream) => {
+                            let earlier_revision = parse_earlier_revision(
+                                earlier_revision,
+                                earlier_raw_text,
+                                title.as_deref(),
+                                text_processor,
+                                error_log,
+                            )?;
+                            stream_revision(PageRevision {
+                                page_id: id
+                                    .ok_or_else(|| Error::Other(format!("Missing id in page")))?,
+                                title: title.clone().unwrap_or_default(),
+                                namespace: namespace.ok_or_else(|| {
+                                    Error::Other(format!("Missing namespace in page"))
+                                })?,
+                                revision: earlier_revision,
+                            })?;
+                        }
+                    }
+                }
+                b"restrictions" => {
+                    restrictions = Some(
+                        parse_string("restrictions", tag.attributes(), reader, buffer, warnings)
+                            .await?,
+                    );
+                }
+                b"DiscussionThreading" => {
+                    debug!("Skipping DiscussionThreading in page {title:?}");
+                    skip_element(b"DiscussionThreading", reader, buffer).await?;
+                }
+                _ => return Err(Error::Other(format!("Found unexpected tag {tag:?}"))),
+            },
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(b"page") {
+                    let page = Page {
+                        title: if let Some(title) = title {
+                            title
+                        } else {
+                            return Err(Error::Other(format!("Missing title in page")));
+                        },
+                        namespace: if let Some(namespace) = namespace {
+                            namespace
+                        } else {
+                            return Err(Error::Other(format!("Missing namespace in page")));
+                        },
+                        id: if let Some(id) = id {
+                            id
+                        } else {
+                            return Err(Error::Other(format!("Missing id in page")));
+                        },
+                        revision: if let Some(revision) = revision {
+                            revision
+                        } else {
+                            return Err(Error::Other(format!("Missing revision in page")));
+                        },
+                        history,
+                        redirect,
+                        restrictions,
+                        extra,
+                    };
+                    Ok(Some((page, raw_text)))
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => match tag.name().into_inner() {
+                b"redirect" => {
+                    for attribute in tag.attributes() {
+                        let attribute = attribute?;
+                        match attribute.key {
+                            QName(b"title") => {
+                                redirect = Some(String::from_utf8(attribute.value.to_vec())?);
+                            }
+                            _ => report_unexpected(
+                                Warning::UnknownAttribute {
+                                    tag: "redirect".to_string(),
+                                    attribute: String::from_utf8_lossy(attribute.key.into_inner())
+                                        .into_owned(),
+                                },
+                                warnings,
+                                error_log,
+                            )?,
+                        }
+                    }
+                }
+                b"restrictions" => { /* ignore empty restrictions */ }
+                b"DiscussionThreading" => { /* ignore empty discussion threading */ }
+                _ => report_unexpected(unknown_tag(&tag), warnings, error_log)?,
+            },
+            RelevantEvent::Text(text) => {
+                report_unexpected(Warning::UnexpectedText { text }, warnings, error_log)?
+            }
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+/// Parse the text of an earlier revision of a page, without extracting words.
+fn parse_earlier_revision(
+    mut revision: Revision,
+    raw_text: Option<RawText>,
+    title: Option<&str>,
+    text_processor: &TextProcessor,
+    error_log: &mut impl Write,
+) -> Result<Revision> {
+    let title = title.ok_or_else(|| Error::Other(format!("Missing title in page")))?;
+    if let Some(raw_text) = raw_text {
+        let (text, error_log_entry) = text_processor.parse(title, raw_text);
+        if let Some(error_log_entry) = error_log_entry {
+            error_log.write_all(error_log_entry.as_bytes())?;
+        }
+        revision.text = Some(text);
+    }
+    Ok(revision)
+}
+
+/// Parse the xml of a revision.
+/// Its text is returned separately, and the text of the returned revision is `None`.
+async fn parse_revision(
+    attributes: Attributes<'_>,
+    title: Option<String>,
+    context: &PageParseContext<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    error_log: &mut impl Write,
+) -> Result<(Revision, Option<RawText>)> {
+    let warnings = context.warnings;
+    let extra = collect_attributes(attributes)?;
+
+    let mut id = None;
+    let mut parentid = None;
+    let mut timestamp = None;
+    let mut contributor = None;
+    let mut comment = None;
+    let mut model = None;
+    let mut format = None;
+    let mut text = None;
+    let mut sha1 = None;
+    let mut minor = false;
+    let mut origin = None;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => match tag.name().into_inner() {
+                b"id" => {
+                    id = Some(
+                        parse_string("id", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("id is not an integer in {tag:?}"))
+                            })?,
+                    );
+                }
+                b"parentid" => {
+                    parentid = Some(
+                        parse_string("parentid", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("parentid is not an integer in {tag:?}"))
+                            })?,
+                    );
+                }
+                b"timestamp" => {
+                    timestamp = Some(parse_timestamp(
+                        &parse_string("timestamp", tag.attributes(), reader, buffer, warnings)
+                            .await?,
+                    )?);
+                }
+                b"contributor" => {
+                    contributor =
+                        Some(parse_contributor(tag.attributes(), reader, buffer, warnings).await?);
+                }
+                b"comment" => {
+                    comment = Some(
+                        parse_string("comment", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"model" => {
+                    model = Some(
+                        parse_string("model", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"format" => {
+                    format = Some(
+                        parse_string("format", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"text" => {
+                    text = Some(
+                        parse_text(tag.attributes(), title.as_deref(), context, reader, buffer)
+                            .await?,
+                    );
+                }
+                b"sha1" => {
+                    sha1 = Some(
+                        parse_string("sha1", tag.attributes(), reader, buffer, warnings).await?,
+                    );
+                }
+                b"origin" => {
+                    origin = Some(
+                        parse_string("origin", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("origin is not an integer in {tag:?}"))
+                            })?,
+                    );
+                }
+                _ => return Err(Error::Other(format!("Found unexpected tag {tag:?}"))),
+            },
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(b"revision") {
+                    if text.is_none() {
+                        debug!("No text for revision with id {id:?} and comment {comment:?}");
+                        warnings.report(Warning::MissingField {
+                            tag: "revision".to_string(),
+                            field: "text".to_string(),
+                        })?;
+                    }
+
+                    let revision = Revision {
+                        id: if let Some(id) = id {
+                            id
+                        } else {
+                            return Err(Error::Other(format!("Missing id in revision")));
+                        },
+                        parentid,
+                        timestamp: if let Some(timestamp) = timestamp {
+                            timestamp
+                        } else {
+                            return Err(Error::Other(format!("Missing timestamp in revision")));
+                        },
+                        contributor,
+                        comment,
+                        model: if let Some(model) = model {
+                            model
+                        } else {
+                            return Err(Error::Other(format!("Missing model in revision")));
+                        },
+                        format: if let Some(format) = format {
+                            format
+                        } else {
+                            return Err(Error::Other(format!("Missing format in revision")));
+                        },
+                        text: None,
+                        sha1: if let Some(sha1) = sha1 {
+                            sha1
+                        } else {
+                            return Err(Error::Other(format!("Missing sha1 in revision")));
+                        },
+                        minor,
+                        origin,
+                        extra,
+                    };
+
+                    let text_sha1 = text.as_ref().and_then(|text| text.sha1.as_deref());
+                    if let Some(text_sha1) = text_sha1 {
+                        if !text_sha1.eq_ignore_ascii_case(&revision.sha1) {
+                            let page_name = title.as_deref().unwrap_or("<unknown>");
+                            warnings.report(Warning::Sha1Mismatch {
+                                title: title.clone(),
+                                revision_id: revision.id,
+                                stated: revision.sha1.clone(),
+                                actual: text_sha1.to_string(),
+                            })?;
+                            writeln!(
+                                error_log,
+                                "Page: {page_name}\nSha1 of revision {} is '{}', but the text has '{text_sha1}'\n",
+                                revision.id,
+                                revision.sha1,
+                            )
+                            .unwrap_or_else(|error| panic!("Writing to error log failed: {error}"));
+                        }
+                    }
+
+                    Ok((revision, text))
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => {
+                match tag.name().into_inner() {
+                    b"minor" => {
+                        minor = true;
+                    }
+                    b"comment" => { /* ignore empty comment */ }
+                    b"text" => { /* ignore empty text */ }
+                    b"contributor" => { /* ignore empty contributor */ }
+                    _ => report_unexpected(unknown_tag(&tag), warnings, error_log)?,
+                }
+            }
+            RelevantEvent::Text(text) => {
+                report_unexpected(Warning::UnexpectedText { text }, warnings, error_log)?
+            }
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+async fn parse_contributor(
+    attributes: Attributes<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    warnings: &WarningReporter,
+) -> Result<Contributor> {
+    warn_about_attributes(b"contributor", attributes, warnings)?;
+
+    let mut username = None;
+    let mut id: Option<i64> = None;
+    let mut ip = None;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => match tag.name().into_inner() {
+                b"username" => {
+                    username = Some(
+                        parse_string("username", tag.attributes(), reader, buffer, warnings)
+                            .await?,
+                    );
+                }
+                b"id" => {
+                    id = Some(
+                        parse_string("id", tag.attributes(), reader, buffer, warnings)
+                            .await?
+                            .parse()
+                            .map_err(|_| {
+                                Error::Other(format!("id is not an integer in {tag:?}"))
+                            })?,
+                    );
+                }
+                b"ip" => {
+                    ip =
+                        Some(parse_string("ip", tag.attributes(), reader, buffer, warnings).await?);
+                }
+                _ => return Err(Error::Other(format!("Found unexpected tag {tag:?}"))),
+            },
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(b"contributor") {
+                    if let (Some(username), Some(id), None) = (&username, &id, &ip) {
+                        Ok(Contributor::User {
+                            username: username.clone(),
+                            id: *id,
+                        })
+                    } else if let (None, None, Some(ip)) = (&username, &id, &ip) {
+                        Ok(Contributor::Anonymous { ip: ip.clone() })
+                    } else {
+                        Err(Error::Other(format!("Unknown combination of fields for contributor: {username:?}, {id:?}, {ip:?}")))
+                    }
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => warnings.report(unknown_tag(&tag))?,
+            RelevantEvent::Text(text) => warnings.report(Warning::UnexpectedText { text })?,
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+/// Parse the xml of the text of a revision, without parsing its wikitext.
+async fn parse_text(
+    attributes: Attributes<'_>,
+    title: Option<&str>,
+    context: &PageParseContext<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+) -> Result<RawText> {
+    let mut bytes: Option<usize> = None;
+    let mut xml_space = None;
+    let mut extra = BTreeMap::new();
+
+    for attribute in attributes {
+        let attribute = attribute?;
+        match attribute.key.into_inner() {
+            b"bytes" => {
+                bytes = Some(
+                    String::from_utf8(attribute.value.to_vec())?
+                        .parse()
+                        .map_err(|_| {
+                            Error::Other(format!("bytes is not an integer in {attribute:?}"))
+                        })?,
+                );
+            }
+            b"xml:space" => {
+                xml_space = Some(match attribute.value.as_ref() {
+                    b"preserve" => XmlSpace::Preserve,
+                    _ => {
+                        return Err(Error::Other(format!(
+                            "Found unexpected attribute value {attribute:?}"
+                        )))
+                    }
+                });
+            }
+            key => {
+                extra.insert(
+                    String::from_utf8(key.to_vec())?,
+                    attribute.unescape_value()?.into_owned(),
+                );
+            }
+        }
+    }
+
+    let mut text = None;
+    let mut sha1 = None;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => {
+                return Err(Error::Other(format!("Found unexpected tag {tag:?}")));
+            }
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(b"text") {
+                    Ok(RawText {
+                        xml_space: if let Some(xml_space) = xml_space {
+                            xml_space
+                        } else {
+                            return Err(Error::Other(format!("Missing tag xml:space in text")));
+                        },
+                        text: if let Some(text) = text {
+                            text
+                        } else {
+                            return Err(Error::Other(format!("Missing text in text")));
+                        },
+                        sha1,
+                        extra,
+                    })
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => context.warnings.report(unknown_tag(&tag))?,
+            RelevantEvent::Text(raw_text) => {
+                if let Some(bytes) = bytes {
+                    let raw_text_len = raw_text.len();
+                    if raw_text_len != bytes {
+                        context.warnings.report(Warning::LengthMismatch {
+                            title: title.map(ToString::to_string),
+                            stated: bytes,
+                            actual: raw_text_len,
+                        })?;
+                    }
+                } else {
+                    context.warnings.report(Warning::MissingField {
+                        tag: "text".to_string(),
+                        field: "bytes".to_string(),
+                    })?;
+                }
+                if text.is_some() {
+                    return Err(Error::Other(format!(
+                        "Found multiple texts in text of page {title:?}"
+                    )));
+                }
+
+                if context.verify_sha1 {
+                    sha1 = Some(sha1_base36(&raw_text));
+                }
+                text = Some(raw_text);
+            }
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+/// The text of a revision as read from the xml, before its wikitext is parsed.
+struct RawText {
+    xml_space: XmlSpace,
+    text: String,
+    sha1: Option<String>,
+    extra: BTreeMap<String, String>,
+}
+
+/// The parsed text of a revision, together with the words extracted from it.
+struct ProcessedText {
+    text: Text,
+    words: Vec<Word>,
+    /// The errors that occurred while parsing and extracting, formatted for the error log.
+    error_log_entry: Option<String>,
+    /// If given, the wikitext has errors and the page is dropped according to the [`WikitextErrorPolicy`].
+    rejection: Option<WikitextRejection>,
+    /// The warnings about the text, which are reported when the page is consumed to keep them in order.
+    warnings: Vec<Warning>,
+}
+
+/// The wikitext errors of a page that is dropped according to the [`WikitextErrorPolicy`].
+struct WikitextRejection {
+    errors: Vec<String>,
+    /// The text as it is in the dump, if the page is quarantined.
+    original_text: Option<String>,
+}
+
+/// Parses the wikitext of pages and extracts their words.
+/// It owns everything it needs, such that it can be shared with the page workers.
+struct TextProcessor {
+    word_extraction_options: WordExtractionOptions,
+    dump_version: DumpVersion,
+    compatibility_shims: CompatibilityShims,
+    template_expander: Option<Arc<TemplateExpander>>,
+    wikitext_errors: WikitextErrorPolicy,
+}
+
+impl TextProcessor {
+    fn new(context: &PageParseContext<'_>) -> Self {
+        Self {
+            word_extraction_options: context.word_extraction_options.clone(),
+            dump_version: context.dump_version.clone(),
+            compatibility_shims: context.compatibility_shims.clone(),
+            template_expander: context.template_expander.clone(),
+            wikitext_errors: context.wikitext_errors,
+        }
+    }
+
+    fn process(&self, namespace: i64, title: &str, raw_text: RawText) -> ProcessedText {
+        debug!("Parsing '{title}'");
+        let original_text = if self.wikitext_errors == WikitextErrorPolicy::Quarantine {
+            Some(raw_text.text.clone())
+        } else {
+            None
+        };
+        let (text, raw_text, mut errors) = self.parse_raw_text(title, raw_text);
+
+        if !errors.is_empty() && self.wikitext_errors != WikitextErrorPolicy::Keep {
+            return ProcessedText {
+                text,
+                words: Vec::new(),
+                error_log_entry: error_log_entry(title, &errors, &raw_text),
+                rejection: Some(WikitextRejection {
+                    errors,
+                    original_text,
+                }),
+                warnings: Vec::new(),
+            };
+        }
+
+        let mut words = Vec::new();
+        let mut warnings = Vec::new();
+        extract_words_with_namespace(
+            &self.word_extraction_options,
+            Some(namespace),
+            title,
+            &text.text,
+            |word| words.push(word),
+            |error| {
+                if let Error::UnknownHeading {
+                    language_english_name,
+                    label,
+                } = &error
+                {
+                    warnings.push(Warning::UnknownSection {
+                        title: title.to_string(),
+                        language_english_name: language_english_name.clone(),
+                        heading: label.clone(),
+                    });
+                }
+                errors.push(format!("{error:#?}"))
+            },
+        );
+
+        ProcessedText {
+            text,
+            words,
+            error_log_entry: error_log_entry(title, &errors, &raw_text),
+            rejection: None,
+            warnings,
+        }
+    }
+
+    /// Parse the text without extracting words, e.g. of an earlier revision of a page.
+    /// Returns the parsed text and the errors that occurred, formatted for the error log.
+    fn parse(&self, title: &str, raw_text: RawText) -> (Text, Option<String>) {
+        let (text, raw_text, errors) = self.parse_raw_text(title, raw_text);
+        let error_log_entry = error_log_entry(title, &errors, &raw_text);
+        (text, error_log_entry)
+    }
+
+    /// Apply the compatibility shims and the template expander and parse the resulting wikitext.
+    /// Returns the parsed text, the wikitext that was parsed and the parser errors, formatted for the error log.
+    fn parse_raw_text(&self, title: &str, raw_text: RawText) -> (Text, String, Vec<String>) {
+        let RawText {
+            xml_space,
+            text: raw_text,
+            sha1,
+            extra,
+        } = raw_text;
+        let bytes = raw_text.len() as u64;
+
+        let raw_text = if self.compatibility_shims.is_empty() {
+            raw_text
+        } else {
+            self.compatibility_shims
+                .apply(&self.dump_version, title, raw_text)
+        };
+        let raw_text = if let Some(template_expander) = &self.template_expander {
+            template_expander.expand_wikitext(&raw_text)
+        } else {
+            raw_text
+        };
+        let mut errors = Vec::new();
+        let parsed_text = parse_wikitext(&raw_text, title.to_string(), |error| {
+            errors.push(format!("{error:#?}"))
+        });
+
+        let text = Text {
+            xml_space,
+            text: parsed_text,
+            sha1,
+            bytes: Some(bytes),
+            extra,
+        };
+        (text, raw_text, errors)
+    }
+}
+
+/// Format the errors of a page for the error log, if there are any.
+fn error_log_entry(title: &str, errors: &[String], raw_text: &str) -> Option<String> {
+    if errors.is_empty() {
+        return None;
+    }
+
+    debug!("Page '{title}' has {} errors", errors.len());
+    let mut error_log_entry = format!("Page: {title}\n");
+    for error in errors {
+        error_log_entry.push_str(error);
+        error_log_entry.push('\n');
+    }
+    error_log_entry.push_str(&format!("\nContent: {raw_text}\n\n"));
+    Some(error_log_entry)
+}
+
+/// Collect attributes by name, such that additions to the dump schema do not break parsing.
+fn collect_attributes(attributes: Attributes<'_>) -> Result<BTreeMap<String, String>> {
+    let mut collected = BTreeMap::new();
+    for attribute in attributes {
+        let attribute = attribute?;
+        collected.insert(
+            String::from_utf8(attribute.key.into_inner().to_vec())?,
+            attribute.unescape_value()?.into_owned(),
+        );
+    }
+    if !collected.is_empty() {
+        debug!("Collected unknown attributes {collected:?}");
+    }
+    Ok(collected)
+}
+
+/// Write the warning about unexpected xml in full into the error log, and report it.
+fn report_unexpected(
+    warning: Warning,
+    warnings: &WarningReporter,
+    error_log: &mut impl Write,
+) -> Result<()> {
+    writeln!(error_log, "{warning}\n")?;
+    warnings.report(warning)
+}
+
+/// The warning about an unexpected empty tag.
+fn unknown_tag(tag: &BytesStart<'_>) -> Warning {
+    Warning::UnknownTag {
+        tag: String::from_utf8_lossy(tag.name().into_inner()).into_owned(),
+    }
+}
+
+/// Attributes that are expected on tags whose type has no place to store them, and are ignored silently.
+/// `deleted` marks suppressed contents, like in `<contributor deleted="deleted" />`.
+const IGNORED_ATTRIBUTES: &[&[u8]] = &[b"deleted"];
+
+/// Ignore the attributes of a tag whose type has no place to store them,
+/// but warn about them, unless they are [`IGNORED_ATTRIBUTES`].
+fn warn_about_attributes(
+    tag_name: &[u8],
+    attributes: Attributes<'_>,
+    warnings: &WarningReporter,
+) -> Result<()> {
+    for attribute in attributes {
+        let attribute = attribute?;
+        if IGNORED_ATTRIBUTES.contains(&attribute.key.into_inner()) {
+            trace!(
+                "Ignoring attribute {attribute:?} of tag {:?}",
+                String::from_utf8_lossy(tag_name)
+            );
+            continue;
+        }
+        warnings.report(Warning::UnknownAttribute {
+            tag: String::from_utf8_lossy(tag_name).into_owned(),
+            attribute: String::from_utf8_lossy(attribute.key.into_inner()).into_owned(),
+        })?;
+    }
+    Ok(())
+}
+
+async fn parse_string(
+    name: impl AsRef<[u8]>,
+    attributes: Attributes<'_>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+    warnings: &WarningReporter,
+) -> Result<String> {
+    let name = name.as_ref();
+    warn_about_attributes(name, attributes, warnings)?;
+
+    let mut value = String::new();
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(tag) => {
+                return Err(Error::Other(format!("Found unexpected tag {tag:?}")));
+            }
+            RelevantEvent::End(tag) => {
+                return if tag.name() == QName(name) {
+                    Ok(value)
+                } else {
+                    Err(Error::Other(format!(
+                        "Found unexpected closing tag {tag:?}"
+                    )))
+                };
+            }
+            RelevantEvent::Empty(tag) => warnings.report(unknown_tag(&tag))?,
+            RelevantEvent::Text(text) => value = text,
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+/// Skip an element whose start tag was just read, including all its children.
+async fn skip_element(
+    name: impl AsRef<[u8]>,
+    reader: &mut Reader<impl AsyncBufRead + Unpin>,
+    buffer: &mut Vec<u8>,
+) -> Result<()> {
+    let name = name.as_ref();
+    let mut depth = 0usize;
+
+    loop {
+        match read_relevant_event(reader, buffer).await? {
+            RelevantEvent::Start(_) => depth += 1,
+            RelevantEvent::End(tag) => {
+                if depth == 0 {
+                    return if tag.name() == QName(name) {
+                        Ok(())
+                    } else {
+                        Err(Error::Other(format!(
+                            "Found unexpected closing tag {tag:?}"
+                        )))
+                    };
+                }
+                depth -= 1;
+            }
+            RelevantEvent::Empty(_) | RelevantEvent::Text(_) => {}
+            RelevantEvent::Eof => return Err(Error::Other(format!("Unexpected eof"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::sink::SiteinfoSink;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn sends_queued_output_before_reading_input() {
+        let (mut output, mut receiver) = ChannelWriter::channel();
+        let chunk_count = OUTPUT_CHANNEL_CAPACITY * 3;
+        for index in 0..chunk_count {
+            output.write_all(&index.to_le_bytes()).unwrap();
+        }
+        assert_eq!(output.queued().len(), chunk_count - OUTPUT_CHANNEL_CAPACITY);
+
+        let mut input = Backpressured::new(&b"<mediawiki/>"[..], &[&output]);
+        drop(output);
+        let read = async move {
+            let mut buffer = Vec::new();
+            input.read_to_end(&mut buffer).await.unwrap();
+            buffer
+        };
+        let receive = async {
+            let mut chunks = Vec::new();
+            while let Some(chunk) = receiver.recv().await {
+                chunks.push(chunk);
+            }
+            chunks
+        };
+        let (buffer, chunks) = tokio::join!(read, receive);
+
+        assert_eq!(buffer, b"<mediawiki/>");
+        assert_eq!(
+            chunks,
+            (0..chunk_count)
+                .map(|index| index.to_le_bytes().to_vec())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    /// A dump with unsorted namespaces and pages with several languages.
+    fn dump_with_languages(page_count: usize) -> String {
+        let mut dump = String::from(
+            r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/" version="0.10" xml:lang="en">
+  <siteinfo>
+    <sitename>Wiktionary</sitename>
+    <dbname>enwiktionary</dbname>
+    <base>https://en.wiktionary.org/wiki/Wiktionary:Main_Page</base>
+    <generator>MediaWiki 1.41.0-wmf.18</generator>
+    <case>case-sensitive</case>
+    <namespaces>
+      <namespace key="1" case="case-sensitive">Talk</namespace>
+      <namespace key="-1" case="first-letter">Special</namespace>
+      <namespace key="0" case="case-sensitive" />
+    </namespaces>
+  </siteinfo>
+"#,
+        );
+        for index in 0..page_count {
+            dump.push_str(&format!(
+                r#"  <page>
+    <title>word{index}</title>
+    <ns>0</ns>
+    <id>{index}</id>
+    <revision>
+      <id>{index}</id>
+      <timestamp>2023-07-01T00:00:00Z</timestamp>
+      <contributor>
+        <username>Example</username>
+        <id>1</id>
+      </contributor>
+      <model>wikitext</model>
+      <format>text/x-wiki</format>
+      <text xml:space="preserve">==Swedish==
+
+===Noun===
+{{{{sv-noun}}}}
+
+==English==
+
+===Noun===
+{{{{en-noun}}}}
+
+===Verb===
+{{{{en-verb}}}}
+
+==German==
+
+===Noun===
+{{{{de-noun}}}}</text>
+      <sha1>0000000000000000000000000000000</sha1>
+    </revision>
+  </page>
+"#
+            ));
+        }
+        dump.push_str("</mediawiki>\n");
+        dump
+    }
+
+    async fn parse_with_page_workers(
+        dump: &str,
+        page_workers: usize,
+    ) -> (Option<Siteinfo>, Vec<Page>, Vec<Word>) {
+        let options = DumpParseOptions {
+            page_workers,
+            ..Default::default()
+        };
+        let mut sink = (SiteinfoSink::default(), Vec::new());
+        let mut words = Vec::new();
+        parse_dump_file_with_streams(
+            dump.as_bytes(),
+            |_| None,
+            0,
+            PageParseContext::new(&options, Default::default(), None),
+            &mut sink,
+            &mut |word| {
+                words.push(word);
+                async { Ok::<_, Box<dyn std::error::Error + Send + Sync>>(()) }
+            },
+            &mut |_, _, _| {},
+            std::io::sink(),
+            None::<std::io::Sink>,
+            None::<std::io::Sink>,
+        )
+        .await
+        .unwrap();
+        (sink.0.siteinfo, sink.1, words)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn parses_in_the_same_order_with_page_workers() {
+        let dump = dump_with_languages(64);
+        let first = parse_with_page_workers(&dump, 4).await;
+        let second = parse_with_page_workers(&dump, 4).await;
+        assert_eq!(first, second);
+
+        let (siteinfo, pages, words) = first;
+        let namespace_keys: Vec<_> = siteinfo
+            .as_ref()
+            .unwrap()
+            .namespaces
+            .iter()
+            .map(|namespace| namespace.key)
+            .collect();
+        // The nameless main namespace is not kept.
+        assert_eq!(namespace_keys, [-1, 1]);
+
+        let titles: Vec<_> = pages.iter().map(|page| page.title.as_str()).collect();
+        let expected_titles: Vec<_> = (0..64).map(|index| format!("word{index}")).collect();
+        assert_eq!(titles, expected_titles);
+
+        let records: Vec<_> = words
+            .iter()
+            .map(|word| (word.word.as_str(), &*word.language_english_name))
+            .collect();
+        let expected_records: Vec<_> = expected_titles
+            .iter()
+            .flat_map(|title| {
+                [
+                    (title.as_str(), "Swedish"),
+                    (title.as_str(), "English"),
+                    (title.as_str(), "English"),
+                    (title.as_str(), "German"),
+                ]
+            })
+            .collect();
+        assert_eq!(records, expected_records);
+
+        // Parsing on the calling task gives the same result.
+        assert_eq!(
+            parse_with_page_workers(&dump, 0).await,
+            (siteinfo, pages, words)
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Writing parsed pages back into the xml format of MediaWiki exports.
+
+use crate::dates::format_timestamp;
+use crate::error::Result;
+use crate::parser::words::templates::{section_paragraphs, SectionLine};
+use crate::parser::{Contributor, Namespace, Page, Revision, Siteinfo, Text};
+use quick_xml::escape::escape;
+use std::collections::BTreeMap;
+use std::io::Write;
+use wikitext_parser::{Section, TextPiece, Wikitext};
+
+/// The version of the export schema written by [`ExportXmlWriter`].
+pub const EXPORT_SCHEMA_VERSION: &str = "0.11";
+
+/// Writes a siteinfo and pages as MediaWiki export xml, which can be parsed again like a dump.
+///
+/// This allows to create small fixtures for tests, and to re-create trimmed dumps from filtered pages.
+/// Since pages only contain parsed wikitext, their texts are written by rendering the parsed wikitext,
+/// which reproduces sections, lists, templates and links, but not bold and italic formatting.
+/// Use [`write_page_with_text`](Self::write_page_with_text) to write the original wikitext instead.
+pub struct ExportXmlWriter<W: Write> {
+    output: W,
+}
+
+impl<W: Write> ExportXmlWriter<W> {
+    /// Create a new writer and write the opening `mediawiki` tag.
+    pub fn new(mut output: W, language: &str) -> Result<Self> {
+        writeln!(
+            output,
+            r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-{EXPORT_SCHEMA_VERSION}/" version="{EXPORT_SCHEMA_VERSION}" xml:lang="{}">"#,
+            escape(language)
+        )?;
+        Ok(Self { output })
+    }
+
+    pub fn write_siteinfo(&mut self, siteinfo: &Siteinfo) -> Result<()> {
+        let output = &mut self.output;
+        writeln!(output, "  <siteinfo{}>", attributes(&siteinfo.extra))?;
+        write_element(output, 4, "sitename", &siteinfo.sitename)?;
+        write_element(output, 4, "dbname", &siteinfo.dbname)?;
+        write_element(output, 4, "base", &siteinfo.base)?;
+        write_element(output, 4, "generator", &siteinfo.generator)?;
+        write_element(output, 4, "case", &siteinfo.case)?;
+        writeln!(output, "    <namespaces>")?;
+        for Namespace {
+            key,
+            case,
+            name,
+            extra,
+        } in &siteinfo.namespaces
+        {
+            writeln!(
+                output,
+                r#"      <namespace key="{key}" case="{}"{}>{}</namespace>"#,
+                escape(case),
+                attributes(extra),
+                escape(name)
+            )?;
+        }
+        writeln!(output, "    </namespaces>")?;
+        writeln!(output, "  </siteinfo>")?;
+        Ok(())
+    }
+
+    /// Write the page, rendering its parsed wikitext.
+    /// Earlier revisions collected from a full-history dump are written before the latest revision.
+    pub fn write_page(&mut self, page: &Page) -> Result<()> {
+        let text = page.revision.text.as_ref().map(rendered_text);
+        self.write_page_with_optional_text(page, text.as_deref())
+    }
+
+    /// Write the page with the given wikitext instead of the parsed wikitext of its latest revision.
+    pub fn write_page_with_text(&mut self, page: &Page, text: &str) -> Result<()> {
+        self.write_page_with_optional_text(page, Some(text))
+    }
+
+    fn write_page_with_optional_text(&mut self, page: &Page, text: Option<&str>) -> Result<()> {
+        let output = &mut self.output;
+        writeln!(output, "  <page{}>", attributes(&page.extra))?;
+        write_element(output, 4, "title", &page.title)?;
+        write_element(output, 4, "ns", &page.namespace.to_string())?;
+        write_element(output, 4, "id", &page.id.to_string())?;
+        if let Some(redirect) = &page.redirect {
+            writeln!(output, r#"    <redirect title="{}" />"#, escape(redirect))?;
+        }
+        if let Some(restrictions) = &page.restrictions {
+            write_element(output, 4, "restrictions", restrictions)?;
+        }
+        for revision in &page.history {
+            let text = revision.text.as_ref().map(rendered_text);
+            write_revision(output, revision, text.as_deref())?;
+        }
+        write_revision(output, &page.revision, text)?;
+        writeln!(output, "  </page>")?;
+        Ok(())
+    }
+
+    /// Write the closing `mediawiki` tag, flush and return the underlying writer.
+    pub fn finish(mut self) -> Result<W> {
+        writeln!(self.output, "</mediawiki>")?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+fn write_revision(output: &mut impl Write, revision: &Revision, text: Option<&str>) -> Result<()> {
+    writeln!(output, "    <revision{}>", attributes(&revision.extra))?;
+    write_element(output, 6, "id", &revision.id.to_string())?;
+    if let Some(parentid) = revision.parentid {
+        write_element(output, 6, "parentid", &parentid.to_string())?;
+    }
+    write_element(
+        output,
+        6,
+        "timestamp",
+        &format_timestamp(&revision.timestamp),
+    )?;
+    match &revision.contributor {
+        Some(Contributor::User { username, id }) => {
+            writeln!(output, "      <contributor>")?;
+            write_element(output, 8, "username", username)?;
+            write_element(output, 8, "id", &id.to_string())?;
+            writeln!(output, "      </contributor>")?;
+        }
+        Some(Contributor::Anonymous { ip }) => {
+            writeln!(output, "      <contributor>")?;
+            write_element(output, 8, "ip", ip)?;
+            writeln!(output, "      </contributor>")?;
+        }
+        None => {}
+    }
+    if revision.minor {
+        writeln!(output, "      <minor />")?;
+    }
+    if let Some(comment) = &revision.comment {
+        write_element(output, 6, "comment", comment)?;
+    }
+    if let Some(origin) = revision.origin {
+        write_element(output, 6, "origin", &origin.to_string())?;
+    }
+    write_element(output, 6, "model", &revision.model)?;
+    write_element(output, 6, "format", &revision.format)?;
+    if let Some(text) = text {
+        let extra = revision
+            .text
+            .as_ref()
+            .map(|text| attributes(&text.extra))
+            .unwrap_or_default();
+        writeln!(
+            output,
+            r#"      <text bytes="{}" xml:space="preserve"{extra}>{}</text>"#,
+            text.len(),
+            escape(text)
+        )?;
+    }
+    write_element(output, 6, "sha1", &revision.sha1)?;
+    writeln!(output, "    </revision>")?;
+    Ok(())
+}
+
+fn write_element(
+    output: &mut impl Write,
+    indentation: usize,
+    name: &str,
+    value: &str,
+) -> Result<()> {
+    writeln!(
+        output,
+        "{:indentation$}<{name}>{}</{name}>",
+        "",
+        escape(value)
+    )?;
+    Ok(())
+}
+
+/// Attributes not known to the parser, formatted to be appended to a tag name.
+fn attributes(extra: &BTreeMap<String, String>) -> String {
+    extra
+        .iter()
+        .map(|(name, value)| format!(r#" {name}="{}""#, escape(value)))
+        .collect()
+}
+
+fn rendered_text(text: &Text) -> String {
+    render_wikitext(&text.text)
+}
+
+/// Render parsed wikitext back into wikitext.
+/// The result parses into the same sections, lists, templates and links, but is not necessarily equal to the original.
+pub fn render_wikitext(wikitext: &Wikitext) -> String {
+    let mut result = String::new();
+    // The root section has the title of the page as headline, which is not part of the text.
+    render_section_content(&wikitext.root_section, &mut result);
+    for subsection in &wikitext.root_section.subsections {
+        render_section(subsection, &mut result);
+    }
+    result.trim_end().to_string()
+}
+
+fn render_section(section: &Section, result: &mut String) {
+    let marker = "=".repeat(section.headline.level as usize);
+    result.push_str(&format!("{marker}{}{marker}\n", section.headline.label));
+    render_section_content(section, result);
+    for subsection in &section.subsections {
+        render_section(subsection, result);
+    }
+}
+
+fn render_section_content(section: &Section, result: &mut String) {
+    for paragraph in section_paragraphs(section) {
+        for line in paragraph {
+            match line {
+                SectionLine::Normal { text } => render_text(&text, result),
+                SectionLine::List { list_prefix, text } => {
+                    result.push_str(&list_prefix);
+                    result.push(' ');
+                    render_text(&text, result);
+                }
+            }
+            result.push('\n');
+        }
+        result.push('\n');
+    }
+}
+
+fn render_text(text: &wikitext_parser::Text, result: &mut String) {
+    for piece in &text.pieces {
+        match piece {
+            TextPiece::Text { text, .. } => result.push_str(text),
+            TextPiece::DoubleBraceExpression { tag, attributes } => {
+                result.push_str("{{");
+                render_text(tag, result);
+                for attribute in attributes {
+                    result.push('|');
+                    if let Some(name) = &attribute.name {
+                        result.push_str(name);
+                        result.push('=');
+                    }
+                    render_text(&attribute.value, result);
+                }
+                result.push_str("}}");
+            }
+            TextPiece::InternalLink { target, label, .. } => {
+                result.push_str("[[");
+                render_text(target, result);
+                if let Some(label) = label {
+                    result.push('|');
+                    render_text(label, result);
+                }
+                result.push_str("]]");
+            }
+            TextPiece::ListItem { list_prefix, text } => {
+                result.push('\n');
+                result.push_str(list_prefix);
+                result.push(' ');
+                render_text(text, result);
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::error::{Error, Result};
+use async_compression::tokio::bufread::BzDecoder;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::BufRead;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+/// An entry of a multistream index file.
+/// Each line of such a file has the format `offset:page_id:title`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct MultistreamIndexEntry {
+    /// The byte offset of the bz2 stream that contains the page within the multistream dump file.
+    pub offset: u64,
+    pub page_id: i64,
+    pub title: String,
+}
+
+impl MultistreamIndexEntry {
+    /// Parse a single line of a multistream index file.
+    /// The title may contain colons, so only the first two colons are treated as separators.
+    pub fn parse_line(line: &str) -> Result<Self> {
+        let mut parts = line.trim_end_matches(['\r', '\n']).splitn(3, ':');
+        let (offset, page_id, title) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(offset), Some(page_id), Some(title)) => (offset, page_id, title),
+            _ => {
+                return Err(Error::Other(format!(
+                    "Multistream index line has less than three fields: {line:?}"
+                )))
+            }
+        };
+
+        Ok(Self {
+            offset: offset.parse().map_err(|_| {
+                Error::Other(format!(
+                    "Offset is not an integer in multistream index line {line:?}"
+                ))
+            })?,
+            page_id: page_id.parse().map_err(|_| {
+                Error::Other(format!(
+                    "Page id is not an integer in multistream index line {line:?}"
+                ))
+            })?,
+            title: title.to_string(),
+        })
+    }
+}
+
+/// An iterator over the entries of an uncompressed multistream index.
+pub struct MultistreamIndexEntries<R> {
+    lines: std::io::Lines<R>,
+}
+
+impl<R: BufRead> MultistreamIndexEntries<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for MultistreamIndexEntries<R> {
+    type Item = Result<MultistreamIndexEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            if !line.is_empty() {
+                return Some(MultistreamIndexEntry::parse_line(&line));
+            }
+        }
+    }
+}
+
+/// A multistream index loaded into memory, for answering queries about a dump without decompressing it.
+#[derive(Debug, Clone, Default)]
+pub struct MultistreamIndex {
+    entries: Vec<MultistreamIndexEntry>,
+    title_to_entry: HashMap<String, usize>,
+}
+
+impl MultistreamIndex {
+    /// Load a multistream index file.
+    /// The file may be compressed with bzip2 (`.txt.bz2`) or uncompressed (`.txt`).
+    pub async fn from_file(index_file: impl AsRef<Path>) -> Result<Self> {
+        let index_file = index_file.as_ref();
+        let file = File::open(index_file).await?;
+
+        if index_file.extension().map(OsStr::to_str) == Some(Some("bz2")) {
+            debug!("Found file extension '.bz2' for multistream index file {index_file:?}");
+            Self::from_bz2_stream(BufReader::new(file)).await
+        } else if index_file.extension().map(OsStr::to_str) == Some(Some("txt")) {
+            debug!("Found file extension '.txt' for multistream index file {index_file:?}");
+            Self::from_stream(BufReader::new(file)).await
+        } else {
+            Err(Error::Other(format!(
+                "Unknown file extension in multistream index file {index_file:?}"
+            )))
+        }
+    }
+
+    /// Load a multistream index compressed with bzip2 from a stream.
+    /// Like the dumps they belong to, index files consist of multiple concatenated bzip2 streams, which are all read.
+    pub async fn from_bz2_stream(stream: impl AsyncBufRead + Unpin) -> Result<Self> {
+        let mut decoder = BzDecoder::new(stream);
+        decoder.multiple_members(true);
+        Self::from_stream(BufReader::new(decoder)).await
+    }
+
+    /// Load an uncompressed multistream index from a stream.
+    pub async fn from_stream(stream: impl AsyncBufRead + Unpin) -> Result<Self> {
+        let mut lines = stream.lines();
+        let mut index = Self::default();
+
+        while let Some(line) = lines.next_line().await? {
+            if !line.is_empty() {
+                index.push(MultistreamIndexEntry::parse_line(&line)?);
+            }
+        }
+
+        debug!("Loaded multistream index with {} entries", index.len());
+        Ok(index)
+    }
+
+    fn push(&mut self, entry: MultistreamIndexEntry) {
+        self.title_to_entry
+            .insert(entry.title.clone(), self.entries.len());
+        self.entries.push(entry);
+    }
+
+    /// Returns true if the dump contains a page with the given title.
+    pub fn contains_title(&self, title: &str) -> bool {
+        self.title_to_entry.contains_key(title)
+    }
+
+    /// Returns the index entry of the page with the given title.
+    pub fn get(&self, title: &str) -> Option<&MultistreamIndexEntry> {
+        self.title_to_entry
+            .get(title)
+            .map(|entry| &self.entries[*entry])
+    }
+
+    /// The entries in the order of the index file.
+    pub fn entries(&self) -> &[MultistreamIndexEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl FromIterator<MultistreamIndexEntry> for MultistreamIndex {
+    fn from_iter<T: IntoIterator<Item = MultistreamIndexEntry>>(iter: T) -> Self {
+        let mut index = Self::default();
+        for entry in iter {
+            index.push(entry);
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn parses_index_lines() {
+        assert_eq!(
+            MultistreamIndexEntry::parse_line("629:10:dictionary\n").unwrap(),
+            MultistreamIndexEntry {
+                offset: 629,
+                page_id: 10,
+                title: "dictionary".to_string(),
+            }
+        );
+        assert_eq!(
+            MultistreamIndexEntry::parse_line("1234:56:Wiktionary:Main Page:Archive").unwrap(),
+            MultistreamIndexEntry {
+                offset: 1234,
+                page_id: 56,
+                title: "Wiktionary:Main Page:Archive".to_string(),
+            }
+        );
+        assert!(MultistreamIndexEntry::parse_line("629:10").is_err());
+        assert!(MultistreamIndexEntry::parse_line("x:10:dictionary").is_err());
+    }
+
+    #[test]
+    fn iterates_entries_and_skips_empty_lines() {
+        let entries: Vec<_> = MultistreamIndexEntries::new(&b"0:1:a\n\n0:2:b:c\r\n"[..])
+            .map(|entry| entry.unwrap().title)
+            .collect();
+        assert_eq!(entries, ["a", "b:c"]);
+    }
+
+    #[tokio::test]
+    async fn reads_all_members_of_bz2_stream() {
+        let mut compressed = Vec::new();
+        for member in ["0:1:free\n0:2:Talk:free\n", "5000:3:gratis\n"] {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+            encoder.write_all(member.as_bytes()).unwrap();
+            compressed.extend(encoder.finish().unwrap());
+        }
+
+        let index = MultistreamIndex::from_bz2_stream(&compressed[..])
+            .await
+            .unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get("Talk:free").unwrap().page_id, 2);
+        assert_eq!(index.get("gratis").unwrap().offset, 5000);
+    }
+}
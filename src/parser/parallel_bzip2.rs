@@ -0,0 +1,216 @@
+//! Parallel decompression of multistream bzip2 dumps.
+//!
+//! The `pages-articles-multistream` dumps of wiktionary are concatenations of independent bzip2 streams
+//! of a hundred pages each.
+//! [`ParallelBzDecoder`] splits the file at the stream headers into chunks of at least [`MINIMUM_CHUNK_SIZE`] bytes,
+//! decompresses the chunks on a pool of threads, and yields the decompressed chunks in the order of the file.
+//! Dumps that consist of a single stream are decompressed by a single thread.
+
+use std::io::Read;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+
+/// The minimum amount of compressed bytes decompressed by a thread at once.
+/// Streams are grouped into chunks of at least this size, to keep the overhead per chunk small.
+pub const MINIMUM_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The magic bytes of a block, which follow the header `BZh1` to `BZh9` at the start of a stream.
+const BLOCK_MAGIC: [u8; 6] = [0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+/// The magic bytes of the end of a stream, which follow the header of an empty stream.
+const END_OF_STREAM_MAGIC: [u8; 6] = [0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+/// The length of the header of a stream including the magic bytes of its first block.
+const STREAM_START_LENGTH: usize = 10;
+
+type DecompressedChunk = std::io::Result<Vec<u8>>;
+
+/// Decompresses a multistream bzip2 file on multiple threads, yielding the decompressed bytes in order.
+pub struct ParallelBzDecoder {
+    decompressed_chunks: mpsc::Receiver<DecompressedChunk>,
+    current_chunk: Vec<u8>,
+    current_offset: usize,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl ParallelBzDecoder {
+    /// Open the file and start decompressing it on the given amount of threads.
+    pub fn open(path: impl AsRef<Path>, threads: usize) -> Result<Self> {
+        Ok(Self::new(std::fs::File::open(path)?, threads))
+    }
+
+    /// Start decompressing the input on the given amount of threads, which is at least one.
+    ///
+    /// The input is read by a separate thread, and at most twice as many chunks as threads are held in memory at once.
+    pub fn new(input: impl Read + Send + 'static, threads: usize) -> Self {
+        let threads = threads.max(1);
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let (job_sender, job_receiver) =
+            sync_channel::<(Vec<u8>, SyncSender<DecompressedChunk>)>(threads);
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let (ordered_sender, ordered_receiver) = sync_channel(threads * 2);
+        let (decompressed_sender, decompressed_chunks) = mpsc::channel(threads * 2);
+
+        for _ in 0..threads {
+            let job_receiver = job_receiver.clone();
+            std::thread::spawn(move || loop {
+                let job = job_receiver
+                    .lock()
+                    .map_err(|_| ())
+                    .and_then(|job_receiver| job_receiver.recv().map_err(|_| ()));
+                if let Ok((chunk, result_sender)) = job {
+                    // The receiver is gone if decompression stopped early.
+                    result_sender.send(decompress_chunk(&chunk)).ok();
+                } else {
+                    break;
+                }
+            });
+        }
+
+        {
+            let bytes_read = bytes_read.clone();
+            std::thread::spawn(move || split_chunks(input, job_sender, ordered_sender, bytes_read));
+        }
+        std::thread::spawn(move || collect_chunks(ordered_receiver, decompressed_sender));
+
+        Self {
+            decompressed_chunks,
+            current_chunk: Vec::new(),
+            current_offset: 0,
+            bytes_read,
+        }
+    }
+
+    /// A shared counter of the compressed bytes read from the input so far.
+    pub fn bytes_read(&self) -> Arc<AtomicU64> {
+        self.bytes_read.clone()
+    }
+}
+
+impl AsyncRead for ParallelBzDecoder {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        while self.current_offset == self.current_chunk.len() {
+            match self.decompressed_chunks.poll_recv(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.current_chunk = chunk;
+                    self.current_offset = 0;
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Err(error)),
+                // All chunks were read.
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let amount = buf
+            .remaining()
+            .min(self.current_chunk.len() - self.current_offset);
+        let offset = self.current_offset;
+        buf.put_slice(&self.current_chunk[offset..offset + amount]);
+        self.current_offset += amount;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Read the input and hand chunks of whole streams to the decompression threads.
+/// For each chunk, a receiver of its decompressed bytes is sent in order to the collecting thread.
+fn split_chunks(
+    mut input: impl Read,
+    job_sender: SyncSender<(Vec<u8>, SyncSender<DecompressedChunk>)>,
+    ordered_sender: SyncSender<Receiver<DecompressedChunk>>,
+    bytes_read: Arc<AtomicU64>,
+) {
+    let mut buffer = Vec::with_capacity(2 * MINIMUM_CHUNK_SIZE);
+    let mut read_buffer = vec![0; MINIMUM_CHUNK_SIZE];
+    // Stream starts are searched from here, such that no byte is searched twice.
+    let mut search_offset = MINIMUM_CHUNK_SIZE;
+
+    loop {
+        let amount = match input.read(&mut read_buffer) {
+            Ok(amount) => amount,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => {
+                let (result_sender, result_receiver) = sync_channel(1);
+                result_sender.send(Err(error)).ok();
+                ordered_sender.send(result_receiver).ok();
+                return;
+            }
+        };
+        bytes_read.fetch_add(amount as u64, Ordering::Relaxed);
+
+        if amount == 0 {
+            if !buffer.is_empty() {
+                send_chunk(buffer, &job_sender, &ordered_sender);
+            }
+            return;
+        }
+        buffer.extend_from_slice(&read_buffer[..amount]);
+
+        while let Some(stream_start) = find_stream_start(&buffer, search_offset) {
+            let rest = buffer.split_off(stream_start);
+            let chunk = std::mem::replace(&mut buffer, rest);
+            if !send_chunk(chunk, &job_sender, &ordered_sender) {
+                return;
+            }
+            search_offset = MINIMUM_CHUNK_SIZE;
+        }
+        search_offset = search_offset.max(buffer.len().saturating_sub(STREAM_START_LENGTH - 1));
+    }
+}
+
+/// Hand a chunk to the decompression threads.
+/// Returns false if decompression stopped, because the decoder was dropped.
+fn send_chunk(
+    chunk: Vec<u8>,
+    job_sender: &SyncSender<(Vec<u8>, SyncSender<DecompressedChunk>)>,
+    ordered_sender: &SyncSender<Receiver<DecompressedChunk>>,
+) -> bool {
+    let (result_sender, result_receiver) = sync_channel(1);
+    ordered_sender.send(result_receiver).is_ok() && job_sender.send((chunk, result_sender)).is_ok()
+}
+
+/// Hand the decompressed chunks to the decoder in the order of the input, until the first error.
+fn collect_chunks(
+    ordered_receiver: Receiver<Receiver<DecompressedChunk>>,
+    decompressed_sender: mpsc::Sender<DecompressedChunk>,
+) {
+    for result_receiver in ordered_receiver {
+        let result = result_receiver.recv().unwrap_or_else(|_| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "bzip2 decompression thread stopped unexpectedly",
+            ))
+        });
+        let is_error = result.is_err();
+        if decompressed_sender.blocking_send(result).is_err() || is_error {
+            return;
+        }
+    }
+}
+
+/// The offset of the first stream header at or after `offset`, which is followed by the magic bytes of a block
+/// or of the end of the stream.
+fn find_stream_start(bytes: &[u8], offset: usize) -> Option<usize> {
+    (offset..(bytes.len() + 1).saturating_sub(STREAM_START_LENGTH)).find(|&index| {
+        let candidate = &bytes[index..index + STREAM_START_LENGTH];
+        candidate.starts_with(b"BZh")
+            && (b'1'..=b'9').contains(&candidate[3])
+            && (candidate[4..] == BLOCK_MAGIC || candidate[4..] == END_OF_STREAM_MAGIC)
+    })
+}
+
+fn decompress_chunk(chunk: &[u8]) -> DecompressedChunk {
+    let mut decompressed = Vec::with_capacity(chunk.len() * 5);
+    bzip2::read::MultiBzDecoder::new(chunk).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
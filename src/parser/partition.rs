@@ -0,0 +1,166 @@
+//! Writing the parsed pages into one file per language or namespace.
+
+use crate::error::{Error, Result};
+use crate::parser::sink::PageSink;
+use crate::parser::{Page, Siteinfo};
+use log::debug;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The maximum number of partition files that are open at the same time.
+/// The English wiktionary has thousands of languages, more than the usual limit of open files.
+const MAX_OPEN_PARTITIONS: usize = 256;
+
+/// How the output is split into files.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PartitionBy {
+    /// One file per language section, e.g. `finnish.jsonl`.
+    /// Pages with multiple language sections are written into each of their files,
+    /// and pages without language sections into `no_language.jsonl`.
+    Language,
+    /// One file per namespace, e.g. `main.jsonl` or `template.jsonl`.
+    Namespace,
+}
+
+impl FromStr for PartitionBy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "language" => Ok(Self::Language),
+            "namespace" => Ok(Self::Namespace),
+            other => Err(Error::Other(format!(
+                "Unknown partitioning {other:?}, expected 'language' or 'namespace'"
+            ))),
+        }
+    }
+}
+
+/// Writes pages as one json object per line into a file per partition in the given directory.
+/// The siteinfo is written to `siteinfo.json`.
+#[derive(Debug)]
+pub struct PartitionedOutput {
+    directory: PathBuf,
+    partition_by: PartitionBy,
+    /// Namespace names by key, known once the siteinfo was written.
+    namespace_names: BTreeMap<i64, String>,
+    open_files: BTreeMap<String, BufWriter<File>>,
+    /// Files that were created already, and are appended to when opened again.
+    created_files: BTreeSet<String>,
+}
+
+impl PartitionedOutput {
+    pub fn new(directory: impl AsRef<Path>, partition_by: PartitionBy) -> Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            partition_by,
+            namespace_names: Default::default(),
+            open_files: Default::default(),
+            created_files: Default::default(),
+        })
+    }
+
+    fn partitions(&self, page: &Page) -> BTreeSet<String> {
+        match self.partition_by {
+            PartitionBy::Language => {
+                let partitions: BTreeSet<_> = page
+                    .revision
+                    .text
+                    .iter()
+                    .filter(|text| text.text.root_section.headline.level == 1)
+                    .flat_map(|text| &text.text.root_section.subsections)
+                    .map(|section| partition_file_name(&section.headline.label))
+                    .collect();
+                if partitions.is_empty() {
+                    std::iter::once("no_language".to_string()).collect()
+                } else {
+                    partitions
+                }
+            }
+            PartitionBy::Namespace => {
+                let name = match self.namespace_names.get(&page.namespace) {
+                    Some(name) if !name.is_empty() => partition_file_name(name),
+                    Some(_) => "main".to_string(),
+                    None => format!("namespace_{}", page.namespace),
+                };
+                std::iter::once(name).collect()
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        for file in self.open_files.values_mut() {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, partition: &str) -> Result<&mut BufWriter<File>> {
+        if !self.open_files.contains_key(partition) {
+            if self.open_files.len() >= MAX_OPEN_PARTITIONS {
+                debug!("Closing {} partition files", self.open_files.len());
+                self.flush()?;
+                self.open_files.clear();
+            }
+
+            let path = self.directory.join(format!("{partition}.jsonl"));
+            let file = if self.created_files.insert(partition.to_string()) {
+                File::create(path)?
+            } else {
+                OpenOptions::new().append(true).open(path)?
+            };
+            self.open_files
+                .insert(partition.to_string(), BufWriter::new(file));
+        }
+
+        Ok(self.open_files.get_mut(partition).unwrap())
+    }
+}
+
+impl PageSink for PartitionedOutput {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        self.namespace_names = siteinfo
+            .namespaces
+            .iter()
+            .map(|namespace| (namespace.key, namespace.name.clone()))
+            .collect();
+        let file = BufWriter::new(File::create(self.directory.join("siteinfo.json"))?);
+        serde_json::to_writer_pretty(file, &siteinfo)?;
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        let line = serde_json::to_string(&page)?;
+        for partition in self.partitions(&page) {
+            let file = self.open(&partition)?;
+            file.write_all(line.as_bytes())?;
+            file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+/// A file name for the partition of a language or namespace, e.g. `serbo_croatian` for `Serbo-Croatian`.
+fn partition_file_name(name: &str) -> String {
+    let mut file_name = String::new();
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            file_name.extend(c.to_lowercase());
+        } else {
+            file_name.push('_');
+        }
+    }
+    if file_name.is_empty() {
+        file_name.push_str("unnamed");
+    }
+    file_name
+}
@@ -0,0 +1,543 @@
+//! Parsing dumps that are split into multiple files, like the multi-part `pages-articles` dumps of large wikis,
+//! into a single output.
+
+use crate::error::{Error, Result};
+use crate::parser::dump::{
+    open_output_sink, open_quarantine, open_word_links, parse_dump_file_part, write_statistics,
+};
+use crate::parser::sink::{OutputFormat, PageSink};
+use crate::parser::statistics::ParseStatistics;
+use crate::parser::words::Word;
+use crate::parser::{parse_dump_file_with_options, DumpParseOptions, Page, PageRevision, Siteinfo};
+use crate::progress::{ProgressPhase, ProgressUpdate};
+use crate::wiktionary_edition::WiktionaryEdition;
+use log::{debug, info};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use wikitext_parser::Wikitext;
+
+/// Expand the given input files into a list of files.
+/// The file name of each pattern may contain the wildcards `*` and `?`, which match any amount of characters
+/// and any single character, respectively.
+/// The files matching a pattern are sorted by name, with numbers in the names compared by value,
+/// such that e.g. `pages-articles2.xml` comes before `pages-articles10.xml`.
+/// Patterns without wildcards are kept as they are, even if the file does not exist.
+pub fn expand_input_files(patterns: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut input_files = Vec::new();
+    for pattern in patterns {
+        let file_pattern = pattern
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .filter(|file_name| file_name.contains(['*', '?'].as_ref()));
+        let file_pattern = if let Some(file_pattern) = file_pattern {
+            file_pattern
+        } else {
+            input_files.push(pattern.clone());
+            continue;
+        };
+
+        let directory = match pattern.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let mut matches = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let entry = entry?;
+            if let Some(file_name) = entry.file_name().to_str() {
+                if wildcard_matches(file_pattern, file_name) && entry.file_type()?.is_file() {
+                    matches.push(pattern.with_file_name(file_name));
+                }
+            }
+        }
+        if matches.is_empty() {
+            return Err(Error::Other(format!("No input files match {pattern:?}")));
+        }
+        matches.sort_by(|a, b| natural_order(&a.to_string_lossy(), &b.to_string_lossy()));
+        input_files.extend(matches);
+    }
+    Ok(input_files)
+}
+
+fn wildcard_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<_> = pattern.chars().collect();
+    let name: Vec<_> = name.chars().collect();
+    // The positions after the last `*` in the pattern and the name, to backtrack to on a mismatch.
+    let mut backtrack = None;
+    let (mut pattern_index, mut name_index) = (0, 0);
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                pattern_index += 1;
+                backtrack = Some((pattern_index, name_index));
+            }
+            Some(&character) if character == '?' || character == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => {
+                if let Some((star_pattern_index, star_name_index)) = backtrack {
+                    pattern_index = star_pattern_index;
+                    name_index = star_name_index + 1;
+                    backtrack = Some((star_pattern_index, name_index));
+                } else {
+                    return false;
+                }
+            }
+        }
+    }
+    pattern[pattern_index..]
+        .iter()
+        .all(|character| *character == '*')
+}
+
+/// Compare strings such that runs of digits are compared by their value.
+fn natural_order(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_character), Some(b_character))
+                if a_character.is_ascii_digit() && b_character.is_ascii_digit() =>
+            {
+                let a_digits = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+                let b_digits = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+                let a_number = a[..a_digits].trim_start_matches('0');
+                let b_number = b[..b_digits].trim_start_matches('0');
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(b_number));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a = &a[a_digits..];
+                b = &b[b_digits..];
+            }
+            (Some(a_character), Some(b_character)) => {
+                if a_character != b_character {
+                    return a_character.cmp(&b_character);
+                }
+                a = &a[a_character.len_utf8()..];
+                b = &b[b_character.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Like [`parse_dump_file_with_options`], but parses a dump that is split into the given input files,
+/// e.g. as returned by [`expand_input_files`], into a single output.
+///
+/// The pages are written in the order of the input files, with the siteinfo of the first file,
+/// and the statistics, error log, quarantine and word links cover all parts.
+/// If [`DumpParseOptions::parallel_parts`] is greater than one, multiple parts are parsed at the same time,
+/// and the words of each part are handed to the word consumer after the pages of the part.
+pub async fn parse_dump_files_with_options<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_files: &[PathBuf],
+    output_file: Option<impl AsRef<Path>>,
+    word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let mut sink = open_output_sink(output_file, options)?;
+    parse_dump_files_with_sink(
+        input_files,
+        &mut sink,
+        word_consumer,
+        page_consumer,
+        error_log,
+        options,
+    )
+    .await
+}
+
+/// Like [`parse_dump_files_with_options`], but hands the siteinfo and the parsed pages to the given sink
+/// instead of writing them into an output file.
+pub async fn parse_dump_files_with_sink<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_files: &[PathBuf],
+    sink: &mut impl PageSink,
+    mut word_consumer: impl FnMut(Word) -> WordConsumerResult,
+    mut page_consumer: impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    if input_files.is_empty() {
+        return Err(Error::Other(format!("No input files given")));
+    }
+
+    let error_log = error_log.as_ref();
+    let mut error_log_writer = std::io::BufWriter::new(std::fs::File::create(error_log)?);
+    let mut quarantine = open_quarantine(options)?;
+    let mut word_links = open_word_links(options)?;
+    let mut part_sink = PartSink {
+        sink,
+        siteinfo: None,
+    };
+
+    let result = if options.parallel_parts > 1 && input_files.len() > 1 {
+        parse_parts_in_parallel(
+            input_files,
+            &mut part_sink,
+            &mut word_consumer,
+            &mut page_consumer,
+            error_log,
+            &mut error_log_writer,
+            quarantine.as_mut(),
+            word_links.as_mut(),
+            options,
+        )
+        .await
+    } else {
+        parse_parts_sequentially(
+            input_files,
+            &mut part_sink,
+            &mut word_consumer,
+            &mut page_consumer,
+            &mut error_log_writer,
+            quarantine.as_mut(),
+            word_links.as_mut(),
+            options,
+        )
+        .await
+    };
+
+    match result {
+        Ok(statistics) => {
+            part_sink.sink.finish()?;
+            write_statistics(&statistics, options)?;
+            Ok(statistics)
+        }
+        Err(Error::Cancelled) => {
+            // Complete the output of the parts parsed so far, like a cancelled single file.
+            part_sink.sink.finish()?;
+            Err(Error::Cancelled)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn parse_parts_sequentially<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_files: &[PathBuf],
+    sink: &mut impl PageSink,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log: &mut impl Write,
+    mut quarantine: Option<&mut impl Write>,
+    mut word_links: Option<&mut impl Write>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let mut statistics = ParseStatistics::default();
+    for (index, input_file) in input_files.iter().enumerate() {
+        info!(
+            "Parsing part {}/{} {input_file:?}",
+            index + 1,
+            input_files.len()
+        );
+        let part_statistics = parse_dump_file_part(
+            input_file,
+            sink,
+            word_consumer,
+            page_consumer,
+            error_log,
+            quarantine.as_deref_mut(),
+            word_links.as_deref_mut(),
+            options,
+        )
+        .await?;
+        statistics.merge(&part_statistics);
+    }
+    Ok(statistics)
+}
+
+/// The temporary files a part is parsed into by [`parse_parts_in_parallel`].
+struct PartFiles {
+    pages: PathBuf,
+    words: PathBuf,
+    error_log: PathBuf,
+    quarantine: PathBuf,
+    word_links: PathBuf,
+}
+
+impl PartFiles {
+    fn new(directory: &Path, index: usize) -> Self {
+        let file = |name: &str| directory.join(format!("part{index}-{name}"));
+        Self {
+            pages: file("pages.jsonl"),
+            words: file("words.jsonl"),
+            error_log: file("errors.log"),
+            quarantine: file("quarantine.xml"),
+            word_links: file("word-links.jsonl"),
+        }
+    }
+
+    fn remove(&self) {
+        for file in [
+            &self.pages,
+            &self.words,
+            &self.error_log,
+            &self.quarantine,
+            &self.word_links,
+        ] {
+            // Files that were never created are fine.
+            std::fs::remove_file(file).ok();
+        }
+    }
+}
+
+/// Removes the temporary directory of the parts when dropped, also if parsing fails.
+struct TemporaryDirectory(PathBuf);
+
+impl Drop for TemporaryDirectory {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_dir_all(&self.0) {
+            debug!("Could not remove temporary directory {:?}: {error}", self.0);
+        }
+    }
+}
+
+/// Parse the parts on `options.parallel_parts` threads, each with its own async runtime, into temporary files,
+/// and replay these in the order of the input files.
+#[allow(clippy::too_many_arguments)]
+async fn parse_parts_in_parallel<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    input_files: &[PathBuf],
+    sink: &mut PartSink<'_, impl PageSink>,
+    word_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    error_log_path: &Path,
+    error_log: &mut impl Write,
+    mut quarantine: Option<&mut impl Write>,
+    mut word_links: Option<&mut impl Write>,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let mut directory = error_log_path.as_os_str().to_owned();
+    directory.push(".parts");
+    let directory = TemporaryDirectory(PathBuf::from(directory));
+    std::fs::create_dir_all(&directory.0)?;
+
+    let mut receivers = Vec::new();
+    let mut queue = VecDeque::new();
+    for (index, input_file) in input_files.iter().enumerate() {
+        let (sender, receiver) = oneshot::channel();
+        let files = PartFiles::new(&directory.0, index);
+        let part_options = DumpParseOptions {
+            output_pretty: false,
+            output_format: OutputFormat::JsonLines,
+            quarantine_file: options
+                .quarantine_file
+                .as_ref()
+                .map(|_| files.quarantine.clone()),
+            siteinfo_file: None,
+            word_link_file: options
+                .word_link_file
+                .as_ref()
+                .map(|_| files.word_links.clone()),
+            partition_by: None,
+            statistics_file: None,
+            progress: None,
+            parallel_parts: 0,
+            ..options.clone()
+        };
+        queue.push_back((input_file.clone(), files, part_options, sender));
+        receivers.push((input_file, receiver));
+    }
+    let queue = Arc::new(Mutex::new(queue));
+
+    for thread_index in 0..options.parallel_parts.min(input_files.len()) {
+        let queue = queue.clone();
+        std::thread::Builder::new()
+            .name(format!("dump-part-{thread_index}"))
+            .spawn(move || loop {
+                let next = queue.lock().unwrap().pop_front();
+                let (input_file, files, part_options, sender) = if let Some(next) = next {
+                    next
+                } else {
+                    break;
+                };
+                if sender.is_closed() {
+                    // Replaying stopped early, so nobody is interested in the remaining parts.
+                    continue;
+                }
+                let result = parse_part_into_files(&input_file, &files, &part_options);
+                sender.send(result).ok();
+            })?;
+    }
+
+    // Progress is reported once per part, since the parts do not report their own progress.
+    let start = std::time::Instant::now();
+    let bytes_total = input_files
+        .iter()
+        .map(|input_file| Ok(std::fs::metadata(input_file)?.len()))
+        .sum::<Result<u64>>()?;
+    let mut bytes_done = 0;
+    let mut statistics = ParseStatistics::default();
+    let mut edition = options.word_extraction.edition.clone();
+    for (index, (input_file, receiver)) in receivers.into_iter().enumerate() {
+        let files = PartFiles::new(&directory.0, index);
+        let part_statistics = receiver.await.map_err(|_| {
+            Error::Other(format!(
+                "The thread parsing {input_file:?} stopped unexpectedly"
+            ))
+        })??;
+        info!(
+            "Parsed part {}/{} {input_file:?}",
+            index + 1,
+            input_files.len()
+        );
+        if !options.word_extraction.edition.is_known() {
+            edition = WiktionaryEdition::from_file_name(input_file);
+        }
+
+        replay_pages(&files.pages, sink, page_consumer, &mut edition)?;
+        for line in std::io::BufReader::new(std::fs::File::open(&files.words)?).lines() {
+            let word: Word = serde_json::from_str(&line?)?;
+            word_consumer(word)
+                .await
+                .map_err(|error| Error::WordConsumer { source: error })?;
+        }
+        append_file(&files.error_log, error_log)?;
+        if let Some(quarantine) = quarantine.as_deref_mut() {
+            append_file(&files.quarantine, quarantine)?;
+        }
+        if let Some(word_links) = word_links.as_deref_mut() {
+            append_file(&files.word_links, word_links)?;
+        }
+        files.remove();
+
+        statistics.merge(&part_statistics);
+        bytes_done += std::fs::metadata(input_file)?.len();
+        if let Some(progress) = &options.progress {
+            progress.report(&ProgressUpdate {
+                phase: ProgressPhase::Parse,
+                bytes_done,
+                bytes_total: Some(bytes_total),
+                pages: statistics.pages,
+                pages_total: None,
+                words: statistics.words,
+                elapsed: start.elapsed(),
+                finished: index + 1 == input_files.len(),
+            });
+        }
+    }
+    Ok(statistics)
+}
+
+/// Parse a part into its temporary files on the current thread.
+fn parse_part_into_files(
+    input_file: &Path,
+    files: &PartFiles,
+    options: &DumpParseOptions,
+) -> Result<ParseStatistics> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut words = std::io::BufWriter::new(std::fs::File::create(&files.words)?);
+    let statistics = runtime.block_on(parse_dump_file_with_options(
+        input_file,
+        Some(&files.pages),
+        |word| {
+            let result = serde_json::to_writer(&mut words, &word)
+                .map_err(Error::from)
+                .and_then(|()| Ok(words.write_all(b"\n")?));
+            std::future::ready(result.map_err(Into::into))
+        },
+        |_, _, _| {},
+        &files.error_log,
+        options,
+    ))?;
+    words.flush()?;
+    Ok(statistics)
+}
+
+/// Hand the siteinfo, pages and revisions of a part's output file to the sink, and the pages to the page consumer.
+fn replay_pages(
+    pages: &Path,
+    sink: &mut impl PageSink,
+    page_consumer: &mut impl FnMut(&WiktionaryEdition, &str, &Wikitext),
+    edition: &mut WiktionaryEdition,
+) -> Result<()> {
+    let reader = std::io::BufReader::new(std::fs::File::open(pages)?);
+    for value in serde_json::Deserializer::from_reader(reader).into_iter::<serde_json::Value>() {
+        let value = value?;
+        if value.get("dbname").is_some() {
+            let siteinfo: Siteinfo = serde_json::from_value(value)?;
+            let siteinfo_edition = WiktionaryEdition::from_dbname(siteinfo.dbname());
+            if siteinfo_edition.is_known() {
+                *edition = siteinfo_edition;
+            }
+            sink.site_info(siteinfo)?;
+        } else if value.get("revision").is_some() && value.get("id").is_some() {
+            let page: Page = serde_json::from_value(value)?;
+            if let Some(text) = page.revision().text() {
+                page_consumer(edition, page.title(), text.wikitext());
+            }
+            sink.page(page)?;
+        } else {
+            let revision: PageRevision = serde_json::from_value(value)?;
+            sink.revision(revision)?;
+        }
+    }
+    Ok(())
+}
+
+fn append_file(source: &Path, target: &mut impl Write) -> Result<()> {
+    match std::fs::File::open(source) {
+        Ok(mut source) => {
+            std::io::copy(&mut source, target)?;
+            Ok(())
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Forwards the siteinfo of the first part only, and does not finish the sink after each part.
+struct PartSink<'a, Sink> {
+    sink: &'a mut Sink,
+    siteinfo: Option<Siteinfo>,
+}
+
+impl<Sink: PageSink> PageSink for PartSink<'_, Sink> {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        if let Some(first) = &self.siteinfo {
+            if first.dbname() != siteinfo.dbname() {
+                return Err(Error::Other(format!(
+                    "A part of the dump is of database {:?}, but the first part is of database {:?}",
+                    siteinfo.dbname(),
+                    first.dbname()
+                )));
+            }
+            Ok(())
+        } else {
+            self.siteinfo = Some(siteinfo.clone());
+            self.sink.site_info(siteinfo)
+        }
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        self.sink.page(page)
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        self.sink.revision(revision)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//! Consumers of the pages parsed from a dump.
+
+use crate::error::{Error, Result};
+use crate::parser::{Page, PageRevision, Siteinfo};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::str::FromStr;
+
+/// Receives the siteinfo and the pages of a dump while it is parsed.
+///
+/// Implement this to consume parsed pages in memory, write them to a database or fan them out to multiple outputs.
+/// Sinks can be combined as tuples, which hand everything to both sinks, and as `Option`s, which ignore everything if `None`.
+pub trait PageSink {
+    /// Called once for the siteinfo, before any page.
+    /// Exports may not have a siteinfo, in which case this is not called.
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()>;
+
+    /// Called for each page in the order of the dump.
+    fn page(&mut self, page: Page) -> Result<()>;
+
+    /// Called for each earlier revision of a page when streaming the revisions of a full-history dump,
+    /// as soon as the revision is parsed and before its page.
+    /// Ignores the revision by default.
+    fn revision(&mut self, _revision: PageRevision) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after the last page, e.g. to flush buffered output.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<Sink: PageSink + ?Sized> PageSink for &mut Sink {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        (**self).site_info(siteinfo)
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        (**self).page(page)
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        (**self).revision(revision)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+}
+
+impl<Sink: PageSink + ?Sized> PageSink for Box<Sink> {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        (**self).site_info(siteinfo)
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        (**self).page(page)
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        (**self).revision(revision)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+}
+
+impl<Sink: PageSink> PageSink for Option<Sink> {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        if let Some(sink) = self {
+            sink.site_info(siteinfo)?;
+        }
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        if let Some(sink) = self {
+            sink.page(page)?;
+        }
+        Ok(())
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        if let Some(sink) = self {
+            sink.revision(revision)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(sink) = self {
+            sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+impl<First: PageSink, Second: PageSink> PageSink for (First, Second) {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        self.0.site_info(siteinfo.clone())?;
+        self.1.site_info(siteinfo)
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        self.0.page(page.clone())?;
+        self.1.page(page)
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        self.0.revision(revision.clone())?;
+        self.1.revision(revision)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.0.finish()?;
+        self.1.finish()
+    }
+}
+
+/// Collects the pages in memory, ignoring the siteinfo.
+impl PageSink for Vec<Page> {
+    fn site_info(&mut self, _siteinfo: Siteinfo) -> Result<()> {
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        self.push(page);
+        Ok(())
+    }
+}
+
+/// Ignores everything.
+impl PageSink for () {
+    fn site_info(&mut self, _siteinfo: Siteinfo) -> Result<()> {
+        Ok(())
+    }
+
+    fn page(&mut self, _page: Page) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps the siteinfo, ignoring the pages, e.g. to configure processing that depends on it after parsing.
+#[derive(Debug, Clone, Default)]
+pub struct SiteinfoSink {
+    pub siteinfo: Option<Siteinfo>,
+}
+
+impl PageSink for SiteinfoSink {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        self.siteinfo = Some(siteinfo);
+        Ok(())
+    }
+
+    fn page(&mut self, _page: Page) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// How the json objects of the output are separated.
+/// Serialized with the same names as parsed by [`FromStr`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Json objects are concatenated without separator.
+    Json,
+    /// One json object per line, also known as NDJSON.
+    /// Objects are never pretty-printed in this format.
+    #[serde(alias = "jsonl", alias = "ndjson")]
+    JsonLines,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Json
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json-lines" | "jsonl" | "ndjson" => Ok(Self::JsonLines),
+            other => Err(Error::Other(format!(
+                "Unknown output format {other:?}, expected 'json' or 'json-lines'"
+            ))),
+        }
+    }
+}
+
+/// Writes the siteinfo and the pages as json objects in the given format.
+#[derive(Debug)]
+pub struct JsonPageSink<Output> {
+    output: Output,
+    format: OutputFormat,
+    pretty: bool,
+    write_siteinfo: bool,
+    write_pages: bool,
+}
+
+impl<Output: Write> JsonPageSink<Output> {
+    /// Write concatenated json objects, see [`OutputFormat::Json`].
+    pub fn new(output: Output, pretty: bool) -> Self {
+        Self::with_format(output, OutputFormat::Json, pretty)
+    }
+
+    /// Write json objects in the given format.
+    /// `pretty` is ignored for [`OutputFormat::JsonLines`].
+    pub fn with_format(output: Output, format: OutputFormat, pretty: bool) -> Self {
+        Self {
+            output,
+            format,
+            pretty: pretty && format != OutputFormat::JsonLines,
+            write_siteinfo: true,
+            write_pages: true,
+        }
+    }
+
+    /// Write only the siteinfo, e.g. into a separate file.
+    pub fn siteinfo_only(mut self) -> Self {
+        self.write_pages = false;
+        self
+    }
+
+    /// Write only the pages, e.g. if the siteinfo is written into a separate file.
+    pub fn pages_only(mut self) -> Self {
+        self.write_siteinfo = false;
+        self
+    }
+
+    pub fn into_inner(self) -> Output {
+        self.output
+    }
+}
+
+impl<Output: Write> JsonPageSink<Output> {
+    fn write(&mut self, value: &impl serde::Serialize) -> Result<()> {
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut self.output, value)?;
+        } else {
+            serde_json::to_writer(&mut self.output, value)?;
+        }
+        if self.format == OutputFormat::JsonLines {
+            self.output.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl<Output: Write> PageSink for JsonPageSink<Output> {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        if self.write_siteinfo {
+            self.write(&siteinfo)?;
+            self.output.flush()?;
+        }
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        if self.write_pages {
+            self.write(&page)?;
+        }
+        Ok(())
+    }
+
+    fn revision(&mut self, revision: PageRevision) -> Result<()> {
+        if self.write_pages {
+            self.write(&revision)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+/// Read the siteinfo written by a [`JsonPageSink`] in either format, which is its first object.
+/// Returns `None` if the first object is not a siteinfo, e.g. if it was written with [`pages_only`](JsonPageSink::pages_only).
+pub fn read_json_siteinfo(input: impl std::io::Read) -> Result<Option<Siteinfo>> {
+    match serde_json::Deserializer::from_reader(input)
+        .into_iter::<serde_json::Value>()
+        .next()
+    {
+        Some(Ok(value)) if value.get("dbname").is_some() => {
+            Ok(Some(serde_json::from_value(value)?))
+        }
+        Some(Ok(_)) | None => Ok(None),
+        Some(Err(error)) => Err(error.into()),
+    }
+}
+
+/// Read the pages written by a [`JsonPageSink`] in either format, skipping the siteinfo and streamed revisions.
+/// The pages are read lazily, so the input does not need to fit into memory.
+pub fn read_json_pages(input: impl std::io::Read) -> impl Iterator<Item = Result<Page>> {
+    serde_json::Deserializer::from_reader(input)
+        .into_iter::<serde_json::Value>()
+        .filter_map(|value| match value {
+            // Only pages have a revision and an id, streamed revisions have a page id instead.
+            Ok(value) if value.get("revision").is_some() && value.get("id").is_some() => {
+                Some(serde_json::from_value(value).map_err(Error::from))
+            }
+            Ok(_) => None,
+            Err(error) => Some(Err(error.into())),
+        })
+}
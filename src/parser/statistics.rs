@@ -0,0 +1,373 @@
+use crate::error::{Error, Result};
+use crate::parser::sink::{read_json_pages, PageSink};
+use crate::parser::words::classification::{PageClassifier, PagePipeline};
+use crate::parser::words::language_names::UNKNOWN_LANGUAGE_CODE;
+use crate::parser::words::{extract_words_with_namespace, Word, WordExtractionOptions};
+use crate::parser::{parse_dump_file_with_sink, DumpParseOptions, Namespace, Page, Siteinfo};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Counts of the pages of a dump, in total and per namespace, collected while parsing it.
+/// Pages skipped by a [`TitleFilter`](crate::parser::titles::TitleFilter) are not counted.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct ParseStatistics {
+    pub pages: u64,
+    pub redirects: u64,
+    /// The bytes of the texts of the pages, before any compatibility shims or template expansion.
+    pub text_bytes: u64,
+    /// Statistics per namespace, by the key of the namespace.
+    /// Namespaces listed in the siteinfo are included even if they contain no pages.
+    pub namespaces: BTreeMap<i64, NamespaceStatistics>,
+    /// The amount of extracted words.
+    #[serde(default)]
+    pub words: u64,
+    /// The language names of extracted words that could not be resolved to a language code,
+    /// with the amount of words of each.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unknown_language_names: BTreeMap<String, u64>,
+    /// The amount of pages that were skipped or quarantined because of wikitext errors,
+    /// see [`WikitextErrorPolicy`](crate::parser::WikitextErrorPolicy).
+    /// They are included in the counts of pages.
+    #[serde(default)]
+    pub rejected_pages: u64,
+}
+
+/// Counts of the pages of a single namespace.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct NamespaceStatistics {
+    /// The name of the namespace as given in the siteinfo, if any, e.g. `Thesaurus`.
+    /// The main namespace has an empty name.
+    pub name: Option<String>,
+    pub pages: u64,
+    pub redirects: u64,
+    pub text_bytes: u64,
+}
+
+impl ParseStatistics {
+    /// Take the names of the namespaces from the siteinfo.
+    pub fn set_namespace_names(&mut self, namespaces: &[Namespace]) {
+        for namespace in namespaces {
+            self.namespaces.entry(namespace.key).or_default().name = Some(namespace.name.clone());
+        }
+    }
+
+    /// Count the page, whose text has the given amount of bytes.
+    pub fn add_page(&mut self, page: &Page, text_bytes: usize) {
+        let is_redirect = u64::from(page.redirect.is_some());
+        let text_bytes = text_bytes as u64;
+
+        self.pages += 1;
+        self.redirects += is_redirect;
+        self.text_bytes += text_bytes;
+
+        let namespace = self.namespaces.entry(page.namespace).or_default();
+        namespace.pages += 1;
+        namespace.redirects += is_redirect;
+        namespace.text_bytes += text_bytes;
+    }
+
+    /// Count the extracted word, and its language name if it could not be resolved to a language code.
+    /// Each unknown language name is warned about when it first occurs.
+    pub fn add_word(&mut self, word: &Word) {
+        self.words += 1;
+        if &*word.language_code == UNKNOWN_LANGUAGE_CODE {
+            let count = self
+                .unknown_language_names
+                .entry(word.language_english_name.to_string())
+                .or_default();
+            if *count == 0 {
+                warn!(
+                    "Unknown language name {:?} of word {:?}",
+                    word.language_english_name, word.word
+                );
+            }
+            *count += 1;
+        }
+    }
+
+    /// Add the counts of other statistics, e.g. of another part of the same dump.
+    pub fn merge(&mut self, other: &Self) {
+        self.pages += other.pages;
+        self.redirects += other.redirects;
+        self.text_bytes += other.text_bytes;
+        self.words += other.words;
+        self.rejected_pages += other.rejected_pages;
+        for (key, other_namespace) in &other.namespaces {
+            let namespace = self.namespaces.entry(*key).or_default();
+            if namespace.name.is_none() {
+                namespace.name = other_namespace.name.clone();
+            }
+            namespace.pages += other_namespace.pages;
+            namespace.redirects += other_namespace.redirects;
+            namespace.text_bytes += other_namespace.text_bytes;
+        }
+        for (name, count) in &other.unknown_language_names {
+            *self.unknown_language_names.entry(name.clone()).or_default() += count;
+        }
+    }
+
+    /// The namespaces that contain at least one page.
+    pub fn non_empty_namespaces(&self) -> impl Iterator<Item = (i64, &NamespaceStatistics)> {
+        self.namespaces
+            .iter()
+            .filter(|(_, namespace)| namespace.pages > 0)
+            .map(|(key, namespace)| (*key, namespace))
+    }
+
+    /// Write the statistics as pretty json, omitting namespaces without pages.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let statistics = Self {
+            namespaces: self
+                .non_empty_namespaces()
+                .map(|(key, namespace)| (key, namespace.clone()))
+                .collect(),
+            ..self.clone()
+        };
+        let output = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(output, &statistics)?;
+        Ok(())
+    }
+}
+
+/// Formats the statistics as one line for the totals, one line per non-empty namespace,
+/// and a line for words with unknown language names, if any.
+impl Display for ParseStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} pages, {} redirects, {}MiB of text",
+            self.pages,
+            self.redirects,
+            self.text_bytes / (1024 * 1024)
+        )?;
+        for (key, namespace) in self.non_empty_namespaces() {
+            write!(
+                f,
+                "\n  namespace {key} ({}): {} pages, {} redirects, {}MiB of text",
+                namespace.name.as_deref().unwrap_or("unknown"),
+                namespace.pages,
+                namespace.redirects,
+                namespace.text_bytes / (1024 * 1024)
+            )?;
+        }
+        if !self.unknown_language_names.is_empty() {
+            write!(
+                f,
+                "\n  {} words with {} unknown language names",
+                self.unknown_language_names.values().sum::<u64>(),
+                self.unknown_language_names.len()
+            )?;
+        }
+        if self.rejected_pages > 0 {
+            write!(
+                f,
+                "\n  {} pages rejected because of wikitext errors",
+                self.rejected_pages
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The amount of entries of each ranking shown when formatting [`DumpStatistics`].
+const DISPLAYED_RANKING_LENGTH: usize = 10;
+
+/// Statistics of the contents of a dump, or of the parsed output of a dump, to report the numbers of a release.
+/// Collected by [`collect_dump_statistics`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct DumpStatistics {
+    /// The counts of pages, redirects, text bytes and words, in total and per namespace.
+    pub pages: ParseStatistics,
+    /// The amount of extracted words per english language name.
+    pub words_per_language: BTreeMap<String, u64>,
+    /// The amount of extracted words per canonical word type.
+    pub words_per_type: BTreeMap<String, u64>,
+    /// The amount of sections whose heading is not recognised by word extraction, per heading as written.
+    pub unknown_sections: BTreeMap<String, u64>,
+    /// The amount of errors reported by word extraction, including the unknown sections.
+    pub extraction_errors: u64,
+}
+
+impl DumpStatistics {
+    /// Count the page, whose text bytes are only known if the page was parsed by this version of the crate.
+    pub fn add_page(&mut self, page: &Page) {
+        let text_bytes = page
+            .revision()
+            .text()
+            .and_then(|text| text.bytes())
+            .unwrap_or_default();
+        self.pages.add_page(page, text_bytes as usize);
+    }
+
+    pub fn add_word(&mut self, word: &Word) {
+        self.pages.add_word(word);
+        *self
+            .words_per_language
+            .entry(word.language_english_name.to_string())
+            .or_default() += 1;
+        *self
+            .words_per_type
+            .entry(word.word_type.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn add_extraction_error(&mut self, error: &Error) {
+        self.extraction_errors += 1;
+        if let Error::UnknownHeading { label, .. } = error {
+            *self
+                .unknown_sections
+                .entry(label.trim().to_string())
+                .or_default() += 1;
+        }
+    }
+
+    /// The average size of the texts of the pages in bytes, or `None` if there are no pages.
+    pub fn average_text_bytes(&self) -> Option<f64> {
+        if self.pages.pages == 0 {
+            None
+        } else {
+            Some(self.pages.text_bytes as f64 / self.pages.pages as f64)
+        }
+    }
+
+    /// Write the statistics as pretty json, omitting namespaces without pages.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let statistics = Self {
+            pages: ParseStatistics {
+                namespaces: self
+                    .pages
+                    .non_empty_namespaces()
+                    .map(|(key, namespace)| (key, namespace.clone()))
+                    .collect(),
+                ..self.pages.clone()
+            },
+            ..self.clone()
+        };
+        let output = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(output, &statistics)?;
+        Ok(())
+    }
+}
+
+/// Formats the statistics like [`ParseStatistics`], followed by the average text size
+/// and the most frequent languages, word types and unknown sections.
+impl Display for DumpStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pages)?;
+        if let Some(average_text_bytes) = self.average_text_bytes() {
+            write!(f, "\n{average_text_bytes:.0} bytes of text per page")?;
+        }
+        write!(f, "\n{} words", self.pages.words)?;
+        write_ranking(f, "languages", &self.words_per_language)?;
+        write_ranking(f, "word types", &self.words_per_type)?;
+        write!(
+            f,
+            "\n{} extraction errors, {} in unknown sections",
+            self.extraction_errors,
+            self.unknown_sections.values().sum::<u64>()
+        )?;
+        write_ranking(f, "unknown sections", &self.unknown_sections)
+    }
+}
+
+fn write_ranking(
+    f: &mut Formatter<'_>,
+    name: &str,
+    counts: &BTreeMap<String, u64>,
+) -> std::fmt::Result {
+    let mut ranking: Vec<_> = counts.iter().collect();
+    ranking.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    if ranking.is_empty() {
+        return Ok(());
+    }
+    write!(f, "\n{} {name}, most frequent:", ranking.len())?;
+    for (key, count) in ranking.into_iter().take(DISPLAYED_RANKING_LENGTH) {
+        write!(f, "\n  {key}: {count}")?;
+    }
+    Ok(())
+}
+
+/// Collect the statistics of a dump, or of a `.json`, `.jsonl` or `.ndjson` file written by
+/// [`parse_dump_file_with_options`](crate::parser::parse_dump_file_with_options).
+///
+/// Words are extracted with the given word extraction options. Errors of parsing are written to `error_log`.
+/// Parsed output has no siteinfo, so the names of the namespaces are unknown,
+/// and its text bytes are only known if it was written by this version of the crate.
+pub async fn collect_dump_statistics(
+    input_file: impl AsRef<Path>,
+    error_log: impl AsRef<Path>,
+    options: &DumpParseOptions,
+) -> Result<DumpStatistics> {
+    let input_file = input_file.as_ref();
+    let mut sink = StatisticsSink {
+        statistics: DumpStatistics::default(),
+        word_extraction: &options.word_extraction,
+    };
+
+    let is_parsed_output = input_file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| matches!(extension, "json" | "jsonl" | "ndjson"))
+        .unwrap_or(false);
+    if is_parsed_output {
+        let input = std::io::BufReader::new(std::fs::File::open(input_file)?);
+        for page in read_json_pages(input) {
+            sink.page(page?)?;
+        }
+    } else {
+        // The sink extracts the words itself, since it needs the extraction errors.
+        let mut parse_options = options.clone();
+        parse_options.word_extraction.page_classifier = PageClassifier {
+            rules: Vec::new(),
+            default_pipeline: PagePipeline::Ignore,
+        };
+        parse_dump_file_with_sink(
+            input_file,
+            &mut sink,
+            |_| std::future::ready(Ok(())),
+            |_, _, _| {},
+            error_log,
+            &parse_options,
+        )
+        .await?;
+    }
+
+    Ok(sink.statistics)
+}
+
+struct StatisticsSink<'a> {
+    statistics: DumpStatistics,
+    word_extraction: &'a WordExtractionOptions,
+}
+
+impl PageSink for StatisticsSink<'_> {
+    fn site_info(&mut self, siteinfo: Siteinfo) -> Result<()> {
+        self.statistics
+            .pages
+            .set_namespace_names(&siteinfo.namespaces);
+        Ok(())
+    }
+
+    fn page(&mut self, page: Page) -> Result<()> {
+        self.statistics.add_page(&page);
+        if let Some(text) = page.revision().text() {
+            let statistics = &mut self.statistics;
+            let mut words = Vec::new();
+            extract_words_with_namespace(
+                self.word_extraction,
+                Some(page.namespace()),
+                page.title(),
+                text.wikitext(),
+                |word| words.push(word),
+                |error| statistics.add_extraction_error(&error),
+            );
+            for word in &words {
+                statistics.add_word(word);
+            }
+        }
+        Ok(())
+    }
+}
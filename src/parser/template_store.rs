@@ -0,0 +1,345 @@
+use crate::error::{Error, Result};
+use crate::parser::dump::open_dump_file;
+use crate::parser::words::templates::Template;
+use crate::parser::xml::{dump_reader, read_relevant_event, RelevantEvent};
+use lazy_static::lazy_static;
+use log::{debug, info};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+lazy_static! {
+    static ref NOINCLUDE_PATTERN: Regex = Regex::new(r"(?s)<noinclude>.*?</noinclude>").unwrap();
+    static ref ONLYINCLUDE_PATTERN: Regex =
+        Regex::new(r"(?s)<onlyinclude>(.*?)</onlyinclude>").unwrap();
+    static ref INCLUDEONLY_TAG_PATTERN: Regex = Regex::new(r"</?includeonly>").unwrap();
+}
+
+/// Templates are expanded at most this deep, to stop recursive templates.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+/// The sources of the `Template:` and `Module:` pages of a dump, collected in a first pass over the dump.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct TemplateStore {
+    /// Maps normalised template names without namespace to their wikitext.
+    templates: BTreeMap<String, String>,
+    /// Maps normalised module names without namespace to their lua source.
+    modules: BTreeMap<String, String>,
+}
+
+impl TemplateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect all templates and modules of a dump file.
+    /// This only reads titles and texts, and does not parse any wikitext, so it is much faster than a full pass.
+    pub async fn from_dump_file(input_file: impl AsRef<Path>) -> Result<Self> {
+        let input_file = input_file.as_ref();
+        info!("Collecting templates and modules of {input_file:?}");
+        let mut reader = dump_reader(open_dump_file(input_file).await?).await?;
+        let mut buffer = Vec::new();
+        let mut store = Self::new();
+        let mut current_tag = None;
+        let mut title: Option<String> = None;
+
+        loop {
+            match read_relevant_event(&mut reader, &mut buffer).await? {
+                RelevantEvent::Start(tag) => {
+                    current_tag = Some(tag.name().into_inner().to_vec());
+                    if current_tag.as_deref() == Some(b"page") {
+                        title = None;
+                    }
+                }
+                RelevantEvent::End(_) | RelevantEvent::Empty(_) => current_tag = None,
+                RelevantEvent::Text(text) => match current_tag.as_deref() {
+                    Some(b"title") => title = Some(text),
+                    Some(b"text") => {
+                        if let Some(title) = &title {
+                            store.insert(title, text);
+                        }
+                    }
+                    _ => {}
+                },
+                RelevantEvent::Eof => break,
+            }
+        }
+
+        info!(
+            "Collected {} templates and {} modules",
+            store.templates.len(),
+            store.modules.len()
+        );
+        Ok(store)
+    }
+
+    /// Insert a page if it is a template or module, otherwise ignore it.
+    pub fn insert(&mut self, title: &str, text: String) {
+        if let Some(name) = title.strip_prefix("Template:") {
+            self.templates.insert(normalise_name(name), text);
+        } else if let Some(name) = title.strip_prefix("Module:") {
+            self.modules.insert(normalise_name(name), text);
+        }
+    }
+
+    /// The wikitext of the template with the given name, without the `Template:` namespace.
+    pub fn template(&self, name: &str) -> Option<&str> {
+        self.templates
+            .get(&normalise_name(name))
+            .map(String::as_str)
+    }
+
+    /// The lua source of the module with the given name, without the `Module:` namespace.
+    pub fn module(&self, name: &str) -> Option<&str> {
+        self.modules.get(&normalise_name(name)).map(String::as_str)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+/// Expands invocations of selected templates in raw wikitext, using the sources of a [`TemplateStore`].
+///
+/// Only parameters and nested invocations of selected templates are expanded.
+/// Parser functions and modules are not evaluated, so templates that rely on them should not be selected.
+#[derive(Debug, Clone)]
+pub struct TemplateExpander {
+    store: TemplateStore,
+    selected: BTreeSet<String>,
+}
+
+impl TemplateExpander {
+    pub fn new(
+        store: TemplateStore,
+        selected: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self> {
+        let selected: BTreeSet<_> = selected
+            .into_iter()
+            .map(|name| normalise_name(name.as_ref()))
+            .collect();
+        if let Some(missing) = selected
+            .iter()
+            .find(|name| !store.templates.contains_key(*name))
+        {
+            return Err(Error::Other(format!(
+                "Selected template {missing:?} is not in the template store"
+            )));
+        }
+        Ok(Self { store, selected })
+    }
+
+    /// Expand the invocations of the selected templates in the raw wikitext of a page.
+    pub fn expand_wikitext(&self, text: &str) -> String {
+        self.expand(text, 0)
+    }
+
+    /// Expand a single invocation, or return `None` if the template is not selected.
+    pub fn expand_template(&self, template: &Template) -> Option<String> {
+        let name = normalise_name(&template.name);
+        if !self.selected.contains(&name) {
+            return None;
+        }
+
+        let mut arguments = template.named.clone();
+        for (index, value) in template.positional.iter().enumerate() {
+            arguments.insert((index + 1).to_string(), value.clone());
+        }
+        Some(self.expand_body(&name, &arguments, 0))
+    }
+
+    fn expand(&self, text: &str, depth: usize) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            result.push_str(&rest[..start]);
+            let after_start = &rest[start + 2..];
+            let end = if let Some(end) = find_closing(after_start, "{{", "}}") {
+                end
+            } else {
+                // Unbalanced braces, keep the rest as is.
+                result.push_str(&rest[start..]);
+                return result;
+            };
+            let inner = &after_start[..end];
+            rest = &after_start[end + 2..];
+
+            let parts = split_top_level(inner);
+            let name = normalise_name(parts[0]);
+            if depth < MAX_EXPANSION_DEPTH && self.selected.contains(&name) {
+                let mut arguments = BTreeMap::new();
+                let mut position = 0;
+                for part in &parts[1..] {
+                    let part = self.expand(part, depth + 1);
+                    if let Some((key, value)) = named_argument(&part) {
+                        arguments.insert(key.to_string(), value.trim().to_string());
+                    } else {
+                        position += 1;
+                        arguments.insert(position.to_string(), part);
+                    }
+                }
+                result.push_str(&self.expand_body(&name, &arguments, depth + 1));
+            } else {
+                result.push_str("{{");
+                result.push_str(&self.expand(inner, depth + 1));
+                result.push_str("}}");
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    fn expand_body(
+        &self,
+        name: &str,
+        arguments: &BTreeMap<String, String>,
+        depth: usize,
+    ) -> String {
+        let body = self
+            .store
+            .templates
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or_default();
+        debug!("Expanding template {name:?}");
+        let body = transcluded_part(body);
+        let body = substitute_parameters(&body, arguments);
+        self.expand(&body, depth)
+    }
+}
+
+/// Template names are case-insensitive in their first letter, and underscores are spaces.
+fn normalise_name(name: &str) -> String {
+    let name = name.trim().replace('_', " ");
+    let mut chars = name.chars();
+    chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .chain(chars)
+        .collect()
+}
+
+/// The part of a template that is transcluded, according to its `noinclude`, `onlyinclude` and `includeonly` tags.
+fn transcluded_part(body: &str) -> String {
+    let body = if ONLYINCLUDE_PATTERN.is_match(body) {
+        ONLYINCLUDE_PATTERN
+            .captures_iter(body)
+            .map(|captures| captures[1].to_string())
+            .collect()
+    } else {
+        NOINCLUDE_PATTERN.replace_all(body, "").into_owned()
+    };
+    INCLUDEONLY_TAG_PATTERN.replace_all(&body, "").into_owned()
+}
+
+/// Replace parameters like `{{{1}}}` or `{{{lang|en}}}` by their arguments or defaults.
+/// Parameters without argument and default are kept.
+fn substitute_parameters(body: &str, arguments: &BTreeMap<String, String>) -> String {
+    let mut result = String::with_capacity(body.len());
+    let mut rest = body;
+
+    while let Some(start) = rest.find("{{{") {
+        result.push_str(&rest[..start]);
+        let after_start = &rest[start + 3..];
+        let end = if let Some(end) = find_closing(after_start, "{{{", "}}}") {
+            end
+        } else {
+            result.push_str(&rest[start..]);
+            return result;
+        };
+        let inner = &after_start[..end];
+        rest = &after_start[end + 3..];
+
+        let (name, default) = if let Some((name, default)) = inner.split_once('|') {
+            (name.trim(), Some(default))
+        } else {
+            (inner.trim(), None)
+        };
+        if let Some(argument) = arguments.get(name) {
+            result.push_str(argument);
+        } else if let Some(default) = default {
+            result.push_str(&substitute_parameters(default, arguments));
+        } else {
+            result.push_str("{{{");
+            result.push_str(inner);
+            result.push_str("}}}");
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// The offset of the delimiter closing an expression whose opening delimiter precedes the text.
+fn find_closing(text: &str, open: &str, close: &str) -> Option<usize> {
+    let mut depth = 1;
+    let mut offset = 0;
+    while offset < text.len() {
+        let rest = &text[offset..];
+        if rest.starts_with(open) {
+            depth += 1;
+            offset += open.len();
+        } else if rest.starts_with(close) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(offset);
+            }
+            offset += close.len();
+        } else {
+            offset += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+    None
+}
+
+/// Split the inside of a template invocation at the pipes that are not within nested templates or links.
+fn split_top_level(inner: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut part_start = 0;
+    let bytes = inner.as_bytes();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        match &bytes[offset..] {
+            [b'{', b'{', ..] | [b'[', b'[', ..] => {
+                depth += 1;
+                offset += 2;
+            }
+            [b'}', b'}', ..] | [b']', b']', ..] => {
+                depth = depth.saturating_sub(1);
+                offset += 2;
+            }
+            [b'|', ..] if depth == 0 => {
+                parts.push(&inner[part_start..offset]);
+                offset += 1;
+                part_start = offset;
+            }
+            _ => offset += 1,
+        }
+    }
+    parts.push(&inner[part_start..]);
+    parts
+}
+
+/// Split an argument like `lang=en` into its name and value, if it is named.
+fn named_argument(argument: &str) -> Option<(&str, &str)> {
+    let (key, value) = argument.split_once('=')?;
+    let key = key.trim();
+    if key.is_empty() || key.contains(['{', '[', '<']) {
+        None
+    } else {
+        Some((key, value))
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::{Error, Result};
+use crate::parser::dump::open_dump_file;
+use crate::parser::xml::{dump_reader, read_relevant_event, RelevantEvent};
+use log::info;
+use quick_xml::name::QName;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+/// The title of a page, as listed in the all-titles dump, but with the id of the page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TitleEntry {
+    pub namespace: i64,
+    pub title: String,
+    pub page_id: i64,
+}
+
+/// Collect the titles of all pages of a dump file.
+/// Revisions are skipped without being unescaped or parsed, so this is much faster than a full pass.
+pub async fn extract_titles(
+    input_file: impl AsRef<Path>,
+    mut title_consumer: impl FnMut(TitleEntry) -> Result<()>,
+) -> Result<()> {
+    let input_file = input_file.as_ref();
+    info!("Extracting titles of {input_file:?}");
+    let mut reader = dump_reader(open_dump_file(input_file).await?).await?;
+    let mut buffer = Vec::new();
+    let mut skip_buffer = Vec::new();
+
+    let mut current_tag = None;
+    let mut title = None;
+    let mut namespace = None;
+    let mut page_id = None;
+
+    loop {
+        match read_relevant_event(&mut reader, &mut buffer).await? {
+            RelevantEvent::Start(tag) => match tag.name().into_inner() {
+                b"siteinfo" | b"revision" | b"upload" => {
+                    reader
+                        .read_to_end_into_async(tag.name(), &mut skip_buffer)
+                        .await?;
+                    skip_buffer.clear();
+                }
+                b"page" => {
+                    title = None;
+                    namespace = None;
+                    page_id = None;
+                }
+                name => current_tag = Some(name.to_vec()),
+            },
+            RelevantEvent::End(tag) => {
+                current_tag = None;
+                if tag.name() == QName(b"page") {
+                    match (title.take(), namespace.take(), page_id.take()) {
+                        (Some(title), Some(namespace), Some(page_id)) => {
+                            title_consumer(TitleEntry {
+                                namespace,
+                                title,
+                                page_id,
+                            })?;
+                        }
+                        (title, _, _) => {
+                            return Err(Error::Other(format!(
+                                "Missing title, namespace or id in page {title:?}"
+                            )))
+                        }
+                    }
+                }
+            }
+            RelevantEvent::Empty(_) => {}
+            RelevantEvent::Text(text) => match current_tag.as_deref() {
+                Some(b"title") => title = Some(text),
+                Some(b"ns") => {
+                    namespace = Some(text.trim().parse().map_err(|_| {
+                        Error::Other(format!("Namespace is not an integer: {text:?}"))
+                    })?)
+                }
+                Some(b"id") => {
+                    page_id = Some(text.trim().parse().map_err(|_| {
+                        Error::Other(format!("Page id is not an integer: {text:?}"))
+                    })?)
+                }
+                _ => {}
+            },
+            RelevantEvent::Eof => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the titles of all pages of a dump file as `namespace \t title \t page id` lines.
+/// Returns the number of titles written.
+pub async fn write_titles(input_file: impl AsRef<Path>, output: impl Write) -> Result<u64> {
+    let mut output = std::io::BufWriter::new(output);
+    let mut count = 0;
+    extract_titles(input_file, |entry| {
+        writeln!(
+            output,
+            "{}\t{}\t{}",
+            entry.namespace, entry.title, entry.page_id
+        )?;
+        count += 1;
+        Ok(())
+    })
+    .await?;
+    output.flush()?;
+    Ok(count)
+}
+
+/// Restricts parsing to pages with certain titles.
+/// A title matches if it matches the pattern or is in the title list.
+#[derive(Debug, Clone, Default)]
+pub struct TitleFilter {
+    pattern: Option<Regex>,
+    titles: HashSet<String>,
+}
+
+impl TitleFilter {
+    /// A filter that matches no title until a pattern or titles are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match titles that contain a match of the regex. Use `^` and `$` to match whole titles.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self> {
+        self.pattern = Some(
+            Regex::new(pattern)
+                .map_err(|error| Error::Other(format!("Invalid title pattern: {error}")))?,
+        );
+        Ok(self)
+    }
+
+    /// Match the given titles exactly.
+    pub fn with_titles(mut self, titles: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.titles.extend(titles.into_iter().map(Into::into));
+        self
+    }
+
+    /// Match the titles listed in the file, one per line.
+    /// Files written by [`write_titles`] are accepted as well, and their titles are taken from the second column.
+    pub fn with_title_list_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let titles: Vec<_> = content
+            .lines()
+            .map(|line| line.split('\t').nth(1).unwrap_or(line).trim())
+            .filter(|title| !title.is_empty())
+            .map(ToString::to_string)
+            .collect();
+        Ok(self.with_titles(titles))
+    }
+
+    pub fn matches(&self, title: &str) -> bool {
+        self.titles.contains(title)
+            || self
+                .pattern
+                .as_ref()
+                .map(|pattern| pattern.is_match(title))
+                .unwrap_or(false)
+    }
+}
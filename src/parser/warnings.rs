@@ -0,0 +1,227 @@
+//! Structured warnings about unexpected content of dumps that parsing continues after.
+//!
+//! By default, warnings are logged.
+//! Pass a [`WarningReporter`] in the `warnings` field of [`DumpParseOptions`](crate::parser::DumpParseOptions)
+//! to count or filter warnings by their [`WarningKind`] instead, or to abort parsing on specific kinds.
+
+use crate::error::{Error, Result};
+use crate::log_excerpt::excerpt;
+use log::{log, Level};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Something unexpected in a dump that parsing continues after.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Warning {
+    /// A tag the parser does not know at its position, which is ignored.
+    UnknownTag { tag: String },
+    /// An attribute the parser does not know, which is ignored.
+    UnknownAttribute { tag: String, attribute: String },
+    /// Text where the parser expects only tags, which is ignored.
+    UnexpectedText { text: String },
+    /// A section whose heading is not recognised by word extraction, which is skipped.
+    UnknownSection {
+        title: String,
+        language_english_name: String,
+        heading: String,
+    },
+    /// The length of a text differs from the length stated by its `bytes` attribute.
+    LengthMismatch {
+        title: Option<String>,
+        stated: usize,
+        actual: usize,
+    },
+    /// An optional field is missing.
+    MissingField { tag: String, field: String },
+    /// The SHA-1 of the text of a revision differs from the SHA-1 stated in the dump.
+    Sha1Mismatch {
+        title: Option<String>,
+        revision_id: i64,
+        stated: String,
+        actual: String,
+    },
+    /// The edition given in the options or inferred from the file name differs from the edition of the siteinfo,
+    /// which is used instead.
+    EditionMismatch { given: String, siteinfo: String },
+    /// The rest of a malformed page was skipped and quarantined.
+    SkippedPage { error: String },
+}
+
+/// The kind of a [`Warning`], to filter warnings by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    UnknownTag,
+    UnknownAttribute,
+    UnexpectedText,
+    UnknownSection,
+    LengthMismatch,
+    MissingField,
+    Sha1Mismatch,
+    EditionMismatch,
+    SkippedPage,
+}
+
+impl FromStr for WarningKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "unknown_tag" => Ok(Self::UnknownTag),
+            "unknown_attribute" => Ok(Self::UnknownAttribute),
+            "unexpected_text" => Ok(Self::UnexpectedText),
+            "unknown_section" => Ok(Self::UnknownSection),
+            "length_mismatch" => Ok(Self::LengthMismatch),
+            "missing_field" => Ok(Self::MissingField),
+            "sha1_mismatch" => Ok(Self::Sha1Mismatch),
+            "edition_mismatch" => Ok(Self::EditionMismatch),
+            "skipped_page" => Ok(Self::SkippedPage),
+            other => Err(Error::Other(format!(
+                "Unknown warning kind {other:?}, expected 'unknown_tag', 'unknown_attribute', 'unexpected_text', \
+                'unknown_section', 'length_mismatch', 'missing_field', 'sha1_mismatch', 'edition_mismatch' or 'skipped_page'"
+            ))),
+        }
+    }
+}
+
+impl Warning {
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Self::UnknownTag { .. } => WarningKind::UnknownTag,
+            Self::UnknownAttribute { .. } => WarningKind::UnknownAttribute,
+            Self::UnexpectedText { .. } => WarningKind::UnexpectedText,
+            Self::UnknownSection { .. } => WarningKind::UnknownSection,
+            Self::LengthMismatch { .. } => WarningKind::LengthMismatch,
+            Self::MissingField { .. } => WarningKind::MissingField,
+            Self::Sha1Mismatch { .. } => WarningKind::Sha1Mismatch,
+            Self::EditionMismatch { .. } => WarningKind::EditionMismatch,
+            Self::SkippedPage { .. } => WarningKind::SkippedPage,
+        }
+    }
+
+    /// The level the warning is logged with if no [`WarningSink`] is given.
+    /// Unknown sections and missing optional fields are common in regular dumps, so they are only logged for debugging.
+    pub fn log_level(&self) -> Level {
+        match self.kind() {
+            WarningKind::UnknownSection | WarningKind::MissingField => Level::Debug,
+            _ => Level::Warn,
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTag { tag } => write!(f, "Unexpected tag <{tag}>"),
+            Self::UnknownAttribute { tag, attribute } => {
+                write!(f, "Ignoring unexpected attribute {attribute} of tag {tag}")
+            }
+            Self::UnexpectedText { text } => write!(f, "Unexpected text {text:?}"),
+            Self::UnknownSection {
+                title,
+                language_english_name,
+                heading,
+            } => write!(
+                f,
+                "Unknown section {heading:?} of language {language_english_name} in page {title:?}"
+            ),
+            Self::LengthMismatch {
+                title,
+                stated,
+                actual,
+            } => write!(
+                f,
+                "Text length mismatch in page {title:?}, attribute states {stated}, but we got {actual}"
+            ),
+            Self::MissingField { tag, field } => write!(f, "Missing {field} in {tag}"),
+            Self::Sha1Mismatch {
+                title,
+                revision_id,
+                stated,
+                actual,
+            } => write!(
+                f,
+                "Sha1 of revision {revision_id} of page {title:?} is '{stated}', but the text has '{actual}'"
+            ),
+            Self::EditionMismatch { given, siteinfo } => write!(
+                f,
+                "Edition {given} given or inferred from the file name does not match edition {siteinfo} from siteinfo, using the latter"
+            ),
+            Self::SkippedPage { error } => {
+                write!(f, "Skipping to the next page after error: {error}")
+            }
+        }
+    }
+}
+
+/// Receives warnings.
+///
+/// Warnings are sent from the parsing task, so implementations should return quickly.
+/// Returning an error aborts parsing with that error.
+pub trait WarningSink: Send + Sync {
+    fn warning(&self, warning: &Warning) -> Result<()>;
+}
+
+impl<F: Fn(&Warning) -> Result<()> + Send + Sync> WarningSink for F {
+    fn warning(&self, warning: &Warning) -> Result<()> {
+        self(warning)
+    }
+}
+
+/// Hands warnings to a [`WarningSink`], or logs them with their [log level](Warning::log_level) by default.
+#[derive(Clone, Default)]
+pub struct WarningReporter {
+    sink: Option<Arc<dyn WarningSink>>,
+}
+
+impl Debug for WarningReporter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WarningReporter")
+            .field("logs", &self.sink.is_none())
+            .finish_non_exhaustive()
+    }
+}
+
+impl WarningReporter {
+    /// Hand each warning to the given closure or [`WarningSink`] implementation instead of logging it.
+    pub fn new(sink: impl WarningSink + 'static) -> Self {
+        Self {
+            sink: Some(Arc::new(sink)),
+        }
+    }
+
+    /// A reporter that logs each warning, with long contents shortened to an [excerpt](crate::log_excerpt).
+    pub fn log() -> Self {
+        Self::default()
+    }
+
+    /// A reporter that aborts parsing on warnings of the given kinds, and logs the others.
+    pub fn failing_on(kinds: impl IntoIterator<Item = WarningKind>) -> Self {
+        let kinds: HashSet<_> = kinds.into_iter().collect();
+        Self::new(move |warning: &Warning| {
+            if kinds.contains(&warning.kind()) {
+                Err(Error::Other(format!("Failing on warning: {warning}")))
+            } else {
+                log_warning(warning);
+                Ok(())
+            }
+        })
+    }
+
+    pub fn report(&self, warning: Warning) -> Result<()> {
+        if let Some(sink) = &self.sink {
+            sink.warning(&warning)
+        } else {
+            log_warning(&warning);
+            Ok(())
+        }
+    }
+}
+
+fn log_warning(warning: &Warning) {
+    log!(warning.log_level(), "{}", excerpt(&warning.to_string()));
+}
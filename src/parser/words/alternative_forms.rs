@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::templates::{
+    section_definitions, section_list_items, text_links, text_templates, Template,
+};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// A pair of a word and an alternative form of it.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AlternativeForm {
+    pub language_english_name: String,
+    /// The word, i.e. the title of the page.
+    pub word: String,
+    /// The alternative form, variant spelling or romanization of the word.
+    pub alternative: String,
+    pub kind: AlternativeFormKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum AlternativeFormKind {
+    /// Listed in an `Alternative forms` section, or given by `{{alternative form of}}`.
+    AlternativeForm,
+    /// A variant spelling, e.g. given by `{{alternative spelling of}}` or `{{obsolete spelling of}}`.
+    Spelling,
+    /// A romanization of a word in a different script, e.g. given by `{{ja-romanization of}}`.
+    Romanization,
+}
+
+/// Headings of sections that list alternative forms.
+static ALTERNATIVE_FORMS_HEADINGS: &[&str] = &[
+    "Alternative forms",
+    "Alternative form",
+    "Alternative spellings",
+    "Alternative spelling",
+    "Variant spelling",
+    "Spelling variant",
+    "Variants",
+];
+/// Templates that list alternative forms after the language code, like `{{alter|en|colour|color}}`.
+static ALTER_TEMPLATES: &[&str] = &["alter", "alt"];
+
+/// Extract the alternative forms from a wiktionary page.
+///
+/// Alternative forms are taken from `Alternative forms` sections, and from definitions that consist of
+/// templates like `{{alternative spelling of|en|colour}}` or `{{romanization of|...}}`.
+pub fn wikitext_to_alternative_forms(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(AlternativeForm),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(AlternativeForm),
+) {
+    for subsection in &section.subsections {
+        let mut emit = |alternative: &str, kind| {
+            let alternative = alternative.trim();
+            if !alternative.is_empty() && alternative != title {
+                result_consumer(AlternativeForm {
+                    language_english_name: language_english_name.to_string(),
+                    word: title.to_string(),
+                    alternative: alternative.to_string(),
+                    kind,
+                });
+            }
+        };
+
+        if ALTERNATIVE_FORMS_HEADINGS.contains(&subsection.headline.label.trim()) {
+            for (_, text) in &section_list_items(subsection) {
+                for alternative in text_links(text) {
+                    emit(&alternative, AlternativeFormKind::AlternativeForm);
+                }
+                for template in text_templates(text) {
+                    if template.is_any_of(ALTER_TEMPLATES) {
+                        // Arguments after the first empty one are dialect labels.
+                        for alternative in template
+                            .positional
+                            .iter()
+                            .skip(1)
+                            .take_while(|argument| !argument.is_empty())
+                        {
+                            emit(alternative, AlternativeFormKind::AlternativeForm);
+                        }
+                    }
+                }
+            }
+        } else {
+            for text in &section_definitions(subsection) {
+                for template in text_templates(text) {
+                    if let Some((alternative, kind)) = form_of(&template) {
+                        emit(alternative, kind);
+                    }
+                }
+            }
+        }
+
+        parse_section(title, language_english_name, subsection, result_consumer);
+    }
+}
+
+/// The target of a form-of template that declares the page title an alternative form of it.
+fn form_of(template: &Template) -> Option<(&str, AlternativeFormKind)> {
+    let name = template.name.as_str();
+    let kind = if name == "alternative form of" || name == "alt form" || name == "alt form of" {
+        AlternativeFormKind::AlternativeForm
+    } else if name.ends_with("spelling of") || name == "alt sp" || name == "alt sp of" {
+        AlternativeFormKind::Spelling
+    } else if name.ends_with("romanization of") || name.ends_with("-romanization") {
+        // Romanization templates of some languages like Japanese do not take a language code.
+        let target = if name.starts_with("romanization of") {
+            template.positional(1)
+        } else {
+            template.positional(0)
+        };
+        return target.map(|target| (target, AlternativeFormKind::Romanization));
+    } else {
+        return None;
+    };
+
+    template.positional(1).map(|target| (target, kind))
+}
@@ -0,0 +1,233 @@
+//! Extraction of the quotations collected on the pages of the `Citations:` namespace,
+//! like `Citations:word`, which complement the quotations within the entries.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::clean_text::{clean_text, CleanTextOptions};
+use crate::parser::words::templates::{section_list_items, text_templates, Template};
+use crate::parser::words::IGNORED_LANGUAGE_PATTERN;
+
+/// The title prefix of the pages of the `Citations:` namespace.
+pub const CITATIONS_TITLE_PREFIX: &str = "Citations:";
+
+lazy_static! {
+    static ref YEAR_PATTERN: Regex = Regex::new("\\b(\\d{3,4})\\b").unwrap();
+}
+
+/// A quotation of a word, as collected on its citations page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Citation {
+    /// The word, i.e. the title of the page without the `Citations:` prefix.
+    pub word: String,
+    pub language_english_name: String,
+    /// The heading of the section of the sense the quotation is collected for, like `Noun: a small house`, if any.
+    pub sense: Option<String>,
+    /// The date of the quotation as given, like `1851` or `2011 October 3`.
+    pub date: Option<String>,
+    /// The year of the quotation, if the date contains one.
+    pub year: Option<i32>,
+    pub author: Option<String>,
+    /// The title of the work, journal, newspaper or website the quotation is from.
+    /// For quotations in free text, this is the whole citation line.
+    pub source: Option<String>,
+    pub url: Option<String>,
+    /// The quoted text as plain text.
+    pub text: String,
+    /// The English translation of the quoted text, if it is not in English.
+    pub translation: Option<String>,
+}
+
+/// Templates whose names start with this prefix are quotation templates, like `{{quote-book}}`.
+static QUOTE_TEMPLATE_PREFIX: &str = "quote-";
+/// Templates whose names start with this prefix are quotation templates of specific works,
+/// like `{{RQ:Shakespeare Hamlet}}`, named by their work.
+static WORK_QUOTE_TEMPLATE_PREFIX: &str = "RQ:";
+/// Named arguments of quotation templates that give the source, in order of preference.
+static SOURCE_ARGUMENTS: &[&str] = &["title", "work", "journal", "newspaper", "magazine", "site"];
+
+/// Extract the citations of a page of the `Citations:` namespace.
+/// Pages outside of the namespace are ignored.
+///
+/// Quotations are recognised in quotation templates like `{{quote-book|en|year=1851|author=...|passage=...}}`,
+/// and in free text as a list item like `#* '''1851''', Herman Melville, ''Moby-Dick''`
+/// followed by a nested list item with the quoted text.
+pub fn wikitext_to_citations(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(Citation),
+) {
+    let word = if let Some(word) = title.strip_prefix(CITATIONS_TITLE_PREFIX) {
+        word
+    } else {
+        return;
+    };
+    if wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.trim();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            word,
+            language_english_name,
+            None,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    word: &str,
+    language_english_name: &str,
+    sense: Option<&str>,
+    section: &Section,
+    result_consumer: &mut impl FnMut(Citation),
+) {
+    let new_citation = || Citation {
+        word: word.to_string(),
+        language_english_name: language_english_name.to_string(),
+        sense: sense.map(ToString::to_string),
+        date: None,
+        year: None,
+        author: None,
+        source: None,
+        url: None,
+        text: String::new(),
+        translation: None,
+    };
+    // A citation in free text, waiting for its quoted text in the following list items.
+    let mut pending_citation: Option<(String, Citation)> = None;
+
+    for (list_prefix, text) in &section_list_items(section) {
+        let quote_template = text_templates(text).into_iter().find(is_quote_template);
+
+        if let Some(template) = quote_template {
+            flush_citation(pending_citation.take(), result_consumer);
+            let mut citation = new_citation();
+            if fill_citation_from_template(&mut citation, &template) {
+                result_consumer(citation);
+            }
+        } else if list_prefix.ends_with('*') {
+            flush_citation(pending_citation.take(), result_consumer);
+            let line = clean_text(text, &CleanTextOptions::default());
+            if !line.is_empty() {
+                let mut citation = new_citation();
+                citation.year = parse_year(&line);
+                citation.date = citation.year.map(|year| year.to_string());
+                citation.source = Some(line);
+                pending_citation = Some((list_prefix.to_string(), citation));
+            }
+        } else if let Some((citation_prefix, citation)) = pending_citation.as_mut() {
+            // Nested list items like `#*:` contain the quoted text.
+            if list_prefix.starts_with(citation_prefix.as_str()) {
+                let line = clean_text(text, &CleanTextOptions::default());
+                if !line.is_empty() {
+                    if !citation.text.is_empty() {
+                        citation.text.push(' ');
+                    }
+                    citation.text.push_str(&line);
+                }
+                continue;
+            }
+            flush_citation(pending_citation.take(), result_consumer);
+        }
+    }
+    flush_citation(pending_citation, result_consumer);
+
+    for subsection in &section.subsections {
+        parse_section(
+            word,
+            language_english_name,
+            Some(subsection.headline.label.trim()),
+            subsection,
+            result_consumer,
+        );
+    }
+}
+
+/// Hand a citation in free text to the consumer, if its quoted text was found.
+fn flush_citation(
+    pending_citation: Option<(String, Citation)>,
+    result_consumer: &mut impl FnMut(Citation),
+) {
+    if let Some((_, citation)) = pending_citation {
+        if !citation.text.is_empty() {
+            result_consumer(citation);
+        }
+    }
+}
+
+fn is_quote_template(template: &Template) -> bool {
+    template.name.starts_with(QUOTE_TEMPLATE_PREFIX)
+        || template.name.starts_with(WORK_QUOTE_TEMPLATE_PREFIX)
+}
+
+/// Fill the citation with the arguments of a quotation template.
+/// Returns false if the template contains no quoted text.
+fn fill_citation_from_template(citation: &mut Citation, template: &Template) -> bool {
+    let non_empty = |value: Option<&str>| {
+        value
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToString::to_string)
+    };
+
+    let text = if let Some(text) = non_empty(template.named("passage"))
+        .or_else(|| non_empty(template.named("text")))
+        .or_else(|| non_empty(template.named("quote")))
+    {
+        text
+    } else {
+        return false;
+    };
+    citation.text = text;
+    citation.translation =
+        non_empty(template.named("translation")).or_else(|| non_empty(template.named("t")));
+
+    citation.date = non_empty(template.named("date"))
+        .or_else(|| non_empty(template.named("year")))
+        .or_else(|| {
+            // `{{quote-book|en|1851|...}}` gives the date as second positional argument.
+            non_empty(template.positional(1)).filter(|date| parse_year(date).is_some())
+        });
+    citation.year = citation.date.as_deref().and_then(parse_year);
+
+    citation.author = non_empty(template.named("author"))
+        .or_else(|| non_empty(template.named("authors")))
+        .or_else(|| {
+            let last = non_empty(template.named("last"))?;
+            Some(if let Some(first) = non_empty(template.named("first")) {
+                format!("{first} {last}")
+            } else {
+                last
+            })
+        });
+
+    citation.source = SOURCE_ARGUMENTS
+        .iter()
+        .find_map(|argument| non_empty(template.named(argument)))
+        .or_else(|| {
+            template
+                .name
+                .strip_prefix(WORK_QUOTE_TEMPLATE_PREFIX)
+                .map(|work| work.trim().to_string())
+        });
+    citation.url = non_empty(template.named("url"));
+    true
+}
+
+/// The first number with three or four digits in the date, like `1851` in `c. 1851`.
+fn parse_year(date: &str) -> Option<i32> {
+    YEAR_PATTERN
+        .captures(date)
+        .and_then(|captures| captures.get(1))
+        .and_then(|year| year.as_str().parse().ok())
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use wikitext_parser::{Section, TextPiece, Wikitext};
+
+use crate::error::Result;
+use crate::parser::words::templates::{section_texts, text_to_plain};
+
+/// What is done with a page after it was classified by a [`PageClassifier`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum PagePipeline {
+    /// The page is a dictionary entry, and words are extracted from it.
+    Words,
+    /// The page is a thesaurus entry.
+    Thesaurus,
+    /// The page lists rhymes.
+    Rhymes,
+    /// The page is not parsed for any dataset.
+    Ignore,
+}
+
+/// A rule mapping pages to a pipeline.
+/// A page matches the rule if it matches all conditions that are given.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ClassificationRule {
+    /// The key of the namespace of the page, e.g. `0` for the main namespace.
+    /// Pages whose namespace is unknown never match this condition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_suffix: Option<String>,
+    /// A category the page is in, without the `Category:` prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub pipeline: PagePipeline,
+}
+
+/// Maps pages to pipelines by an ordered list of rules.
+/// The first matching rule decides the pipeline, and pages not matching any rule get the default pipeline.
+///
+/// Classifiers can be loaded from json files like
+/// `{"rules": [{"title_prefix": "Thesaurus:", "pipeline": "thesaurus"}], "default_pipeline": "words"}`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PageClassifier {
+    pub rules: Vec<ClassificationRule>,
+    pub default_pipeline: PagePipeline,
+}
+
+/// Title prefixes of pages that do not contain entries.
+static IGNORED_TITLE_PREFIXES: &[&str] = &[
+    "Wiktionary:",
+    "Appendix:",
+    "Help:",
+    "Template:",
+    "MediaWiki:",
+    "Citations:",
+    "Module:",
+    "Reconstruction:",
+    "Concordance:",
+];
+/// Title suffixes of subpages that do not contain entries.
+static IGNORED_TITLE_SUFFIXES: &[&str] = &["/derived terms"];
+
+impl Default for PageClassifier {
+    /// The rules of the English wiktionary, where words are extracted from all pages that are not in a special namespace.
+    fn default() -> Self {
+        let mut rules = vec![
+            ClassificationRule::title_prefix("Thesaurus:", PagePipeline::Thesaurus),
+            ClassificationRule::title_prefix("Rhymes:", PagePipeline::Rhymes),
+        ];
+        rules.extend(
+            IGNORED_TITLE_PREFIXES
+                .iter()
+                .map(|prefix| ClassificationRule::title_prefix(prefix, PagePipeline::Ignore)),
+        );
+        rules.extend(
+            IGNORED_TITLE_SUFFIXES
+                .iter()
+                .map(|suffix| ClassificationRule {
+                    title_suffix: Some(suffix.to_string()),
+                    ..ClassificationRule::new(PagePipeline::Ignore)
+                }),
+        );
+
+        Self {
+            rules,
+            default_pipeline: PagePipeline::Words,
+        }
+    }
+}
+
+impl ClassificationRule {
+    /// A rule without conditions, which matches all pages.
+    pub fn new(pipeline: PagePipeline) -> Self {
+        Self {
+            namespace: None,
+            title_prefix: None,
+            title_suffix: None,
+            category: None,
+            pipeline,
+        }
+    }
+
+    pub fn title_prefix(prefix: &str, pipeline: PagePipeline) -> Self {
+        Self {
+            title_prefix: Some(prefix.to_string()),
+            ..Self::new(pipeline)
+        }
+    }
+
+    pub fn matches(&self, namespace: Option<i64>, title: &str, categories: &[String]) -> bool {
+        if let Some(rule_namespace) = self.namespace {
+            if namespace != Some(rule_namespace) {
+                return false;
+            }
+        }
+        if let Some(title_prefix) = &self.title_prefix {
+            if !title.starts_with(title_prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(title_suffix) = &self.title_suffix {
+            if !title.ends_with(title_suffix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(category) = &self.category {
+            if !categories.contains(category) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl PageClassifier {
+    /// Load a classifier from a json file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// The pipeline of a page with the given namespace, if known, title and categories.
+    pub fn classify(
+        &self,
+        namespace: Option<i64>,
+        title: &str,
+        categories: &[String],
+    ) -> PagePipeline {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(namespace, title, categories))
+            .map(|rule| rule.pipeline)
+            .unwrap_or(self.default_pipeline)
+    }
+
+    /// Like [`classify`](Self::classify), but collects the categories from the wikitext of the page,
+    /// if any rule needs them.
+    pub fn classify_wikitext(
+        &self,
+        namespace: Option<i64>,
+        title: &str,
+        wikitext: &Wikitext,
+    ) -> PagePipeline {
+        let categories = if self.rules.iter().any(|rule| rule.category.is_some()) {
+            page_categories(wikitext)
+        } else {
+            Vec::new()
+        };
+        self.classify(namespace, title, &categories)
+    }
+}
+
+/// The categories a page is added to by links like `[[Category:English nouns]]`, without the `Category:` prefix.
+/// Categories added by templates are not included.
+pub fn page_categories(wikitext: &Wikitext) -> Vec<String> {
+    let mut categories = Vec::new();
+    collect_categories(&wikitext.root_section, &mut categories);
+    categories
+}
+
+fn collect_categories(section: &Section, categories: &mut Vec<String>) {
+    for text in section_texts(section) {
+        for piece in &text.pieces {
+            if let TextPiece::InternalLink { target, .. } = piece {
+                let target = text_to_plain(target);
+                if let Some(category) = target.trim().strip_prefix("Category:") {
+                    categories.push(category.trim().to_string());
+                }
+            }
+        }
+    }
+    for subsection in &section.subsections {
+        collect_categories(subsection, categories);
+    }
+}
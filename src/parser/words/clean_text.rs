@@ -0,0 +1,243 @@
+//! Conversion of wikitext to clean plain text, e.g. for glosses and definitions.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use wikitext_parser::{Text, TextPiece};
+
+use crate::parser::words::templates::LINK_TEMPLATES;
+
+lazy_static! {
+    static ref COMMENT_PATTERN: Regex = Regex::new("(?s)<!--.*?(-->|$)").unwrap();
+    static ref REFERENCE_PATTERN: Regex =
+        Regex::new("(?is)<ref\\b[^>]*/>|<ref\\b[^>]*>.*?(</ref\\s*>|$)").unwrap();
+    static ref HTML_TAG_PATTERN: Regex = Regex::new("</?[a-zA-Z][a-zA-Z0-9]*\\b[^<>]*>").unwrap();
+    static ref EMPTY_PARENTHESES_PATTERN: Regex = Regex::new("\\(\\s*[,;]?\\s*\\)").unwrap();
+    static ref SPACE_BEFORE_PUNCTUATION_PATTERN: Regex = Regex::new("\\s+([,;:.!?)])").unwrap();
+    static ref SPACE_AFTER_PARENTHESIS_PATTERN: Regex = Regex::new("\\(\\s+").unwrap();
+}
+
+/// Options for [`clean_text`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CleanTextOptions {
+    /// Keep templates that are not known to the cleaner as wiki markup like `{{name|argument}}`,
+    /// instead of dropping them.
+    pub keep_unknown_templates: bool,
+    /// Remove references like `<ref>...</ref>` including their content.
+    pub strip_references: bool,
+    /// Remove html comments, and html tags while keeping their content.
+    pub strip_html: bool,
+    /// Collapse whitespace, and remove empty parentheses and spaces before punctuation left behind by removed markup.
+    pub normalise_whitespace: bool,
+}
+
+impl Default for CleanTextOptions {
+    fn default() -> Self {
+        Self {
+            keep_unknown_templates: false,
+            strip_references: true,
+            strip_html: true,
+            normalise_whitespace: true,
+        }
+    }
+}
+
+impl CleanTextOptions {
+    /// Keep everything that is not known to be markup, including unknown templates and html.
+    pub fn lenient() -> Self {
+        Self {
+            keep_unknown_templates: true,
+            strip_references: false,
+            strip_html: false,
+            normalise_whitespace: true,
+        }
+    }
+}
+
+/// Templates that render as their first argument.
+static FIRST_ARGUMENT_TEMPLATES: &[&str] = &[
+    "non-gloss definition",
+    "non-gloss",
+    "n-g",
+    "ngd",
+    "taxlink",
+    "taxfmt",
+    "vern",
+    "smallcaps",
+    "sc",
+    "nowrap",
+    "upright",
+    "small",
+    "sub",
+    "sup",
+];
+/// Templates that link to Wikipedia, with an optional label as second argument.
+static WIKIPEDIA_TEMPLATES: &[&str] = &["w", "wikipedia", "pedia"];
+/// Templates that render their arguments in parentheses.
+static PARENTHESISED_TEMPLATES: &[&str] = &[
+    "q",
+    "qual",
+    "qualifier",
+    "i",
+    "qf",
+    "gloss",
+    "gl",
+    "sense",
+    "s",
+];
+/// Templates that render their arguments after the language code in parentheses.
+static LABEL_TEMPLATES: &[&str] = &["lb", "lbl", "label", "context", "cx"];
+/// Arguments of label templates that join labels.
+static LABEL_CONNECTORS: &[&str] = &["_", "and", "or", ","];
+/// Templates that are never rendered, e.g. because they only contain metadata.
+static HIDDEN_TEMPLATES: &[&str] = &["senseid", "anchor", "attention", "rfclarify", "rfdef"];
+
+/// Convert wikitext to clean plain text.
+///
+/// Links are replaced by their display text, common formatting templates like `{{l|en|word}}`, `{{w|Paris}}`,
+/// `{{q|rare}}` or `{{plural of|en|dog}}` are resolved, and other markup is removed according to the options.
+pub fn clean_text(text: &Text, options: &CleanTextOptions) -> String {
+    let mut result = String::new();
+    append_clean(text, options, &mut result);
+
+    if options.strip_references {
+        result = REFERENCE_PATTERN.replace_all(&result, "").into_owned();
+    }
+    if options.strip_html {
+        result = COMMENT_PATTERN.replace_all(&result, "").into_owned();
+        result = HTML_TAG_PATTERN.replace_all(&result, "").into_owned();
+    }
+    if options.normalise_whitespace {
+        result = result.split_whitespace().collect::<Vec<_>>().join(" ");
+        result = EMPTY_PARENTHESES_PATTERN
+            .replace_all(&result, "")
+            .into_owned();
+        result = SPACE_AFTER_PARENTHESIS_PATTERN
+            .replace_all(&result, "(")
+            .into_owned();
+        result = SPACE_BEFORE_PUNCTUATION_PATTERN
+            .replace_all(&result, "$1")
+            .into_owned();
+        result = result.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    result
+}
+
+fn append_clean(text: &Text, options: &CleanTextOptions, result: &mut String) {
+    for piece in &text.pieces {
+        match piece {
+            TextPiece::Text { text, .. } => result.push_str(text),
+            TextPiece::DoubleBraceExpression { tag, attributes } => {
+                let arguments: Vec<_> = attributes
+                    .iter()
+                    .map(|attribute| {
+                        (
+                            attribute.name.as_deref().map(str::trim),
+                            clean_text(&attribute.value, options),
+                        )
+                    })
+                    .collect();
+                append_template(
+                    clean_text(tag, &CleanTextOptions::default()).trim(),
+                    &arguments,
+                    options,
+                    result,
+                )
+            }
+            TextPiece::InternalLink { target, label, .. } => {
+                append_clean(label.as_ref().unwrap_or(target), options, result)
+            }
+            TextPiece::ListItem { text, .. } => append_clean(text, options, result),
+        }
+    }
+}
+
+/// Append the rendering of a template with the given cleaned arguments, which are named or positional.
+fn append_template(
+    name: &str,
+    arguments: &[(Option<&str>, String)],
+    options: &CleanTextOptions,
+    result: &mut String,
+) {
+    let positional: Vec<_> = arguments
+        .iter()
+        .filter(|(argument_name, _)| argument_name.is_none())
+        .map(|(_, value)| value)
+        .collect();
+    let named = |key: &str| {
+        arguments
+            .iter()
+            .find(|(argument_name, _)| *argument_name == Some(key))
+            .map(|(_, value)| value.clone())
+            .filter(|value| !value.is_empty())
+    };
+    let argument = |index: usize| {
+        positional
+            .get(index)
+            .map(|argument| argument.as_str())
+            .filter(|argument| !argument.is_empty())
+    };
+
+    if LINK_TEMPLATES.contains(&name) {
+        if let Some(display) = named("alt").or_else(|| argument(2).map(ToString::to_string)) {
+            result.push_str(&display);
+        } else if let Some(term) = argument(1) {
+            result.push_str(term);
+        }
+    } else if WIKIPEDIA_TEMPLATES.contains(&name) {
+        if let Some(display) = argument(1).or_else(|| argument(0)) {
+            result.push_str(display);
+        }
+    } else if FIRST_ARGUMENT_TEMPLATES.contains(&name) {
+        if let Some(display) = argument(0) {
+            result.push_str(display);
+        }
+    } else if PARENTHESISED_TEMPLATES.contains(&name) {
+        append_parenthesised(positional.iter().copied(), result);
+    } else if LABEL_TEMPLATES.contains(&name) {
+        append_parenthesised(
+            positional
+                .iter()
+                .copied()
+                .skip(1)
+                .filter(|label| !LABEL_CONNECTORS.contains(&label.as_str())),
+            result,
+        );
+    } else if name.ends_with(" of") {
+        // Form-of templates like `{{plural of|en|dog}}`.
+        result.push_str(name);
+        if let Some(term) = named("alt")
+            .or_else(|| argument(2).map(ToString::to_string))
+            .or_else(|| argument(1).map(ToString::to_string))
+        {
+            result.push(' ');
+            result.push_str(&term);
+        }
+    } else if HIDDEN_TEMPLATES.contains(&name) || !options.keep_unknown_templates {
+        // Drop the template.
+    } else {
+        result.push_str("{{");
+        result.push_str(name);
+        for (argument_name, value) in arguments {
+            result.push('|');
+            if let Some(argument_name) = argument_name {
+                result.push_str(argument_name);
+                result.push('=');
+            }
+            result.push_str(value);
+        }
+        result.push_str("}}");
+    }
+}
+
+fn append_parenthesised<'a>(arguments: impl Iterator<Item = &'a String>, result: &mut String) {
+    let arguments: Vec<_> = arguments
+        .map(String::as_str)
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    if !arguments.is_empty() {
+        result.push('(');
+        result.push_str(&arguments.join(", "));
+        result.push(')');
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Text, Wikitext};
+
+use crate::parser::words::clean_text::{clean_text, CleanTextOptions};
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::templates::{section_definitions, text_templates};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// The definitions of a word of a certain language and word type.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct WordDefinitions {
+    pub word: String,
+    pub language_english_name: String,
+    /// The canonical word type.
+    pub word_type: String,
+    /// The definitions in order of appearance.
+    pub definitions: Vec<Definition>,
+}
+
+/// A single numbered definition line, i.e. a list item with prefix `#`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Definition {
+    /// The definition as plain text, without labels and with collapsed whitespace.
+    pub gloss: String,
+    /// Labels and qualifiers like `obsolete` or `transitive`, in order of appearance.
+    pub labels: Vec<String>,
+    /// Short descriptions of the sense from gloss templates like `{{gloss|of a dog}}`, in order of appearance.
+    /// They describe the definition rather than qualify it, so they are neither part of the labels nor of the gloss.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glosses: Vec<String>,
+}
+
+/// Templates whose arguments after the language code are labels, like `{{lb|en|transitive}}`.
+static LABEL_TEMPLATES: &[&str] = &["lb", "lbl", "label", "context", "cx"];
+/// Templates whose arguments are all qualifiers, like `{{q|rare}}`.
+static QUALIFIER_TEMPLATES: &[&str] = &["q", "qual", "qualifier", "i", "qf"];
+/// Templates whose first argument is a short description of the sense, like `{{gloss|of a dog}}`.
+static GLOSS_TEMPLATES: &[&str] = &["gloss", "gl", "sense", "s"];
+/// Arguments of label templates that join labels instead of being labels themselves.
+static LABEL_CONNECTORS: &[&str] = &["_", "and", "or", ","];
+/// Labels of definitions that are unsuitable for children, compared case-insensitively.
+pub static OFFENSIVE_LABELS: &[&str] = &[
+    "vulgar",
+    "offensive",
+    "derogatory",
+    "pejorative",
+    "slur",
+    "ethnic slur",
+    "obscene",
+    "profane",
+    "sexual slang",
+];
+
+impl Definition {
+    /// Returns true if the definition has one of the [`OFFENSIVE_LABELS`], like `vulgar` or `derogatory`.
+    pub fn is_offensive(&self) -> bool {
+        self.labels.iter().any(|label| {
+            OFFENSIVE_LABELS
+                .iter()
+                .any(|offensive| label.trim().eq_ignore_ascii_case(offensive))
+        })
+    }
+}
+
+impl WordDefinitions {
+    /// Remove the definitions that are [offensive](Definition::is_offensive).
+    /// Returns the amount of removed definitions.
+    pub fn remove_offensive_definitions(&mut self) -> usize {
+        let amount = self.definitions.len();
+        self.definitions
+            .retain(|definition| !definition.is_offensive());
+        amount - self.definitions.len()
+    }
+}
+
+/// Extract the definitions of each word type section of a wiktionary page.
+/// Word type sections without definitions are omitted.
+pub fn wikitext_to_definitions(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(WordDefinitions),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(WordDefinitions),
+) {
+    for subsection in &section.subsections {
+        if let HeadingKind::WordType(word_type) = classify_heading(&subsection.headline.label) {
+            let definitions: Vec<_> = section_definitions(subsection)
+                .iter()
+                .filter_map(parse_definition)
+                .collect();
+            if !definitions.is_empty() {
+                result_consumer(WordDefinitions {
+                    word: title.to_string(),
+                    language_english_name: language_english_name.to_string(),
+                    word_type: word_type.to_string(),
+                    definitions,
+                });
+            }
+        }
+
+        parse_section(title, language_english_name, subsection, result_consumer);
+    }
+}
+
+/// Parse the text of a definition line into its gloss, labels and the glosses of gloss templates.
+/// Returns `None` if the definition is empty.
+///
+/// Labels are taken from label and qualifier templates, and from parentheses at the start of the gloss,
+/// like in `(obsolete) A dog.`.
+pub fn parse_definition(text: &Text) -> Option<Definition> {
+    let mut labels = Vec::new();
+    let mut glosses = Vec::new();
+    for template in text_templates(text) {
+        if template.is_any_of(GLOSS_TEMPLATES) {
+            if let Some(gloss) = template.positional.first() {
+                if !gloss.is_empty() {
+                    push_unique(&mut glosses, gloss);
+                }
+            }
+            continue;
+        }
+
+        let arguments = if template.is_any_of(LABEL_TEMPLATES) {
+            // The first argument is the language code.
+            template.positional.get(1..).unwrap_or_default()
+        } else if template.is_any_of(QUALIFIER_TEMPLATES) {
+            &template.positional[..]
+        } else {
+            continue;
+        };
+
+        for argument in arguments {
+            if !argument.is_empty() && !LABEL_CONNECTORS.contains(&argument.as_str()) {
+                push_unique(&mut labels, argument);
+            }
+        }
+    }
+
+    // Label and gloss templates are rendered in parentheses, and are removed from the gloss together with other labels.
+    let mut gloss = clean_text(text, &CleanTextOptions::default());
+    while gloss.starts_with('(') {
+        if let Some(end) = gloss.find(')') {
+            let parenthesised = gloss[1..end].trim();
+            if glosses
+                .iter()
+                .any(|existing| existing.trim() == parenthesised)
+            {
+                gloss = gloss[end + 1..].trim_start().to_string();
+                continue;
+            }
+            for label in gloss[1..end].split(',') {
+                let label = label.trim();
+                if !label.is_empty() {
+                    push_unique(&mut labels, label);
+                }
+            }
+            gloss = gloss[end + 1..].trim_start().to_string();
+        } else {
+            break;
+        }
+    }
+
+    if gloss.is_empty() && labels.is_empty() && glosses.is_empty() {
+        None
+    } else {
+        Some(Definition {
+            gloss,
+            labels,
+            glosses,
+        })
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !values.iter().any(|existing| existing == value) {
+        values.push(value.to_string());
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::templates::{section_templates, Template};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// A relation of a word to a term it originates from or is related to, as given by an etymology template.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct EtymologyOrigin {
+    /// The english name of the language of the word.
+    pub language_english_name: String,
+    /// The word, i.e. the title of the page.
+    pub word: String,
+    pub relation: EtymologyRelation,
+    /// The wiktionary language code of the source term, e.g. `enm`.
+    pub source_language_code: String,
+    /// The source term, if given. Some templates only name the source language.
+    pub source_term: Option<String>,
+    /// The meaning of the source term, if given.
+    pub gloss: Option<String>,
+}
+
+/// The kind of an [`EtymologyOrigin`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum EtymologyRelation {
+    /// `{{inh}}`: inherited from an ancestor language.
+    Inherited,
+    /// `{{der}}`: derived from another language, without further specification.
+    Derived,
+    /// `{{bor}}`: borrowed from another language.
+    Borrowed,
+    /// `{{lbor}}`: borrowed from a classical language by learned speakers.
+    LearnedBorrowing,
+    /// `{{cog}}`: a cognate in another language, i.e. not an origin but a relative.
+    Cognate,
+}
+
+static INHERITED_TEMPLATES: &[&str] = &["inh", "inh+", "inherited"];
+static DERIVED_TEMPLATES: &[&str] = &["der", "der+", "derived"];
+static BORROWED_TEMPLATES: &[&str] = &["bor", "bor+", "borrowed"];
+static LEARNED_BORROWING_TEMPLATES: &[&str] = &["lbor", "learned borrowing"];
+static COGNATE_TEMPLATES: &[&str] = &["cog", "cognate"];
+
+/// Extract structured etymological origins from the etymology sections of a wiktionary page.
+pub fn wikitext_to_etymology_origins(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(EtymologyOrigin),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(EtymologyOrigin),
+) {
+    for subsection in &section.subsections {
+        if let HeadingKind::Etymology | HeadingKind::NumberedEtymology =
+            classify_heading(&subsection.headline.label)
+        {
+            for template in section_templates(subsection) {
+                if let Some(origin) = template_to_origin(title, language_english_name, &template) {
+                    result_consumer(origin);
+                }
+            }
+        }
+
+        parse_section(title, language_english_name, subsection, result_consumer);
+    }
+}
+
+fn template_to_origin(
+    title: &str,
+    language_english_name: &str,
+    template: &Template,
+) -> Option<EtymologyOrigin> {
+    let relation = if template.is_any_of(INHERITED_TEMPLATES) {
+        EtymologyRelation::Inherited
+    } else if template.is_any_of(DERIVED_TEMPLATES) {
+        EtymologyRelation::Derived
+    } else if template.is_any_of(BORROWED_TEMPLATES) {
+        EtymologyRelation::Borrowed
+    } else if template.is_any_of(LEARNED_BORROWING_TEMPLATES) {
+        EtymologyRelation::LearnedBorrowing
+    } else if template.is_any_of(COGNATE_TEMPLATES) {
+        EtymologyRelation::Cognate
+    } else {
+        return None;
+    };
+
+    // All templates but cognates start with the language code of the word itself.
+    let first_argument = if relation == EtymologyRelation::Cognate {
+        0
+    } else {
+        1
+    };
+    let non_empty = |value: Option<&str>| {
+        value
+            .filter(|value| !value.is_empty() && *value != "-")
+            .map(ToString::to_string)
+    };
+
+    let source_language_code = non_empty(template.positional(first_argument))?;
+    let source_term = non_empty(template.positional(first_argument + 1));
+    let gloss = non_empty(template.positional(first_argument + 3))
+        .or_else(|| non_empty(template.named("t")))
+        .or_else(|| non_empty(template.named("gloss")));
+
+    Some(EtymologyOrigin {
+        language_english_name: language_english_name.to_string(),
+        word: title.to_string(),
+        relation,
+        source_language_code,
+        source_term,
+        gloss,
+    })
+}
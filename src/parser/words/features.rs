@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use wikitext_parser::Section;
+
+use crate::parser::words::templates::{section_headword_templates, Template};
+
+/// Grammatical features of a word, as declared by the headword templates of its word type section.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct GrammaticalFeatures {
+    /// The genders of the word, in order of appearance.
+    /// Words with multiple genders, like `{{head|es|noun|g=m|g2=f}}`, have all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genders: Vec<Gender>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub countability: Option<Countability>,
+    /// Whether the word only occurs in one number, like `{{fr-noun|m-p}}` for a plurale tantum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<NumberRestriction>,
+    /// Other features like `animate`, `personal` or `perfective`, without duplicates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Gender {
+    Masculine,
+    Feminine,
+    Neuter,
+    /// The common gender of e.g. Danish and Swedish.
+    Common,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Countability {
+    Countable,
+    Uncountable,
+    /// Countable in some senses and uncountable in others, like `{{en-noun|~}}`.
+    Both,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberRestriction {
+    SingularOnly,
+    PluralOnly,
+}
+
+/// Named arguments of headword templates that give genders, like `g=m` and `g2=f`.
+static GENDER_ARGUMENTS: &[&str] = &["g", "g1", "g2", "g3", "g4"];
+/// Named arguments of headword templates that give the aspect of verbs, like `a=impf`.
+static ASPECT_ARGUMENTS: &[&str] = &["a", "aspect"];
+/// Word types of headword templates like `{{fr-noun}}` whose first argument is a gender.
+static GENDERED_TEMPLATE_WORD_TYPES: &[&str] = &["noun", "proper noun", "pn", "adj"];
+/// Aspects given as positional arguments of verb headword templates like `{{ru-verb|де́лать|impf}}`.
+static POSITIONAL_ASPECTS: &[&str] = &["pf", "impf", "both", "biasp"];
+/// Tags of `{{head}}` templates that declare the countability or number of the word.
+static HEAD_TAG_FEATURES: &[(&str, Option<Countability>, Option<NumberRestriction>)] = &[
+    ("countable", Some(Countability::Countable), None),
+    ("uncountable", Some(Countability::Uncountable), None),
+    ("plural only", None, Some(NumberRestriction::PluralOnly)),
+    ("plurale tantum", None, Some(NumberRestriction::PluralOnly)),
+    ("singular only", None, Some(NumberRestriction::SingularOnly)),
+    (
+        "singulare tantum",
+        None,
+        Some(NumberRestriction::SingularOnly),
+    ),
+];
+
+impl GrammaticalFeatures {
+    /// The features declared by the headword templates of the given word type section.
+    pub fn from_section(section: &Section) -> Self {
+        let mut features = Self::default();
+        for template in section_headword_templates(section) {
+            features.add_template(&template);
+        }
+        features
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.genders.is_empty()
+            && self.countability.is_none()
+            && self.number.is_none()
+            && self.tags.is_empty()
+    }
+
+    /// Add the features declared by a headword template like `{{de-noun|g=m}}`, `{{fr-noun|f}}` or `{{en-noun|-}}`.
+    pub fn add_template(&mut self, template: &Template) {
+        for (name, value) in &template.named {
+            if GENDER_ARGUMENTS.contains(&name.as_str())
+                || ASPECT_ARGUMENTS.contains(&name.as_str())
+            {
+                self.add_gender_specification(value);
+            }
+        }
+
+        if template.name == "head" {
+            // After the language and the word type, the arguments alternate between tags and forms.
+            for tags in template.positional.iter().skip(2).step_by(2) {
+                for tag in tags.split([',', ';']) {
+                    self.add_head_tag(tag.trim());
+                }
+            }
+            return;
+        }
+
+        let word_type = if let Some((_, word_type)) = template.name.rsplit_once('-') {
+            word_type
+        } else {
+            return;
+        };
+        if word_type == "verb" {
+            for argument in &template.positional {
+                if POSITIONAL_ASPECTS.contains(&argument.trim()) {
+                    self.add_gender_specification(argument);
+                }
+            }
+            return;
+        }
+        if !GENDERED_TEMPLATE_WORD_TYPES.contains(&word_type) {
+            return;
+        }
+
+        match template.positional(0).map(str::trim) {
+            Some("-") => self.countability = Some(Countability::Uncountable),
+            Some("~") => self.countability = Some(Countability::Both),
+            // Newer templates like `{{de-noun|m,es,er}}` combine the gender with inflection classes.
+            Some(first) => {
+                let specification = first.split(',').next().unwrap_or_default();
+                if starts_with_gender(specification) {
+                    self.add_gender_specification(specification);
+                }
+            }
+            None => {}
+        }
+        if template.name == "en-noun" && self.countability.is_none() {
+            self.countability = Some(Countability::Countable);
+        }
+    }
+
+    /// Add the features of a gender and number specification like `m`, `f-p`, `m-an` or `impf`.
+    /// Unknown parts are ignored.
+    pub fn add_gender_specification(&mut self, specification: &str) {
+        for part in specification.trim().split('-') {
+            match part {
+                "m" => self.add_gender(Gender::Masculine),
+                "f" => self.add_gender(Gender::Feminine),
+                "n" => self.add_gender(Gender::Neuter),
+                "c" => self.add_gender(Gender::Common),
+                "mf" | "m/f" => {
+                    self.add_gender(Gender::Masculine);
+                    self.add_gender(Gender::Feminine);
+                }
+                "p" => self.number = Some(NumberRestriction::PluralOnly),
+                "s" => self.number = Some(NumberRestriction::SingularOnly),
+                "d" => self.add_tag("dual"),
+                "an" => self.add_tag("animate"),
+                "in" => self.add_tag("inanimate"),
+                "pr" => self.add_tag("personal"),
+                "np" => self.add_tag("nonpersonal"),
+                "pf" => self.add_tag("perfective"),
+                "impf" => self.add_tag("imperfective"),
+                "both" | "biasp" => {
+                    self.add_tag("perfective");
+                    self.add_tag("imperfective");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add_head_tag(&mut self, tag: &str) {
+        if let Some((_, countability, number)) = HEAD_TAG_FEATURES
+            .iter()
+            .find(|(head_tag, _, _)| *head_tag == tag)
+        {
+            self.countability = countability.or(self.countability);
+            self.number = number.or(self.number);
+        }
+    }
+
+    fn add_gender(&mut self, gender: Gender) {
+        if !self.genders.contains(&gender) {
+            self.genders.push(gender);
+        }
+    }
+
+    fn add_tag(&mut self, tag: &str) {
+        if !self.tags.iter().any(|existing| existing == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+}
+
+fn starts_with_gender(specification: &str) -> bool {
+    matches!(
+        specification.split('-').next(),
+        Some("m" | "f" | "n" | "c" | "mf" | "m/f")
+    )
+}
+
+impl Gender {
+    /// The abbreviation of the gender used by wiktionary, e.g. `m`.
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Gender::Masculine => "m",
+            Gender::Feminine => "f",
+            Gender::Neuter => "n",
+            Gender::Common => "c",
+        }
+    }
+}
+
+/// Formats the gender as its abbreviation.
+impl Display for Gender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.abbreviation())
+    }
+}
@@ -0,0 +1,104 @@
+use crate::parser::words::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+
+/// Canonical word types of closed-class words that carry grammatical rather than lexical meaning.
+pub static FUNCTION_WORD_TYPES: &[&str] = &[
+    "Article",
+    "Determiner",
+    "Determinative",
+    "Pronoun",
+    "Preposition",
+    "Postposition",
+    "Ambiposition",
+    "Circumposition",
+    "Conjunction",
+    "Particle",
+    "Clitic",
+    "Enclitic",
+    "Classifier",
+];
+
+/// Returns true if the canonical word type is one of [`FUNCTION_WORD_TYPES`].
+pub fn is_function_word_type(word_type: &str) -> bool {
+    FUNCTION_WORD_TYPES.contains(&word_type)
+}
+
+impl Word {
+    /// Returns true if the word is a function word, like an article, pronoun or conjunction.
+    /// The word type is expected to be canonical.
+    pub fn is_function_word(&self) -> bool {
+        is_function_word_type(&self.word_type)
+    }
+}
+
+/// Where a function word list was extracted from, such that users of stopword lists can trace them back.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct FunctionWordProvenance {
+    /// The name of the dump file the words were extracted from.
+    pub source: String,
+    /// The abbreviation of the wiktionary edition, if known.
+    pub edition: Option<String>,
+}
+
+/// Collects function words per language, e.g. to be used as stopword lists.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionWordLists {
+    /// Maps english language names to words to their function word types.
+    languages: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl FunctionWordLists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the word if it is a function word, otherwise ignore it.
+    pub fn add_word(&mut self, word: &Word) {
+        if word.is_function_word() {
+            self.languages
+                .entry(word.language_english_name.to_string())
+                .or_default()
+                .entry(word.word.clone())
+                .or_default()
+                .insert(word.word_type.to_string());
+        }
+    }
+
+    /// The english names of the languages that have function words.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.languages.keys().map(String::as_str)
+    }
+
+    /// The number of function words of the language.
+    pub fn len(&self, language_english_name: &str) -> usize {
+        self.languages
+            .get(language_english_name)
+            .map(BTreeMap::len)
+            .unwrap_or(0)
+    }
+
+    /// Write the function words of a language as `word \t word types` lines, sorted by word.
+    /// Word types are separated by commas, and the provenance is written as header comment.
+    pub fn write_tsv(
+        &self,
+        language_english_name: &str,
+        provenance: &FunctionWordProvenance,
+        mut output: impl Write,
+    ) -> std::io::Result<()> {
+        writeln!(
+            output,
+            "# Function words of {language_english_name} extracted from {} (edition: {})",
+            provenance.source,
+            provenance.edition.as_deref().unwrap_or("unknown")
+        )?;
+        if let Some(words) = self.languages.get(language_english_name) {
+            for (word, word_types) in words {
+                let word_types: Vec<_> = word_types.iter().map(String::as_str).collect();
+                writeln!(output, "{word}\t{}", word_types.join(","))?;
+            }
+        }
+        Ok(())
+    }
+}
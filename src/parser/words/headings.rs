@@ -0,0 +1,406 @@
+pub(crate) use crate::edit_distance::is_single_edit_apart;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+/// Canonical names of headings that declare a word type.
+static WORD_TYPE_HEADINGS: &[&str] = &[
+    "Word",
+    "Noun",
+    "Proper noun",
+    "Dependent noun",
+    "Prenoun",
+    "Participle",
+    "Gerund",
+    "Gerundive",
+    "Verb",
+    "Preverb",
+    "Predicative",
+    "Conjugation",
+    "Adjective",
+    "Comparative-only adjectives",
+    "Determinative",
+    "Adverb",
+    "Adnominal",
+    "Inflection",
+    "Pronoun",
+    "Preposition",
+    "Postposition",
+    "Ambiposition",
+    "Circumposition",
+    "Conjunction",
+    "Initial",
+    "Prefix",
+    "Suffix",
+    "Final",
+    "Affix",
+    "Infix",
+    "Interfix",
+    "Circumfix",
+    "Clitic",
+    "Article",
+    "Particle",
+    "Locative",
+    "Determiner",
+    "Classifier",
+    "Subordinate modifier",
+    "Contraction",
+    "Combining form",
+    "Compound part",
+    "Enclitic",
+    "Relative",
+    "Phrase",
+    "Propositional phrase",
+    "Prepositional phrase",
+    "Proverb",
+    "Idiom",
+    "Honorific title",
+    "Ideophone",
+    "Phonogram",
+    "Onomatopoeia",
+    "Phoneme",
+    "Ligature",
+    "Syllable",
+    "Letter",
+    "Symbol",
+    "Counter",
+    "Number",
+    "Numeral",
+    "Multiple parts of speech",
+    "Punctuation mark",
+    "Diacritical mark",
+    "Root",
+];
+
+/// Canonical names of headings that do not contain information we extract.
+static IGNORED_HEADINGS: &[&str] = &[
+    "Variant spelling",
+    "Relational form",
+    "Spelling variant",
+    "Other usage",
+    "Other version",
+    "Possessed form",
+    "Graphical note",
+    "Design",
+    "Echo word",
+    "From",
+    "Description",
+    "Derived character",
+    "Derived",
+    "Derivative",
+    "Alternate spelling",
+    "Accentuation note",
+    "Accentological note",
+    "Usage",
+    "Citation",
+    "Example",
+    "Source",
+    "User note",
+    "Work to be done",
+    "Stem",
+    "Sign value",
+    "Reconstruction",
+    "Production",
+    "Logogram",
+    "Holonym",
+    "Meronym",
+    "Form",
+    "Dialectal synonym",
+    "Decadent",
+    "Abbreviation",
+    "Borrowed term",
+    "External link",
+    "Related word",
+    "Standard form",
+    "Nom glyph origin",
+    "Reading",
+    "Synonym",
+    "Antonym",
+    "Hyponym",
+    "Hypernym",
+    "Paronym",
+    "Translation",
+    "Coordinate term",
+    "Dialectal variant",
+    "Romanization",
+    "Statistic",
+    "Declension",
+    "Alternative script",
+    "Phrasal verb",
+    "Trivia",
+    "Han character",
+    "Hanzi",
+    "Glyph origin",
+    "Definition",
+    "Compound",
+    "Descendant",
+    "Kanji",
+    "Hanja",
+    "Note",
+    "Derived term",
+    "Usage note",
+    "Alternative form",
+    "Alternative",
+    "Pronunciation",
+    "Further reading",
+    "Anagram",
+    "Reference",
+    "Refs",
+    "Further reference",
+    "See also",
+    "Mutation",
+    "Interjection",
+    "Quotation",
+    "Gallery",
+    "Related term",
+];
+
+/// Headings that are used for word types under a different name than the canonical word type heading.
+static WORD_TYPE_HEADING_ALIASES: &[(&str, &str)] =
+    &[("Punctuation", "Punctuation mark"), ("Emoji", "Symbol")];
+
+/// Headings whose normalised form has at least this many characters may match a known heading with one typo.
+pub(crate) const FUZZY_MINIMUM_LENGTH: usize = 6;
+
+lazy_static! {
+    static ref KNOWN_HEADINGS: HashMap<String, HeadingKind> = {
+        let mut known_headings = HashMap::new();
+        for heading in IGNORED_HEADINGS {
+            known_headings.insert(normalise_heading(heading), HeadingKind::Ignored);
+        }
+        for heading in WORD_TYPE_HEADINGS {
+            known_headings.insert(normalise_heading(heading), HeadingKind::WordType(heading));
+        }
+        for (alias, heading) in WORD_TYPE_HEADING_ALIASES {
+            known_headings.insert(normalise_heading(alias), HeadingKind::WordType(heading));
+        }
+        known_headings
+    };
+}
+
+/// The meaning of a section heading below the language level.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HeadingKind {
+    /// A word type, given by its canonical spelling.
+    WordType(&'static str),
+    /// An unnumbered `Etymology` heading, whose siblings contain the details of the word.
+    Etymology,
+    /// A numbered heading like `Etymology 2`, whose children contain the details of the word.
+    NumberedEtymology,
+    /// A heading that does not contain information we extract.
+    Ignored,
+    /// A heading that could not be recognised.
+    Unknown,
+}
+
+/// Classify a section heading.
+///
+/// Headings are compared case-insensitively, ignoring whitespace, hyphens and underscores,
+/// trailing numbers and plural forms.
+/// Headings that are not found verbatim match a known heading with a single typo if they are long enough.
+pub fn classify_heading(label: &str) -> HeadingKind {
+    let label = label.trim();
+    let unnumbered_label = label.trim_end_matches(|c: char| c.is_ascii_digit());
+    let numbered = unnumbered_label.len() != label.len();
+    let normalised_label = normalise_heading(unnumbered_label);
+
+    if normalised_label == "etymology" || normalised_label == "etymologies" {
+        return if numbered {
+            HeadingKind::NumberedEtymology
+        } else {
+            HeadingKind::Etymology
+        };
+    }
+
+    for variant in heading_variants(&normalised_label) {
+        if let Some(kind) = KNOWN_HEADINGS.get(&variant) {
+            return *kind;
+        }
+    }
+
+    if normalised_label.chars().count() >= FUZZY_MINIMUM_LENGTH {
+        for variant in heading_variants(&normalised_label) {
+            if let Some(kind) = KNOWN_HEADINGS
+                .iter()
+                .filter(|(known_heading, _)| is_single_edit_apart(&variant, known_heading))
+                // Sort to get a deterministic result if there are multiple candidates.
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, kind)| *kind)
+            {
+                return kind;
+            }
+        }
+    }
+
+    HeadingKind::Unknown
+}
+
+/// A key that is equal for the spelling variants of a heading that [`classify_heading`] treats alike,
+/// without the typo tolerance.
+/// The key is lowercase, without whitespace, hyphens, underscores and trailing numbers, and singular.
+pub fn heading_key(label: &str) -> String {
+    let label = label.trim().trim_end_matches(|c: char| c.is_ascii_digit());
+    let normalised_label = normalise_heading(label);
+    if let Some(stem) = normalised_label.strip_suffix("ies") {
+        format!("{stem}y")
+    } else if let Some(stem) = normalised_label.strip_suffix('s') {
+        stem.to_string()
+    } else {
+        normalised_label
+    }
+}
+
+/// Lowercase the heading and remove all whitespace, hyphens and underscores.
+pub(crate) fn normalise_heading(heading: &str) -> String {
+    heading
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The singular and plural variants of a normalised heading, starting with the heading itself.
+fn heading_variants(normalised_heading: &str) -> Vec<String> {
+    let mut variants = vec![normalised_heading.to_string()];
+    if let Some(stem) = normalised_heading.strip_suffix("ies") {
+        variants.push(format!("{stem}y"));
+    }
+    if let Some(stem) = normalised_heading.strip_suffix('s') {
+        variants.push(stem.to_string());
+    } else {
+        variants.push(format!("{normalised_heading}s"));
+    }
+    variants
+}
+
+/// Returns true if the heading is a spelling variant of the given canonical heading,
+/// using the same normalisation as [`classify_heading`], but without typo tolerance.
+pub fn heading_matches(label: &str, canonical: &str) -> bool {
+    let label = label.trim().trim_end_matches(|c: char| c.is_ascii_digit());
+    let canonical = normalise_heading(canonical);
+    heading_variants(&normalise_heading(label)).contains(&canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_word_type_headings() {
+        // The most frequent word type headings of the English edition.
+        for (label, word_type) in [
+            ("Noun", "Noun"),
+            ("Verb", "Verb"),
+            ("Adjective", "Adjective"),
+            ("Proper noun", "Proper noun"),
+            ("Adverb", "Adverb"),
+            ("Prepositional phrase", "Prepositional phrase"),
+        ] {
+            assert_eq!(
+                classify_heading(label),
+                HeadingKind::WordType(word_type),
+                "{label}"
+            );
+        }
+        // Interjections are listed as ignored, since they are not extracted as words.
+        assert_eq!(classify_heading("Interjection"), HeadingKind::Ignored);
+    }
+
+    #[test]
+    fn classifies_spelling_variants() {
+        assert_eq!(
+            classify_heading("Proper Noun"),
+            HeadingKind::WordType("Proper noun")
+        );
+        assert_eq!(
+            classify_heading("proper-noun"),
+            HeadingKind::WordType("Proper noun")
+        );
+        assert_eq!(classify_heading(" Nouns "), HeadingKind::WordType("Noun"));
+        assert_eq!(
+            classify_heading("Prepositional_phrases"),
+            HeadingKind::WordType("Prepositional phrase")
+        );
+        assert_eq!(classify_heading("Noun 2"), HeadingKind::WordType("Noun"));
+        assert_eq!(classify_heading("Alternative forms"), HeadingKind::Ignored);
+        assert_eq!(classify_heading("Further references"), HeadingKind::Ignored);
+    }
+
+    #[test]
+    fn classifies_aliases() {
+        assert_eq!(
+            classify_heading("Punctuation"),
+            HeadingKind::WordType("Punctuation mark")
+        );
+        assert_eq!(classify_heading("Emoji"), HeadingKind::WordType("Symbol"));
+    }
+
+    #[test]
+    fn classifies_etymologies() {
+        assert_eq!(classify_heading("Etymology"), HeadingKind::Etymology);
+        assert_eq!(
+            classify_heading("Etymology 2"),
+            HeadingKind::NumberedEtymology
+        );
+        assert_eq!(classify_heading("Etymologies"), HeadingKind::Etymology);
+    }
+
+    #[test]
+    fn classifies_near_misses() {
+        // Typos that occur in dumps.
+        assert_eq!(
+            classify_heading("Adjectve"),
+            HeadingKind::WordType("Adjective")
+        );
+        assert_eq!(classify_heading("Pronounciation"), HeadingKind::Ignored);
+        assert_eq!(classify_heading("Synomyms"), HeadingKind::Ignored);
+        // Short headings must match exactly, since a single typo changes too much of them.
+        assert_eq!(classify_heading("Nun"), HeadingKind::Unknown);
+        assert_eq!(classify_heading("Verbb"), HeadingKind::Unknown);
+    }
+
+    #[test]
+    fn classifies_unknown_headings() {
+        assert_eq!(classify_heading("Conjugation table"), HeadingKind::Unknown);
+        assert_eq!(classify_heading("Lorem ipsum"), HeadingKind::Unknown);
+        assert_eq!(classify_heading(""), HeadingKind::Unknown);
+    }
+
+    #[test]
+    fn normalises_headings() {
+        assert_eq!(normalise_heading("Proper noun"), "propernoun");
+        assert_eq!(normalise_heading("Proper-Noun_"), "propernoun");
+        assert_eq!(normalise_heading("Ü ber"), "über");
+    }
+
+    #[test]
+    fn lists_heading_variants() {
+        assert_eq!(heading_variants("noun"), vec!["noun", "nouns"]);
+        assert_eq!(heading_variants("nouns"), vec!["nouns", "noun"]);
+        assert_eq!(
+            heading_variants("entries"),
+            vec!["entries", "entry", "entrie"]
+        );
+    }
+
+    #[test]
+    fn checks_single_edits() {
+        assert!(is_single_edit_apart("noun", "nouns"));
+        assert!(is_single_edit_apart("nouns", "noun"));
+        assert!(is_single_edit_apart("adjective", "adjectlve"));
+        assert!(is_single_edit_apart("pronunciation", "pronounciation"));
+        assert!(!is_single_edit_apart("noun", "noun"));
+        assert!(!is_single_edit_apart("noun", "nuon"));
+        assert!(!is_single_edit_apart("noun", "nounss"));
+        assert!(!is_single_edit_apart("verb", "adverb"));
+    }
+
+    #[test]
+    fn matches_headings() {
+        assert!(heading_matches("Usage notes", "Usage note"));
+        assert!(heading_matches("Usage note", "Usage notes"));
+        assert!(heading_matches("Translations", "Translation"));
+        assert!(heading_matches("Derived terms 2", "Derived term"));
+        assert!(!heading_matches("Derived terms", "Related term"));
+        assert!(!heading_matches("Transaltions", "Translation"));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::parser::words::Word;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Links a word entry to the page and revision it was extracted from in a dump of a certain date.
+/// Collecting these links of several dumps allows to follow entries over time.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct WordLink {
+    /// The identifier of the entry, see [`word_id`].
+    pub word_id: String,
+    pub word: String,
+    pub language_english_name: String,
+    pub word_type: String,
+    pub page_id: i64,
+    pub revision_id: i64,
+    /// The date of the dump, if known.
+    /// It is serialised like `20230801`.
+    #[serde(default, with = "crate::dates::optional_dump_date")]
+    pub dump_date: Option<NaiveDate>,
+}
+
+impl WordLink {
+    pub fn new(word: &Word, page_id: i64, revision_id: i64, dump_date: Option<NaiveDate>) -> Self {
+        Self {
+            word_id: word_id(word),
+            word: word.word.clone(),
+            language_english_name: word.language_english_name.to_string(),
+            word_type: word.word_type.to_string(),
+            page_id,
+            revision_id,
+            dump_date,
+        }
+    }
+}
+
+/// An identifier of a word entry that stays the same across dumps.
+///
+/// It is the 64 bit FNV-1a hash of the word, its language, its word type and its [etymology](Word::etymology) in hexadecimal.
+/// Homographs declared in different etymology sections therefore get different identifiers,
+/// while the etymology is left out of the hash of words that are not declared in one.
+/// Since it does not depend on page or revision ids, it survives edits, moves and re-creations of pages.
+/// Word types are hashed as given, so identifiers of words with localised word types differ from canonical ones.
+pub fn word_id(word: &Word) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let etymology = word
+        .etymology
+        .map(|etymology| etymology.to_string())
+        .unwrap_or_default();
+    let mut parts = vec![
+        &*word.language_english_name,
+        word.word.as_str(),
+        &*word.word_type,
+    ];
+    if word.etymology.is_some() {
+        parts.push(&etymology);
+    }
+
+    let mut hash = OFFSET_BASIS;
+    // The unit separator cannot appear in titles, so the concatenation is unambiguous.
+    for part in parts {
+        for byte in part.bytes().chain([0x1f]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    format!("{hash:016x}")
+}
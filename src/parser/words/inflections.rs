@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::templates::{
+    section_definitions, section_headword_templates, text_templates, Template,
+};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// An inflected form of a lemma, e.g. `went` of `go` with the tags `past`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct InflectedForm {
+    pub language_english_name: String,
+    pub form: String,
+    pub lemma: String,
+    /// The grammatical tags of the form, like `genitive` and `plural`.
+    pub tags: Vec<String>,
+    pub source: InflectionSource,
+}
+
+/// Where an [`InflectedForm`] was found.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum InflectionSource {
+    /// A form-of definition like `{{plural of|en|cat}}` on the page of the form.
+    FormOfDefinition,
+    /// A headword template like `{{de-noun|pl=Häuser}}` or `{{head|en|noun|plural|cats}}` on the page of the lemma.
+    HeadTemplate,
+}
+
+/// Templates listing the tags of the form after the language, the lemma and an optional display form,
+/// like `{{inflection of|de|Haus||gen|s}}`.
+static INFLECTION_OF_TEMPLATES: &[&str] = &["inflection of", "infl of"];
+/// Parts of names of form-of templates that do not name an inflection, like `{{alternative spelling of}}`.
+static NON_INFLECTION_FORM_OF_PARTS: &[&str] = &[
+    "alternative",
+    "alt ",
+    "spelling",
+    "romanization",
+    "abbreviation",
+    "acronym",
+    "initialism",
+    "synonym",
+    "clipping",
+    "ellipsis",
+    "contraction",
+    "euphemistic",
+    "dialect",
+    "short for",
+    "archaic form",
+    "obsolete form",
+    "nonstandard form",
+];
+/// Separators between the tag sets of `{{inflection of}}`.
+static TAG_SET_SEPARATORS: &[&str] = &[";", "//"];
+/// Named arguments of headword templates that give an inflected form, and the tags of that form.
+/// Arguments may be numbered for additional forms, like `pl2`.
+static HEAD_TEMPLATE_FORM_ARGUMENTS: &[(&str, &[&str])] = &[
+    ("pl", &["plural"]),
+    ("gen", &["genitive"]),
+    ("genpl", &["genitive", "plural"]),
+    ("f", &["feminine"]),
+    ("fpl", &["feminine", "plural"]),
+    ("m", &["masculine"]),
+    ("mpl", &["masculine", "plural"]),
+    ("comp", &["comparative"]),
+    ("sup", &["superlative"]),
+    ("past", &["past"]),
+    ("past_ptc", &["past", "participle"]),
+    ("pres_ptc", &["present", "participle"]),
+    ("pres_3sg", &["third-person", "singular", "present"]),
+    ("dim", &["diminutive"]),
+];
+
+/// Extract inflected forms from a wiktionary page.
+///
+/// Forms are taken from form-of definitions on the page of the form,
+/// and from the arguments of headword templates on the page of the lemma.
+/// Inflection tables are only covered if their templates were expanded beforehand,
+/// e.g. by [`TemplateExpander`](crate::parser::template_store::TemplateExpander), into such definitions or templates.
+pub fn wikitext_to_inflected_forms(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(InflectedForm),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(InflectedForm),
+) {
+    for subsection in &section.subsections {
+        if let HeadingKind::WordType(_) = classify_heading(&subsection.headline.label) {
+            let mut emit = |form: &str, lemma: &str, tags: Vec<String>, source| {
+                let (form, lemma) = (form.trim(), lemma.trim());
+                if !form.is_empty() && !lemma.is_empty() && form != lemma && form != "-" {
+                    result_consumer(InflectedForm {
+                        language_english_name: language_english_name.to_string(),
+                        form: form.to_string(),
+                        lemma: lemma.to_string(),
+                        tags,
+                        source,
+                    });
+                }
+            };
+
+            for template in section_headword_templates(subsection) {
+                for (form, tags) in head_template_forms(&template) {
+                    emit(form, title, tags, InflectionSource::HeadTemplate);
+                }
+            }
+
+            for text in &section_definitions(subsection) {
+                for template in text_templates(text) {
+                    if let Some((lemma, tags)) = form_of(&template) {
+                        emit(title, lemma, tags, InflectionSource::FormOfDefinition);
+                    }
+                }
+            }
+        }
+
+        parse_section(title, language_english_name, subsection, result_consumer);
+    }
+}
+
+fn head_template_forms(template: &Template) -> Vec<(&str, Vec<String>)> {
+    let mut forms = Vec::new();
+
+    if template.name == "head" {
+        // After the language and the word type, the arguments alternate between tags and forms.
+        let mut arguments = template.positional.iter().skip(2);
+        while let (Some(tags), Some(form)) = (arguments.next(), arguments.next()) {
+            if !tags.is_empty() {
+                forms.push((
+                    form.as_str(),
+                    tags.split_whitespace().map(ToString::to_string).collect(),
+                ));
+            }
+        }
+    } else {
+        for (name, value) in &template.named {
+            let unnumbered_name = name.trim_end_matches(|c: char| c.is_ascii_digit());
+            if let Some((_, tags)) = HEAD_TEMPLATE_FORM_ARGUMENTS
+                .iter()
+                .find(|(argument, _)| *argument == unnumbered_name)
+            {
+                forms.push((
+                    value.as_str(),
+                    tags.iter().map(ToString::to_string).collect(),
+                ));
+            }
+        }
+    }
+
+    forms
+}
+
+/// The lemma and tags of a form-of template that declares the page title an inflected form of the lemma.
+fn form_of(template: &Template) -> Option<(&str, Vec<String>)> {
+    let name = template.name.as_str();
+    if template.is_any_of(INFLECTION_OF_TEMPLATES) {
+        let tags = template
+            .positional
+            .iter()
+            .skip(3)
+            .filter(|tag| !tag.is_empty() && !TAG_SET_SEPARATORS.contains(&tag.as_str()))
+            .map(ToString::to_string)
+            .collect();
+        template.positional(1).map(|lemma| (lemma, tags))
+    } else if let Some(tags) = name.strip_suffix(" of") {
+        if NON_INFLECTION_FORM_OF_PARTS
+            .iter()
+            .any(|part| name.contains(part))
+        {
+            return None;
+        }
+        // Form-of templates like `{{past participle of|en|go}}` name their tags.
+        let tags = tags.split_whitespace().map(ToString::to_string).collect();
+        template.positional(1).map(|lemma| (lemma, tags))
+    } else {
+        None
+    }
+}
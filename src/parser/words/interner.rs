@@ -0,0 +1,43 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    static ref INTERNED_STRINGS: Mutex<HashSet<Arc<str>>> = Mutex::new(HashSet::new());
+}
+
+/// Returns a shared instance of the given string.
+///
+/// A dump repeats the same few thousand language names and word types millions of times,
+/// so [`Word`](crate::parser::words::Word)s share them instead of owning a copy each.
+/// Interned strings are never freed, hence only strings from such small sets should be interned.
+pub fn intern(string: &str) -> Arc<str> {
+    // A poisoned lock only means that another thread panicked while inserting, which leaves the set intact.
+    let mut interned_strings = INTERNED_STRINGS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(interned) = interned_strings.get(string) {
+        interned.clone()
+    } else {
+        let interned: Arc<str> = Arc::from(string);
+        interned_strings.insert(interned.clone());
+        interned
+    }
+}
+
+/// The amount of distinct strings interned so far.
+pub fn interned_string_count() -> usize {
+    INTERNED_STRINGS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .len()
+}
+
+/// Deserialize a string and intern it, such that words read back from json share their strings as well.
+pub(crate) fn deserialize_interned<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Arc<str>, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    Ok(intern(&string))
+}
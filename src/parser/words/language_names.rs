@@ -0,0 +1,205 @@
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::language_code::LanguageCode;
+use crate::parser::words::interner::intern;
+
+/// The code of words whose language name is not known, which is the ISO 639 code for undetermined languages.
+pub const UNKNOWN_LANGUAGE_CODE: &str = "und";
+
+/// Names of languages without a wiktionary edition, or whose section name differs from the name of their edition,
+/// with their wiktionary language codes.
+static BUILTIN_LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("Translingual", "mul"),
+    ("Alemannic German", "gsw"),
+    ("Aromanian", "rup"),
+    ("Min Nan", "nan"),
+    ("Hokkien", "nan-hbl"),
+    ("Serbo-Croatian", "sh"),
+    ("Norwegian Bokmål", "nb"),
+    ("Norwegian Nynorsk", "nn"),
+    ("West Frisian", "fy"),
+    ("Low German", "nds"),
+    ("Scots", "sco"),
+    ("Mandarin", "cmn"),
+    ("Cantonese", "yue"),
+    ("Hakka", "hak"),
+    ("Literary Chinese", "lzh"),
+    ("Middle Chinese", "ltc"),
+    ("Old Chinese", "och"),
+    ("Old English", "ang"),
+    ("Middle English", "enm"),
+    ("Old High German", "goh"),
+    ("Middle High German", "gmh"),
+    ("Old Saxon", "osx"),
+    ("Middle Low German", "gml"),
+    ("Old Dutch", "odt"),
+    ("Middle Dutch", "dum"),
+    ("Old Frisian", "ofs"),
+    ("Old Norse", "non"),
+    ("Gothic", "got"),
+    ("Old French", "fro"),
+    ("Middle French", "frm"),
+    ("Old Spanish", "osp"),
+    ("Old Irish", "sga"),
+    ("Middle Irish", "mga"),
+    ("Ancient Greek", "grc"),
+    ("Old Church Slavonic", "cu"),
+    ("Old East Slavic", "orv"),
+    ("Old Armenian", "xcl"),
+    ("Classical Syriac", "syc"),
+    ("Aramaic", "arc"),
+    ("Middle Persian", "pal"),
+    ("Old Persian", "peo"),
+    ("Egyptian", "egy"),
+    ("Akkadian", "akk"),
+    ("Sumerian", "sux"),
+    ("Hittite", "hit"),
+    ("Proto-Indo-European", "ine-pro"),
+    ("Proto-Germanic", "gem-pro"),
+    ("Proto-West Germanic", "gmw-pro"),
+    ("Proto-Slavic", "sla-pro"),
+    ("Proto-Italic", "itc-pro"),
+    ("Proto-Celtic", "cel-pro"),
+    ("Proto-Hellenic", "grk-pro"),
+    ("Proto-Indo-Iranian", "iir-pro"),
+    ("Proto-Uralic", "urj-pro"),
+];
+
+lazy_static! {
+    static ref BUILTIN_LANGUAGE_NAMES: Arc<LanguageNames> = Arc::new(LanguageNames::builtin());
+}
+
+/// Resolves the english language names of the sections of pages, like `Old High German`, to their language codes, like `goh`.
+///
+/// The codes are those used by wiktionary, which are ISO 639 codes where one exists,
+/// and codes like `ine-pro` for reconstructed languages and language families otherwise.
+/// The builtin names cover the languages of all wiktionary editions and common historical and reconstructed languages.
+/// For all languages, load wiktionary's language data with [`load`](Self::load).
+#[derive(Clone, Default)]
+pub struct LanguageNames {
+    /// Maps lowercase names to codes.
+    codes: HashMap<String, Arc<str>>,
+}
+
+/// Formats only the amount of names, since there may be thousands.
+impl Debug for LanguageNames {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LanguageNames({} names)", self.codes.len())
+    }
+}
+
+/// An entry of a language data file, by code.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LanguageData {
+    /// Only the canonical name, like `{"goh": "Old High German"}`.
+    Name(String),
+    /// Like `{"goh": {"canonicalName": "Old High German", "otherNames": ["Old German"]}}`.
+    Entry {
+        #[serde(rename = "canonicalName", alias = "canonical_name", alias = "name")]
+        canonical_name: String,
+        #[serde(
+            default,
+            rename = "otherNames",
+            alias = "other_names",
+            alias = "aliases"
+        )]
+        other_names: Vec<String>,
+    },
+    /// A list starting with the canonical name, like the data modules of wiktionary, e.g. `{"goh": ["Old High German", 35218, "gmw"]}`.
+    List(Vec<serde_json::Value>),
+}
+
+impl LanguageNames {
+    /// The builtin names, shared by all [`WordExtractionOptions`](crate::parser::words::WordExtractionOptions) that do not load their own.
+    pub fn shared_builtin() -> Arc<Self> {
+        BUILTIN_LANGUAGE_NAMES.clone()
+    }
+
+    /// The english names of the languages of all wiktionary editions and the builtin names.
+    pub fn builtin() -> Self {
+        let mut language_names = Self::default();
+        for language_code in LanguageCode::ALL {
+            if *language_code != LanguageCode::SimpleEnglish {
+                language_names.insert(
+                    language_code.to_english_name(),
+                    language_code.to_wiktionary_abbreviation(),
+                );
+            }
+        }
+        for (name, code) in BUILTIN_LANGUAGE_CODES {
+            language_names.insert(name, code);
+        }
+        language_names
+    }
+
+    /// The builtin names, extended and overridden by the names of a json file of wiktionary's language data.
+    ///
+    /// The file maps codes either to canonical names, to objects with a `canonicalName` and optional `otherNames`,
+    /// or to lists starting with the canonical name, as in the data modules of wiktionary.
+    /// Other names never override canonical names.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let data: BTreeMap<String, LanguageData> = serde_json::from_reader(file)?;
+
+        let mut language_names = Self::builtin();
+        let mut other_names = Vec::new();
+        for (code, data) in &data {
+            match data {
+                LanguageData::Name(canonical_name) => language_names.insert(canonical_name, code),
+                LanguageData::Entry {
+                    canonical_name,
+                    other_names: entry_other_names,
+                } => {
+                    language_names.insert(canonical_name, code);
+                    other_names.extend(entry_other_names.iter().map(|name| (name, code)));
+                }
+                LanguageData::List(values) => {
+                    if let Some(canonical_name) = values.first().and_then(|value| value.as_str()) {
+                        language_names.insert(canonical_name, code);
+                    }
+                }
+            }
+        }
+        for (name, code) in other_names {
+            language_names
+                .codes
+                .entry(name.to_lowercase())
+                .or_insert_with(|| intern(code));
+        }
+
+        Ok(language_names)
+    }
+
+    /// Add or replace the code of a language name.
+    pub fn insert(&mut self, name: &str, code: &str) {
+        self.codes.insert(name.to_lowercase(), intern(code));
+    }
+
+    /// The code of the language with the given english name, ignoring case.
+    pub fn code(&self, name: &str) -> Option<&Arc<str>> {
+        self.codes.get(&name.to_lowercase())
+    }
+
+    /// The code of the language with the given english name, or [`UNKNOWN_LANGUAGE_CODE`] if the name is unknown.
+    pub fn resolve(&self, name: &str) -> Arc<str> {
+        self.code(name)
+            .cloned()
+            .unwrap_or_else(|| intern(UNKNOWN_LANGUAGE_CODE))
+    }
+
+    /// The amount of known names.
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+}
@@ -0,0 +1,210 @@
+//! Extraction rules per wiktionary edition, like the localised names of word types and headings to ignore.
+//!
+//! The builtin packs are json files embedded into the binary, one per edition, in `src/parser/words/language_packs`.
+//! To fix the extraction of an edition without a new release, copy its pack into a directory, edit it,
+//! and load the directory with [`LanguagePacks::load_directory`].
+//! The command line interface loads the directory given by `--language-pack-dir` or the environment variable
+//! [`LANGUAGE_PACK_DIRECTORY_VARIABLE`].
+//!
+//! A pack looks like this, where all fields except `edition` are optional:
+//!
+//! ```json
+//! {
+//!   "edition": "de",
+//!   "word_types": [["Noun", "Substantiv"], ["Verb", "Verb"]],
+//!   "etymology_headings": ["Herkunft"],
+//!   "ignored_headings": ["Übersetzungen", "Aussprache"],
+//!   "ignored_languages": []
+//! }
+//! ```
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::parser::words::headings::{classify_heading, normalise_heading, HeadingKind};
+use crate::wiktionary_edition::WiktionaryEdition;
+
+/// The environment variable the command line interface reads the directory of language packs from.
+pub const LANGUAGE_PACK_DIRECTORY_VARIABLE: &str = "WDP_LANGUAGE_PACK_DIR";
+
+/// The embedded packs, by the file they were embedded from.
+static BUILTIN_LANGUAGE_PACK_FILES: &[(&str, &str)] = &[
+    ("en.json", include_str!("language_packs/en.json")),
+    ("fr.json", include_str!("language_packs/fr.json")),
+    ("de.json", include_str!("language_packs/de.json")),
+    ("ru.json", include_str!("language_packs/ru.json")),
+    ("fi.json", include_str!("language_packs/fi.json")),
+];
+
+lazy_static! {
+    static ref BUILTIN_LANGUAGE_PACKS: Arc<LanguagePacks> = Arc::new(LanguagePacks::builtin());
+}
+
+/// The extraction rules of a single edition.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LanguagePack {
+    /// The abbreviation of the edition, like `de`.
+    pub edition: String,
+    /// Pairs of canonical and localised word types, like `["Noun", "Substantiv"]`.
+    /// The canonical word types must be known word type headings of the English wiktionary.
+    #[serde(default)]
+    pub word_types: Vec<(String, String)>,
+    /// Localised headings with the meaning of `Etymology`, which may be numbered like `Etymology 2`.
+    #[serde(default)]
+    pub etymology_headings: Vec<String>,
+    /// Localised headings that do not contain information we extract.
+    #[serde(default)]
+    pub ignored_headings: Vec<String>,
+    /// Names of language sections whose words are not extracted, in addition to `Translingual`.
+    #[serde(default)]
+    pub ignored_languages: Vec<String>,
+    /// The kinds of the localised headings by their normalised form.
+    #[serde(skip)]
+    headings: HashMap<String, HeadingKind>,
+}
+
+impl LanguagePack {
+    /// Parse and validate a pack from json.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut language_pack: Self = serde_json::from_str(json)?;
+        language_pack.index()?;
+        Ok(language_pack)
+    }
+
+    /// Load and validate a pack from a json file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        Self::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|error| Error::Other(format!("Error loading language pack {path:?}: {error}")))
+    }
+
+    /// Build the lookup of localised headings, checking that all canonical word types are known.
+    fn index(&mut self) -> Result<()> {
+        let mut headings = HashMap::new();
+        for heading in &self.ignored_headings {
+            headings.insert(normalise_heading(heading), HeadingKind::Ignored);
+        }
+        for (canonical, localised) in &self.word_types {
+            if let HeadingKind::WordType(canonical) = classify_heading(canonical) {
+                headings.insert(
+                    normalise_heading(localised),
+                    HeadingKind::WordType(canonical),
+                );
+            } else {
+                return Err(Error::Other(format!(
+                    "Language pack {:?} maps {localised:?} to the unknown word type {canonical:?}",
+                    self.edition
+                )));
+            }
+        }
+        for heading in &self.etymology_headings {
+            headings.insert(normalise_heading(heading), HeadingKind::Etymology);
+        }
+        self.headings = headings;
+        Ok(())
+    }
+
+    /// Classify a section heading by the localised headings of this pack,
+    /// falling back to the headings of the English wiktionary.
+    pub fn classify_heading(&self, label: &str) -> HeadingKind {
+        let label = label.trim();
+        let unnumbered_label = label
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .trim_end();
+        let numbered = unnumbered_label.len() != label.len();
+
+        match self.headings.get(&normalise_heading(unnumbered_label)) {
+            Some(HeadingKind::Etymology) if numbered => HeadingKind::NumberedEtymology,
+            Some(kind) => *kind,
+            None => classify_heading(label),
+        }
+    }
+
+    /// Returns true if the words of the language section with the given name are not extracted.
+    pub fn is_ignored_language(&self, language_english_name: &str) -> bool {
+        self.ignored_languages
+            .iter()
+            .any(|ignored| ignored == language_english_name)
+    }
+
+    /// The name of a canonical word type as used by this edition, if known.
+    pub fn localised_word_type(&self, canonical: &str) -> Option<&str> {
+        self.word_types
+            .iter()
+            .find(|(pack_canonical, _)| pack_canonical == canonical)
+            .map(|(_, localised)| localised.as_str())
+    }
+
+    /// The canonical name of a word type as used by this edition, if known.
+    pub fn canonical_word_type(&self, localised: &str) -> Option<&str> {
+        self.word_types
+            .iter()
+            .find(|(_, pack_localised)| pack_localised == localised)
+            .map(|(canonical, _)| canonical.as_str())
+    }
+}
+
+/// The language packs of all editions that have one, by the abbreviation of their edition.
+#[derive(Debug, Clone, Default)]
+pub struct LanguagePacks {
+    packs: HashMap<String, LanguagePack>,
+}
+
+impl LanguagePacks {
+    /// The builtin packs, shared by all [`WordExtractionOptions`](crate::parser::words::WordExtractionOptions) that do not load their own.
+    pub fn shared_builtin() -> Arc<Self> {
+        BUILTIN_LANGUAGE_PACKS.clone()
+    }
+
+    /// The packs embedded into the binary.
+    pub fn builtin() -> Self {
+        let mut language_packs = Self::default();
+        for (file, json) in BUILTIN_LANGUAGE_PACK_FILES {
+            let language_pack = LanguagePack::from_json(json)
+                .unwrap_or_else(|error| panic!("Invalid builtin language pack {file}: {error}"));
+            language_packs.insert(language_pack);
+        }
+        language_packs
+    }
+
+    /// The builtin packs, where the packs of the json files in the given directory replace the builtin packs of their editions.
+    pub fn load_directory(directory: impl AsRef<Path>) -> Result<Self> {
+        let mut language_packs = Self::builtin();
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |extension| extension == "json")
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        for path in paths {
+            language_packs.insert(LanguagePack::load(path)?);
+        }
+        Ok(language_packs)
+    }
+
+    /// Add a pack, replacing the pack of the same edition if there is one.
+    pub fn insert(&mut self, language_pack: LanguagePack) {
+        self.packs
+            .insert(language_pack.edition.clone(), language_pack);
+    }
+
+    /// The pack of the given edition, if there is one.
+    pub fn get(&self, edition: &WiktionaryEdition) -> Option<&LanguagePack> {
+        self.packs
+            .get(edition.language_code()?.to_wiktionary_abbreviation())
+    }
+}
+
+/// The builtin pack of the given edition, if there is one.
+pub fn builtin_language_pack(edition: &WiktionaryEdition) -> Option<&'static LanguagePack> {
+    BUILTIN_LANGUAGE_PACKS.get(edition)
+}
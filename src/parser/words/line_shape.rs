@@ -0,0 +1,242 @@
+//! Reads the shape of the lines of wikitext-parser through their serde representation.
+//!
+//! wikitext-parser does not export the type of its lines, but they implement [`Serialize`].
+//! [`line_shape`] serializes a line into its list prefix and the number of its text pieces,
+//! without touching the pieces themselves.
+//! The pieces can then be taken in order from [`Section::iter_text_pieces`](wikitext_parser::Section::iter_text_pieces).
+
+use serde::ser::{Impossible, SerializeSeq, SerializeStruct, SerializeStructVariant};
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+
+/// The list prefix of a line if it is a list item, and the number of pieces of its text.
+pub fn line_shape(line: &impl Serialize) -> Result<(Option<String>, usize), UnexpectedShape> {
+    match line.serialize(ShapeSerializer)? {
+        Shape::Line {
+            list_prefix,
+            piece_count,
+        } => Ok((list_prefix, piece_count)),
+        _ => Err(UnexpectedShape("line is not a struct variant")),
+    }
+}
+
+#[derive(Debug)]
+pub struct UnexpectedShape(&'static str);
+
+impl Display for UnexpectedShape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unexpected serde representation of a line: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnexpectedShape {}
+
+impl serde::ser::Error for UnexpectedShape {
+    fn custom<T: Display>(_message: T) -> Self {
+        Self("custom error")
+    }
+}
+
+enum Shape {
+    /// The list prefix of a line.
+    String(String),
+    /// The number of pieces of a text.
+    Count(usize),
+    Line {
+        list_prefix: Option<String>,
+        piece_count: usize,
+    },
+}
+
+struct ShapeSerializer;
+
+/// The fields of a line or a text.
+#[derive(Default)]
+struct ShapeFields {
+    list_prefix: Option<String>,
+    piece_count: usize,
+}
+
+struct SeqCount(usize);
+
+macro_rules! unexpected {
+    ($($method:ident($($argument:ty),*);)*) => {
+        $(fn $method(self, $(_: $argument),*) -> Result<Shape, UnexpectedShape> {
+            Err(UnexpectedShape(stringify!($method)))
+        })*
+    };
+}
+
+impl Serializer for ShapeSerializer {
+    type Ok = Shape;
+    type Error = UnexpectedShape;
+    type SerializeSeq = SeqCount;
+    type SerializeTuple = Impossible<Shape, UnexpectedShape>;
+    type SerializeTupleStruct = Impossible<Shape, UnexpectedShape>;
+    type SerializeTupleVariant = Impossible<Shape, UnexpectedShape>;
+    type SerializeMap = Impossible<Shape, UnexpectedShape>;
+    type SerializeStruct = ShapeFields;
+    type SerializeStructVariant = ShapeFields;
+
+    fn serialize_str(self, v: &str) -> Result<Shape, UnexpectedShape> {
+        Ok(Shape::String(v.to_string()))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqCount, UnexpectedShape> {
+        Ok(SeqCount(0))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ShapeFields, UnexpectedShape> {
+        Ok(ShapeFields::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ShapeFields, UnexpectedShape> {
+        Ok(ShapeFields::default())
+    }
+
+    unexpected! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Shape, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_some"))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<Shape, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_newtype_struct"))
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Shape, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_newtype_variant"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_tuple_struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_tuple_variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, UnexpectedShape> {
+        Err(UnexpectedShape("serialize_map"))
+    }
+}
+
+/// Counts the elements of a sequence without serializing them.
+impl SerializeSeq for SeqCount {
+    type Ok = Shape;
+    type Error = UnexpectedShape;
+
+    fn serialize_element<T: ?Sized + Serialize>(
+        &mut self,
+        _value: &T,
+    ) -> Result<(), UnexpectedShape> {
+        self.0 += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape, UnexpectedShape> {
+        Ok(Shape::Count(self.0))
+    }
+}
+
+impl ShapeFields {
+    fn add_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), UnexpectedShape> {
+        match value.serialize(ShapeSerializer)? {
+            Shape::String(list_prefix) => self.list_prefix = Some(list_prefix),
+            Shape::Count(piece_count) => self.piece_count = piece_count,
+            Shape::Line { .. } => return Err(UnexpectedShape("nested line")),
+        }
+        Ok(())
+    }
+}
+
+/// A text with its field `pieces`.
+impl SerializeStruct for ShapeFields {
+    type Ok = Shape;
+    type Error = UnexpectedShape;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), UnexpectedShape> {
+        self.add_field(value)
+    }
+
+    fn end(self) -> Result<Shape, UnexpectedShape> {
+        Ok(Shape::Count(self.piece_count))
+    }
+}
+
+/// A line with the fields `text` and, if it is a list item, `list_prefix`.
+impl SerializeStructVariant for ShapeFields {
+    type Ok = Shape;
+    type Error = UnexpectedShape;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), UnexpectedShape> {
+        self.add_field(value)
+    }
+
+    fn end(self) -> Result<Shape, UnexpectedShape> {
+        Ok(Shape::Line {
+            list_prefix: self.list_prefix,
+            piece_count: self.piece_count,
+        })
+    }
+}
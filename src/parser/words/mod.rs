@@ -1,21 +1,60 @@
 use lazy_static::lazy_static;
+use log::{debug, trace, warn};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::future::Future;
-use wikitext_parser::Section;
+use std::sync::Arc;
+use wikitext_parser::{parse_wikitext, Section};
 
 use crate::error::{Error, Result};
+use crate::log_excerpt::debug_excerpt;
 use crate::parser::Wikitext;
+use crate::wiktionary_edition::WiktionaryEdition;
+
+use self::classification::{PageClassifier, PagePipeline};
+use self::features::GrammaticalFeatures;
+use self::headings::{classify_heading, HeadingKind};
+use self::interner::{deserialize_interned, intern};
+use self::language_names::{LanguageNames, UNKNOWN_LANGUAGE_CODE};
+use self::language_pack::{LanguagePack, LanguagePacks};
+use self::word_types::WordTypeNaming;
+
+pub mod alternative_forms;
+pub mod citations;
+pub mod classification;
+pub mod clean_text;
+pub mod definitions;
+pub mod etymology;
+pub mod features;
+pub mod function_words;
+pub mod headings;
+pub mod ids;
+pub mod inflections;
+pub mod interner;
+pub mod language_names;
+pub mod language_pack;
+mod line_shape;
+pub mod morphology;
+pub mod phrases;
+pub mod symbols;
+pub mod templates;
+pub mod translations;
+pub mod word_types;
 
 lazy_static! {
-    static ref IGNORED_PATTERN: Regex =
-        Regex::new("(Wiktionary:|Appendix:|Help:|Rhymes:|Template:|MediaWiki:|Citations:|Module:|Reconstruction:|Thesaurus:|Concordance:).*|.*(/derived terms)").unwrap();
-    static ref WORD_TYPE_PATTERN: Regex =
-        Regex::new("Word|Noun|Proper noun|Dependent noun|Prenoun|Participle|Gerund(ive)?|Verb|Preverb|Predicative|Conjugation|Adjective|Comparative-only adjectives|Determinative|Adverb|Adnominal|Inflection|Pronoun|Preposition|Postposition|Ambiposition|Circumposition|Conjunction|Initial|Prefix|Suffix|Final|Affix|Infix|Interfix|Circumfix|Clitic|Article|Particle|Locative|Determiner|Classifier|Subordinate modifier|Contraction|Combining form|Compound part|Enclitic|Relative|Phrase|Propositional phrase|Proverb|Idiom|Honorific title|Ideophone|Phonogram|Onomatopoeia|Phoneme|Ligature|Syllable|Letter|Symbol|Counter|Number|Numeral|Multiple parts of speech|Punctuation mark|Diacritical mark|Root")
-            .unwrap();
+    static ref DEFAULT_PAGE_CLASSIFIER: PageClassifier = PageClassifier::default();
     static ref IGNORED_LANGUAGE_PATTERN: Regex = Regex::new("Translingual").unwrap();
-    static ref IGNORED_SUBSECTION_PATTERN: Regex = Regex::new("Variant spellings|Relational forms|Spelling variants|Other usage|Other versions|Possessed forms|Graphical notes|Design|Echo word|From|Description|Derived characters|Derived|Derivatives|Alternate spelling|Accentuation notes|Accentological notes|Usage|Citations?|Examples?|Sources|User notes?|Work to be done|Stem|Sign values|Reconstruction|Production|Logogram|Holonyms?|Meronyms|Forms?|Dialectal synonyms?|Decadents?|Abbreviations?|Borrowed terms?|External (L|l)inks?|Related words?|Standard form|Nom glyph origin|Readings?|Synonyms?|Antonyms?|Hyponyms?|Hypernyms?|Paronyms?|Translations?|Coordinate terms?|Dialectal variants?|Romanization|Statistics?|Declension|Alternative scripts?|Phrasal verbs?|Trivia|Han character|Hanzi|Glyph origin|Definitions?|Compounds?|Descendants?|Kanji|Hanja|Notes?|Derived (t|T)erms?|Usage notes|Alternative forms|Alternative|Etymology|Pronunciation( [1-9][0-9]*)?|Further reading|Anagrams|References?|Refs|Further references?|See ?(a|A)lso|Mutation|Interjection|Quotations|Gallery|Related (t|T)erms?").unwrap();
 }
 
+/// Returns true if words are not extracted from the page with the given title according to the default classifier.
+/// This is used by extractors that are not configured by [`WordExtractionOptions`].
+fn is_ignored_title(title: &str) -> bool {
+    DEFAULT_PAGE_CLASSIFIER.classify(None, title, &[]) != PagePipeline::Words
+}
+
+/// A word extracted from a wiktionary page.
+/// Words are ordered by `word`, then by `language_english_name`, then by `word_type`, then by `features`, and then by `etymology`.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Word {
     /// The word itself.
     /// Multiple `Word`s may have the same `word` if they are of a different language or type.
@@ -23,155 +62,405 @@ pub struct Word {
 
     /// The english name of the language this word is from.
     /// While different languages may contain the same words, there will be a separate word instance for each.
-    pub language_english_name: String,
+    /// The name is [interned](interner::intern), such that all words of a language share it.
+    #[serde(deserialize_with = "deserialize_interned")]
+    pub language_english_name: Arc<str>,
+
+    /// The code of the language, resolved from its english name by [`LanguageNames`],
+    /// or [`UNKNOWN_LANGUAGE_CODE`] if the name is unknown.
+    /// The code is [interned](interner::intern) as well.
+    #[serde(
+        default = "unknown_language_code",
+        deserialize_with = "deserialize_interned"
+    )]
+    pub language_code: Arc<str>,
 
-    /// The word type, as declared by wiktionary.
+    /// The word type, as declared by wiktionary, in its canonical English spelling.
+    /// Use [`WordTypeNaming`] to get the name used by the edition instead.
     /// While a word may have multiple types, there will be a separate word instance for each.
-    pub word_type: String,
+    /// The word type is [interned](interner::intern), such that all words of a type share it.
+    #[serde(deserialize_with = "deserialize_interned")]
+    pub word_type: Arc<str>,
+
+    /// Grammatical features like gender and countability, as declared by the headword templates of the word.
+    #[serde(default, skip_serializing_if = "GrammaticalFeatures::is_empty")]
+    pub features: GrammaticalFeatures,
+
+    /// The number of the etymology section the word is declared in, counted in the order of the page,
+    /// like `2` for the second `===Etymology 2===`, or `None` if it is declared directly in the language section.
+    /// It distinguishes homographs of the same language and word type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etymology: Option<u32>,
+}
+
+fn unknown_language_code() -> Arc<str> {
+    intern(UNKNOWN_LANGUAGE_CODE)
+}
+
+/// The english language name of translingual entries, like taxonomic names, symbols and CJK characters.
+pub const TRANSLINGUAL_LANGUAGE_ENGLISH_NAME: &str = "Translingual";
+/// The pseudo-language code wiktionary uses for translingual entries.
+pub const TRANSLINGUAL_LANGUAGE_CODE: &str = "mul";
+
+/// Options for [`extract_words_from_wikitext`] and [`extract_words_with_options`].
+#[derive(Debug, Clone)]
+pub struct WordExtractionOptions {
+    /// The edition the wikitext is from.
+    pub edition: WiktionaryEdition,
+    /// How word types are named.
+    pub word_type_naming: WordTypeNaming,
+    /// Also extract words from `Translingual` sections, which are ignored by default.
+    /// Their words have [`TRANSLINGUAL_LANGUAGE_ENGLISH_NAME`] as language and [`TRANSLINGUAL_LANGUAGE_CODE`] as code.
+    pub include_translingual: bool,
+    /// Decides which pages words are extracted from.
+    /// Words are only extracted from pages classified as [`PagePipeline::Words`].
+    pub page_classifier: PageClassifier,
+    /// Resolves the language names of words to their codes.
+    pub language_names: Arc<LanguageNames>,
+    /// Localised headings and ignore lists of editions, used for the headings of pages of [`edition`](Self::edition).
+    pub language_packs: Arc<LanguagePacks>,
+}
+
+impl Default for WordExtractionOptions {
+    fn default() -> Self {
+        Self {
+            edition: WiktionaryEdition::Unknown,
+            word_type_naming: WordTypeNaming::Canonical,
+            include_translingual: false,
+            page_classifier: PageClassifier::default(),
+            language_names: LanguageNames::shared_builtin(),
+            language_packs: LanguagePacks::shared_builtin(),
+        }
+    }
+}
+
+/// Extract words from the raw wikitext of a single page.
+/// This is meant for applications that obtain pages from other sources than dump files.
+/// Errors while parsing and extracting are logged and otherwise ignored.
+pub fn extract_words_from_wikitext(
+    title: &str,
+    wikitext: &str,
+    options: &WordExtractionOptions,
+) -> Vec<Word> {
+    let wikitext = parse_wikitext(wikitext, title.to_string(), |error| {
+        debug!("Error parsing page {title:?}: {}", debug_excerpt(&error))
+    });
+
+    let mut words = Vec::new();
+    extract_words_with_options(
+        options,
+        title,
+        &wikitext,
+        |word| words.push(word),
+        |error| debug!("Error extracting words from page {title:?}: {error}"),
+    );
+    words
 }
 
-/// Extract words from a wiktionary page.
+/// Extract words from a wiktionary page of the given edition.
+/// All editions are parsed with the layout of the English wiktionary,
+/// with the localised headings of their [language pack](language_pack) if they have one.
 /// Errors while extracting are handed to `error_consumer`,
 /// while errors while consuming results are returned.
 pub async fn wikitext_to_words<
     WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
 >(
+    edition: &WiktionaryEdition,
+    title: &str,
+    wikitext: &Wikitext,
+    result_consumer: impl FnMut(Word) -> WordConsumerResult,
+    error_consumer: impl FnMut(Error),
+) -> Result<()> {
+    let options = WordExtractionOptions {
+        edition: edition.clone(),
+        ..Default::default()
+    };
+    wikitext_to_words_with_options(&options, title, wikitext, result_consumer, error_consumer).await
+}
+
+/// Like [`wikitext_to_words`], but configured by the given options.
+pub async fn wikitext_to_words_with_options<
+    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+>(
+    options: &WordExtractionOptions,
     title: &str,
     wikitext: &Wikitext,
     mut result_consumer: impl FnMut(Word) -> WordConsumerResult,
-    mut error_consumer: impl FnMut(Error),
+    error_consumer: impl FnMut(Error),
 ) -> Result<()> {
-    if IGNORED_PATTERN.is_match(title) {
+    let mut words = Vec::new();
+    extract_words_with_options(
+        options,
+        title,
+        wikitext,
+        |word| words.push(word),
+        error_consumer,
+    );
+
+    for word in words {
+        result_consumer(word)
+            .await
+            .map_err(|error| Error::WordConsumer { source: error })?;
+    }
+
+    Ok(())
+}
+
+/// Extract words from a wiktionary page of the given edition, without requiring an async runtime.
+/// All editions are parsed with the layout of the English wiktionary,
+/// with the localised headings of their [language pack](language_pack) if they have one.
+/// Errors while extracting are handed to `error_consumer`.
+pub fn extract_words(
+    edition: &WiktionaryEdition,
+    title: &str,
+    wikitext: &Wikitext,
+    result_consumer: impl FnMut(Word),
+    error_consumer: impl FnMut(Error),
+) {
+    let options = WordExtractionOptions {
+        edition: edition.clone(),
+        ..Default::default()
+    };
+    extract_words_with_options(&options, title, wikitext, result_consumer, error_consumer);
+}
+
+/// Like [`extract_words`], but configured by the given options.
+pub fn extract_words_with_options(
+    options: &WordExtractionOptions,
+    title: &str,
+    wikitext: &Wikitext,
+    result_consumer: impl FnMut(Word),
+    error_consumer: impl FnMut(Error),
+) {
+    extract_words_with_namespace(
+        options,
+        None,
+        title,
+        wikitext,
+        result_consumer,
+        error_consumer,
+    );
+}
+
+/// Like [`extract_words_with_options`], but with the namespace of the page for its classification.
+pub fn extract_words_with_namespace(
+    options: &WordExtractionOptions,
+    namespace: Option<i64>,
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(Word),
+    mut error_consumer: impl FnMut(Error),
+) {
+    let pipeline = options
+        .page_classifier
+        .classify_wikitext(namespace, title, wikitext);
+    if pipeline != PagePipeline::Words {
         // silently ignore non-words
-        return Ok(());
+        trace!("Not extracting words from page {title:?} classified as {pipeline:?}");
+        return;
     }
+    let edition = &options.edition;
+    let language_pack = options.language_packs.get(edition);
+    trace!("Extracting words from page {title:?} of edition {edition:?}");
 
     let root_section = &wikitext.root_section;
 
     if root_section.headline.level == 1 {
         let word = &root_section.headline.label;
+        let mut result_consumer = |mut word: Word| {
+            // Translingual entries get their pseudo-language code even if the language names do not list it.
+            word.language_code =
+                if &*word.language_english_name == TRANSLINGUAL_LANGUAGE_ENGLISH_NAME {
+                    intern(TRANSLINGUAL_LANGUAGE_CODE)
+                } else {
+                    options.language_names.resolve(&word.language_english_name)
+                };
+            options
+                .word_type_naming
+                .apply_with_language_pack(language_pack, &mut word);
+            result_consumer(word)
+        };
 
         for subsection in &root_section.subsections {
-            parse_language_subsection(word, subsection, &mut result_consumer, &mut error_consumer)
-                .await?;
+            parse_language_subsection(
+                word,
+                subsection,
+                options.include_translingual,
+                language_pack,
+                &mut result_consumer,
+                &mut error_consumer,
+            );
         }
     } else {
         error_consumer(Error::Other(
             "Root section is not at headline level 1".to_string(),
         ));
     }
-
-    Ok(())
 }
 
-async fn parse_language_subsection<
-    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
->(
+fn parse_language_subsection(
     word: &str,
     language_subsection: &Section,
-    result_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    include_translingual: bool,
+    language_pack: Option<&LanguagePack>,
+    result_consumer: &mut impl FnMut(Word),
     error_consumer: &mut impl FnMut(Error),
-) -> Result<()> {
+) {
     let language_english_name = language_subsection.headline.label.as_str();
-    if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+    let is_included_translingual =
+        include_translingual && language_english_name == TRANSLINGUAL_LANGUAGE_ENGLISH_NAME;
+    if !is_included_translingual && IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
         // silently ignore high-level metalanguages
-        return Ok(());
+        return;
+    }
+    if language_pack.map_or(false, |language_pack| {
+        language_pack.is_ignored_language(language_english_name)
+    }) {
+        return;
     }
 
     if language_subsection.subsections.is_empty() {
         result_consumer(Word {
             word: word.to_string(),
-            language_english_name: language_english_name.to_string(),
-            word_type: "Unknown".to_string(),
-        })
-        .await
-        .map_err(|error| Error::WordConsumer { source: error })?;
+            language_english_name: intern(language_english_name),
+            language_code: unknown_language_code(),
+            word_type: intern("Unknown"),
+            features: GrammaticalFeatures::default(),
+            etymology: None,
+        });
     } else {
+        // Details are either directly in the language section, or in numbered etymology sections below it.
+        // Words are collected in the order of the page, such that pages with details at both levels are merged deterministically.
         let mut toplevel_details = false;
         let mut bottomlevel_details = false;
-        let mut bottomlevel_errors = Vec::new();
-
-        for unknown_subsection in &language_subsection.subsections {
-            if unknown_subsection.headline.label == "Etymology"
-                || WORD_TYPE_PATTERN.is_match(&unknown_subsection.headline.label)
-            {
-                toplevel_details = true;
-            } else if unknown_subsection.headline.label != "Etymology"
-                && unknown_subsection.headline.label.starts_with("Etymology")
-            {
-                bottomlevel_details = true;
-                parse_details_subsection(
-                    word,
-                    language_english_name,
-                    unknown_subsection,
-                    result_consumer,
-                    error_consumer,
-                )
-                .await?;
-            } else if IGNORED_SUBSECTION_PATTERN.is_match(&unknown_subsection.headline.label) {
-                // ignore
-            } else {
-                bottomlevel_errors.push(Error::Other(format!(
-                    "Unknown subsection of language: {}",
-                    unknown_subsection.headline.label
-                )));
-            }
-        }
+        let mut words = Vec::new();
+        let mut unknown_heading_errors = Vec::new();
+        let mut etymology_count = 0;
 
-        if toplevel_details {
-            parse_details_subsection(
-                word,
-                language_english_name,
-                language_subsection,
-                result_consumer,
-                error_consumer,
-            )
-            .await?;
+        for subsection in &language_subsection.subsections {
+            match classify_heading_with_language_pack(language_pack, &subsection.headline.label) {
+                HeadingKind::WordType(word_type) => {
+                    toplevel_details = true;
+                    words.push(word_type_section_word(
+                        word,
+                        language_english_name,
+                        word_type,
+                        subsection,
+                        None,
+                    ));
+                }
+                HeadingKind::Etymology => {
+                    toplevel_details = true;
+                }
+                HeadingKind::NumberedEtymology => {
+                    bottomlevel_details = true;
+                    etymology_count += 1;
+                    parse_details_subsection(
+                        word,
+                        language_english_name,
+                        subsection,
+                        etymology_count,
+                        language_pack,
+                        &mut |word| words.push(word),
+                        error_consumer,
+                    );
+                }
+                HeadingKind::Ignored => {}
+                HeadingKind::Unknown => {
+                    unknown_heading_errors.push(Error::UnknownHeading {
+                        language_english_name: language_english_name.to_string(),
+                        label: subsection.headline.label.clone(),
+                    });
+                }
+            }
         }
 
         if toplevel_details && bottomlevel_details {
-            error_consumer(Error::Other(format!(
-                "Found both toplevel and bottomlevel details for language {language_english_name}"
-            )));
+            // Toplevel details apply to all etymologies, so a word declared both at the toplevel
+            // and within an etymology is the same word.
+            warn!("Found both toplevel and bottomlevel details for language {language_english_name} in page {word:?}, merging them");
+            words = words
+                .iter()
+                .filter(|word| {
+                    word.etymology.is_some()
+                        || !words.iter().any(|other| {
+                            other.etymology.is_some()
+                                && other.word_type == word.word_type
+                                && other.features == word.features
+                        })
+                })
+                .cloned()
+                .collect();
         }
 
-        if bottomlevel_details {
-            for error in bottomlevel_errors {
+        if toplevel_details || bottomlevel_details {
+            for error in unknown_heading_errors {
                 error_consumer(error);
             }
         }
+        for word in words {
+            result_consumer(word);
+        }
     }
+}
 
-    Ok(())
+/// The word declared by a word type section like `===Noun===`.
+fn word_type_section_word(
+    word: &str,
+    language_english_name: &str,
+    word_type: &str,
+    word_type_section: &Section,
+    etymology: Option<u32>,
+) -> Word {
+    Word {
+        word: word.to_string(),
+        language_english_name: intern(language_english_name),
+        language_code: unknown_language_code(),
+        word_type: intern(word_type),
+        features: GrammaticalFeatures::from_section(word_type_section),
+        etymology,
+    }
 }
 
-async fn parse_details_subsection<
-    WordConsumerResult: Future<Output = std::result::Result<(), Box<dyn std::error::Error + Send + Sync>>>,
->(
+fn parse_details_subsection(
     word: &str,
     language_english_name: &str,
     details_subsection: &Section,
-    result_consumer: &mut impl FnMut(Word) -> WordConsumerResult,
+    etymology: u32,
+    language_pack: Option<&LanguagePack>,
+    result_consumer: &mut impl FnMut(Word),
     error_consumer: &mut impl FnMut(Error),
-) -> Result<()> {
+) {
     for details_section in &details_subsection.subsections {
-        let word_type = &details_section.headline.label;
-        if WORD_TYPE_PATTERN.is_match(word_type) {
-            result_consumer(Word {
-                word: word.to_string(),
-                language_english_name: language_english_name.to_string(),
-                word_type: word_type.clone(),
-            })
-            .await
-            .map_err(|error| Error::WordConsumer { source: error })?;
-        } else if IGNORED_SUBSECTION_PATTERN.is_match(word_type) {
-            // ignore
-        } else {
-            error_consumer(Error::Other(format!(
-                "Unknown details subsection: {word_type}"
-            )));
+        let label = &details_section.headline.label;
+        match classify_heading_with_language_pack(language_pack, label) {
+            HeadingKind::WordType(word_type) => {
+                result_consumer(word_type_section_word(
+                    word,
+                    language_english_name,
+                    word_type,
+                    details_section,
+                    Some(etymology),
+                ));
+            }
+            HeadingKind::Etymology | HeadingKind::NumberedEtymology | HeadingKind::Ignored => {}
+            HeadingKind::Unknown => {
+                error_consumer(Error::UnknownHeading {
+                    language_english_name: language_english_name.to_string(),
+                    label: label.clone(),
+                });
+            }
         }
     }
+}
 
-    Ok(())
+fn classify_heading_with_language_pack(
+    language_pack: Option<&LanguagePack>,
+    label: &str,
+) -> HeadingKind {
+    if let Some(language_pack) = language_pack {
+        language_pack.classify_heading(label)
+    } else {
+        classify_heading(label)
+    }
 }
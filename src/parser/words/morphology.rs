@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::headings::{classify_heading, heading_matches, HeadingKind};
+use crate::parser::words::templates::{
+    section_list_items, section_templates, text_links, Template,
+};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// A derivational relation between a derived form and the parts it is built from.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct MorphologyEntry {
+    /// The english name of the language of the derived form.
+    pub language_english_name: String,
+    /// The word formed from `base` and `affixes`.
+    pub derived_form: String,
+    /// The word the derived form is built upon, if known.
+    pub base: Option<String>,
+    /// The affixes, with hyphens marking where they attach, e.g. `un-` or `-ness`.
+    pub affixes: Vec<String>,
+    pub source: MorphologySource,
+}
+
+/// Where a [`MorphologyEntry`] was found.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MorphologySource {
+    /// An etymology template like `{{affix|en|un|do}}` on the page of the derived form.
+    EtymologyTemplate,
+    /// A `Derived terms` section on the page of the base.
+    DerivedTerms,
+    /// The page of an affix itself, i.e. a `Prefix` or `Suffix` entry.
+    AffixEntry,
+}
+
+static AFFIX_TEMPLATES: &[&str] = &["affix", "af", "compound", "com"];
+static PREFIX_TEMPLATES: &[&str] = &["prefix", "pre"];
+static SUFFIX_TEMPLATES: &[&str] = &["suffix", "suf"];
+static CONFIX_TEMPLATES: &[&str] = &["confix", "con"];
+
+/// Extract derivational morphology from a wiktionary page.
+pub fn wikitext_to_morphology(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(MorphologyEntry),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(MorphologyEntry),
+) {
+    for subsection in &section.subsections {
+        let label = &subsection.headline.label;
+        match classify_heading(label) {
+            HeadingKind::Etymology | HeadingKind::NumberedEtymology => {
+                for template in section_templates(subsection) {
+                    if let Some(entry) = template_to_entry(title, language_english_name, &template)
+                    {
+                        result_consumer(entry);
+                    }
+                }
+            }
+            HeadingKind::WordType("Prefix" | "Suffix" | "Infix" | "Interfix") => {
+                result_consumer(MorphologyEntry {
+                    language_english_name: language_english_name.to_string(),
+                    derived_form: title.to_string(),
+                    base: None,
+                    affixes: vec![title.to_string()],
+                    source: MorphologySource::AffixEntry,
+                });
+            }
+            _ if heading_matches(label, "Derived term") => {
+                for (_, text) in &section_list_items(subsection) {
+                    for derived_form in text_links(text) {
+                        if derived_form != title {
+                            result_consumer(MorphologyEntry {
+                                language_english_name: language_english_name.to_string(),
+                                derived_form,
+                                base: Some(title.to_string()),
+                                affixes: Vec::new(),
+                                source: MorphologySource::DerivedTerms,
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        parse_section(title, language_english_name, subsection, result_consumer);
+    }
+}
+
+fn template_to_entry(
+    title: &str,
+    language_english_name: &str,
+    template: &Template,
+) -> Option<MorphologyEntry> {
+    // The first argument is the language code, the remaining ones are the parts.
+    let parts: Vec<&str> = template
+        .positional
+        .iter()
+        .skip(1)
+        .map(String::as_str)
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    let (base, affixes) = if template.is_any_of(AFFIX_TEMPLATES) {
+        let base = parts
+            .iter()
+            .find(|part| !part.starts_with('-') && !part.ends_with('-'))
+            .map(ToString::to_string);
+        let affixes = parts
+            .iter()
+            .filter(|part| part.starts_with('-') || part.ends_with('-'))
+            .map(ToString::to_string)
+            .collect();
+        (base, affixes)
+    } else if template.is_any_of(PREFIX_TEMPLATES) {
+        let (prefix, base) = (parts.first()?, parts.get(1)?);
+        (
+            Some(base.to_string()),
+            vec![format!("{}-", prefix.trim_end_matches('-'))],
+        )
+    } else if template.is_any_of(SUFFIX_TEMPLATES) {
+        let (base, suffix) = (parts.first()?, parts.get(1)?);
+        (
+            Some(base.to_string()),
+            vec![format!("-{}", suffix.trim_start_matches('-'))],
+        )
+    } else if template.is_any_of(CONFIX_TEMPLATES) {
+        match parts.as_slice() {
+            [prefix, suffix] => (
+                None,
+                vec![
+                    format!("{}-", prefix.trim_end_matches('-')),
+                    format!("-{}", suffix.trim_start_matches('-')),
+                ],
+            ),
+            [prefix, base, suffix, ..] => (
+                Some(base.to_string()),
+                vec![
+                    format!("{}-", prefix.trim_end_matches('-')),
+                    format!("-{}", suffix.trim_start_matches('-')),
+                ],
+            ),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+
+    if affixes.is_empty() && base.is_none() {
+        return None;
+    }
+
+    Some(MorphologyEntry {
+        language_english_name: language_english_name.to_string(),
+        derived_form: title.to_string(),
+        base,
+        affixes,
+        source: MorphologySource::EtymologyTemplate,
+    })
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::clean_text::{clean_text, CleanTextOptions};
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::templates::{
+    section_definitions, section_templates, text_templates, Template,
+};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// A phrase, proverb or idiom.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PhraseEntry {
+    /// The english name of the language of the phrase.
+    pub language_english_name: String,
+    /// The phrase, i.e. the title of the page.
+    pub phrase: String,
+    /// One of the word types in [`PHRASE_WORD_TYPES`].
+    pub word_type: String,
+    /// The definitions as plain text.
+    pub definitions: Vec<String>,
+    /// Literal translations, as given by `{{lit}}` templates or `lit=` arguments.
+    pub literal_translations: Vec<String>,
+}
+
+/// The word types of the entries that are extracted.
+pub static PHRASE_WORD_TYPES: &[&str] = &["Phrase", "Proverb", "Idiom", "Prepositional phrase"];
+static LITERAL_TEMPLATES: &[&str] = &["lit", "literally"];
+
+/// Extract the phrases, proverbs and idioms from a wiktionary page.
+pub fn wikitext_to_phrases(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(PhraseEntry),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        let mut literal_translations = Vec::new();
+        parse_section(
+            title,
+            language_english_name,
+            language_subsection,
+            &mut literal_translations,
+            &mut result_consumer,
+        );
+    }
+}
+
+/// Literal translations of etymology sections apply to all phrases that follow them.
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    section: &Section,
+    literal_translations: &mut Vec<String>,
+    result_consumer: &mut impl FnMut(PhraseEntry),
+) {
+    for subsection in &section.subsections {
+        match classify_heading(&subsection.headline.label) {
+            HeadingKind::Etymology | HeadingKind::NumberedEtymology => {
+                literal_translations.clear();
+                for template in section_templates(subsection) {
+                    push_literal_translation(&template, literal_translations);
+                }
+            }
+            HeadingKind::WordType(word_type) if PHRASE_WORD_TYPES.contains(&word_type) => {
+                let mut entry = PhraseEntry {
+                    language_english_name: language_english_name.to_string(),
+                    phrase: title.to_string(),
+                    word_type: word_type.to_string(),
+                    definitions: Vec::new(),
+                    literal_translations: literal_translations.clone(),
+                };
+                for text in &section_definitions(subsection) {
+                    for template in text_templates(text) {
+                        push_literal_translation(&template, &mut entry.literal_translations);
+                    }
+                    let definition = clean_text(text, &CleanTextOptions::default());
+                    if !definition.is_empty() {
+                        entry.definitions.push(definition);
+                    }
+                }
+                result_consumer(entry);
+            }
+            _ => {}
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            subsection,
+            literal_translations,
+            result_consumer,
+        );
+    }
+}
+
+fn push_literal_translation(template: &Template, literal_translations: &mut Vec<String>) {
+    let literal_translation = if template.is_any_of(LITERAL_TEMPLATES) {
+        template.positional(0)
+    } else {
+        template.named("lit")
+    };
+
+    if let Some(literal_translation) = literal_translation.filter(|literal| !literal.is_empty()) {
+        if !literal_translations
+            .iter()
+            .any(|existing| existing == literal_translation)
+        {
+            literal_translations.push(literal_translation.to_string());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::parser::words::definitions::parse_definition;
+use crate::parser::words::headings::{classify_heading, HeadingKind};
+use crate::parser::words::is_ignored_title;
+use crate::parser::words::templates::section_definitions;
+
+/// An entry of a page whose title consists of punctuation marks, symbols or emoji instead of letters and digits.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct SymbolEntry {
+    /// The symbol, i.e. the title of the page.
+    pub symbol: String,
+    /// The english name of the language of the entry, most often `Translingual`.
+    pub language_english_name: String,
+    /// The word type of the entry, e.g. `Symbol` or `Punctuation mark`.
+    pub word_type: String,
+    pub kind: SymbolKind,
+    /// The codepoints of the symbol, in order.
+    pub codepoints: Vec<Codepoint>,
+    /// The glosses of the definitions of the entry.
+    pub definitions: Vec<String>,
+}
+
+/// What kind of characters a symbol consists of.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    /// Only punctuation marks, like `?` or `‽`.
+    Punctuation,
+    /// Contains at least one emoji, like `😀`, possibly combined with modifiers and joiners.
+    Emoji,
+    /// Any other symbols, like `€`, `→` or `♀`.
+    Symbol,
+}
+
+/// A unicode codepoint of a symbol.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Codepoint {
+    pub value: u32,
+    /// The codepoint in the usual notation, e.g. `U+1F600`.
+    pub notation: String,
+    /// The name of the unicode block containing the codepoint, if it is one of the blocks of punctuation, symbols or emoji.
+    pub block: Option<String>,
+}
+
+/// Unicode blocks of punctuation, symbols and emoji, as inclusive ranges of codepoints.
+static SYMBOL_BLOCKS: &[(u32, u32, &str)] = &[
+    (0x0000, 0x007F, "Basic Latin"),
+    (0x0080, 0x00FF, "Latin-1 Supplement"),
+    (0x2000, 0x206F, "General Punctuation"),
+    (0x20A0, 0x20CF, "Currency Symbols"),
+    (0x2100, 0x214F, "Letterlike Symbols"),
+    (0x2190, 0x21FF, "Arrows"),
+    (0x2200, 0x22FF, "Mathematical Operators"),
+    (0x2300, 0x23FF, "Miscellaneous Technical"),
+    (0x2460, 0x24FF, "Enclosed Alphanumerics"),
+    (0x2500, 0x257F, "Box Drawing"),
+    (0x25A0, 0x25FF, "Geometric Shapes"),
+    (0x2600, 0x26FF, "Miscellaneous Symbols"),
+    (0x2700, 0x27BF, "Dingbats"),
+    (0x2E00, 0x2E7F, "Supplemental Punctuation"),
+    (0x3000, 0x303F, "CJK Symbols and Punctuation"),
+    (0xFE00, 0xFE0F, "Variation Selectors"),
+    (0xFF00, 0xFFEF, "Halfwidth and Fullwidth Forms"),
+    (0x1F000, 0x1F02F, "Mahjong Tiles"),
+    (0x1F0A0, 0x1F0FF, "Playing Cards"),
+    (0x1F100, 0x1F1FF, "Enclosed Alphanumeric Supplement"),
+    (0x1F300, 0x1F5FF, "Miscellaneous Symbols and Pictographs"),
+    (0x1F600, 0x1F64F, "Emoticons"),
+    (0x1F680, 0x1F6FF, "Transport and Map Symbols"),
+    (0x1F900, 0x1F9FF, "Supplemental Symbols and Pictographs"),
+    (0x1FA70, 0x1FAFF, "Symbols and Pictographs Extended-A"),
+];
+
+/// Ranges of codepoints that are emoji or only occur within emoji sequences.
+static EMOJI_RANGES: &[(u32, u32)] = &[(0x2600, 0x27BF), (0x1F000, 0x1F2FF), (0x1F300, 0x1FAFF)];
+
+/// Punctuation outside of ASCII, besides the punctuation blocks.
+static OTHER_PUNCTUATION: &[char] = &['¡', '§', '«', '¶', '·', '»', '¿'];
+
+impl Codepoint {
+    pub fn new(character: char) -> Self {
+        let value = u32::from(character);
+        Self {
+            value,
+            notation: format!("U+{value:04X}"),
+            block: SYMBOL_BLOCKS
+                .iter()
+                .find(|(first, last, _)| (*first..=*last).contains(&value))
+                .map(|(_, _, block)| block.to_string()),
+        }
+    }
+}
+
+impl SymbolKind {
+    /// The kind of the symbol, or `None` if it contains letters or digits or is empty.
+    pub fn of(symbol: &str) -> Option<Self> {
+        if symbol.trim().is_empty() || symbol.chars().any(char::is_alphanumeric) {
+            None
+        } else if symbol.chars().any(is_emoji) {
+            Some(Self::Emoji)
+        } else if symbol
+            .chars()
+            .all(|character| character.is_whitespace() || is_punctuation(character))
+        {
+            Some(Self::Punctuation)
+        } else {
+            Some(Self::Symbol)
+        }
+    }
+}
+
+fn is_emoji(character: char) -> bool {
+    let value = u32::from(character);
+    EMOJI_RANGES
+        .iter()
+        .any(|(first, last)| (*first..=*last).contains(&value))
+}
+
+fn is_punctuation(character: char) -> bool {
+    let value = u32::from(character);
+    character.is_ascii_punctuation() && !"$+<=>^`|~".contains(character)
+        || OTHER_PUNCTUATION.contains(&character)
+        || (0x2010..=0x205E).contains(&value)
+        || (0x2E00..=0x2E7F).contains(&value)
+        || (0x3001..=0x3003).contains(&value)
+        || (0x3008..=0x3011).contains(&value)
+}
+
+/// Extract the entries of a wiktionary page whose title is a punctuation mark, symbol or emoji.
+/// Unlike other extractors, this includes `Translingual` sections, which contain most symbols.
+pub fn wikitext_to_symbols(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(SymbolEntry),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+    let kind = if let Some(kind) = SymbolKind::of(title) {
+        kind
+    } else {
+        return;
+    };
+    let codepoints: Vec<_> = title.chars().map(Codepoint::new).collect();
+
+    for language_subsection in &wikitext.root_section.subsections {
+        parse_section(
+            title,
+            kind,
+            &codepoints,
+            &language_subsection.headline.label,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    kind: SymbolKind,
+    codepoints: &[Codepoint],
+    language_english_name: &str,
+    section: &Section,
+    result_consumer: &mut impl FnMut(SymbolEntry),
+) {
+    for subsection in &section.subsections {
+        if let HeadingKind::WordType(word_type) = classify_heading(&subsection.headline.label) {
+            result_consumer(SymbolEntry {
+                symbol: title.to_string(),
+                language_english_name: language_english_name.to_string(),
+                word_type: word_type.to_string(),
+                kind,
+                codepoints: codepoints.to_vec(),
+                definitions: section_definitions(subsection)
+                    .iter()
+                    .filter_map(parse_definition)
+                    .map(|definition| definition.gloss)
+                    .collect(),
+            });
+        }
+
+        parse_section(
+            title,
+            kind,
+            codepoints,
+            language_english_name,
+            subsection,
+            result_consumer,
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::parser::words::line_shape::line_shape;
+use std::collections::BTreeMap;
+use wikitext_parser::{Section, Text, TextPiece};
+
+/// A template invocation like `{{affix|en|un|do}}`, with its arguments converted to plain text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Template {
+    /// The name of the template, trimmed.
+    pub name: String,
+    /// The unnamed arguments, in order.
+    pub positional: Vec<String>,
+    /// The named arguments.
+    pub named: BTreeMap<String, String>,
+}
+
+impl Template {
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.positional.get(index).map(String::as_str)
+    }
+
+    pub fn named(&self, name: &str) -> Option<&str> {
+        self.named.get(name).map(String::as_str)
+    }
+
+    /// Returns true if the name of the template is any of the given names.
+    pub fn is_any_of(&self, names: &[&str]) -> bool {
+        names.contains(&self.name.as_str())
+    }
+}
+
+/// A line of a section.
+///
+/// wikitext-parser does not export the type of its lines, so they are converted into this type
+/// by [`section_paragraphs`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SectionLine {
+    Normal { text: Text },
+    List { list_prefix: String, text: Text },
+}
+
+impl SectionLine {
+    pub fn text(&self) -> &Text {
+        match self {
+            SectionLine::Normal { text } => text,
+            SectionLine::List { text, .. } => text,
+        }
+    }
+
+    /// The list prefix like `#` or `*:`, if the line is a list item.
+    pub fn list_prefix(&self) -> Option<&str> {
+        match self {
+            SectionLine::Normal { .. } => None,
+            SectionLine::List { list_prefix, .. } => Some(list_prefix),
+        }
+    }
+}
+
+/// The lines of each paragraph directly in the section, excluding its subsections.
+pub fn section_paragraphs(section: &Section) -> Vec<Vec<SectionLine>> {
+    // The pieces of all lines of the section, in order.
+    let mut pieces = section.iter_text_pieces();
+    section
+        .paragraphs
+        .iter()
+        .map(|paragraph| {
+            paragraph
+                .lines
+                .iter()
+                .map(|line| {
+                    // Unwrap cannot panic because lines serialise as a struct variant
+                    // with an optional string list prefix and a text.
+                    let (list_prefix, piece_count) = line_shape(line).unwrap();
+                    let text = Text {
+                        pieces: pieces.by_ref().take(piece_count).cloned().collect(),
+                    };
+                    match list_prefix {
+                        None => SectionLine::Normal { text },
+                        Some(list_prefix) => SectionLine::List { list_prefix, text },
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The lines of all paragraphs directly in the section, excluding its subsections.
+pub fn section_lines(section: &Section) -> Vec<SectionLine> {
+    section_paragraphs(section).into_iter().flatten().collect()
+}
+
+/// The texts of all lines directly in the section, excluding its subsections.
+pub fn section_texts(section: &Section) -> Vec<Text> {
+    section_lines(section)
+        .into_iter()
+        .map(|line| match line {
+            SectionLine::Normal { text } => text,
+            SectionLine::List { text, .. } => text,
+        })
+        .collect()
+}
+
+/// The texts of all list items directly in the section, together with their list prefix like `#` or `*:`.
+pub fn section_list_items(section: &Section) -> Vec<(String, Text)> {
+    section_lines(section)
+        .into_iter()
+        .filter_map(|line| match line {
+            SectionLine::Normal { .. } => None,
+            SectionLine::List { list_prefix, text } => Some((list_prefix, text)),
+        })
+        .collect()
+}
+
+/// The texts of the definitions directly in the section, i.e. of the list items with prefix `#`.
+/// Nested list items like examples, quotations and subsenses are excluded.
+pub fn section_definitions(section: &Section) -> Vec<Text> {
+    section_list_items(section)
+        .into_iter()
+        .filter(|(list_prefix, _)| list_prefix == "#")
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// The templates in the lines of the section that are not list items, i.e. the headword line.
+pub fn section_headword_templates(section: &Section) -> Vec<Template> {
+    section_lines(section)
+        .iter()
+        .filter_map(|line| match line {
+            SectionLine::Normal { text } => Some(text_templates(text)),
+            SectionLine::List { .. } => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// All templates directly in the section, excluding its subsections, in order of appearance.
+pub fn section_templates(section: &Section) -> Vec<Template> {
+    let mut templates = Vec::new();
+    for text in section_texts(section) {
+        collect_templates(&text, &mut templates);
+    }
+    templates
+}
+
+/// All templates in the text, including templates nested in arguments, in order of appearance.
+pub fn text_templates(text: &Text) -> Vec<Template> {
+    let mut templates = Vec::new();
+    collect_templates(text, &mut templates);
+    templates
+}
+
+fn collect_templates(text: &Text, templates: &mut Vec<Template>) {
+    for piece in &text.pieces {
+        match piece {
+            TextPiece::DoubleBraceExpression { tag, attributes } => {
+                let mut template = Template {
+                    name: text_to_plain(tag).trim().to_string(),
+                    positional: Vec::new(),
+                    named: BTreeMap::new(),
+                };
+                for attribute in attributes {
+                    let value = text_to_plain(&attribute.value).trim().to_string();
+                    if let Some(name) = &attribute.name {
+                        template.named.insert(name.trim().to_string(), value);
+                    } else {
+                        template.positional.push(value);
+                    }
+                }
+                templates.push(template);
+
+                for attribute in attributes {
+                    collect_templates(&attribute.value, templates);
+                }
+            }
+            TextPiece::InternalLink { label, .. } => {
+                if let Some(label) = label {
+                    collect_templates(label, templates);
+                }
+            }
+            TextPiece::ListItem { text, .. } => collect_templates(text, templates),
+            TextPiece::Text { .. } => {}
+        }
+    }
+}
+
+/// The targets of all internal links in the text, without fragments, in order of appearance.
+/// Links written as link templates like `{{l|en|word}}` are included.
+pub fn text_links(text: &Text) -> Vec<String> {
+    let mut links = Vec::new();
+    collect_links(text, &mut links);
+    links
+}
+
+/// Templates that link to a term, with the language as first and the term as second argument.
+pub(crate) static LINK_TEMPLATES: &[&str] = &["l", "link", "m", "mention", "l-self", "ll"];
+
+fn collect_links(text: &Text, links: &mut Vec<String>) {
+    for piece in &text.pieces {
+        match piece {
+            TextPiece::InternalLink { target, .. } => {
+                let target = text_to_plain(target);
+                let target = target.split('#').next().unwrap_or_default().trim();
+                if !target.is_empty() && !target.contains(':') {
+                    links.push(target.to_string());
+                }
+            }
+            TextPiece::DoubleBraceExpression { tag, attributes } => {
+                if LINK_TEMPLATES.contains(&text_to_plain(tag).trim()) {
+                    if let Some(term) = attributes
+                        .iter()
+                        .filter(|attribute| attribute.name.is_none())
+                        .nth(1)
+                    {
+                        let term = text_to_plain(&term.value).trim().to_string();
+                        if !term.is_empty() {
+                            links.push(term);
+                        }
+                    }
+                }
+            }
+            TextPiece::ListItem { text, .. } => collect_links(text, links),
+            TextPiece::Text { .. } => {}
+        }
+    }
+}
+
+/// Convert the text to plain text, keeping the plain text of links and dropping templates.
+pub fn text_to_plain(text: &Text) -> String {
+    let mut result = String::new();
+    append_plain(text, &mut result);
+    result
+}
+
+fn append_plain(text: &Text, result: &mut String) {
+    for piece in &text.pieces {
+        match piece {
+            TextPiece::Text { text, .. } => result.push_str(text),
+            TextPiece::DoubleBraceExpression { .. } => {}
+            TextPiece::InternalLink { target, label, .. } => {
+                append_plain(label.as_ref().unwrap_or(target), result)
+            }
+            TextPiece::ListItem { text, .. } => append_plain(text, result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wikitext_parser::parse_wikitext;
+
+    #[test]
+    fn reads_paragraphs_of_normal_lines_and_list_items() {
+        let wikitext = parse_wikitext(
+            "{{en-noun}} '''cat'''\n# A [[mammal]].\n#: The cat sleeps.\n\n\nSee also.\n",
+            "cat".to_string(),
+            |error| panic!("{error:?}"),
+        );
+        let paragraphs: Vec<Vec<_>> = section_paragraphs(&wikitext.root_section)
+            .into_iter()
+            .map(|paragraph| {
+                paragraph
+                    .iter()
+                    .map(|line| {
+                        (
+                            line.list_prefix().map(ToString::to_string),
+                            text_to_plain(line.text()),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            paragraphs,
+            [
+                vec![
+                    (None, " cat".to_string()),
+                    (Some("#".to_string()), " A mammal.".to_string()),
+                    (Some("#:".to_string()), " The cat sleeps.".to_string()),
+                ],
+                vec![(None, "See also.".to_string())],
+            ]
+        );
+        assert_eq!(
+            section_templates(&wikitext.root_section)
+                .iter()
+                .map(|template| template.name.as_str())
+                .collect::<Vec<_>>(),
+            ["en-noun"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use wikitext_parser::{Section, Wikitext};
+
+use crate::language_tag::LanguageTag;
+use crate::parser::words::headings::{classify_heading, heading_matches, HeadingKind};
+use crate::parser::words::templates::{section_texts, text_templates};
+use crate::parser::words::{is_ignored_title, IGNORED_LANGUAGE_PATTERN};
+
+/// A translation of a word into another language.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Translation {
+    /// The english name of the language of the translated word.
+    pub source_language_english_name: String,
+    /// The translated word, i.e. the title of the page.
+    pub source: String,
+    /// The word type of the translated word, if the translations are below a word type heading.
+    pub word_type: Option<String>,
+    /// The sense of the translated word, as given by `{{trans-top}}`.
+    pub gloss: Option<String>,
+    /// The wiktionary language code of the translation, e.g. `fr`.
+    pub target_language_code: String,
+    /// The script of the translation as given by the `sc` argument, e.g. `Latn` for a Serbo-Croatian translation
+    /// in latin script.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_script: Option<String>,
+    /// The translation.
+    pub target: String,
+}
+
+impl Translation {
+    /// The language tag of the translation, including its script, e.g. `sh-Latn`.
+    /// Returns `None` if the language code is not a valid language tag.
+    pub fn target_language_tag(&self) -> Option<LanguageTag> {
+        let tag: LanguageTag = self.target_language_code.parse().ok()?;
+        Some(match &self.target_script {
+            Some(script) if tag.script.is_none() => tag.with_script(script),
+            _ => tag,
+        })
+    }
+}
+
+/// Templates that contain a translation, with the language code as first and the translation as second argument.
+static TRANSLATION_TEMPLATES: &[&str] = &["t", "t+", "tt", "tt+", "t-simple", "t+check", "t-check"];
+static TRANSLATION_TOP_TEMPLATES: &[&str] = &["trans-top", "checktrans-top", "trans-top-also"];
+static TRANSLATION_BOTTOM_TEMPLATES: &[&str] = &["trans-bottom"];
+
+/// Extract the translations from a wiktionary page.
+pub fn wikitext_to_translations(
+    title: &str,
+    wikitext: &Wikitext,
+    mut result_consumer: impl FnMut(Translation),
+) {
+    if is_ignored_title(title) || wikitext.root_section.headline.level != 1 {
+        return;
+    }
+
+    for language_subsection in &wikitext.root_section.subsections {
+        let language_english_name = language_subsection.headline.label.as_str();
+        if IGNORED_LANGUAGE_PATTERN.is_match(language_english_name) {
+            continue;
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            None,
+            language_subsection,
+            &mut result_consumer,
+        );
+    }
+}
+
+fn parse_section(
+    title: &str,
+    language_english_name: &str,
+    word_type: Option<&str>,
+    section: &Section,
+    result_consumer: &mut impl FnMut(Translation),
+) {
+    for subsection in &section.subsections {
+        let label = &subsection.headline.label;
+        let word_type = if let HeadingKind::WordType(word_type) = classify_heading(label) {
+            Some(word_type)
+        } else {
+            word_type
+        };
+
+        if heading_matches(label, "Translation") {
+            parse_translations_section(
+                title,
+                language_english_name,
+                word_type,
+                subsection,
+                result_consumer,
+            );
+        }
+
+        parse_section(
+            title,
+            language_english_name,
+            word_type,
+            subsection,
+            result_consumer,
+        );
+    }
+}
+
+fn parse_translations_section(
+    title: &str,
+    language_english_name: &str,
+    word_type: Option<&str>,
+    section: &Section,
+    result_consumer: &mut impl FnMut(Translation),
+) {
+    let mut gloss = None;
+
+    for text in &section_texts(section) {
+        for template in text_templates(text) {
+            if template.is_any_of(TRANSLATION_TOP_TEMPLATES) {
+                gloss = template
+                    .positional(0)
+                    .filter(|gloss| !gloss.is_empty())
+                    .map(ToString::to_string);
+            } else if template.is_any_of(TRANSLATION_BOTTOM_TEMPLATES) {
+                gloss = None;
+            } else if template.is_any_of(TRANSLATION_TEMPLATES) {
+                if let (Some(target_language_code), Some(target)) =
+                    (template.positional(0), template.positional(1))
+                {
+                    if !target_language_code.is_empty() && !target.is_empty() {
+                        result_consumer(Translation {
+                            source_language_english_name: language_english_name.to_string(),
+                            source: title.to_string(),
+                            word_type: word_type.map(ToString::to_string),
+                            gloss: gloss.clone(),
+                            target_language_code: target_language_code.to_string(),
+                            target_script: template
+                                .named("sc")
+                                .filter(|script| !script.is_empty())
+                                .map(ToString::to_string),
+                            target: target.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
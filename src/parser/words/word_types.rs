@@ -0,0 +1,68 @@
+use crate::error::{Error, Result};
+use crate::parser::words::interner::intern;
+use crate::parser::words::language_pack::{builtin_language_pack, LanguagePack};
+use crate::parser::words::Word;
+use crate::wiktionary_edition::WiktionaryEdition;
+use std::str::FromStr;
+
+/// How word types are named in the output.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WordTypeNaming {
+    /// The canonical English names, e.g. `Noun`, independent of the edition.
+    Canonical,
+    /// The names used by the edition the word was extracted from, e.g. `Substantiv` in the German wiktionary.
+    /// Word types without a localised name keep their canonical name.
+    Localised,
+}
+
+impl Default for WordTypeNaming {
+    fn default() -> Self {
+        Self::Canonical
+    }
+}
+
+impl FromStr for WordTypeNaming {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "canonical" => Ok(Self::Canonical),
+            "localised" | "localized" => Ok(Self::Localised),
+            other => Err(Error::Other(format!(
+                "Unknown word type naming {other:?}, expected 'canonical' or 'localised'"
+            ))),
+        }
+    }
+}
+
+impl WordTypeNaming {
+    /// Rename the word type of the word according to this naming.
+    /// The word type of the word is expected to be canonical.
+    pub fn apply(&self, edition: &WiktionaryEdition, word: &mut Word) {
+        self.apply_with_language_pack(builtin_language_pack(edition), word);
+    }
+
+    /// Like [`apply`](Self::apply), but with the localised names of the given language pack.
+    pub fn apply_with_language_pack(&self, language_pack: Option<&LanguagePack>, word: &mut Word) {
+        if *self == WordTypeNaming::Localised {
+            if let Some(localised) = language_pack
+                .and_then(|language_pack| language_pack.localised_word_type(&word.word_type))
+            {
+                word.word_type = intern(localised);
+            }
+        }
+    }
+}
+
+/// The name of a canonical word type as used by the given edition,
+/// according to the builtin [language pack](crate::parser::words::language_pack) of the edition,
+/// or `None` if the edition has no language pack with localised names, or the word type has no known localised name.
+pub fn localised_word_type(edition: &WiktionaryEdition, canonical: &str) -> Option<&'static str> {
+    builtin_language_pack(edition)?.localised_word_type(canonical)
+}
+
+/// The canonical name of a word type as used by the given edition, or `None` if it is unknown.
+/// This is the inverse of [`localised_word_type`].
+pub fn canonical_word_type(edition: &WiktionaryEdition, localised: &str) -> Option<&'static str> {
+    builtin_language_pack(edition)?.canonical_word_type(localised)
+}
@@ -1,10 +1,14 @@
-use crate::error::Result;
-use log::{debug, trace};
+use crate::error::{Error, Result};
+use log::{debug, trace, warn};
 use quick_xml::{
-    events::{BytesEnd, BytesStart, Event},
+    events::{BytesDecl, BytesEnd, BytesStart, Event},
     Reader,
 };
-use tokio::io::AsyncBufRead;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const UTF16_LE_BOM: &[u8] = &[0xff, 0xfe];
+const UTF16_BE_BOM: &[u8] = &[0xfe, 0xff];
 
 #[derive(Clone, Debug)]
 pub enum RelevantEvent<'a> {
@@ -20,6 +24,59 @@ pub enum RelevantEvent<'a> {
     Eof,
 }
 
+/// Create a reader for a dump or an export of pages.
+/// A leading UTF-8 byte order mark is skipped, and UTF-16 input is rejected with a clear error,
+/// since dumps are always expected to be UTF-8.
+pub async fn dump_reader<Input: AsyncBufRead + Unpin>(mut input: Input) -> Result<Reader<Input>> {
+    // The buffer of the input may hold fewer bytes than a byte order mark is long,
+    // so its bytes are consumed one by one as they arrive.
+    // Xml never starts with a byte of a byte order mark otherwise, so no content is lost on a mismatch.
+    let mut byte_order_mark: &[u8] = &[];
+    let mut matched = 0;
+    while let Some(&byte) = input.fill_buf().await?.first() {
+        if matched == 0 {
+            byte_order_mark = if let Some(byte_order_mark) = [UTF8_BOM, UTF16_LE_BOM, UTF16_BE_BOM]
+                .into_iter()
+                .find(|byte_order_mark| byte_order_mark[0] == byte)
+            {
+                byte_order_mark
+            } else {
+                break;
+            };
+        } else if byte_order_mark[matched] != byte {
+            break;
+        }
+
+        input.consume(1);
+        matched += 1;
+        if matched == byte_order_mark.len() {
+            break;
+        }
+    }
+
+    if matched == 0 {
+        // No byte order mark.
+    } else if matched < byte_order_mark.len() {
+        return Err(Error::Other(format!(
+            "Found an incomplete byte order mark {:x?}",
+            &byte_order_mark[..matched]
+        )));
+    } else if byte_order_mark == UTF8_BOM {
+        debug!("Skipping UTF-8 byte order mark");
+    } else {
+        return Err(Error::Other(format!(
+            "Found a UTF-16 byte order mark, but only UTF-8 input is supported"
+        )));
+    }
+
+    let mut reader = Reader::from_reader(input);
+    // Whitespace-only text is skipped by `read_relevant_event`, and text is never trimmed,
+    // because leading and trailing whitespace is significant in wikitext.
+    reader.trim_text(false);
+    reader.check_end_names(true);
+    Ok(reader)
+}
+
 pub async fn read_relevant_event(
     reader: &mut Reader<impl AsyncBufRead + Unpin>,
     buffer: &mut Vec<u8>,
@@ -55,6 +112,7 @@ pub async fn read_relevant_event(
             }
             Event::Decl(decl) => {
                 debug!("Found XML declaration {decl:?}");
+                check_declared_encoding(&decl);
             }
             Event::PI(pi) => {
                 debug!("Found processing instruction {pi:?}");
@@ -72,3 +130,102 @@ pub async fn read_relevant_event(
     trace!("Read relevant event {relevant_event:?}");
     Ok(relevant_event)
 }
+
+/// Warn about declared encodings other than UTF-8.
+/// The input is parsed as UTF-8 anyway, which works for ASCII-compatible encodings as long as no other characters appear.
+fn check_declared_encoding(decl: &BytesDecl<'_>) {
+    match decl.encoding() {
+        Some(Ok(encoding)) => {
+            let encoding = String::from_utf8_lossy(&encoding).to_ascii_lowercase();
+            if !matches!(encoding.as_str(), "utf-8" | "utf8" | "us-ascii" | "ascii") {
+                warn!("Declared encoding {encoding:?} is not supported, parsing as UTF-8");
+            }
+        }
+        Some(Err(error)) => warn!("Malformed encoding in XML declaration: {error}"),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::BufReader;
+
+    /// Read the name of the first tag, with an input buffer of the given capacity.
+    async fn first_tag_name(input: &[u8], capacity: usize) -> Result<String> {
+        let mut reader = dump_reader(BufReader::with_capacity(capacity, input)).await?;
+        let mut buffer = Vec::new();
+        match read_relevant_event(&mut reader, &mut buffer).await? {
+            RelevantEvent::Start(tag) | RelevantEvent::Empty(tag) => {
+                Ok(String::from_utf8_lossy(tag.name().as_ref()).into_owned())
+            }
+            other => Err(Error::Other(format!("Expected a tag, but got {other:?}"))),
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_utf8_bom() {
+        let input = b"\xef\xbb\xbf<mediawiki></mediawiki>";
+        for capacity in [1, 2, 3, 8192] {
+            assert_eq!(first_tag_name(input, capacity).await.unwrap(), "mediawiki");
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_utf8_bom_before_declaration() {
+        let input =
+            b"\xef\xbb\xbf<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<mediawiki></mediawiki>";
+        for capacity in [1, 2, 3, 8192] {
+            assert_eq!(first_tag_name(input, capacity).await.unwrap(), "mediawiki");
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_start_without_bom() {
+        let input = b"<?xml version=\"1.0\"?><mediawiki/>";
+        for capacity in [1, 2, 3, 8192] {
+            assert_eq!(first_tag_name(input, capacity).await.unwrap(), "mediawiki");
+        }
+        for capacity in [1, 8192] {
+            assert_eq!(first_tag_name(b"<a/>", capacity).await.unwrap(), "a");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_utf16_bom() {
+        let inputs: [&[u8]; 2] = [
+            b"\xff\xfe<\x00a\x00/\x00>\x00",
+            b"\xfe\xff\x00<\x00a\x00/\x00>",
+        ];
+        for input in inputs {
+            for capacity in [1, 2, 8192] {
+                assert!(dump_reader(BufReader::with_capacity(capacity, input))
+                    .await
+                    .is_err());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_incomplete_bom() {
+        for input in [&b"\xef\xbb"[..], &b"\xef\xbb<a/>"[..]] {
+            for capacity in [1, 8192] {
+                assert!(dump_reader(BufReader::with_capacity(capacity, input))
+                    .await
+                    .is_err());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_empty_input() {
+        let mut reader = dump_reader(BufReader::with_capacity(1, &b""[..]))
+            .await
+            .unwrap();
+        let mut buffer = Vec::new();
+        assert!(matches!(
+            read_relevant_event(&mut reader, &mut buffer).await.unwrap(),
+            RelevantEvent::Eof
+        ));
+    }
+}
@@ -0,0 +1,320 @@
+//! Progress reporting for applications that render their own progress bars instead of reading the logs.
+//!
+//! Pass a [`ProgressReporter`] in the `progress` field of [`DownloadOptions`](crate::download::DownloadOptions)
+//! or [`DumpParseOptions`](crate::parser::DumpParseOptions), either wrapping a closure or a [`Progress`] implementation,
+//! or created by [`ProgressReporter::channel`] to receive the latest update through a [`tokio::sync::watch`] channel.
+//! Applications that extract words from pages themselves, e.g. with [`wikitext_to_words`](crate::parser::words::wikitext_to_words),
+//! can report their progress with a [`ProgressTracker`].
+
+use log::info;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// The default minimum time between two updates of a [`ProgressReporter`].
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a [`ProgressUpdate`] is about.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ProgressPhase {
+    /// Downloading a dump file, where the bytes are the bytes received.
+    Download,
+    /// Parsing a dump file, where the bytes are the bytes of the (possibly compressed) file read.
+    Parse,
+    /// Extracting words from pages that were parsed before, tracked by a [`ProgressTracker`].
+    Extract,
+}
+
+/// The state of a download, a parse or an extraction at one point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressUpdate {
+    pub phase: ProgressPhase,
+    pub bytes_done: u64,
+    /// The total amount of bytes, if known.
+    pub bytes_total: Option<u64>,
+    /// The amount of pages parsed so far, always zero for downloads.
+    pub pages: u64,
+    /// The total amount of pages, if known.
+    pub pages_total: Option<u64>,
+    /// The amount of words extracted so far, always zero for downloads.
+    pub words: u64,
+    /// The time since the download or parse started.
+    pub elapsed: Duration,
+    /// True for the last update of a download or parse that completed successfully.
+    pub finished: bool,
+}
+
+impl ProgressUpdate {
+    /// The fraction of bytes done, between zero and one, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.bytes_total.map(|bytes_total| {
+            if bytes_total == 0 {
+                1.0
+            } else {
+                (self.bytes_done as f64 / bytes_total as f64).min(1.0)
+            }
+        })
+    }
+
+    /// The average amount of bytes done per second since the start.
+    pub fn bytes_per_second(&self) -> f64 {
+        per_second(self.bytes_done, self.elapsed)
+    }
+
+    /// The average amount of pages parsed per second since the start.
+    pub fn pages_per_second(&self) -> f64 {
+        per_second(self.pages, self.elapsed)
+    }
+
+    /// The average amount of words extracted per second since the start.
+    pub fn words_per_second(&self) -> f64 {
+        per_second(self.words, self.elapsed)
+    }
+
+    /// The estimated time until all bytes are done, or all pages if the total bytes are unknown,
+    /// assuming the average rate since the start.
+    /// `None` if both totals are unknown or nothing was done yet.
+    pub fn eta(&self) -> Option<Duration> {
+        if let Some(bytes_total) = self.bytes_total {
+            eta(self.bytes_done, bytes_total, self.bytes_per_second())
+        } else {
+            eta(self.pages, self.pages_total?, self.pages_per_second())
+        }
+    }
+}
+
+fn eta(done: u64, total: u64, per_second: f64) -> Option<Duration> {
+    let remaining = total.saturating_sub(done);
+    if remaining == 0 {
+        Some(Duration::ZERO)
+    } else if per_second > 0.0 {
+        Some(Duration::from_secs_f64(remaining as f64 / per_second))
+    } else {
+        None
+    }
+}
+
+fn per_second(amount: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        amount as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+/// Formats the update like `Parse 41.3% 512/1240MiB 81234 pages 1532 pages/s 140210 words 2810 words/s ETA 7m 54s`.
+impl Display for ProgressUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.phase)?;
+        if let Some(fraction) = self.fraction() {
+            write!(f, " {:.1}%", fraction * 100.0)?;
+        }
+        write!(f, " {}", self.bytes_done / (1024 * 1024))?;
+        if let Some(bytes_total) = self.bytes_total {
+            write!(f, "/{}", bytes_total / (1024 * 1024))?;
+        }
+        write!(f, "MiB")?;
+        if self.phase != ProgressPhase::Download {
+            write!(
+                f,
+                " {} pages {:.0} pages/s {} words {:.0} words/s",
+                self.pages,
+                self.pages_per_second(),
+                self.words,
+                self.words_per_second()
+            )?;
+        }
+        if let Some(eta) = self.eta() {
+            let seconds = eta.as_secs();
+            let (minutes, seconds) = (seconds / 60, seconds % 60);
+            let (hours, minutes) = (minutes / 60, minutes % 60);
+            if hours > 0 {
+                write!(f, " ETA {hours}h {minutes}m")?;
+            } else {
+                write!(f, " ETA {minutes}m {seconds}s")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Receives progress updates.
+///
+/// Updates are sent from the task doing the work, so implementations should return quickly.
+pub trait Progress: Send + Sync {
+    fn update(&self, update: &ProgressUpdate);
+}
+
+impl<F: Fn(&ProgressUpdate) + Send + Sync> Progress for F {
+    fn update(&self, update: &ProgressUpdate) {
+        self(update)
+    }
+}
+
+/// Hands progress updates to a [`Progress`] implementation, at most once per interval,
+/// plus a final update when the work is finished.
+#[derive(Clone)]
+pub struct ProgressReporter {
+    progress: Arc<dyn Progress>,
+    interval: Duration,
+}
+
+impl Debug for ProgressReporter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProgressReporter {
+    /// Report progress to the given closure or [`Progress`] implementation every [`DEFAULT_PROGRESS_INTERVAL`].
+    pub fn new(progress: impl Progress + 'static) -> Self {
+        Self {
+            progress: Arc::new(progress),
+            interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// A reporter that sends each update to the returned receiver, which always holds the latest update.
+    /// The receiver holds `None` until the first update.
+    pub fn channel() -> (Self, watch::Receiver<Option<ProgressUpdate>>) {
+        let (sender, receiver) = watch::channel(None);
+        (
+            Self::new(move |update: &ProgressUpdate| {
+                // Sending only fails if all receivers were dropped, in which case nobody is interested anymore.
+                sender.send(Some(update.clone())).ok();
+            }),
+            receiver,
+        )
+    }
+
+    /// A reporter that logs each update with level `info`.
+    pub fn log() -> Self {
+        Self::new(|update: &ProgressUpdate| info!("{update}"))
+    }
+
+    /// Change the minimum time between two updates.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The minimum time between two updates.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Hand the update to the receiver.
+    /// Callers are responsible for respecting the interval.
+    pub fn report(&self, update: &ProgressUpdate) {
+        self.progress.update(update);
+    }
+}
+
+/// Tracks the progress of work done page by page, and hands it to a reporter at the reporter's interval.
+///
+/// Call [`add_page`](Self::add_page) after each page, optionally [`set_bytes`](Self::set_bytes)
+/// with the position in the input, and [`finish`](Self::finish) at the end.
+#[derive(Debug)]
+pub struct ProgressTracker {
+    reporter: ProgressReporter,
+    update: ProgressUpdate,
+    start: Instant,
+    last_report: Instant,
+}
+
+impl ProgressTracker {
+    pub fn new(reporter: ProgressReporter, phase: ProgressPhase) -> Self {
+        let start = Instant::now();
+        Self {
+            reporter,
+            update: ProgressUpdate {
+                phase,
+                bytes_done: 0,
+                bytes_total: None,
+                pages: 0,
+                pages_total: None,
+                words: 0,
+                elapsed: Duration::ZERO,
+                finished: false,
+            },
+            start,
+            last_report: start,
+        }
+    }
+
+    /// Set the total amount of pages, to estimate the remaining time if the total bytes are unknown.
+    pub fn with_pages_total(mut self, pages_total: u64) -> Self {
+        self.update.pages_total = Some(pages_total);
+        self
+    }
+
+    /// Set the bytes of the input done so far, and the total bytes if known.
+    pub fn set_bytes(&mut self, bytes_done: u64, bytes_total: Option<u64>) {
+        self.update.bytes_done = bytes_done;
+        self.update.bytes_total = bytes_total;
+    }
+
+    /// Count a page and the words extracted from it, and report the progress if the interval has passed.
+    pub fn add_page(&mut self, words: u64) {
+        self.update.pages += 1;
+        self.update.words += words;
+
+        let now = Instant::now();
+        if now - self.last_report >= self.reporter.interval() {
+            self.last_report = now;
+            self.update.elapsed = now - self.start;
+            self.reporter.report(&self.update);
+        }
+    }
+
+    /// The progress so far.
+    pub fn update(&self) -> &ProgressUpdate {
+        &self.update
+    }
+
+    /// Report the final progress.
+    pub fn finish(mut self) {
+        if let Some(bytes_total) = self.update.bytes_total {
+            self.update.bytes_done = bytes_total;
+        }
+        self.update.elapsed = self.start.elapsed();
+        self.update.finished = true;
+        self.reporter.report(&self.update);
+    }
+}
+
+/// Counts the bytes read from the inner reader, such that the progress through an input can be tracked
+/// while the reader itself is owned by a deserialiser.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bytes_read: Default::default(),
+        }
+    }
+
+    /// A shared counter of the bytes read so far.
+    pub fn bytes_read(&self) -> Arc<AtomicU64> {
+        self.bytes_read.clone()
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(amount as u64, Ordering::Relaxed);
+        Ok(amount)
+    }
+}
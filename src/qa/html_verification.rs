@@ -0,0 +1,230 @@
+//! Verification of extracted words against the html rendering of their pages by the Wikimedia REST api,
+//! to estimate the precision of the extraction.
+
+use crate::error::Result;
+use crate::language_code::LanguageCode;
+use crate::parser::words::headings::normalise_heading;
+use crate::parser::words::word_types::canonical_word_type;
+use crate::parser::words::Word;
+use crate::qa::{api_client, RandomSequence};
+use crate::urls::rest_page_html_url;
+use crate::wiktionary_edition::WiktionaryEdition;
+use lazy_static::lazy_static;
+use log::{debug, info};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+lazy_static! {
+    static ref HEADING_PATTERN: Regex =
+        Regex::new("(?s)<h([1-6])\\b[^>]*>(.*?)</h[1-6]\\s*>").unwrap();
+    static ref TAG_PATTERN: Regex = Regex::new("(?s)<[^>]*>").unwrap();
+}
+
+/// The time between two requests to the REST api, which asks clients to limit their request rate.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The result of checking a single extracted word against the html of its page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct WordVerification {
+    pub word: Word,
+    /// The page exists.
+    pub page_found: bool,
+    /// The page has a section whose level 2 heading is the English name of the language of the word.
+    /// If not, the other checks consider the whole page, e.g. for editions with localised language headings.
+    pub language_section_found: bool,
+    /// The word occurs in the text of the language section.
+    pub headword_found: bool,
+    /// The language section has a heading naming the word type, either canonical or as localised by the edition.
+    pub word_type_found: bool,
+}
+
+impl WordVerification {
+    /// The word is confirmed by the html if both its headword and its word type are found.
+    pub fn is_confirmed(&self) -> bool {
+        self.page_found && self.headword_found && self.word_type_found
+    }
+}
+
+/// The results of verifying a sample of extracted words against the html of their pages.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct HtmlVerificationReport {
+    pub words: Vec<WordVerification>,
+}
+
+impl HtmlVerificationReport {
+    pub fn confirmed(&self) -> impl Iterator<Item = &WordVerification> {
+        self.words.iter().filter(|word| word.is_confirmed())
+    }
+
+    pub fn unconfirmed(&self) -> impl Iterator<Item = &WordVerification> {
+        self.words.iter().filter(|word| !word.is_confirmed())
+    }
+
+    /// The fraction of sampled words confirmed by the html, or `None` if no words were sampled.
+    pub fn precision(&self) -> Option<f64> {
+        if self.words.is_empty() {
+            None
+        } else {
+            Some(self.confirmed().count() as f64 / self.words.len() as f64)
+        }
+    }
+
+    /// The 95% Wilson score interval of the precision, or `None` if no words were sampled.
+    pub fn precision_interval(&self) -> Option<(f64, f64)> {
+        let precision = self.precision()?;
+        let n = self.words.len() as f64;
+        let z: f64 = 1.96;
+        let denominator = 1.0 + z * z / n;
+        let centre = (precision + z * z / (2.0 * n)) / denominator;
+        let margin =
+            z * (precision * (1.0 - precision) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+        Some(((centre - margin).max(0.0), (centre + margin).min(1.0)))
+    }
+}
+
+/// Select up to `count` random words, and check each against the html of its page,
+/// as rendered by the REST api of the wiktionary in the given language.
+///
+/// Each page is fetched once, even if multiple sampled words are from it.
+/// Words of other namespaces than the main namespace are not supported, since the word is taken as the page title.
+pub async fn verify_words_against_html(
+    language_code: &LanguageCode,
+    words: impl IntoIterator<Item = Word>,
+    count: usize,
+) -> Result<HtmlVerificationReport> {
+    let sample = sample_words(words, count);
+    let mut sample_by_title: BTreeMap<String, Vec<Word>> = BTreeMap::new();
+    for word in sample {
+        sample_by_title
+            .entry(word.word.clone())
+            .or_default()
+            .push(word);
+    }
+
+    let edition = WiktionaryEdition::Known(language_code.clone());
+    let client = api_client()?;
+    let mut verified_words = Vec::new();
+    info!(
+        "Verifying words of {} pages against their html",
+        sample_by_title.len()
+    );
+    for (index, (title, words)) in sample_by_title.into_iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+        let url = rest_page_html_url(language_code, &title)?;
+        debug!("Fetching {url}");
+        let response = client.get(url).send().await?;
+        let html = if response.status() == reqwest::StatusCode::NOT_FOUND {
+            None
+        } else {
+            Some(response.error_for_status()?.text().await?)
+        };
+
+        for word in words {
+            verified_words.push(if let Some(html) = &html {
+                verify_word(&edition, word, html)
+            } else {
+                WordVerification {
+                    word,
+                    page_found: false,
+                    language_section_found: false,
+                    headword_found: false,
+                    word_type_found: false,
+                }
+            });
+        }
+    }
+
+    Ok(HtmlVerificationReport {
+        words: verified_words,
+    })
+}
+
+/// Reservoir sampling, such that the words do not need to be held in memory.
+fn sample_words(words: impl IntoIterator<Item = Word>, count: usize) -> Vec<Word> {
+    let mut random = RandomSequence::from_time();
+    let mut sample = Vec::with_capacity(count);
+    for (index, word) in words.into_iter().enumerate() {
+        if sample.len() < count {
+            sample.push(word);
+        } else {
+            let replaced = (random.next() % (index as u64 + 1)) as usize;
+            if replaced < count {
+                sample[replaced] = word;
+            }
+        }
+    }
+    sample
+}
+
+/// Check the word against the html of its page.
+pub fn verify_word(edition: &WiktionaryEdition, word: Word, html: &str) -> WordVerification {
+    let headings: Vec<_> = HEADING_PATTERN
+        .captures_iter(html)
+        .map(|captures| {
+            let whole = captures.get(0).unwrap();
+            let level: u8 = captures[1].parse().unwrap_or(6);
+            (
+                level,
+                html_to_text(&captures[2]),
+                whole.start(),
+                whole.end(),
+            )
+        })
+        .collect();
+
+    // The language section starts after its level 2 heading and ends at the next one.
+    let language_heading = headings
+        .iter()
+        .position(|(level, text, _, _)| *level == 2 && text == &*word.language_english_name);
+    let (section_headings, section) = if let Some(language_heading) = language_heading {
+        let end = headings[language_heading + 1..]
+            .iter()
+            .find(|(level, _, _, _)| *level <= 2)
+            .map_or(html.len(), |(_, _, start, _)| *start);
+        let start = headings[language_heading].3;
+        (
+            headings[language_heading + 1..]
+                .iter()
+                .filter(|(_, _, heading_start, _)| *heading_start < end)
+                .collect::<Vec<_>>(),
+            &html[start..end],
+        )
+    } else {
+        (headings.iter().collect(), html)
+    };
+
+    let headword_found = html_to_text(section).contains(&word.word);
+    let word_type = normalise_heading(&word.word_type);
+    let word_type_found = section_headings.iter().any(|(_, text, _, _)| {
+        normalise_heading(text) == word_type
+            || canonical_word_type(edition, text)
+                .map_or(false, |canonical| normalise_heading(canonical) == word_type)
+    });
+
+    WordVerification {
+        word,
+        page_found: true,
+        language_section_found: language_heading.is_some(),
+        headword_found,
+        word_type_found,
+    }
+}
+
+/// Remove the tags and decode the common entities of html.
+fn html_to_text(html: &str) -> String {
+    TAG_PATTERN
+        .replace_all(html, "")
+        .replace("&nbsp;", " ")
+        .replace("&#160;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
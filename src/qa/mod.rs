@@ -0,0 +1,319 @@
+//! Evaluation of the word extraction on live pages, to check its coverage between dump releases.
+
+use crate::analysis::page_history::PageSnapshot;
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::parser::words::{extract_words, Word};
+use crate::urls::mediawiki_api;
+use crate::wiktionary_edition::WiktionaryEdition;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use wikitext_parser::parse_wikitext;
+
+pub mod html_verification;
+
+/// The result of extracting words from a single live page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PageSampleResult {
+    pub title: String,
+    pub words: Vec<Word>,
+    pub parser_errors: Vec<String>,
+    pub extraction_errors: Vec<String>,
+}
+
+impl PageSampleResult {
+    /// A page fails if it has errors or yields no words.
+    pub fn is_failure(&self) -> bool {
+        self.words.is_empty()
+            || !self.parser_errors.is_empty()
+            || !self.extraction_errors.is_empty()
+    }
+}
+
+/// The results of extracting words from a sample of the pages of a category.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CategorySampleReport {
+    pub category: String,
+    pub pages: Vec<PageSampleResult>,
+}
+
+impl CategorySampleReport {
+    pub fn failures(&self) -> impl Iterator<Item = &PageSampleResult> {
+        self.pages.iter().filter(|page| page.is_failure())
+    }
+}
+
+#[derive(Deserialize)]
+struct CategoryMembersResponse {
+    query: CategoryMembersQuery,
+    /// The parameters to request the next members with, if there are more.
+    #[serde(rename = "continue", default)]
+    continuation: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+struct CategoryMembersQuery {
+    categorymembers: Vec<CategoryMember>,
+}
+
+#[derive(Deserialize)]
+struct CategoryMember {
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct RevisionsResponse {
+    query: RevisionsQuery,
+}
+
+#[derive(Deserialize)]
+struct RevisionsQuery {
+    pages: Vec<RevisionsPage>,
+}
+
+#[derive(Deserialize)]
+struct RevisionsPage {
+    title: String,
+    #[serde(default)]
+    revisions: Vec<RevisionsRevision>,
+}
+
+#[derive(Deserialize)]
+struct RevisionsRevision {
+    slots: RevisionsSlots,
+}
+
+#[derive(Deserialize)]
+struct RevisionsSlots {
+    main: RevisionsSlot,
+}
+
+#[derive(Deserialize)]
+struct RevisionsSlot {
+    content: String,
+}
+
+/// The MediaWiki api returns at most this many category members or page contents per request.
+const CATEGORY_MEMBERS_LIMIT: usize = 500;
+const REVISIONS_LIMIT: usize = 50;
+
+/// Identifies requests to the wikimedia apis, as required by their
+/// [user-agent policy](https://meta.wikimedia.org/wiki/User-Agent_policy).
+const USER_AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ")"
+);
+
+/// A client for the wikimedia apis, which identifies itself with the [`USER_AGENT`] of this crate.
+pub(crate) fn api_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder().user_agent(USER_AGENT).build()?)
+}
+
+/// Fetch `count` random pages of the category, e.g. `English lemmas`, from the MediaWiki api,
+/// and extract words from them.
+///
+/// All members of the category are listed, and the pages are selected by reservoir sampling,
+/// such that each member is equally likely to be sampled.
+/// Large categories take one request per 500 members to list.
+pub async fn sample_category(
+    language_code: &LanguageCode,
+    category: &str,
+    count: usize,
+) -> Result<CategorySampleReport> {
+    let api = mediawiki_api(language_code)?;
+    let client = api_client()?;
+    let mut random = RandomSequence::from_time();
+
+    let category_title = format!("Category:{category}");
+    let limit = CATEGORY_MEMBERS_LIMIT.to_string();
+    info!("Listing members of {category_title:?}");
+    let mut titles: Vec<String> = Vec::with_capacity(count);
+    let mut member_count = 0;
+    let mut continuation = BTreeMap::new();
+    loop {
+        let response: CategoryMembersResponse = client
+            .get(api.clone())
+            .query(&[
+                ("action", "query"),
+                ("list", "categorymembers"),
+                ("cmtitle", category_title.as_str()),
+                ("cmnamespace", "0"),
+                ("cmlimit", limit.as_str()),
+                ("format", "json"),
+                ("formatversion", "2"),
+            ])
+            .query(&continuation)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        for member in response.query.categorymembers {
+            member_count += 1;
+            if titles.len() < count {
+                titles.push(member.title);
+            } else {
+                let index = (random.next() % member_count) as usize;
+                if index < count {
+                    titles[index] = member.title;
+                }
+            }
+        }
+        debug!("Listed {member_count} members of {category_title:?}");
+
+        if let Some(next_continuation) = response.continuation {
+            continuation = next_continuation;
+        } else {
+            break;
+        }
+    }
+    if member_count == 0 {
+        return Err(Error::Other(format!(
+            "Category {category_title:?} has no pages"
+        )));
+    }
+    titles.sort();
+
+    let edition = WiktionaryEdition::Known(language_code.clone());
+    let mut pages = Vec::new();
+    for chunk in titles.chunks(REVISIONS_LIMIT) {
+        let joined_titles = chunk.join("|");
+        debug!("Fetching {} pages", chunk.len());
+        let response: RevisionsResponse = client
+            .get(api.clone())
+            .query(&[
+                ("action", "query"),
+                ("prop", "revisions"),
+                ("rvprop", "content"),
+                ("rvslots", "main"),
+                ("titles", joined_titles.as_str()),
+                ("format", "json"),
+                ("formatversion", "2"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        for page in response.query.pages {
+            if let Some(revision) = page.revisions.into_iter().next() {
+                pages.push(sample_page(
+                    &edition,
+                    page.title,
+                    &revision.slots.main.content,
+                ));
+            }
+        }
+    }
+
+    Ok(CategorySampleReport {
+        category: category.to_string(),
+        pages,
+    })
+}
+
+/// Fetch the current wikitext of the page from the MediaWiki api.
+/// Returns `None` if the page does not exist.
+pub async fn fetch_page_wikitext(
+    language_code: &LanguageCode,
+    title: &str,
+) -> Result<Option<String>> {
+    let api = mediawiki_api(language_code)?;
+    let response: RevisionsResponse = api_client()?
+        .get(api)
+        .query(&[
+            ("action", "query"),
+            ("prop", "revisions"),
+            ("rvprop", "content"),
+            ("rvslots", "main"),
+            ("titles", title),
+            ("format", "json"),
+            ("formatversion", "2"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(response
+        .query
+        .pages
+        .into_iter()
+        .flat_map(|page| page.revisions)
+        .next()
+        .map(|revision| revision.slots.main.content))
+}
+
+/// Fetch the current wikitext of the page and take a snapshot of it, to compare it against a dump.
+/// Returns `None` if the page does not exist.
+pub async fn live_page_snapshot(
+    language_code: &LanguageCode,
+    title: &str,
+) -> Result<Option<PageSnapshot>> {
+    Ok(fetch_page_wikitext(language_code, title)
+        .await?
+        .map(|text| {
+            let wikitext = parse_wikitext(&text, title.to_string(), |error| {
+                debug!("Parser error in live page {title:?}: {error:?}")
+            });
+            PageSnapshot::from_wikitext(
+                &WiktionaryEdition::Known(language_code.clone()),
+                title,
+                &wikitext,
+            )
+        }))
+}
+
+fn sample_page(edition: &WiktionaryEdition, title: String, text: &str) -> PageSampleResult {
+    let mut parser_errors = Vec::new();
+    let wikitext = parse_wikitext(text, title.clone(), |error| {
+        parser_errors.push(format!("{error:?}"))
+    });
+
+    let mut words = Vec::new();
+    let mut extraction_errors = Vec::new();
+    extract_words(
+        edition,
+        &title,
+        &wikitext,
+        |word| words.push(word),
+        |error| extraction_errors.push(error.to_string()),
+    );
+
+    PageSampleResult {
+        title,
+        words,
+        parser_errors,
+        extraction_errors,
+    }
+}
+
+/// A xorshift generator, which is random enough for sampling pages.
+struct RandomSequence(u64);
+
+impl RandomSequence {
+    fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        // The state must not be zero.
+        Self(nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
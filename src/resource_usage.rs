@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// Records the elapsed time of the phases of a run, and the resources used by the process while doing so.
+/// Call [`phase`](Self::phase) whenever a new phase begins, and [`finish`](Self::finish) at the end of the run.
+#[derive(Debug)]
+pub struct ResourceUsageRecorder {
+    start: Instant,
+    initial_io: Option<IoCounters>,
+    current_phase: Option<(String, Instant)>,
+    phases: Vec<PhaseUsage>,
+}
+
+/// The elapsed time of a single phase of a run.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PhaseUsage {
+    pub name: String,
+    pub elapsed_millis: u64,
+}
+
+/// The resources used during a run, as recorded by a [`ResourceUsageRecorder`].
+/// Values that cannot be determined on the current platform are `None`.
+/// Currently, peak memory and io are only determined on Linux.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ResourceUsage {
+    pub elapsed_millis: u64,
+    pub phases: Vec<PhaseUsage>,
+    /// The peak resident set size of the process.
+    /// This covers the whole lifetime of the process, not only the recorded run.
+    pub peak_rss_bytes: Option<u64>,
+    /// The bytes read by the process during the run, including reads from the page cache.
+    pub bytes_read: Option<u64>,
+    /// The bytes written by the process during the run.
+    pub bytes_written: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IoCounters {
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+impl ResourceUsageRecorder {
+    /// Start recording, without starting a phase.
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            initial_io: read_io_counters(),
+            current_phase: None,
+            phases: Vec::new(),
+        }
+    }
+
+    /// End the current phase, if any, and begin a new phase with the given name.
+    pub fn phase(&mut self, name: impl Into<String>) {
+        self.end_phase();
+        self.current_phase = Some((name.into(), Instant::now()));
+    }
+
+    /// End the current phase, if any.
+    pub fn end_phase(&mut self) {
+        if let Some((name, start)) = self.current_phase.take() {
+            self.phases.push(PhaseUsage {
+                name,
+                elapsed_millis: duration_millis(start.elapsed()),
+            });
+        }
+    }
+
+    /// End the current phase, if any, and return the resources used since recording started.
+    pub fn finish(mut self) -> ResourceUsage {
+        self.end_phase();
+        let io = read_io_counters()
+            .zip(self.initial_io)
+            .map(|(current, initial)| IoCounters {
+                bytes_read: current.bytes_read.saturating_sub(initial.bytes_read),
+                bytes_written: current.bytes_written.saturating_sub(initial.bytes_written),
+            });
+
+        ResourceUsage {
+            elapsed_millis: duration_millis(self.start.elapsed()),
+            phases: self.phases,
+            peak_rss_bytes: read_peak_rss_bytes(),
+            bytes_read: io.map(|io| io.bytes_read),
+            bytes_written: io.map(|io| io.bytes_written),
+        }
+    }
+}
+
+impl ResourceUsage {
+    /// The average amount of bytes read per second over the whole run.
+    pub fn read_throughput_bytes_per_second(&self) -> Option<f64> {
+        self.bytes_read.map(|bytes| self.per_second(bytes))
+    }
+
+    /// The average amount of bytes written per second over the whole run.
+    pub fn write_throughput_bytes_per_second(&self) -> Option<f64> {
+        self.bytes_written.map(|bytes| self.per_second(bytes))
+    }
+
+    fn per_second(&self, amount: u64) -> f64 {
+        amount as f64 / (self.elapsed_millis.max(1) as f64 / 1000.0)
+    }
+}
+
+impl Display for ResourceUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Elapsed time: {:.1}s",
+            self.elapsed_millis as f64 / 1000.0
+        )?;
+        for phase in &self.phases {
+            writeln!(
+                f,
+                "  {}: {:.1}s",
+                phase.name,
+                phase.elapsed_millis as f64 / 1000.0
+            )?;
+        }
+        write!(f, "Peak RSS: ")?;
+        write_mib(f, self.peak_rss_bytes.map(|bytes| bytes as f64))?;
+        write!(f, "\nRead: ")?;
+        write_mib(f, self.bytes_read.map(|bytes| bytes as f64))?;
+        write!(f, " (")?;
+        write_mib(f, self.read_throughput_bytes_per_second())?;
+        write!(f, "/s)\nWritten: ")?;
+        write_mib(f, self.bytes_written.map(|bytes| bytes as f64))?;
+        write!(f, " (")?;
+        write_mib(f, self.write_throughput_bytes_per_second())?;
+        write!(f, "/s)")
+    }
+}
+
+fn write_mib(f: &mut Formatter<'_>, bytes: Option<f64>) -> std::fmt::Result {
+    if let Some(bytes) = bytes {
+        write!(f, "{:.1}MiB", bytes / (1024.0 * 1024.0))
+    } else {
+        write!(f, "unknown")
+    }
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Reads the `VmHWM` entry of `/proc/self/status`, which is given in KiB.
+fn read_peak_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Reads the `rchar` and `wchar` entries of `/proc/self/io`.
+fn read_io_counters() -> Option<IoCounters> {
+    let io = std::fs::read_to_string("/proc/self/io").ok()?;
+    let mut bytes_read = None;
+    let mut bytes_written = None;
+    for line in io.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            match key {
+                "rchar" => bytes_read = value.trim().parse().ok(),
+                "wchar" => bytes_written = value.trim().parse().ok(),
+                _ => {}
+            }
+        }
+    }
+
+    Some(IoCounters {
+        bytes_read: bytes_read?,
+        bytes_written: bytes_written?,
+    })
+}
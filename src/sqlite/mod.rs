@@ -0,0 +1,241 @@
+use crate::error::Result;
+use crate::lookup::WordLookup;
+use crate::parser::words::interner::intern;
+use crate::parser::words::Word;
+use log::{debug, info};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::Path;
+
+/// The journal mode of the sqlite database, see <https://www.sqlite.org/pragma.html#pragma_journal_mode>.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+impl JournalMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalMode::Delete => "DELETE",
+            JournalMode::Truncate => "TRUNCATE",
+            JournalMode::Persist => "PERSIST",
+            JournalMode::Memory => "MEMORY",
+            JournalMode::Wal => "WAL",
+            JournalMode::Off => "OFF",
+        }
+    }
+}
+
+/// The synchronous level of the sqlite database, see <https://www.sqlite.org/pragma.html#pragma_synchronous>.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SynchronousLevel {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SynchronousLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SynchronousLevel::Off => "OFF",
+            SynchronousLevel::Normal => "NORMAL",
+            SynchronousLevel::Full => "FULL",
+            SynchronousLevel::Extra => "EXTRA",
+        }
+    }
+}
+
+/// Tuning knobs for writing words into an sqlite database.
+///
+/// The defaults use a write-ahead log with `NORMAL` synchronisation, which cannot corrupt the database,
+/// but may lose the last transactions on power loss.
+/// Words are inserted in transactions of `batch_size` words each,
+/// since committing each word separately is about an order of magnitude slower, even with a write-ahead log.
+/// The `sqlite_batch_size` example measures the insertion speed of different batch sizes.
+#[derive(Debug, Clone)]
+pub struct SqliteConfiguration {
+    pub journal_mode: JournalMode,
+    pub synchronous: SynchronousLevel,
+    /// The amount of words inserted per transaction.
+    pub batch_size: usize,
+    /// The amount of prepared statements that are kept for reuse.
+    pub prepared_statement_cache_capacity: usize,
+}
+
+impl Default for SqliteConfiguration {
+    fn default() -> Self {
+        Self {
+            journal_mode: JournalMode::Wal,
+            synchronous: SynchronousLevel::Normal,
+            batch_size: 10_000,
+            prepared_statement_cache_capacity: 16,
+        }
+    }
+}
+
+/// Writes words into the table `words` of an sqlite database.
+///
+/// Words are buffered and inserted in batches.
+/// Inserting blocks while a batch is written, which slows down the parser if the database cannot keep up.
+/// Call [`finish`](SqliteWordSink::finish) to insert the last batch.
+pub struct SqliteWordSink {
+    connection: Connection,
+    batch: Vec<Word>,
+    batch_size: usize,
+}
+
+impl SqliteWordSink {
+    /// Open or create the database at the given path and create the `words` table if it does not exist.
+    pub fn open(path: impl AsRef<Path>, configuration: &SqliteConfiguration) -> Result<Self> {
+        let path = path.as_ref();
+        debug!("Opening sqlite database {path:?} with {configuration:?}");
+        let connection = Connection::open(path)?;
+
+        let journal_mode: String = connection.pragma_update_and_check(
+            None,
+            "journal_mode",
+            configuration.journal_mode.as_str(),
+            |row| row.get(0),
+        )?;
+        debug!("Sqlite journal mode is {journal_mode}");
+        connection.pragma_update(None, "synchronous", configuration.synchronous.as_str())?;
+        connection
+            .set_prepared_statement_cache_capacity(configuration.prepared_statement_cache_capacity);
+
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS words (
+                word TEXT NOT NULL,
+                language_english_name TEXT NOT NULL,
+                language_code TEXT NOT NULL,
+                word_type TEXT NOT NULL,
+                features TEXT,
+                etymology INTEGER
+            );",
+        )?;
+
+        Ok(Self {
+            connection,
+            batch: Vec::with_capacity(configuration.batch_size),
+            batch_size: configuration.batch_size.max(1),
+        })
+    }
+
+    /// Insert a word, writing the current batch if it is full.
+    pub fn insert(&mut self, word: Word) -> Result<()> {
+        self.batch.push(word);
+        if self.batch.len() >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Insert multiple words, writing batches as they fill up.
+    pub fn insert_batch(&mut self, words: impl IntoIterator<Item = Word>) -> Result<()> {
+        for word in words {
+            self.insert(word)?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered words in a single transaction.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO words (word, language_english_name, language_code, word_type, features, etymology) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for word in &self.batch {
+                // Features are stored as json, or null if the word has none.
+                let features = if word.features.is_empty() {
+                    None
+                } else {
+                    Some(serde_json::to_string(&word.features)?)
+                };
+                statement.execute(params![
+                    word.word,
+                    &*word.language_english_name,
+                    &*word.language_code,
+                    &*word.word_type,
+                    features,
+                    word.etymology
+                ])?;
+            }
+        }
+        transaction.commit()?;
+
+        debug!("Inserted batch of {} words", self.batch.len());
+        self.batch.clear();
+        Ok(())
+    }
+
+    /// Write the remaining buffered words, index them by word and close the database.
+    pub fn finish(mut self) -> Result<()> {
+        self.flush()?;
+        self.connection
+            .execute_batch("CREATE INDEX IF NOT EXISTS words_by_word ON words (word);")?;
+        self.connection.close().map_err(|(_, error)| error)?;
+        info!("Finished writing sqlite database");
+        Ok(())
+    }
+}
+
+/// Looks up words in a database written by a [`SqliteWordSink`].
+/// Wrap it into a [`CachedWordLookup`](crate::lookup::CachedWordLookup) to cache frequently requested words.
+pub struct SqliteWordLookup {
+    connection: Connection,
+}
+
+impl SqliteWordLookup {
+    /// Open the database at the given path read-only.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        debug!("Opening sqlite database {path:?} for lookups");
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self { connection })
+    }
+}
+
+impl WordLookup for SqliteWordLookup {
+    fn lookup(&mut self, word: &str) -> Result<Vec<Word>> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT word, language_english_name, language_code, word_type, features, etymology FROM words WHERE word = ?1",
+        )?;
+        let words = statement
+            .query_map(params![word], |row| {
+                Ok(Word {
+                    word: row.get(0)?,
+                    language_english_name: intern(&row.get::<_, String>(1)?),
+                    language_code: intern(&row.get::<_, String>(2)?),
+                    word_type: intern(&row.get::<_, String>(3)?),
+                    features: row
+                        .get::<_, Option<String>>(4)?
+                        .map(|features| serde_json::from_str(&features))
+                        .transpose()
+                        .map_err(|error| {
+                            rusqlite::Error::FromSqlConversionFailure(
+                                3,
+                                Type::Text,
+                                Box::new(error),
+                            )
+                        })?
+                        .unwrap_or_default(),
+                    etymology: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(words)
+    }
+}
@@ -0,0 +1,181 @@
+//! Checksum files published next to the dumps, like `enwiktionary-20230801-md5sums.txt`.
+//!
+//! They list one `<hex digest>  <file name>` per line, and serve as alternative to the checksums of the status file.
+
+use crate::download::{get_text, RetryPolicy};
+use crate::error::{Error, Result};
+use crate::language_code::LanguageCode;
+use crate::store::{verify_file, StoredFileStatus, StoredFileVerification};
+use crate::urls::{dump_checksum_file, DumpBaseUrl};
+use log::{debug, info, warn};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+}
+
+impl ChecksumAlgorithm {
+    pub const ALL: [ChecksumAlgorithm; 2] = [ChecksumAlgorithm::Md5, ChecksumAlgorithm::Sha1];
+
+    /// The suffix of the checksum file name after the date.
+    pub fn file_name_suffix(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5sums.txt",
+            ChecksumAlgorithm::Sha1 => "sha1sums.txt",
+        }
+    }
+
+    /// Infer the algorithm from the name of a checksum file.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| file_name.ends_with(algorithm.file_name_suffix()))
+    }
+
+    fn digest_length(&self) -> usize {
+        match self {
+            ChecksumAlgorithm::Md5 => 32,
+            ChecksumAlgorithm::Sha1 => 40,
+        }
+    }
+}
+
+/// The parsed content of a checksum file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ChecksumFile {
+    pub algorithm: ChecksumAlgorithm,
+    /// Lowercase hex digests by file name.
+    pub checksums: BTreeMap<String, String>,
+}
+
+impl ChecksumFile {
+    /// Parse the content of a checksum file in the format of `md5sum` and `sha1sum`.
+    /// Empty lines are ignored, and file names may be prefixed with `*` for binary mode.
+    pub fn parse(algorithm: ChecksumAlgorithm, content: &str) -> Result<Self> {
+        let mut checksums = BTreeMap::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (digest, file_name) = line.split_once(char::is_whitespace).ok_or_else(|| {
+                Error::Other(format!(
+                    "Line {} of checksum file has no file name: {line:?}",
+                    line_number + 1
+                ))
+            })?;
+            let file_name = file_name.trim_start();
+            let file_name = file_name.strip_prefix('*').unwrap_or(file_name);
+            if digest.len() != algorithm.digest_length()
+                || !digest.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Err(Error::Other(format!(
+                    "Line {} of checksum file has no valid {algorithm:?} digest: {line:?}",
+                    line_number + 1
+                )));
+            }
+
+            checksums.insert(file_name.to_string(), digest.to_ascii_lowercase());
+        }
+
+        Ok(Self {
+            algorithm,
+            checksums,
+        })
+    }
+
+    /// Load a checksum file, inferring the algorithm from its name.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let algorithm = path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(ChecksumAlgorithm::from_file_name)
+            .ok_or_else(|| Error::Other(format!("Unknown type of checksum file {path:?}")))?;
+        Self::parse(algorithm, &std::fs::read_to_string(path)?)
+    }
+
+    /// The digest of the given file, if listed.
+    pub fn checksum(&self, file_name: &str) -> Option<&str> {
+        self.checksums.get(file_name).map(String::as_str)
+    }
+}
+
+/// Fetch and parse the checksum file of the given dump.
+pub async fn fetch_checksum_file(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    algorithm: ChecksumAlgorithm,
+    retry_policy: &RetryPolicy,
+) -> Result<ChecksumFile> {
+    let url = dump_checksum_file(base_url, language_code, date, algorithm.file_name_suffix())?;
+    info!("Fetching checksums from '{url}'");
+    ChecksumFile::parse(algorithm, &get_text(&url, retry_policy).await?)
+}
+
+/// Find the checksum files in a directory, like the date directories of the target directory.
+pub fn find_checksum_files(directory: impl AsRef<Path>) -> Result<Vec<ChecksumFile>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let is_checksum_file = entry
+            .file_name()
+            .to_str()
+            .and_then(ChecksumAlgorithm::from_file_name)
+            .is_some();
+        if is_checksum_file && entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    paths.iter().map(ChecksumFile::load).collect()
+}
+
+/// Verify the files of a directory against the given checksum files.
+/// Only files that exist are verified, and each file is read once for all checksum files.
+pub async fn verify_directory(
+    directory: impl AsRef<Path>,
+    checksum_files: &[ChecksumFile],
+    max_bytes_per_second: Option<u64>,
+) -> Result<Vec<StoredFileVerification>> {
+    let directory = directory.as_ref();
+    let mut expected: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+    for checksum_file in checksum_files {
+        for (file_name, digest) in &checksum_file.checksums {
+            let entry = expected.entry(file_name.as_str()).or_insert(("", ""));
+            match checksum_file.algorithm {
+                ChecksumAlgorithm::Md5 => entry.0 = digest.as_str(),
+                ChecksumAlgorithm::Sha1 => entry.1 = digest.as_str(),
+            }
+        }
+    }
+
+    let mut verifications = Vec::new();
+    for (file_name, (md5, sha1)) in expected {
+        let path = directory.join(file_name);
+        if !path.exists() {
+            debug!("Skipping {path:?}, because it does not exist");
+            continue;
+        }
+
+        info!("Verifying {path:?}");
+        let status = if let Some(reason) =
+            verify_file(&path, None, md5, sha1, max_bytes_per_second).await?
+        {
+            warn!("File {path:?} is corrupted: {reason}");
+            StoredFileStatus::Corrupted { reason }
+        } else {
+            StoredFileStatus::Valid
+        };
+        verifications.push(StoredFileVerification { path, status });
+    }
+
+    Ok(verifications)
+}
@@ -0,0 +1,399 @@
+//! Maintenance of the directory that dumps are downloaded into.
+//!
+//! Dumps are stored as `<target directory>/<wiktionary abbreviation>/<date>/<file name>`,
+//! next to the status file of their date, which records their checksums.
+
+use crate::download::{download_file, DownloadOptions};
+use crate::error::{Error, Result};
+use crate::urls::{dump_url, DumpBaseUrl};
+use crate::DumpStatusFile;
+use digest::Digest;
+use log::{debug, info, warn};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use tokio::time::{sleep, Duration, Instant};
+
+pub mod checksums;
+
+/// The name of the status file stored next to the dumps of a date.
+pub const DUMP_STATUS_FILE_NAME: &str = "dumpstatus.json";
+
+/// Options for [`verify_store`].
+#[derive(Debug, Clone)]
+pub struct VerifyStoreOptions {
+    /// Limits the rate at which files are read, such that verification does not starve other IO.
+    pub max_bytes_per_second: Option<u64>,
+    /// Re-download files whose checksums do not match from `base_url`.
+    pub repair: bool,
+    pub base_url: DumpBaseUrl,
+    pub progress_delay_seconds: u64,
+}
+
+impl Default for VerifyStoreOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes_per_second: None,
+            repair: false,
+            base_url: DumpBaseUrl::Default,
+            progress_delay_seconds: 10,
+        }
+    }
+}
+
+/// The result of verifying a single stored file.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct StoredFileVerification {
+    pub path: PathBuf,
+    pub status: StoredFileStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum StoredFileStatus {
+    /// The checksums match.
+    Valid,
+    /// The checksums or the size do not match.
+    Corrupted { reason: String },
+    /// The checksums did not match, and the file was downloaded again successfully.
+    Repaired { reason: String },
+    /// The checksums did not match, and downloading the file again failed.
+    RepairFailed { reason: String, error: String },
+}
+
+impl StoredFileStatus {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid | Self::Repaired { .. })
+    }
+}
+
+/// Walk the target directory and verify all stored dumps against the checksums of their status files.
+/// Only files that exist are verified, and directories without status file are skipped with a warning.
+pub async fn verify_store(
+    target_directory: impl AsRef<Path>,
+    options: &VerifyStoreOptions,
+) -> Result<Vec<StoredFileVerification>> {
+    let mut verifications = Vec::new();
+
+    for language_directory in sorted_subdirectories(target_directory.as_ref())? {
+        for date_directory in sorted_subdirectories(&language_directory)? {
+            let status_file_path = date_directory.join(DUMP_STATUS_FILE_NAME);
+            if !status_file_path.exists() {
+                warn!("Skipping {date_directory:?}, because it has no {DUMP_STATUS_FILE_NAME}");
+                continue;
+            }
+            let dump_status_file: DumpStatusFile =
+                serde_json::from_str(&tokio::fs::read_to_string(&status_file_path).await?)?;
+
+            for job in dump_status_file.jobs.values() {
+                for (file_name, properties) in &job.files {
+                    let path = date_directory.join(file_name);
+                    if !path.exists() {
+                        continue;
+                    }
+
+                    info!("Verifying {path:?}");
+                    let mut status = if let Some(reason) = verify_file(
+                        &path,
+                        properties.known_size(),
+                        &properties.md5,
+                        &properties.sha1,
+                        options.max_bytes_per_second,
+                    )
+                    .await?
+                    {
+                        warn!("File {path:?} is corrupted: {reason}");
+                        StoredFileStatus::Corrupted { reason }
+                    } else {
+                        StoredFileStatus::Valid
+                    };
+
+                    if let (true, StoredFileStatus::Corrupted { reason }) =
+                        (options.repair, status.clone())
+                    {
+                        info!("Downloading {path:?} again");
+                        let result = async {
+                            download_file(
+                                &dump_url(&options.base_url, &properties.url)?,
+                                &path,
+                                properties.size,
+                                &DownloadOptions {
+                                    progress_delay_seconds: options.progress_delay_seconds,
+                                    ..Default::default()
+                                },
+                                properties.known_md5(),
+                                properties.known_sha1(),
+                            )
+                            .await
+                        }
+                        .await;
+                        status = match result {
+                            Ok(_) => StoredFileStatus::Repaired { reason },
+                            Err(error) => StoredFileStatus::RepairFailed {
+                                reason,
+                                error: error.to_string(),
+                            },
+                        };
+                    }
+
+                    verifications.push(StoredFileVerification { path, status });
+                }
+            }
+        }
+    }
+
+    Ok(verifications)
+}
+
+/// Returns the reason why the file does not match, or `None` if it matches.
+/// Empty checksums and a missing size are not checked.
+pub(crate) async fn verify_file(
+    path: &Path,
+    expected_size: Option<usize>,
+    md5: &str,
+    sha1: &str,
+    max_bytes_per_second: Option<u64>,
+) -> Result<Option<String>> {
+    if max_bytes_per_second == Some(0) {
+        return Err(Error::Other(format!(
+            "The maximum verification rate must be at least one byte per second"
+        )));
+    }
+
+    let mut file = File::open(path).await?;
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut md5_digest = Md5::default();
+    let mut sha1_digest = Sha1::default();
+    let mut total = 0u64;
+    let start = Instant::now();
+
+    loop {
+        let amount = file.read(&mut buffer).await?;
+        if amount == 0 {
+            break;
+        }
+        md5_digest.update(&buffer[..amount]);
+        sha1_digest.update(&buffer[..amount]);
+        total += amount as u64;
+
+        if let Some(max_bytes_per_second) = max_bytes_per_second {
+            let expected_duration =
+                Duration::from_secs_f64(total as f64 / max_bytes_per_second as f64);
+            let elapsed = start.elapsed();
+            if elapsed < expected_duration {
+                sleep(expected_duration - elapsed).await;
+            }
+        }
+    }
+    debug!("Read {total} bytes of {path:?}");
+
+    if let Some(expected_size) = expected_size {
+        if total != expected_size as u64 {
+            return Ok(Some(format!(
+                "Size is {total}, but should be {expected_size}"
+            )));
+        }
+    }
+    let md5_digest = format!("{:x}", md5_digest.finalize());
+    if !md5.is_empty() && md5_digest != md5 {
+        return Ok(Some(format!(
+            "Md5 checksum is '{md5_digest}', but should be '{md5}'"
+        )));
+    }
+    let sha1_digest = format!("{:x}", sha1_digest.finalize());
+    if !sha1.is_empty() && sha1_digest != sha1 {
+        return Ok(Some(format!(
+            "Sha1 checksum is '{sha1_digest}', but should be '{sha1}'"
+        )));
+    }
+    Ok(None)
+}
+
+/// Options for [`prune_store`].
+#[derive(Debug, Clone)]
+pub struct PruneOptions {
+    /// The number of dates to keep per language, newest first. Must be at least one.
+    pub keep_last: usize,
+    /// Only dates whose stored files all match their checksums are kept and count towards `keep_last`.
+    /// Dates that do not verify are removed.
+    pub keep_verified_only: bool,
+    /// Only report what would be removed, without removing anything.
+    pub dry_run: bool,
+    /// Limits the rate at which files are read while verifying.
+    pub max_bytes_per_second: Option<u64>,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            keep_last: 1,
+            keep_verified_only: false,
+            dry_run: false,
+            max_bytes_per_second: None,
+        }
+    }
+}
+
+/// A date directory removed by [`prune_store`].
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PrunedDate {
+    pub directory: PathBuf,
+    /// The removed dumps and status file.
+    pub removed_files: Vec<PathBuf>,
+    /// The total size of the removed files.
+    pub freed_bytes: u64,
+    pub reason: PruneReason,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum PruneReason {
+    /// There are at least `keep_last` newer dates that are kept.
+    Superseded,
+    /// A stored file does not match its checksums.
+    Unverified { reason: String },
+}
+
+/// Remove superseded dumps from the target directory, keeping the newest `keep_last` dates of each language.
+///
+/// Only the files listed in the status file of a date and the status file itself are removed,
+/// and the date directory only if it is empty afterwards.
+/// Directories without status file are never touched.
+pub async fn prune_store(
+    target_directory: impl AsRef<Path>,
+    options: &PruneOptions,
+) -> Result<Vec<PrunedDate>> {
+    if options.keep_last == 0 {
+        return Err(Error::Other(format!(
+            "At least one date per language must be kept"
+        )));
+    }
+
+    let mut pruned_dates = Vec::new();
+
+    for language_directory in sorted_subdirectories(target_directory.as_ref())? {
+        let mut kept = 0;
+
+        // Dates are formatted as `YYYYMMDD`, so the newest come first in reverse lexicographic order.
+        for date_directory in sorted_subdirectories(&language_directory)?
+            .into_iter()
+            .rev()
+        {
+            let status_file_path = date_directory.join(DUMP_STATUS_FILE_NAME);
+            if !status_file_path.exists() {
+                warn!("Skipping {date_directory:?}, because it has no {DUMP_STATUS_FILE_NAME}");
+                continue;
+            }
+            let dump_status_file: DumpStatusFile =
+                serde_json::from_str(&tokio::fs::read_to_string(&status_file_path).await?)?;
+
+            let reason = if kept >= options.keep_last {
+                Some(PruneReason::Superseded)
+            } else if options.keep_verified_only {
+                verify_date(&date_directory, &dump_status_file, options)
+                    .await?
+                    .map(|reason| PruneReason::Unverified { reason })
+            } else {
+                None
+            };
+
+            let reason = if let Some(reason) = reason {
+                reason
+            } else {
+                debug!("Keeping {date_directory:?}");
+                kept += 1;
+                continue;
+            };
+
+            let mut removed_files = Vec::new();
+            let mut freed_bytes = 0;
+            let file_names = dump_status_file
+                .jobs
+                .values()
+                .flat_map(|job| job.files.keys())
+                .map(String::as_str)
+                .chain([DUMP_STATUS_FILE_NAME]);
+            for file_name in file_names {
+                let path = date_directory.join(file_name);
+                if !path.is_file() {
+                    continue;
+                }
+                freed_bytes += path.metadata()?.len();
+                if !options.dry_run {
+                    tokio::fs::remove_file(&path).await?;
+                }
+                removed_files.push(path);
+            }
+
+            if !options.dry_run {
+                if std::fs::read_dir(&date_directory)?.next().is_none() {
+                    tokio::fs::remove_dir(&date_directory).await?;
+                } else {
+                    warn!("Not removing {date_directory:?}, because it contains unknown files");
+                }
+            }
+
+            info!("Pruned {date_directory:?} ({reason:?}), freeing {freed_bytes} bytes");
+            pruned_dates.push(PrunedDate {
+                directory: date_directory,
+                removed_files,
+                freed_bytes,
+                reason,
+            });
+        }
+    }
+
+    Ok(pruned_dates)
+}
+
+/// Returns the reason why the stored files of a date do not verify, or `None` if they all verify.
+/// A date without any stored file does not verify.
+async fn verify_date(
+    date_directory: &Path,
+    dump_status_file: &DumpStatusFile,
+    options: &PruneOptions,
+) -> Result<Option<String>> {
+    let mut stored_files = 0;
+
+    for job in dump_status_file.jobs.values() {
+        for (file_name, properties) in &job.files {
+            let path = date_directory.join(file_name);
+            if !path.exists() {
+                continue;
+            }
+
+            stored_files += 1;
+            info!("Verifying {path:?}");
+            if let Some(reason) = verify_file(
+                &path,
+                properties.known_size(),
+                &properties.md5,
+                &properties.sha1,
+                options.max_bytes_per_second,
+            )
+            .await?
+            {
+                return Ok(Some(format!("{file_name}: {reason}")));
+            }
+        }
+    }
+
+    if stored_files == 0 {
+        Ok(Some(format!("No dumps are stored")))
+    } else {
+        Ok(None)
+    }
+}
+
+fn sorted_subdirectories(directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut subdirectories = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            subdirectories.push(entry.path());
+        }
+    }
+    subdirectories.sort();
+    Ok(subdirectories)
+}
@@ -1,30 +1,141 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::language_code::LanguageCode;
 use url::Url;
 
 static DUMP_INDEX_URL: &str = "https://dumps.wikimedia.org/backup-index.html";
 static DUMP_BASE_URL: &str = "https://ftp.acc.umu.se/mirror/wikimedia.org/dumps";
+static WIKIMEDIA_CANONICAL_BASE_URL: &str = "https://dumps.wikimedia.org";
 
+/// The pseudo-date of the `latest` directory of each wiktionary, which holds the files of the latest dump
+/// whose articles dump is done, named with `latest` instead of the date.
+/// It has no dump status file, so it is resolved to the actual date it stands for before downloading,
+/// see [`resolve_dump_date`](crate::resolve_dump_date).
+pub const LATEST_DATE: &str = "latest";
+
+/// A known mirror of the wikimedia dumps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Mirror {
+    /// A short name to select the mirror by, like `umu`.
+    pub name: &'static str,
+    pub base_url: &'static str,
+}
+
+/// The known mirrors of the wikimedia dumps, starting with the default one.
+/// Downloads fall back to the next mirror in this order.
+pub static KNOWN_MIRRORS: &[Mirror] = &[
+    Mirror {
+        name: "umu",
+        base_url: DUMP_BASE_URL,
+    },
+    Mirror {
+        name: "wikimedia",
+        base_url: WIKIMEDIA_CANONICAL_BASE_URL,
+    },
+    Mirror {
+        name: "accum",
+        base_url: "https://mirror.accum.se/mirror/wikimedia.org/dumps",
+    },
+    Mirror {
+        name: "us-dev",
+        base_url: "https://wikimedia.mirror.us.dev",
+    },
+];
+
+impl Mirror {
+    /// The known mirror with the given name.
+    pub fn by_name(name: &str) -> Option<&'static Mirror> {
+        KNOWN_MIRRORS.iter().find(|mirror| mirror.name == name)
+    }
+
+    pub fn dump_base_url(&self) -> DumpBaseUrl {
+        if self.base_url == DUMP_BASE_URL {
+            DumpBaseUrl::Default
+        } else if self.base_url == WIKIMEDIA_CANONICAL_BASE_URL {
+            DumpBaseUrl::WikimediaCanonical
+        } else {
+            DumpBaseUrl::Custom(self.base_url.to_string())
+        }
+    }
+}
+
+/// The url of the dump status file of a dump.
+/// Fails for the [`LATEST_DATE`], since the `latest` directory has no dump status file.
 pub fn dump_status_file(
     base_url: &DumpBaseUrl,
     language_code: &LanguageCode,
     date: &str,
 ) -> Result<Url> {
+    if date == LATEST_DATE {
+        return Err(Error::Other(format!(
+            "The '{LATEST_DATE}' directory has no dump status file, resolve it to a date first"
+        )));
+    }
     let base_url = base_url.as_str();
-    let language_abbreviation = language_code.to_wiktionary_abbreviation();
+    let database_prefix = language_code.to_database_prefix();
+    Ok(Url::parse(&format!(
+        "{base_url}/{database_prefix}wiktionary/{date}/dumpstatus.json"
+    ))?)
+}
+
+/// The url of a checksum file of a dump, e.g. `enwiktionary-20230801-md5sums.txt`.
+/// The `suffix` is the part after the date, like `md5sums.txt` or `sha1sums.txt`.
+/// For the [`LATEST_DATE`], this is the checksum file in the `latest` directory, e.g. `enwiktionary-latest-md5sums.txt`.
+pub fn dump_checksum_file(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+    date: &str,
+    suffix: &str,
+) -> Result<Url> {
+    let base_url = base_url.as_str();
+    let database_prefix = language_code.to_database_prefix();
+    Ok(Url::parse(&format!(
+        "{base_url}/{database_prefix}wiktionary/{date}/{database_prefix}wiktionary-{date}-{suffix}"
+    ))?)
+}
+
+/// The url of the feed announcing the articles dump in the `latest` directory,
+/// whose link points to the directory of the date the articles dump is from.
+pub fn latest_articles_dump_feed(
+    base_url: &DumpBaseUrl,
+    language_code: &LanguageCode,
+) -> Result<Url> {
+    let base_url = base_url.as_str();
+    let database_prefix = language_code.to_database_prefix();
     Ok(Url::parse(&format!(
-        "{base_url}/{language_abbreviation}wiktionary/{date}/dumpstatus.json"
+        "{base_url}/{database_prefix}wiktionary/{LATEST_DATE}/{database_prefix}wiktionary-{LATEST_DATE}-pages-articles.xml.bz2-rss.xml"
     ))?)
 }
 
 pub fn available_dates(base_url: &DumpBaseUrl, language_code: &LanguageCode) -> Result<Url> {
     let base_url = base_url.as_str();
+    let database_prefix = language_code.to_database_prefix();
+    Ok(Url::parse(&format!(
+        "{base_url}/{database_prefix}wiktionary/"
+    ))?)
+}
+
+/// The url of the MediaWiki api of the wiktionary in the given language.
+pub fn mediawiki_api(language_code: &LanguageCode) -> Result<Url> {
     let language_abbreviation = language_code.to_wiktionary_abbreviation();
     Ok(Url::parse(&format!(
-        "{base_url}/{language_abbreviation}wiktionary/"
+        "https://{language_abbreviation}.wiktionary.org/w/api.php"
     ))?)
 }
 
+/// The url of the html rendering of the page with the given title by the Wikimedia REST api
+/// of the wiktionary in the given language.
+pub fn rest_page_html_url(language_code: &LanguageCode, title: &str) -> Result<Url> {
+    let language_abbreviation = language_code.to_wiktionary_abbreviation();
+    let mut url = Url::parse(&format!(
+        "https://{language_abbreviation}.wiktionary.org/api/rest_v1/page/html/"
+    ))?;
+    url.path_segments_mut()
+        .map_err(|()| Error::Other(format!("The REST api url cannot have a path")))?
+        .pop_if_empty()
+        .push(&title.replace(' ', "_"));
+    Ok(url)
+}
+
 pub fn dump_url(base_url: &DumpBaseUrl, suffix: &str) -> Result<Url> {
     let base_url = base_url.as_str();
     Ok(Url::parse(&format!("{base_url}{suffix}"))?)
@@ -47,15 +158,36 @@ impl DumpIndexUrl {
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum DumpBaseUrl {
+    /// The mirror of Umeå University in Sweden.
     Default,
+    /// The official host of the dumps at `dumps.wikimedia.org`, which is rate limited more strictly than the mirrors.
+    WikimediaCanonical,
     Custom(String),
 }
 
 impl DumpBaseUrl {
+    /// The default mirror followed by all other known mirrors.
+    pub fn known_mirrors() -> Vec<DumpBaseUrl> {
+        KNOWN_MIRRORS.iter().map(Mirror::dump_base_url).collect()
+    }
+
+    /// This base url followed by all known mirrors other than this one, in the order to fall back to them.
+    pub fn with_fallbacks(&self) -> Vec<DumpBaseUrl> {
+        std::iter::once(self.clone())
+            .chain(
+                Self::known_mirrors()
+                    .into_iter()
+                    .filter(|mirror| mirror.as_str() != self.as_str()),
+            )
+            .collect()
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             DumpBaseUrl::Default => DUMP_BASE_URL,
-            DumpBaseUrl::Custom(custom) => custom,
+            DumpBaseUrl::WikimediaCanonical => WIKIMEDIA_CANONICAL_BASE_URL,
+            // Tolerate a trailing slash, since the paths are appended with a leading slash.
+            DumpBaseUrl::Custom(custom) => custom.trim_end_matches('/'),
         }
     }
 }
@@ -0,0 +1,43 @@
+use crate::language_code::LanguageCode;
+use std::path::Path;
+
+/// The wiktionary edition a dump file belongs to, i.e. the language wiktionary is written in.
+/// The layout of pages differs between editions, so extraction depends on it.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum WiktionaryEdition {
+    Known(LanguageCode),
+    /// The edition could not be determined, or its language is not supported.
+    Unknown,
+}
+
+impl WiktionaryEdition {
+    /// Infer the edition from a database name like `enwiktionary` or `zh_min_nanwiktionary`.
+    pub fn from_dbname(dbname: &str) -> Self {
+        dbname
+            .strip_suffix("wiktionary")
+            .and_then(|prefix| LanguageCode::from_database_prefix(prefix).ok())
+            .map(Self::Known)
+            .unwrap_or(Self::Unknown)
+    }
+
+    /// Infer the edition from the name of a dump file like `enwiktionary-20230801-pages-articles.xml.bz2`.
+    pub fn from_file_name(file: impl AsRef<Path>) -> Self {
+        file.as_ref()
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.split('-').next())
+            .map(Self::from_dbname)
+            .unwrap_or(Self::Unknown)
+    }
+
+    pub fn language_code(&self) -> Option<&LanguageCode> {
+        match self {
+            WiktionaryEdition::Known(language_code) => Some(language_code),
+            WiktionaryEdition::Unknown => None,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        matches!(self, WiktionaryEdition::Known(_))
+    }
+}